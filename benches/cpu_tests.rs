@@ -0,0 +1,92 @@
+//! Measures how fast the CPU test programs run, so that regressions in the interpreter are
+//! visible. Run `cargo bench`, optionally followed by `-- NAME` to select the benchmarks whose
+//! names contain NAME.
+
+#![warn(rust_2018_idioms)]
+
+use std::{
+    env,
+    time::{Duration, Instant},
+};
+
+use i8080::{
+    console::BufferConsole,
+    cpm::{Bdos, BDOS_BASE},
+    cpu::Engine,
+};
+
+fn main() {
+    let filters: Vec<String> = env::args().skip(1).filter(|arg| !arg.starts_with("--")).collect();
+    let benchmarks: [(&str, &str, u32); 4] = [
+        ("8080pre", "tests/cpu_tests/8080PRE.COM", 100),
+        ("tst8080", "tests/cpu_tests/TST8080.COM", 100),
+        ("cputest", "tests/cpu_tests/CPUTEST.COM", 10),
+        ("8080exm", "tests/cpu_tests/8080EXM.COM", 1),
+    ];
+    for &(name, program, samples) in &benchmarks {
+        if !filters.is_empty() && !filters.iter().any(|filter| name.contains(filter.as_str())) {
+            continue;
+        }
+        bench(&format!("run/{}", name), program, samples, |program| {
+            run(program, Engine::Interpreter)
+        });
+        bench(&format!("blocks/{}", name), program, samples, |program| {
+            run(program, Engine::BlockCache)
+        });
+        bench(&format!("step/{}", name), program, samples, step);
+    }
+}
+
+// Runs `program` `samples` times with `f`, which returns the number of states taken, and prints
+// the fastest, mean, and slowest times with the emulated clock rate.
+fn bench<F: Fn(&str) -> u64>(name: &str, program: &str, samples: u32, f: F) {
+    let mut times = Vec::new();
+    let mut states = 0;
+    for _ in 0..samples {
+        let start = Instant::now();
+        states = f(program);
+        times.push(start.elapsed());
+    }
+    let min = *times.iter().min().unwrap();
+    let max = *times.iter().max().unwrap();
+    let mean = times.iter().sum::<Duration>() / samples;
+    println!(
+        "{:<16} time: [{:>10.3?} {:>10.3?} {:>10.3?}]  thrpt: {:>8.1} MHz",
+        name,
+        min,
+        mean,
+        max,
+        states as f64 / mean.as_secs_f64() / 1e6,
+    );
+}
+
+// Runs `program` with the CPU running up to each BDOS or BIOS call on `engine`, as `Bdos::run`
+// does.
+fn run(program: &str, engine: Engine) -> u64 {
+    let mut bdos = Bdos::new(BufferConsole::default(), "tests/cpu_tests");
+    let mut i8080 = bdos.load_program(program, &[] as &[&str]).unwrap();
+    i8080.cpu.set_engine(engine);
+    let mut states = 0;
+    while i8080.cpu.pc != 0x0000 {
+        bdos.trap(&mut i8080).unwrap();
+        if i8080.cpu.pc != 0x0000 {
+            states += i8080.run(u64::MAX, |pc| pc == 0x0000 || pc >= BDOS_BASE);
+        }
+    }
+    states
+}
+
+// Runs `program` one instruction at a time.
+fn step(program: &str) -> u64 {
+    let mut bdos = Bdos::new(BufferConsole::default(), "tests/cpu_tests");
+    let mut i8080 = bdos.load_program(program, &[] as &[&str]).unwrap();
+    let mut states = 0;
+    loop {
+        bdos.trap(&mut i8080).unwrap();
+        if i8080.cpu.pc == 0x0000 {
+            return states;
+        }
+        let (_, instruction_states) = i8080.fetch_execute_instruction().unwrap();
+        states += u64::from(instruction_states);
+    }
+}
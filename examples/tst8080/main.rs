@@ -0,0 +1,57 @@
+//! Runs TST8080.COM, the 8080 CPU diagnostic of Microcosm Associates, as native code that
+//! `i8080-recompile` generated:
+//!
+//! ```text
+//! i8080-recompile tests/cpu_tests/TST8080.COM 0100 0100 > examples/tst8080/recompiled.rs
+//! cargo run --release --example tst8080
+//! ```
+
+#![warn(rust_2018_idioms)]
+
+use std::{error::Error, process};
+
+use i8080::{
+    console::StdioConsole,
+    cpm::{Bdos, BDOS_BASE},
+    cpu::Io,
+    Intel8080,
+};
+
+mod recompiled;
+
+// TST8080.COM uses no I/O port.
+struct NoDevice;
+
+impl Io for NoDevice {
+    fn input(&mut self, _port: u8) -> u8 {
+        0xFF
+    }
+
+    fn output(&mut self, _port: u8, _byte: u8) {}
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("Error: {err}");
+        process::exit(1);
+    }
+}
+
+// Runs TST8080.COM up to each BDOS call until it warm boots.
+fn run() -> Result<(), Box<dyn Error>> {
+    let mut bdos = Bdos::new(StdioConsole::new(), "tests/cpu_tests");
+    let mut i8080 = bdos.load_program("tests/cpu_tests/TST8080.COM", &[] as &[&str])?;
+    loop {
+        bdos.trap(&mut i8080)?;
+        if i8080.cpu.pc == 0x0000 {
+            return Ok(());
+        }
+        if i8080.cpu.is_halted() {
+            return Err(i8080::Error::Halted.into());
+        }
+        let Intel8080 { cpu, memory } = &mut i8080;
+        recompiled::run_with_io(cpu, memory, &mut NoDevice, u64::MAX, |pc| {
+            pc == 0x0000 || pc >= BDOS_BASE
+        });
+    }
+}
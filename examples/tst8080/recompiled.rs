@@ -0,0 +1,1357 @@
+// Recompiled by i8080-recompile from TST8080.COM loaded at 0100H. Do not edit.
+
+use i8080::{
+    cpu::{Cpu, Io, Variant},
+    memory::Memory,
+};
+
+const ORIGIN: u16 = 0x0100;
+const IMAGE: [u8; 1536] = [
+    0xC3, 0xB2, 0x01, 0x4D, 0x49, 0x43, 0x52, 0x4F, 0x43, 0x4F, 0x53, 0x4D, 0x20, 0x41, 0x53, 0x53,
+    0x4F, 0x43, 0x49, 0x41, 0x54, 0x45, 0x53, 0x20, 0x38, 0x30, 0x38, 0x30, 0x2F, 0x38, 0x30, 0x38,
+    0x35, 0x20, 0x43, 0x50, 0x55, 0x20, 0x44, 0x49, 0x41, 0x47, 0x4E, 0x4F, 0x53, 0x54, 0x49, 0x43,
+    0x0D, 0x0A, 0x20, 0x56, 0x45, 0x52, 0x53, 0x49, 0x4F, 0x4E, 0x20, 0x31, 0x2E, 0x30, 0x20, 0x20,
+    0x28, 0x43, 0x29, 0x20, 0x31, 0x39, 0x38, 0x30, 0x0D, 0x0A, 0x24, 0xD5, 0xEB, 0x0E, 0x09, 0xCD,
+    0x05, 0x00, 0xD1, 0xC9, 0x0E, 0x02, 0xCD, 0x05, 0x00, 0xC9, 0xF5, 0xCD, 0x6A, 0x01, 0x5F, 0xCD,
+    0x54, 0x01, 0xF1, 0xCD, 0x6E, 0x01, 0x5F, 0xC3, 0x54, 0x01, 0x0F, 0x0F, 0x0F, 0x0F, 0xE6, 0x0F,
+    0xFE, 0x0A, 0xFA, 0x77, 0x01, 0xC6, 0x07, 0xC6, 0x30, 0xC9, 0x0D, 0x0A, 0x20, 0x43, 0x50, 0x55,
+    0x20, 0x49, 0x53, 0x20, 0x4F, 0x50, 0x45, 0x52, 0x41, 0x54, 0x49, 0x4F, 0x4E, 0x41, 0x4C, 0x24,
+    0x0D, 0x0A, 0x20, 0x43, 0x50, 0x55, 0x20, 0x48, 0x41, 0x53, 0x20, 0x46, 0x41, 0x49, 0x4C, 0x45,
+    0x44, 0x21, 0x20, 0x20, 0x20, 0x20, 0x45, 0x52, 0x52, 0x4F, 0x52, 0x20, 0x45, 0x58, 0x49, 0x54,
+    0x3D, 0x24, 0x31, 0xBD, 0x07, 0x21, 0x03, 0x01, 0xCD, 0x4B, 0x01, 0xE6, 0x00, 0xCA, 0xC3, 0x01,
+    0xCD, 0xA0, 0x06, 0xD2, 0xC9, 0x01, 0xCD, 0xA0, 0x06, 0xEA, 0xCF, 0x01, 0xCD, 0xA0, 0x06, 0xF2,
+    0xD5, 0x01, 0xCD, 0xA0, 0x06, 0xC2, 0xE4, 0x01, 0xDA, 0xE4, 0x01, 0xE2, 0xE4, 0x01, 0xFA, 0xE4,
+    0x01, 0xC3, 0xE7, 0x01, 0xCD, 0xA0, 0x06, 0xC6, 0x06, 0xC2, 0xEF, 0x01, 0xCD, 0xA0, 0x06, 0xDA,
+    0xF8, 0x01, 0xE2, 0xF8, 0x01, 0xF2, 0xFB, 0x01, 0xCD, 0xA0, 0x06, 0xC6, 0x70, 0xE2, 0x03, 0x02,
+    0xCD, 0xA0, 0x06, 0xFA, 0x0C, 0x02, 0xCA, 0x0C, 0x02, 0xD2, 0x0F, 0x02, 0xCD, 0xA0, 0x06, 0xC6,
+    0x81, 0xFA, 0x17, 0x02, 0xCD, 0xA0, 0x06, 0xCA, 0x20, 0x02, 0xDA, 0x20, 0x02, 0xE2, 0x23, 0x02,
+    0xCD, 0xA0, 0x06, 0xC6, 0xFE, 0xDA, 0x2B, 0x02, 0xCD, 0xA0, 0x06, 0xCA, 0x34, 0x02, 0xE2, 0x34,
+    0x02, 0xFA, 0x37, 0x02, 0xCD, 0xA0, 0x06, 0xFE, 0x00, 0xDA, 0x4F, 0x02, 0xCA, 0x4F, 0x02, 0xFE,
+    0xF5, 0xDA, 0x4F, 0x02, 0xC2, 0x4F, 0x02, 0xFE, 0xFF, 0xCA, 0x4F, 0x02, 0xDA, 0x52, 0x02, 0xCD,
+    0xA0, 0x06, 0xCE, 0x0A, 0xCE, 0x0A, 0xFE, 0x0B, 0xCA, 0x5E, 0x02, 0xCD, 0xA0, 0x06, 0xD6, 0x0C,
+    0xD6, 0x0F, 0xFE, 0xF0, 0xCA, 0x6A, 0x02, 0xCD, 0xA0, 0x06, 0xDE, 0xF1, 0xDE, 0x0E, 0xFE, 0xF0,
+    0xCA, 0x76, 0x02, 0xCD, 0xA0, 0x06, 0xE6, 0x55, 0xFE, 0x50, 0xCA, 0x80, 0x02, 0xCD, 0xA0, 0x06,
+    0xF6, 0x3A, 0xFE, 0x7A, 0xCA, 0x8A, 0x02, 0xCD, 0xA0, 0x06, 0xEE, 0x0F, 0xFE, 0x75, 0xCA, 0x94,
+    0x02, 0xCD, 0xA0, 0x06, 0xE6, 0x00, 0xDC, 0xA0, 0x06, 0xE4, 0xA0, 0x06, 0xFC, 0xA0, 0x06, 0xC4,
+    0xA0, 0x06, 0xFE, 0x00, 0xCA, 0xAA, 0x02, 0xCD, 0xA0, 0x06, 0xD6, 0x77, 0xD4, 0xA0, 0x06, 0xEC,
+    0xA0, 0x06, 0xF4, 0xA0, 0x06, 0xCC, 0xA0, 0x06, 0xFE, 0x89, 0xCA, 0xC0, 0x02, 0xCD, 0xA0, 0x06,
+    0xE6, 0xFF, 0xE4, 0xCD, 0x02, 0xFE, 0xD9, 0xCA, 0x2A, 0x03, 0xCD, 0xA0, 0x06, 0xE8, 0xC6, 0x10,
+    0xEC, 0xD9, 0x02, 0xC6, 0x02, 0xE0, 0xCD, 0xA0, 0x06, 0xE0, 0xC6, 0x20, 0xFC, 0xE5, 0x02, 0xC6,
+    0x04, 0xE8, 0xCD, 0xA0, 0x06, 0xF0, 0xC6, 0x80, 0xF4, 0xF1, 0x02, 0xC6, 0x80, 0xF8, 0xCD, 0xA0,
+    0x06, 0xF8, 0xC6, 0x40, 0xD4, 0xFD, 0x02, 0xC6, 0x40, 0xF0, 0xCD, 0xA0, 0x06, 0xD8, 0xC6, 0x8F,
+    0xDC, 0x09, 0x03, 0xD6, 0x02, 0xD0, 0xCD, 0xA0, 0x06, 0xD0, 0xC6, 0xF7, 0xC4, 0x15, 0x03, 0xC6,
+    0xFE, 0xD8, 0xCD, 0xA0, 0x06, 0xC8, 0xC6, 0x01, 0xCC, 0x21, 0x03, 0xC6, 0xD0, 0xC0, 0xCD, 0xA0,
+    0x06, 0xC0, 0xC6, 0x47, 0xFE, 0x47, 0xC8, 0xCD, 0xA0, 0x06, 0x3E, 0x77, 0x3C, 0x47, 0x04, 0x48,
+    0x0D, 0x51, 0x5A, 0x63, 0x6C, 0x7D, 0x3D, 0x4F, 0x59, 0x6B, 0x45, 0x50, 0x62, 0x7C, 0x57, 0x14,
+    0x6A, 0x4D, 0x0C, 0x61, 0x44, 0x05, 0x58, 0x7B, 0x5F, 0x1C, 0x43, 0x60, 0x24, 0x4C, 0x69, 0x55,
+    0x15, 0x7A, 0x67, 0x25, 0x54, 0x42, 0x68, 0x2C, 0x5D, 0x1D, 0x4B, 0x79, 0x6F, 0x2D, 0x65, 0x5C,
+    0x53, 0x4A, 0x41, 0x78, 0xFE, 0x77, 0xC4, 0xA0, 0x06, 0xAF, 0x06, 0x01, 0x0E, 0x03, 0x16, 0x07,
+    0x1E, 0x0F, 0x26, 0x1F, 0x2E, 0x3F, 0x80, 0x81, 0x82, 0x83, 0x84, 0x85, 0x87, 0xFE, 0xF0, 0xC4,
+    0xA0, 0x06, 0x90, 0x91, 0x92, 0x93, 0x94, 0x95, 0xFE, 0x78, 0xC4, 0xA0, 0x06, 0x97, 0xC4, 0xA0,
+    0x06, 0x3E, 0x80, 0x87, 0x06, 0x01, 0x0E, 0x02, 0x16, 0x03, 0x1E, 0x04, 0x26, 0x05, 0x2E, 0x06,
+    0x88, 0x06, 0x80, 0x80, 0x80, 0x89, 0x80, 0x80, 0x8A, 0x80, 0x80, 0x8B, 0x80, 0x80, 0x8C, 0x80,
+    0x80, 0x8D, 0x80, 0x80, 0x8F, 0xFE, 0x37, 0xC4, 0xA0, 0x06, 0x3E, 0x80, 0x87, 0x06, 0x01, 0x98,
+    0x06, 0xFF, 0x80, 0x99, 0x80, 0x9A, 0x80, 0x9B, 0x80, 0x9C, 0x80, 0x9D, 0xFE, 0xE0, 0xC4, 0xA0,
+    0x06, 0x3E, 0x80, 0x87, 0x9F, 0xFE, 0xFF, 0xC4, 0xA0, 0x06, 0x3E, 0xFF, 0x06, 0xFE, 0x0E, 0xFC,
+    0x16, 0xEF, 0x1E, 0x7F, 0x26, 0xF4, 0x2E, 0xBF, 0xA0, 0xA1, 0xA2, 0xA3, 0xA4, 0xA5, 0xA7, 0xFE,
+    0x24, 0xC4, 0xA0, 0x06, 0xAF, 0x06, 0x01, 0x0E, 0x02, 0x16, 0x04, 0x1E, 0x08, 0x26, 0x10, 0x2E,
+    0x20, 0xB0, 0xB1, 0xB2, 0xB3, 0xB4, 0xB5, 0xB7, 0xFE, 0x3F, 0xC4, 0xA0, 0x06, 0x3E, 0x00, 0x26,
+    0x8F, 0x2E, 0x4F, 0xA8, 0xA9, 0xAA, 0xAB, 0xAC, 0xAD, 0xFE, 0xCF, 0xC4, 0xA0, 0x06, 0xAF, 0xC4,
+    0xA0, 0x06, 0x06, 0x44, 0x0E, 0x45, 0x16, 0x46, 0x1E, 0x47, 0x26, 0x06, 0x2E, 0xBF, 0x70, 0x06,
+    0x00, 0x46, 0x3E, 0x44, 0xB8, 0xC4, 0xA0, 0x06, 0x71, 0x0E, 0x00, 0x4E, 0x3E, 0x45, 0xB9, 0xC4,
+    0xA0, 0x06, 0x72, 0x16, 0x00, 0x56, 0x3E, 0x46, 0xBA, 0xC4, 0xA0, 0x06, 0x73, 0x1E, 0x00, 0x5E,
+    0x3E, 0x47, 0xBB, 0xC4, 0xA0, 0x06, 0x74, 0x26, 0x06, 0x2E, 0xBF, 0x66, 0x3E, 0x06, 0xBC, 0xC4,
+    0xA0, 0x06, 0x75, 0x26, 0x06, 0x2E, 0xBF, 0x6E, 0x3E, 0xBF, 0xBD, 0xC4, 0xA0, 0x06, 0x26, 0x06,
+    0x2E, 0xBF, 0x3E, 0x32, 0x77, 0xBE, 0xC4, 0xA0, 0x06, 0x86, 0xFE, 0x64, 0xC4, 0xA0, 0x06, 0xAF,
+    0x7E, 0xFE, 0x32, 0xC4, 0xA0, 0x06, 0x26, 0x06, 0x2E, 0xBF, 0x7E, 0x96, 0xC4, 0xA0, 0x06, 0x3E,
+    0x80, 0x87, 0x8E, 0xFE, 0x33, 0xC4, 0xA0, 0x06, 0x3E, 0x80, 0x87, 0x9E, 0xFE, 0xCD, 0xC4, 0xA0,
+    0x06, 0xA6, 0xC4, 0xA0, 0x06, 0x3E, 0x25, 0xB6, 0xFE, 0x37, 0xC4, 0xA0, 0x06, 0xAE, 0xFE, 0x05,
+    0xC4, 0xA0, 0x06, 0x36, 0x55, 0x34, 0x35, 0x86, 0xFE, 0x5A, 0xC4, 0xA0, 0x06, 0x01, 0xFF, 0x12,
+    0x11, 0xFF, 0x12, 0x21, 0xFF, 0x12, 0x03, 0x13, 0x23, 0x3E, 0x13, 0xB8, 0xC4, 0xA0, 0x06, 0xBA,
+    0xC4, 0xA0, 0x06, 0xBC, 0xC4, 0xA0, 0x06, 0x3E, 0x00, 0xB9, 0xC4, 0xA0, 0x06, 0xBB, 0xC4, 0xA0,
+    0x06, 0xBD, 0xC4, 0xA0, 0x06, 0x0B, 0x1B, 0x2B, 0x3E, 0x12, 0xB8, 0xC4, 0xA0, 0x06, 0xBA, 0xC4,
+    0xA0, 0x06, 0xBC, 0xC4, 0xA0, 0x06, 0x3E, 0xFF, 0xB9, 0xC4, 0xA0, 0x06, 0xBB, 0xC4, 0xA0, 0x06,
+    0xBD, 0xC4, 0xA0, 0x06, 0x32, 0xBF, 0x06, 0xAF, 0x3A, 0xBF, 0x06, 0xFE, 0xFF, 0xC4, 0xA0, 0x06,
+    0x2A, 0xBD, 0x06, 0x22, 0xBF, 0x06, 0x3A, 0xBD, 0x06, 0x47, 0x3A, 0xBF, 0x06, 0xB8, 0xC4, 0xA0,
+    0x06, 0x3A, 0xBE, 0x06, 0x47, 0x3A, 0xC0, 0x06, 0xB8, 0xC4, 0xA0, 0x06, 0x3E, 0xAA, 0x32, 0xBF,
+    0x06, 0x44, 0x4D, 0xAF, 0x0A, 0xFE, 0xAA, 0xC4, 0xA0, 0x06, 0x3C, 0x02, 0x3A, 0xBF, 0x06, 0xFE,
+    0xAB, 0xC4, 0xA0, 0x06, 0x3E, 0x77, 0x32, 0xBF, 0x06, 0x2A, 0xBD, 0x06, 0x11, 0x00, 0x00, 0xEB,
+    0xAF, 0x1A, 0xFE, 0x77, 0xC4, 0xA0, 0x06, 0xAF, 0x84, 0x85, 0xC4, 0xA0, 0x06, 0x3E, 0xCC, 0x12,
+    0x3A, 0xBF, 0x06, 0xFE, 0xCC, 0x12, 0x3A, 0xBF, 0x06, 0xFE, 0xCC, 0xC4, 0xA0, 0x06, 0x21, 0x77,
+    0x77, 0x29, 0x3E, 0xEE, 0xBC, 0xC4, 0xA0, 0x06, 0xBD, 0xC4, 0xA0, 0x06, 0x21, 0x55, 0x55, 0x01,
+    0xFF, 0xFF, 0x09, 0x3E, 0x55, 0xD4, 0xA0, 0x06, 0xBC, 0xC4, 0xA0, 0x06, 0x3E, 0x54, 0xBD, 0xC4,
+    0xA0, 0x06, 0x21, 0xAA, 0xAA, 0x11, 0x33, 0x33, 0x19, 0x3E, 0xDD, 0xBC, 0xC4, 0xA0, 0x06, 0xBD,
+    0xC4, 0xA0, 0x06, 0x37, 0xD4, 0xA0, 0x06, 0x3F, 0xDC, 0xA0, 0x06, 0x3E, 0xAA, 0x2F, 0xFE, 0x55,
+    0xC4, 0xA0, 0x06, 0xB7, 0x27, 0xFE, 0x55, 0xC4, 0xA0, 0x06, 0x3E, 0x88, 0x87, 0x27, 0xFE, 0x76,
+    0xC4, 0xA0, 0x06, 0xAF, 0x3E, 0xAA, 0x27, 0xD4, 0xA0, 0x06, 0xFE, 0x10, 0xC4, 0xA0, 0x06, 0xAF,
+    0x3E, 0x9A, 0x27, 0xD4, 0xA0, 0x06, 0xC4, 0xA0, 0x06, 0x37, 0x3E, 0x42, 0x07, 0xDC, 0xA0, 0x06,
+    0x07, 0xD4, 0xA0, 0x06, 0xFE, 0x09, 0xC4, 0xA0, 0x06, 0x0F, 0xD4, 0xA0, 0x06, 0x0F, 0xFE, 0x42,
+    0xC4, 0xA0, 0x06, 0x17, 0x17, 0xD4, 0xA0, 0x06, 0xFE, 0x08, 0xC4, 0xA0, 0x06, 0x1F, 0x1F, 0xDC,
+    0xA0, 0x06, 0xFE, 0x02, 0xC4, 0xA0, 0x06, 0x01, 0x34, 0x12, 0x11, 0xAA, 0xAA, 0x21, 0x55, 0x55,
+    0xAF, 0xC5, 0xD5, 0xE5, 0xF5, 0x01, 0x00, 0x00, 0x11, 0x00, 0x00, 0x21, 0x00, 0x00, 0x3E, 0xC0,
+    0xC6, 0xF0, 0xF1, 0xE1, 0xD1, 0xC1, 0xDC, 0xA0, 0x06, 0xC4, 0xA0, 0x06, 0xE4, 0xA0, 0x06, 0xFC,
+    0xA0, 0x06, 0x3E, 0x12, 0xB8, 0xC4, 0xA0, 0x06, 0x3E, 0x34, 0xB9, 0xC4, 0xA0, 0x06, 0x3E, 0xAA,
+    0xBA, 0xC4, 0xA0, 0x06, 0xBB, 0xC4, 0xA0, 0x06, 0x3E, 0x55, 0xBC, 0xC4, 0xA0, 0x06, 0xBD, 0xC4,
+    0xA0, 0x06, 0x21, 0x00, 0x00, 0x39, 0x22, 0xC4, 0x06, 0x31, 0xC3, 0x06, 0x3B, 0x3B, 0x33, 0x3B,
+    0x3E, 0x55, 0x32, 0xC1, 0x06, 0x2F, 0x32, 0xC2, 0x06, 0xC1, 0xB8, 0xC4, 0xA0, 0x06, 0x2F, 0xB9,
+    0xC4, 0xA0, 0x06, 0x21, 0xC3, 0x06, 0xF9, 0x21, 0x33, 0x77, 0x3B, 0x3B, 0xE3, 0x3A, 0xC2, 0x06,
+    0xFE, 0x77, 0xC4, 0xA0, 0x06, 0x3A, 0xC1, 0x06, 0xFE, 0x33, 0xC4, 0xA0, 0x06, 0x3E, 0x55, 0xBD,
+    0xC4, 0xA0, 0x06, 0x2F, 0xBC, 0xC4, 0xA0, 0x06, 0x2A, 0xC4, 0x06, 0xF9, 0x21, 0xB4, 0x06, 0xE9,
+    0x21, 0x90, 0x01, 0xCD, 0x4B, 0x01, 0xE1, 0xE5, 0x7C, 0xCD, 0x5A, 0x01, 0xE1, 0x7D, 0xCD, 0x5A,
+    0x01, 0xC3, 0x00, 0x00, 0x21, 0x7A, 0x01, 0xCD, 0x4B, 0x01, 0xC3, 0x00, 0x00, 0xBF, 0x06, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+/// Runs the program as `Cpu::run_with_io` does until the program counter reaches an address for
+/// which `is_breakpoint` returns `true`, the CPU halts, or at least `states` states have elapsed,
+/// returning the number of states taken.
+#[allow(clippy::cognitive_complexity)]
+pub fn run_with_io<I: Io + ?Sized, F: FnMut(u16) -> bool>(
+    cpu: &mut Cpu,
+    memory: &mut Memory,
+    io: &mut I,
+    states: u64,
+    mut is_breakpoint: F,
+) -> u64 {
+    if let Variant::Intel8085 | Variant::Z80 = cpu.variant() {
+        return cpu.run_with_io(memory, io, states, is_breakpoint);
+    }
+    cpu.run_recompiled(|cpu| {
+        let mut elapsed = 0;
+        macro_rules! execute {
+            ($address:expr, $instruction:expr) => {
+                let instruction_states =
+                    cpu.execute_recompiled_instruction($address, $instruction, memory, io);
+                elapsed += u64::from(instruction_states);
+                if elapsed >= states || is_breakpoint(cpu.pc) {
+                    return elapsed;
+                }
+            };
+        }
+        while !cpu.is_halted() {
+            match cpu.pc {
+                0x0100 if is_unchanged(memory, 0x0100, 0x0103) => {
+                    execute!(0x0100, [0xC3, 0xB2, 0x01]); // JMP 01B2H
+                }
+                0x014B if is_unchanged(memory, 0x014B, 0x0152) => {
+                    execute!(0x014B, [0xD5, 0x00, 0x00]); // PUSH D
+                    if !is_unchanged(memory, 0x014C, 0x0152) {
+                        continue;
+                    }
+                    execute!(0x014C, [0xEB, 0x00, 0x00]); // XCHG
+                    execute!(0x014D, [0x0E, 0x09, 0x00]); // MVI C,09H
+                    execute!(0x014F, [0xCD, 0x05, 0x00]); // CALL 0005H
+                }
+                0x0152 if is_unchanged(memory, 0x0152, 0x0154) => {
+                    execute!(0x0152, [0xD1, 0x00, 0x00]); // POP D
+                    execute!(0x0153, [0xC9, 0x00, 0x00]); // RET
+                }
+                0x0154 if is_unchanged(memory, 0x0154, 0x0159) => {
+                    execute!(0x0154, [0x0E, 0x02, 0x00]); // MVI C,02H
+                    execute!(0x0156, [0xCD, 0x05, 0x00]); // CALL 0005H
+                }
+                0x0159 if is_unchanged(memory, 0x0159, 0x015A) => {
+                    execute!(0x0159, [0xC9, 0x00, 0x00]); // RET
+                }
+                0x015A if is_unchanged(memory, 0x015A, 0x015E) => {
+                    execute!(0x015A, [0xF5, 0x00, 0x00]); // PUSH PSW
+                    if !is_unchanged(memory, 0x015B, 0x015E) {
+                        continue;
+                    }
+                    execute!(0x015B, [0xCD, 0x6A, 0x01]); // CALL 016AH
+                }
+                0x015E if is_unchanged(memory, 0x015E, 0x0162) => {
+                    execute!(0x015E, [0x5F, 0x00, 0x00]); // MOV E,A
+                    execute!(0x015F, [0xCD, 0x54, 0x01]); // CALL 0154H
+                }
+                0x0162 if is_unchanged(memory, 0x0162, 0x0166) => {
+                    execute!(0x0162, [0xF1, 0x00, 0x00]); // POP PSW
+                    execute!(0x0163, [0xCD, 0x6E, 0x01]); // CALL 016EH
+                }
+                0x0166 if is_unchanged(memory, 0x0166, 0x016A) => {
+                    execute!(0x0166, [0x5F, 0x00, 0x00]); // MOV E,A
+                    execute!(0x0167, [0xC3, 0x54, 0x01]); // JMP 0154H
+                }
+                0x016A if is_unchanged(memory, 0x016A, 0x016E) => {
+                    execute!(0x016A, [0x0F, 0x00, 0x00]); // RRC
+                    execute!(0x016B, [0x0F, 0x00, 0x00]); // RRC
+                    execute!(0x016C, [0x0F, 0x00, 0x00]); // RRC
+                    execute!(0x016D, [0x0F, 0x00, 0x00]); // RRC
+                }
+                0x016E if is_unchanged(memory, 0x016E, 0x0175) => {
+                    execute!(0x016E, [0xE6, 0x0F, 0x00]); // ANI 0FH
+                    execute!(0x0170, [0xFE, 0x0A, 0x00]); // CPI 0AH
+                    execute!(0x0172, [0xFA, 0x77, 0x01]); // JM 0177H
+                }
+                0x0175 if is_unchanged(memory, 0x0175, 0x0177) => {
+                    execute!(0x0175, [0xC6, 0x07, 0x00]); // ADI 07H
+                }
+                0x0177 if is_unchanged(memory, 0x0177, 0x017A) => {
+                    execute!(0x0177, [0xC6, 0x30, 0x00]); // ADI 30H
+                    execute!(0x0179, [0xC9, 0x00, 0x00]); // RET
+                }
+                0x01B2 if is_unchanged(memory, 0x01B2, 0x01BB) => {
+                    execute!(0x01B2, [0x31, 0xBD, 0x07]); // LXI SP,07BDH
+                    execute!(0x01B5, [0x21, 0x03, 0x01]); // LXI H,0103H
+                    execute!(0x01B8, [0xCD, 0x4B, 0x01]); // CALL 014BH
+                }
+                0x01BB if is_unchanged(memory, 0x01BB, 0x01C0) => {
+                    execute!(0x01BB, [0xE6, 0x00, 0x00]); // ANI 00H
+                    execute!(0x01BD, [0xCA, 0xC3, 0x01]); // JZ 01C3H
+                }
+                0x01C0 if is_unchanged(memory, 0x01C0, 0x01C3) => {
+                    execute!(0x01C0, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x01C3 if is_unchanged(memory, 0x01C3, 0x01C6) => {
+                    execute!(0x01C3, [0xD2, 0xC9, 0x01]); // JNC 01C9H
+                }
+                0x01C6 if is_unchanged(memory, 0x01C6, 0x01C9) => {
+                    execute!(0x01C6, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x01C9 if is_unchanged(memory, 0x01C9, 0x01CC) => {
+                    execute!(0x01C9, [0xEA, 0xCF, 0x01]); // JPE 01CFH
+                }
+                0x01CC if is_unchanged(memory, 0x01CC, 0x01CF) => {
+                    execute!(0x01CC, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x01CF if is_unchanged(memory, 0x01CF, 0x01D2) => {
+                    execute!(0x01CF, [0xF2, 0xD5, 0x01]); // JP 01D5H
+                }
+                0x01D2 if is_unchanged(memory, 0x01D2, 0x01D5) => {
+                    execute!(0x01D2, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x01D5 if is_unchanged(memory, 0x01D5, 0x01D8) => {
+                    execute!(0x01D5, [0xC2, 0xE4, 0x01]); // JNZ 01E4H
+                }
+                0x01D8 if is_unchanged(memory, 0x01D8, 0x01DB) => {
+                    execute!(0x01D8, [0xDA, 0xE4, 0x01]); // JC 01E4H
+                }
+                0x01DB if is_unchanged(memory, 0x01DB, 0x01DE) => {
+                    execute!(0x01DB, [0xE2, 0xE4, 0x01]); // JPO 01E4H
+                }
+                0x01DE if is_unchanged(memory, 0x01DE, 0x01E1) => {
+                    execute!(0x01DE, [0xFA, 0xE4, 0x01]); // JM 01E4H
+                }
+                0x01E1 if is_unchanged(memory, 0x01E1, 0x01E4) => {
+                    execute!(0x01E1, [0xC3, 0xE7, 0x01]); // JMP 01E7H
+                }
+                0x01E4 if is_unchanged(memory, 0x01E4, 0x01E7) => {
+                    execute!(0x01E4, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x01E7 if is_unchanged(memory, 0x01E7, 0x01EC) => {
+                    execute!(0x01E7, [0xC6, 0x06, 0x00]); // ADI 06H
+                    execute!(0x01E9, [0xC2, 0xEF, 0x01]); // JNZ 01EFH
+                }
+                0x01EC if is_unchanged(memory, 0x01EC, 0x01EF) => {
+                    execute!(0x01EC, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x01EF if is_unchanged(memory, 0x01EF, 0x01F2) => {
+                    execute!(0x01EF, [0xDA, 0xF8, 0x01]); // JC 01F8H
+                }
+                0x01F2 if is_unchanged(memory, 0x01F2, 0x01F5) => {
+                    execute!(0x01F2, [0xE2, 0xF8, 0x01]); // JPO 01F8H
+                }
+                0x01F5 if is_unchanged(memory, 0x01F5, 0x01F8) => {
+                    execute!(0x01F5, [0xF2, 0xFB, 0x01]); // JP 01FBH
+                }
+                0x01F8 if is_unchanged(memory, 0x01F8, 0x01FB) => {
+                    execute!(0x01F8, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x01FB if is_unchanged(memory, 0x01FB, 0x0200) => {
+                    execute!(0x01FB, [0xC6, 0x70, 0x00]); // ADI 70H
+                    execute!(0x01FD, [0xE2, 0x03, 0x02]); // JPO 0203H
+                }
+                0x0200 if is_unchanged(memory, 0x0200, 0x0203) => {
+                    execute!(0x0200, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x0203 if is_unchanged(memory, 0x0203, 0x0206) => {
+                    execute!(0x0203, [0xFA, 0x0C, 0x02]); // JM 020CH
+                }
+                0x0206 if is_unchanged(memory, 0x0206, 0x0209) => {
+                    execute!(0x0206, [0xCA, 0x0C, 0x02]); // JZ 020CH
+                }
+                0x0209 if is_unchanged(memory, 0x0209, 0x020C) => {
+                    execute!(0x0209, [0xD2, 0x0F, 0x02]); // JNC 020FH
+                }
+                0x020C if is_unchanged(memory, 0x020C, 0x020F) => {
+                    execute!(0x020C, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x020F if is_unchanged(memory, 0x020F, 0x0214) => {
+                    execute!(0x020F, [0xC6, 0x81, 0x00]); // ADI 81H
+                    execute!(0x0211, [0xFA, 0x17, 0x02]); // JM 0217H
+                }
+                0x0214 if is_unchanged(memory, 0x0214, 0x0217) => {
+                    execute!(0x0214, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x0217 if is_unchanged(memory, 0x0217, 0x021A) => {
+                    execute!(0x0217, [0xCA, 0x20, 0x02]); // JZ 0220H
+                }
+                0x021A if is_unchanged(memory, 0x021A, 0x021D) => {
+                    execute!(0x021A, [0xDA, 0x20, 0x02]); // JC 0220H
+                }
+                0x021D if is_unchanged(memory, 0x021D, 0x0220) => {
+                    execute!(0x021D, [0xE2, 0x23, 0x02]); // JPO 0223H
+                }
+                0x0220 if is_unchanged(memory, 0x0220, 0x0223) => {
+                    execute!(0x0220, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x0223 if is_unchanged(memory, 0x0223, 0x0228) => {
+                    execute!(0x0223, [0xC6, 0xFE, 0x00]); // ADI 0FEH
+                    execute!(0x0225, [0xDA, 0x2B, 0x02]); // JC 022BH
+                }
+                0x0228 if is_unchanged(memory, 0x0228, 0x022B) => {
+                    execute!(0x0228, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x022B if is_unchanged(memory, 0x022B, 0x022E) => {
+                    execute!(0x022B, [0xCA, 0x34, 0x02]); // JZ 0234H
+                }
+                0x022E if is_unchanged(memory, 0x022E, 0x0231) => {
+                    execute!(0x022E, [0xE2, 0x34, 0x02]); // JPO 0234H
+                }
+                0x0231 if is_unchanged(memory, 0x0231, 0x0234) => {
+                    execute!(0x0231, [0xFA, 0x37, 0x02]); // JM 0237H
+                }
+                0x0234 if is_unchanged(memory, 0x0234, 0x0237) => {
+                    execute!(0x0234, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x0237 if is_unchanged(memory, 0x0237, 0x023C) => {
+                    execute!(0x0237, [0xFE, 0x00, 0x00]); // CPI 00H
+                    execute!(0x0239, [0xDA, 0x4F, 0x02]); // JC 024FH
+                }
+                0x023C if is_unchanged(memory, 0x023C, 0x023F) => {
+                    execute!(0x023C, [0xCA, 0x4F, 0x02]); // JZ 024FH
+                }
+                0x023F if is_unchanged(memory, 0x023F, 0x0244) => {
+                    execute!(0x023F, [0xFE, 0xF5, 0x00]); // CPI 0F5H
+                    execute!(0x0241, [0xDA, 0x4F, 0x02]); // JC 024FH
+                }
+                0x0244 if is_unchanged(memory, 0x0244, 0x0247) => {
+                    execute!(0x0244, [0xC2, 0x4F, 0x02]); // JNZ 024FH
+                }
+                0x0247 if is_unchanged(memory, 0x0247, 0x024C) => {
+                    execute!(0x0247, [0xFE, 0xFF, 0x00]); // CPI 0FFH
+                    execute!(0x0249, [0xCA, 0x4F, 0x02]); // JZ 024FH
+                }
+                0x024C if is_unchanged(memory, 0x024C, 0x024F) => {
+                    execute!(0x024C, [0xDA, 0x52, 0x02]); // JC 0252H
+                }
+                0x024F if is_unchanged(memory, 0x024F, 0x0252) => {
+                    execute!(0x024F, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x0252 if is_unchanged(memory, 0x0252, 0x025B) => {
+                    execute!(0x0252, [0xCE, 0x0A, 0x00]); // ACI 0AH
+                    execute!(0x0254, [0xCE, 0x0A, 0x00]); // ACI 0AH
+                    execute!(0x0256, [0xFE, 0x0B, 0x00]); // CPI 0BH
+                    execute!(0x0258, [0xCA, 0x5E, 0x02]); // JZ 025EH
+                }
+                0x025B if is_unchanged(memory, 0x025B, 0x025E) => {
+                    execute!(0x025B, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x025E if is_unchanged(memory, 0x025E, 0x0267) => {
+                    execute!(0x025E, [0xD6, 0x0C, 0x00]); // SUI 0CH
+                    execute!(0x0260, [0xD6, 0x0F, 0x00]); // SUI 0FH
+                    execute!(0x0262, [0xFE, 0xF0, 0x00]); // CPI 0F0H
+                    execute!(0x0264, [0xCA, 0x6A, 0x02]); // JZ 026AH
+                }
+                0x0267 if is_unchanged(memory, 0x0267, 0x026A) => {
+                    execute!(0x0267, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x026A if is_unchanged(memory, 0x026A, 0x0273) => {
+                    execute!(0x026A, [0xDE, 0xF1, 0x00]); // SBI 0F1H
+                    execute!(0x026C, [0xDE, 0x0E, 0x00]); // SBI 0EH
+                    execute!(0x026E, [0xFE, 0xF0, 0x00]); // CPI 0F0H
+                    execute!(0x0270, [0xCA, 0x76, 0x02]); // JZ 0276H
+                }
+                0x0273 if is_unchanged(memory, 0x0273, 0x0276) => {
+                    execute!(0x0273, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x0276 if is_unchanged(memory, 0x0276, 0x027D) => {
+                    execute!(0x0276, [0xE6, 0x55, 0x00]); // ANI 55H
+                    execute!(0x0278, [0xFE, 0x50, 0x00]); // CPI 50H
+                    execute!(0x027A, [0xCA, 0x80, 0x02]); // JZ 0280H
+                }
+                0x027D if is_unchanged(memory, 0x027D, 0x0280) => {
+                    execute!(0x027D, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x0280 if is_unchanged(memory, 0x0280, 0x0287) => {
+                    execute!(0x0280, [0xF6, 0x3A, 0x00]); // ORI 3AH
+                    execute!(0x0282, [0xFE, 0x7A, 0x00]); // CPI 7AH
+                    execute!(0x0284, [0xCA, 0x8A, 0x02]); // JZ 028AH
+                }
+                0x0287 if is_unchanged(memory, 0x0287, 0x028A) => {
+                    execute!(0x0287, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x028A if is_unchanged(memory, 0x028A, 0x0291) => {
+                    execute!(0x028A, [0xEE, 0x0F, 0x00]); // XRI 0FH
+                    execute!(0x028C, [0xFE, 0x75, 0x00]); // CPI 75H
+                    execute!(0x028E, [0xCA, 0x94, 0x02]); // JZ 0294H
+                }
+                0x0291 if is_unchanged(memory, 0x0291, 0x0294) => {
+                    execute!(0x0291, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x0294 if is_unchanged(memory, 0x0294, 0x0299) => {
+                    execute!(0x0294, [0xE6, 0x00, 0x00]); // ANI 00H
+                    execute!(0x0296, [0xDC, 0xA0, 0x06]); // CC 06A0H
+                }
+                0x0299 if is_unchanged(memory, 0x0299, 0x029C) => {
+                    execute!(0x0299, [0xE4, 0xA0, 0x06]); // CPO 06A0H
+                }
+                0x029C if is_unchanged(memory, 0x029C, 0x029F) => {
+                    execute!(0x029C, [0xFC, 0xA0, 0x06]); // CM 06A0H
+                }
+                0x029F if is_unchanged(memory, 0x029F, 0x02A2) => {
+                    execute!(0x029F, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x02A2 if is_unchanged(memory, 0x02A2, 0x02A7) => {
+                    execute!(0x02A2, [0xFE, 0x00, 0x00]); // CPI 00H
+                    execute!(0x02A4, [0xCA, 0xAA, 0x02]); // JZ 02AAH
+                }
+                0x02A7 if is_unchanged(memory, 0x02A7, 0x02AA) => {
+                    execute!(0x02A7, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x02AA if is_unchanged(memory, 0x02AA, 0x02AF) => {
+                    execute!(0x02AA, [0xD6, 0x77, 0x00]); // SUI 77H
+                    execute!(0x02AC, [0xD4, 0xA0, 0x06]); // CNC 06A0H
+                }
+                0x02AF if is_unchanged(memory, 0x02AF, 0x02B2) => {
+                    execute!(0x02AF, [0xEC, 0xA0, 0x06]); // CPE 06A0H
+                }
+                0x02B2 if is_unchanged(memory, 0x02B2, 0x02B5) => {
+                    execute!(0x02B2, [0xF4, 0xA0, 0x06]); // CP 06A0H
+                }
+                0x02B5 if is_unchanged(memory, 0x02B5, 0x02B8) => {
+                    execute!(0x02B5, [0xCC, 0xA0, 0x06]); // CZ 06A0H
+                }
+                0x02B8 if is_unchanged(memory, 0x02B8, 0x02BD) => {
+                    execute!(0x02B8, [0xFE, 0x89, 0x00]); // CPI 89H
+                    execute!(0x02BA, [0xCA, 0xC0, 0x02]); // JZ 02C0H
+                }
+                0x02BD if is_unchanged(memory, 0x02BD, 0x02C0) => {
+                    execute!(0x02BD, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x02C0 if is_unchanged(memory, 0x02C0, 0x02C5) => {
+                    execute!(0x02C0, [0xE6, 0xFF, 0x00]); // ANI 0FFH
+                    execute!(0x02C2, [0xE4, 0xCD, 0x02]); // CPO 02CDH
+                }
+                0x02C5 if is_unchanged(memory, 0x02C5, 0x02CA) => {
+                    execute!(0x02C5, [0xFE, 0xD9, 0x00]); // CPI 0D9H
+                    execute!(0x02C7, [0xCA, 0x2A, 0x03]); // JZ 032AH
+                }
+                0x02CA if is_unchanged(memory, 0x02CA, 0x02CD) => {
+                    execute!(0x02CA, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x02CD if is_unchanged(memory, 0x02CD, 0x02CE) => {
+                    execute!(0x02CD, [0xE8, 0x00, 0x00]); // RPE
+                }
+                0x02CE if is_unchanged(memory, 0x02CE, 0x02D3) => {
+                    execute!(0x02CE, [0xC6, 0x10, 0x00]); // ADI 10H
+                    execute!(0x02D0, [0xEC, 0xD9, 0x02]); // CPE 02D9H
+                }
+                0x02D3 if is_unchanged(memory, 0x02D3, 0x02D6) => {
+                    execute!(0x02D3, [0xC6, 0x02, 0x00]); // ADI 02H
+                    execute!(0x02D5, [0xE0, 0x00, 0x00]); // RPO
+                }
+                0x02D6 if is_unchanged(memory, 0x02D6, 0x02D9) => {
+                    execute!(0x02D6, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x02D9 if is_unchanged(memory, 0x02D9, 0x02DA) => {
+                    execute!(0x02D9, [0xE0, 0x00, 0x00]); // RPO
+                }
+                0x02DA if is_unchanged(memory, 0x02DA, 0x02DF) => {
+                    execute!(0x02DA, [0xC6, 0x20, 0x00]); // ADI 20H
+                    execute!(0x02DC, [0xFC, 0xE5, 0x02]); // CM 02E5H
+                }
+                0x02DF if is_unchanged(memory, 0x02DF, 0x02E2) => {
+                    execute!(0x02DF, [0xC6, 0x04, 0x00]); // ADI 04H
+                    execute!(0x02E1, [0xE8, 0x00, 0x00]); // RPE
+                }
+                0x02E2 if is_unchanged(memory, 0x02E2, 0x02E5) => {
+                    execute!(0x02E2, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x02E5 if is_unchanged(memory, 0x02E5, 0x02E6) => {
+                    execute!(0x02E5, [0xF0, 0x00, 0x00]); // RP
+                }
+                0x02E6 if is_unchanged(memory, 0x02E6, 0x02EB) => {
+                    execute!(0x02E6, [0xC6, 0x80, 0x00]); // ADI 80H
+                    execute!(0x02E8, [0xF4, 0xF1, 0x02]); // CP 02F1H
+                }
+                0x02EB if is_unchanged(memory, 0x02EB, 0x02EE) => {
+                    execute!(0x02EB, [0xC6, 0x80, 0x00]); // ADI 80H
+                    execute!(0x02ED, [0xF8, 0x00, 0x00]); // RM
+                }
+                0x02EE if is_unchanged(memory, 0x02EE, 0x02F1) => {
+                    execute!(0x02EE, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x02F1 if is_unchanged(memory, 0x02F1, 0x02F2) => {
+                    execute!(0x02F1, [0xF8, 0x00, 0x00]); // RM
+                }
+                0x02F2 if is_unchanged(memory, 0x02F2, 0x02F7) => {
+                    execute!(0x02F2, [0xC6, 0x40, 0x00]); // ADI 40H
+                    execute!(0x02F4, [0xD4, 0xFD, 0x02]); // CNC 02FDH
+                }
+                0x02F7 if is_unchanged(memory, 0x02F7, 0x02FA) => {
+                    execute!(0x02F7, [0xC6, 0x40, 0x00]); // ADI 40H
+                    execute!(0x02F9, [0xF0, 0x00, 0x00]); // RP
+                }
+                0x02FA if is_unchanged(memory, 0x02FA, 0x02FD) => {
+                    execute!(0x02FA, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x02FD if is_unchanged(memory, 0x02FD, 0x02FE) => {
+                    execute!(0x02FD, [0xD8, 0x00, 0x00]); // RC
+                }
+                0x02FE if is_unchanged(memory, 0x02FE, 0x0303) => {
+                    execute!(0x02FE, [0xC6, 0x8F, 0x00]); // ADI 8FH
+                    execute!(0x0300, [0xDC, 0x09, 0x03]); // CC 0309H
+                }
+                0x0303 if is_unchanged(memory, 0x0303, 0x0306) => {
+                    execute!(0x0303, [0xD6, 0x02, 0x00]); // SUI 02H
+                    execute!(0x0305, [0xD0, 0x00, 0x00]); // RNC
+                }
+                0x0306 if is_unchanged(memory, 0x0306, 0x0309) => {
+                    execute!(0x0306, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x0309 if is_unchanged(memory, 0x0309, 0x030A) => {
+                    execute!(0x0309, [0xD0, 0x00, 0x00]); // RNC
+                }
+                0x030A if is_unchanged(memory, 0x030A, 0x030F) => {
+                    execute!(0x030A, [0xC6, 0xF7, 0x00]); // ADI 0F7H
+                    execute!(0x030C, [0xC4, 0x15, 0x03]); // CNZ 0315H
+                }
+                0x030F if is_unchanged(memory, 0x030F, 0x0312) => {
+                    execute!(0x030F, [0xC6, 0xFE, 0x00]); // ADI 0FEH
+                    execute!(0x0311, [0xD8, 0x00, 0x00]); // RC
+                }
+                0x0312 if is_unchanged(memory, 0x0312, 0x0315) => {
+                    execute!(0x0312, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x0315 if is_unchanged(memory, 0x0315, 0x0316) => {
+                    execute!(0x0315, [0xC8, 0x00, 0x00]); // RZ
+                }
+                0x0316 if is_unchanged(memory, 0x0316, 0x031B) => {
+                    execute!(0x0316, [0xC6, 0x01, 0x00]); // ADI 01H
+                    execute!(0x0318, [0xCC, 0x21, 0x03]); // CZ 0321H
+                }
+                0x031B if is_unchanged(memory, 0x031B, 0x031E) => {
+                    execute!(0x031B, [0xC6, 0xD0, 0x00]); // ADI 0D0H
+                    execute!(0x031D, [0xC0, 0x00, 0x00]); // RNZ
+                }
+                0x031E if is_unchanged(memory, 0x031E, 0x0321) => {
+                    execute!(0x031E, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x0321 if is_unchanged(memory, 0x0321, 0x0322) => {
+                    execute!(0x0321, [0xC0, 0x00, 0x00]); // RNZ
+                }
+                0x0322 if is_unchanged(memory, 0x0322, 0x0327) => {
+                    execute!(0x0322, [0xC6, 0x47, 0x00]); // ADI 47H
+                    execute!(0x0324, [0xFE, 0x47, 0x00]); // CPI 47H
+                    execute!(0x0326, [0xC8, 0x00, 0x00]); // RZ
+                }
+                0x0327 if is_unchanged(memory, 0x0327, 0x032A) => {
+                    execute!(0x0327, [0xCD, 0xA0, 0x06]); // CALL 06A0H
+                }
+                0x032A if is_unchanged(memory, 0x032A, 0x0369) => {
+                    execute!(0x032A, [0x3E, 0x77, 0x00]); // MVI A,77H
+                    execute!(0x032C, [0x3C, 0x00, 0x00]); // INR A
+                    execute!(0x032D, [0x47, 0x00, 0x00]); // MOV B,A
+                    execute!(0x032E, [0x04, 0x00, 0x00]); // INR B
+                    execute!(0x032F, [0x48, 0x00, 0x00]); // MOV C,B
+                    execute!(0x0330, [0x0D, 0x00, 0x00]); // DCR C
+                    execute!(0x0331, [0x51, 0x00, 0x00]); // MOV D,C
+                    execute!(0x0332, [0x5A, 0x00, 0x00]); // MOV E,D
+                    execute!(0x0333, [0x63, 0x00, 0x00]); // MOV H,E
+                    execute!(0x0334, [0x6C, 0x00, 0x00]); // MOV L,H
+                    execute!(0x0335, [0x7D, 0x00, 0x00]); // MOV A,L
+                    execute!(0x0336, [0x3D, 0x00, 0x00]); // DCR A
+                    execute!(0x0337, [0x4F, 0x00, 0x00]); // MOV C,A
+                    execute!(0x0338, [0x59, 0x00, 0x00]); // MOV E,C
+                    execute!(0x0339, [0x6B, 0x00, 0x00]); // MOV L,E
+                    execute!(0x033A, [0x45, 0x00, 0x00]); // MOV B,L
+                    execute!(0x033B, [0x50, 0x00, 0x00]); // MOV D,B
+                    execute!(0x033C, [0x62, 0x00, 0x00]); // MOV H,D
+                    execute!(0x033D, [0x7C, 0x00, 0x00]); // MOV A,H
+                    execute!(0x033E, [0x57, 0x00, 0x00]); // MOV D,A
+                    execute!(0x033F, [0x14, 0x00, 0x00]); // INR D
+                    execute!(0x0340, [0x6A, 0x00, 0x00]); // MOV L,D
+                    execute!(0x0341, [0x4D, 0x00, 0x00]); // MOV C,L
+                    execute!(0x0342, [0x0C, 0x00, 0x00]); // INR C
+                    execute!(0x0343, [0x61, 0x00, 0x00]); // MOV H,C
+                    execute!(0x0344, [0x44, 0x00, 0x00]); // MOV B,H
+                    execute!(0x0345, [0x05, 0x00, 0x00]); // DCR B
+                    execute!(0x0346, [0x58, 0x00, 0x00]); // MOV E,B
+                    execute!(0x0347, [0x7B, 0x00, 0x00]); // MOV A,E
+                    execute!(0x0348, [0x5F, 0x00, 0x00]); // MOV E,A
+                    execute!(0x0349, [0x1C, 0x00, 0x00]); // INR E
+                    execute!(0x034A, [0x43, 0x00, 0x00]); // MOV B,E
+                    execute!(0x034B, [0x60, 0x00, 0x00]); // MOV H,B
+                    execute!(0x034C, [0x24, 0x00, 0x00]); // INR H
+                    execute!(0x034D, [0x4C, 0x00, 0x00]); // MOV C,H
+                    execute!(0x034E, [0x69, 0x00, 0x00]); // MOV L,C
+                    execute!(0x034F, [0x55, 0x00, 0x00]); // MOV D,L
+                    execute!(0x0350, [0x15, 0x00, 0x00]); // DCR D
+                    execute!(0x0351, [0x7A, 0x00, 0x00]); // MOV A,D
+                    execute!(0x0352, [0x67, 0x00, 0x00]); // MOV H,A
+                    execute!(0x0353, [0x25, 0x00, 0x00]); // DCR H
+                    execute!(0x0354, [0x54, 0x00, 0x00]); // MOV D,H
+                    execute!(0x0355, [0x42, 0x00, 0x00]); // MOV B,D
+                    execute!(0x0356, [0x68, 0x00, 0x00]); // MOV L,B
+                    execute!(0x0357, [0x2C, 0x00, 0x00]); // INR L
+                    execute!(0x0358, [0x5D, 0x00, 0x00]); // MOV E,L
+                    execute!(0x0359, [0x1D, 0x00, 0x00]); // DCR E
+                    execute!(0x035A, [0x4B, 0x00, 0x00]); // MOV C,E
+                    execute!(0x035B, [0x79, 0x00, 0x00]); // MOV A,C
+                    execute!(0x035C, [0x6F, 0x00, 0x00]); // MOV L,A
+                    execute!(0x035D, [0x2D, 0x00, 0x00]); // DCR L
+                    execute!(0x035E, [0x65, 0x00, 0x00]); // MOV H,L
+                    execute!(0x035F, [0x5C, 0x00, 0x00]); // MOV E,H
+                    execute!(0x0360, [0x53, 0x00, 0x00]); // MOV D,E
+                    execute!(0x0361, [0x4A, 0x00, 0x00]); // MOV C,D
+                    execute!(0x0362, [0x41, 0x00, 0x00]); // MOV B,C
+                    execute!(0x0363, [0x78, 0x00, 0x00]); // MOV A,B
+                    execute!(0x0364, [0xFE, 0x77, 0x00]); // CPI 77H
+                    execute!(0x0366, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0369 if is_unchanged(memory, 0x0369, 0x0382) => {
+                    execute!(0x0369, [0xAF, 0x00, 0x00]); // XRA A
+                    execute!(0x036A, [0x06, 0x01, 0x00]); // MVI B,01H
+                    execute!(0x036C, [0x0E, 0x03, 0x00]); // MVI C,03H
+                    execute!(0x036E, [0x16, 0x07, 0x00]); // MVI D,07H
+                    execute!(0x0370, [0x1E, 0x0F, 0x00]); // MVI E,0FH
+                    execute!(0x0372, [0x26, 0x1F, 0x00]); // MVI H,1FH
+                    execute!(0x0374, [0x2E, 0x3F, 0x00]); // MVI L,3FH
+                    execute!(0x0376, [0x80, 0x00, 0x00]); // ADD B
+                    execute!(0x0377, [0x81, 0x00, 0x00]); // ADD C
+                    execute!(0x0378, [0x82, 0x00, 0x00]); // ADD D
+                    execute!(0x0379, [0x83, 0x00, 0x00]); // ADD E
+                    execute!(0x037A, [0x84, 0x00, 0x00]); // ADD H
+                    execute!(0x037B, [0x85, 0x00, 0x00]); // ADD L
+                    execute!(0x037C, [0x87, 0x00, 0x00]); // ADD A
+                    execute!(0x037D, [0xFE, 0xF0, 0x00]); // CPI 0F0H
+                    execute!(0x037F, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0382 if is_unchanged(memory, 0x0382, 0x038D) => {
+                    execute!(0x0382, [0x90, 0x00, 0x00]); // SUB B
+                    execute!(0x0383, [0x91, 0x00, 0x00]); // SUB C
+                    execute!(0x0384, [0x92, 0x00, 0x00]); // SUB D
+                    execute!(0x0385, [0x93, 0x00, 0x00]); // SUB E
+                    execute!(0x0386, [0x94, 0x00, 0x00]); // SUB H
+                    execute!(0x0387, [0x95, 0x00, 0x00]); // SUB L
+                    execute!(0x0388, [0xFE, 0x78, 0x00]); // CPI 78H
+                    execute!(0x038A, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x038D if is_unchanged(memory, 0x038D, 0x0391) => {
+                    execute!(0x038D, [0x97, 0x00, 0x00]); // SUB A
+                    execute!(0x038E, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0391 if is_unchanged(memory, 0x0391, 0x03BA) => {
+                    execute!(0x0391, [0x3E, 0x80, 0x00]); // MVI A,80H
+                    execute!(0x0393, [0x87, 0x00, 0x00]); // ADD A
+                    execute!(0x0394, [0x06, 0x01, 0x00]); // MVI B,01H
+                    execute!(0x0396, [0x0E, 0x02, 0x00]); // MVI C,02H
+                    execute!(0x0398, [0x16, 0x03, 0x00]); // MVI D,03H
+                    execute!(0x039A, [0x1E, 0x04, 0x00]); // MVI E,04H
+                    execute!(0x039C, [0x26, 0x05, 0x00]); // MVI H,05H
+                    execute!(0x039E, [0x2E, 0x06, 0x00]); // MVI L,06H
+                    execute!(0x03A0, [0x88, 0x00, 0x00]); // ADC B
+                    execute!(0x03A1, [0x06, 0x80, 0x00]); // MVI B,80H
+                    execute!(0x03A3, [0x80, 0x00, 0x00]); // ADD B
+                    execute!(0x03A4, [0x80, 0x00, 0x00]); // ADD B
+                    execute!(0x03A5, [0x89, 0x00, 0x00]); // ADC C
+                    execute!(0x03A6, [0x80, 0x00, 0x00]); // ADD B
+                    execute!(0x03A7, [0x80, 0x00, 0x00]); // ADD B
+                    execute!(0x03A8, [0x8A, 0x00, 0x00]); // ADC D
+                    execute!(0x03A9, [0x80, 0x00, 0x00]); // ADD B
+                    execute!(0x03AA, [0x80, 0x00, 0x00]); // ADD B
+                    execute!(0x03AB, [0x8B, 0x00, 0x00]); // ADC E
+                    execute!(0x03AC, [0x80, 0x00, 0x00]); // ADD B
+                    execute!(0x03AD, [0x80, 0x00, 0x00]); // ADD B
+                    execute!(0x03AE, [0x8C, 0x00, 0x00]); // ADC H
+                    execute!(0x03AF, [0x80, 0x00, 0x00]); // ADD B
+                    execute!(0x03B0, [0x80, 0x00, 0x00]); // ADD B
+                    execute!(0x03B1, [0x8D, 0x00, 0x00]); // ADC L
+                    execute!(0x03B2, [0x80, 0x00, 0x00]); // ADD B
+                    execute!(0x03B3, [0x80, 0x00, 0x00]); // ADD B
+                    execute!(0x03B4, [0x8F, 0x00, 0x00]); // ADC A
+                    execute!(0x03B5, [0xFE, 0x37, 0x00]); // CPI 37H
+                    execute!(0x03B7, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x03BA if is_unchanged(memory, 0x03BA, 0x03D1) => {
+                    execute!(0x03BA, [0x3E, 0x80, 0x00]); // MVI A,80H
+                    execute!(0x03BC, [0x87, 0x00, 0x00]); // ADD A
+                    execute!(0x03BD, [0x06, 0x01, 0x00]); // MVI B,01H
+                    execute!(0x03BF, [0x98, 0x00, 0x00]); // SBB B
+                    execute!(0x03C0, [0x06, 0xFF, 0x00]); // MVI B,0FFH
+                    execute!(0x03C2, [0x80, 0x00, 0x00]); // ADD B
+                    execute!(0x03C3, [0x99, 0x00, 0x00]); // SBB C
+                    execute!(0x03C4, [0x80, 0x00, 0x00]); // ADD B
+                    execute!(0x03C5, [0x9A, 0x00, 0x00]); // SBB D
+                    execute!(0x03C6, [0x80, 0x00, 0x00]); // ADD B
+                    execute!(0x03C7, [0x9B, 0x00, 0x00]); // SBB E
+                    execute!(0x03C8, [0x80, 0x00, 0x00]); // ADD B
+                    execute!(0x03C9, [0x9C, 0x00, 0x00]); // SBB H
+                    execute!(0x03CA, [0x80, 0x00, 0x00]); // ADD B
+                    execute!(0x03CB, [0x9D, 0x00, 0x00]); // SBB L
+                    execute!(0x03CC, [0xFE, 0xE0, 0x00]); // CPI 0E0H
+                    execute!(0x03CE, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x03D1 if is_unchanged(memory, 0x03D1, 0x03DA) => {
+                    execute!(0x03D1, [0x3E, 0x80, 0x00]); // MVI A,80H
+                    execute!(0x03D3, [0x87, 0x00, 0x00]); // ADD A
+                    execute!(0x03D4, [0x9F, 0x00, 0x00]); // SBB A
+                    execute!(0x03D5, [0xFE, 0xFF, 0x00]); // CPI 0FFH
+                    execute!(0x03D7, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x03DA if is_unchanged(memory, 0x03DA, 0x03F4) => {
+                    execute!(0x03DA, [0x3E, 0xFF, 0x00]); // MVI A,0FFH
+                    execute!(0x03DC, [0x06, 0xFE, 0x00]); // MVI B,0FEH
+                    execute!(0x03DE, [0x0E, 0xFC, 0x00]); // MVI C,0FCH
+                    execute!(0x03E0, [0x16, 0xEF, 0x00]); // MVI D,0EFH
+                    execute!(0x03E2, [0x1E, 0x7F, 0x00]); // MVI E,7FH
+                    execute!(0x03E4, [0x26, 0xF4, 0x00]); // MVI H,0F4H
+                    execute!(0x03E6, [0x2E, 0xBF, 0x00]); // MVI L,0BFH
+                    execute!(0x03E8, [0xA0, 0x00, 0x00]); // ANA B
+                    execute!(0x03E9, [0xA1, 0x00, 0x00]); // ANA C
+                    execute!(0x03EA, [0xA2, 0x00, 0x00]); // ANA D
+                    execute!(0x03EB, [0xA3, 0x00, 0x00]); // ANA E
+                    execute!(0x03EC, [0xA4, 0x00, 0x00]); // ANA H
+                    execute!(0x03ED, [0xA5, 0x00, 0x00]); // ANA L
+                    execute!(0x03EE, [0xA7, 0x00, 0x00]); // ANA A
+                    execute!(0x03EF, [0xFE, 0x24, 0x00]); // CPI 24H
+                    execute!(0x03F1, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x03F4 if is_unchanged(memory, 0x03F4, 0x040D) => {
+                    execute!(0x03F4, [0xAF, 0x00, 0x00]); // XRA A
+                    execute!(0x03F5, [0x06, 0x01, 0x00]); // MVI B,01H
+                    execute!(0x03F7, [0x0E, 0x02, 0x00]); // MVI C,02H
+                    execute!(0x03F9, [0x16, 0x04, 0x00]); // MVI D,04H
+                    execute!(0x03FB, [0x1E, 0x08, 0x00]); // MVI E,08H
+                    execute!(0x03FD, [0x26, 0x10, 0x00]); // MVI H,10H
+                    execute!(0x03FF, [0x2E, 0x20, 0x00]); // MVI L,20H
+                    execute!(0x0401, [0xB0, 0x00, 0x00]); // ORA B
+                    execute!(0x0402, [0xB1, 0x00, 0x00]); // ORA C
+                    execute!(0x0403, [0xB2, 0x00, 0x00]); // ORA D
+                    execute!(0x0404, [0xB3, 0x00, 0x00]); // ORA E
+                    execute!(0x0405, [0xB4, 0x00, 0x00]); // ORA H
+                    execute!(0x0406, [0xB5, 0x00, 0x00]); // ORA L
+                    execute!(0x0407, [0xB7, 0x00, 0x00]); // ORA A
+                    execute!(0x0408, [0xFE, 0x3F, 0x00]); // CPI 3FH
+                    execute!(0x040A, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x040D if is_unchanged(memory, 0x040D, 0x041E) => {
+                    execute!(0x040D, [0x3E, 0x00, 0x00]); // MVI A,00H
+                    execute!(0x040F, [0x26, 0x8F, 0x00]); // MVI H,8FH
+                    execute!(0x0411, [0x2E, 0x4F, 0x00]); // MVI L,4FH
+                    execute!(0x0413, [0xA8, 0x00, 0x00]); // XRA B
+                    execute!(0x0414, [0xA9, 0x00, 0x00]); // XRA C
+                    execute!(0x0415, [0xAA, 0x00, 0x00]); // XRA D
+                    execute!(0x0416, [0xAB, 0x00, 0x00]); // XRA E
+                    execute!(0x0417, [0xAC, 0x00, 0x00]); // XRA H
+                    execute!(0x0418, [0xAD, 0x00, 0x00]); // XRA L
+                    execute!(0x0419, [0xFE, 0xCF, 0x00]); // CPI 0CFH
+                    execute!(0x041B, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x041E if is_unchanged(memory, 0x041E, 0x0422) => {
+                    execute!(0x041E, [0xAF, 0x00, 0x00]); // XRA A
+                    execute!(0x041F, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0422 if is_unchanged(memory, 0x0422, 0x0438) => {
+                    execute!(0x0422, [0x06, 0x44, 0x00]); // MVI B,44H
+                    execute!(0x0424, [0x0E, 0x45, 0x00]); // MVI C,45H
+                    execute!(0x0426, [0x16, 0x46, 0x00]); // MVI D,46H
+                    execute!(0x0428, [0x1E, 0x47, 0x00]); // MVI E,47H
+                    execute!(0x042A, [0x26, 0x06, 0x00]); // MVI H,06H
+                    execute!(0x042C, [0x2E, 0xBF, 0x00]); // MVI L,0BFH
+                    execute!(0x042E, [0x70, 0x00, 0x00]); // MOV M,B
+                    if !is_unchanged(memory, 0x042F, 0x0438) {
+                        continue;
+                    }
+                    execute!(0x042F, [0x06, 0x00, 0x00]); // MVI B,00H
+                    execute!(0x0431, [0x46, 0x00, 0x00]); // MOV B,M
+                    execute!(0x0432, [0x3E, 0x44, 0x00]); // MVI A,44H
+                    execute!(0x0434, [0xB8, 0x00, 0x00]); // CMP B
+                    execute!(0x0435, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0438 if is_unchanged(memory, 0x0438, 0x0442) => {
+                    execute!(0x0438, [0x71, 0x00, 0x00]); // MOV M,C
+                    if !is_unchanged(memory, 0x0439, 0x0442) {
+                        continue;
+                    }
+                    execute!(0x0439, [0x0E, 0x00, 0x00]); // MVI C,00H
+                    execute!(0x043B, [0x4E, 0x00, 0x00]); // MOV C,M
+                    execute!(0x043C, [0x3E, 0x45, 0x00]); // MVI A,45H
+                    execute!(0x043E, [0xB9, 0x00, 0x00]); // CMP C
+                    execute!(0x043F, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0442 if is_unchanged(memory, 0x0442, 0x044C) => {
+                    execute!(0x0442, [0x72, 0x00, 0x00]); // MOV M,D
+                    if !is_unchanged(memory, 0x0443, 0x044C) {
+                        continue;
+                    }
+                    execute!(0x0443, [0x16, 0x00, 0x00]); // MVI D,00H
+                    execute!(0x0445, [0x56, 0x00, 0x00]); // MOV D,M
+                    execute!(0x0446, [0x3E, 0x46, 0x00]); // MVI A,46H
+                    execute!(0x0448, [0xBA, 0x00, 0x00]); // CMP D
+                    execute!(0x0449, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x044C if is_unchanged(memory, 0x044C, 0x0456) => {
+                    execute!(0x044C, [0x73, 0x00, 0x00]); // MOV M,E
+                    if !is_unchanged(memory, 0x044D, 0x0456) {
+                        continue;
+                    }
+                    execute!(0x044D, [0x1E, 0x00, 0x00]); // MVI E,00H
+                    execute!(0x044F, [0x5E, 0x00, 0x00]); // MOV E,M
+                    execute!(0x0450, [0x3E, 0x47, 0x00]); // MVI A,47H
+                    execute!(0x0452, [0xBB, 0x00, 0x00]); // CMP E
+                    execute!(0x0453, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0456 if is_unchanged(memory, 0x0456, 0x0462) => {
+                    execute!(0x0456, [0x74, 0x00, 0x00]); // MOV M,H
+                    if !is_unchanged(memory, 0x0457, 0x0462) {
+                        continue;
+                    }
+                    execute!(0x0457, [0x26, 0x06, 0x00]); // MVI H,06H
+                    execute!(0x0459, [0x2E, 0xBF, 0x00]); // MVI L,0BFH
+                    execute!(0x045B, [0x66, 0x00, 0x00]); // MOV H,M
+                    execute!(0x045C, [0x3E, 0x06, 0x00]); // MVI A,06H
+                    execute!(0x045E, [0xBC, 0x00, 0x00]); // CMP H
+                    execute!(0x045F, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0462 if is_unchanged(memory, 0x0462, 0x046E) => {
+                    execute!(0x0462, [0x75, 0x00, 0x00]); // MOV M,L
+                    if !is_unchanged(memory, 0x0463, 0x046E) {
+                        continue;
+                    }
+                    execute!(0x0463, [0x26, 0x06, 0x00]); // MVI H,06H
+                    execute!(0x0465, [0x2E, 0xBF, 0x00]); // MVI L,0BFH
+                    execute!(0x0467, [0x6E, 0x00, 0x00]); // MOV L,M
+                    execute!(0x0468, [0x3E, 0xBF, 0x00]); // MVI A,0BFH
+                    execute!(0x046A, [0xBD, 0x00, 0x00]); // CMP L
+                    execute!(0x046B, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x046E if is_unchanged(memory, 0x046E, 0x0479) => {
+                    execute!(0x046E, [0x26, 0x06, 0x00]); // MVI H,06H
+                    execute!(0x0470, [0x2E, 0xBF, 0x00]); // MVI L,0BFH
+                    execute!(0x0472, [0x3E, 0x32, 0x00]); // MVI A,32H
+                    execute!(0x0474, [0x77, 0x00, 0x00]); // MOV M,A
+                    if !is_unchanged(memory, 0x0475, 0x0479) {
+                        continue;
+                    }
+                    execute!(0x0475, [0xBE, 0x00, 0x00]); // CMP M
+                    execute!(0x0476, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0479 if is_unchanged(memory, 0x0479, 0x047F) => {
+                    execute!(0x0479, [0x86, 0x00, 0x00]); // ADD M
+                    execute!(0x047A, [0xFE, 0x64, 0x00]); // CPI 64H
+                    execute!(0x047C, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x047F if is_unchanged(memory, 0x047F, 0x0486) => {
+                    execute!(0x047F, [0xAF, 0x00, 0x00]); // XRA A
+                    execute!(0x0480, [0x7E, 0x00, 0x00]); // MOV A,M
+                    execute!(0x0481, [0xFE, 0x32, 0x00]); // CPI 32H
+                    execute!(0x0483, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0486 if is_unchanged(memory, 0x0486, 0x048F) => {
+                    execute!(0x0486, [0x26, 0x06, 0x00]); // MVI H,06H
+                    execute!(0x0488, [0x2E, 0xBF, 0x00]); // MVI L,0BFH
+                    execute!(0x048A, [0x7E, 0x00, 0x00]); // MOV A,M
+                    execute!(0x048B, [0x96, 0x00, 0x00]); // SUB M
+                    execute!(0x048C, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x048F if is_unchanged(memory, 0x048F, 0x0498) => {
+                    execute!(0x048F, [0x3E, 0x80, 0x00]); // MVI A,80H
+                    execute!(0x0491, [0x87, 0x00, 0x00]); // ADD A
+                    execute!(0x0492, [0x8E, 0x00, 0x00]); // ADC M
+                    execute!(0x0493, [0xFE, 0x33, 0x00]); // CPI 33H
+                    execute!(0x0495, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0498 if is_unchanged(memory, 0x0498, 0x04A1) => {
+                    execute!(0x0498, [0x3E, 0x80, 0x00]); // MVI A,80H
+                    execute!(0x049A, [0x87, 0x00, 0x00]); // ADD A
+                    execute!(0x049B, [0x9E, 0x00, 0x00]); // SBB M
+                    execute!(0x049C, [0xFE, 0xCD, 0x00]); // CPI 0CDH
+                    execute!(0x049E, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x04A1 if is_unchanged(memory, 0x04A1, 0x04A5) => {
+                    execute!(0x04A1, [0xA6, 0x00, 0x00]); // ANA M
+                    execute!(0x04A2, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x04A5 if is_unchanged(memory, 0x04A5, 0x04AD) => {
+                    execute!(0x04A5, [0x3E, 0x25, 0x00]); // MVI A,25H
+                    execute!(0x04A7, [0xB6, 0x00, 0x00]); // ORA M
+                    execute!(0x04A8, [0xFE, 0x37, 0x00]); // CPI 37H
+                    execute!(0x04AA, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x04AD if is_unchanged(memory, 0x04AD, 0x04B3) => {
+                    execute!(0x04AD, [0xAE, 0x00, 0x00]); // XRA M
+                    execute!(0x04AE, [0xFE, 0x05, 0x00]); // CPI 05H
+                    execute!(0x04B0, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x04B3 if is_unchanged(memory, 0x04B3, 0x04BD) => {
+                    execute!(0x04B3, [0x36, 0x55, 0x00]); // MVI M,55H
+                    if !is_unchanged(memory, 0x04B5, 0x04BD) {
+                        continue;
+                    }
+                    execute!(0x04B5, [0x34, 0x00, 0x00]); // INR M
+                    if !is_unchanged(memory, 0x04B6, 0x04BD) {
+                        continue;
+                    }
+                    execute!(0x04B6, [0x35, 0x00, 0x00]); // DCR M
+                    if !is_unchanged(memory, 0x04B7, 0x04BD) {
+                        continue;
+                    }
+                    execute!(0x04B7, [0x86, 0x00, 0x00]); // ADD M
+                    execute!(0x04B8, [0xFE, 0x5A, 0x00]); // CPI 5AH
+                    execute!(0x04BA, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x04BD if is_unchanged(memory, 0x04BD, 0x04CF) => {
+                    execute!(0x04BD, [0x01, 0xFF, 0x12]); // LXI B,12FFH
+                    execute!(0x04C0, [0x11, 0xFF, 0x12]); // LXI D,12FFH
+                    execute!(0x04C3, [0x21, 0xFF, 0x12]); // LXI H,12FFH
+                    execute!(0x04C6, [0x03, 0x00, 0x00]); // INX B
+                    execute!(0x04C7, [0x13, 0x00, 0x00]); // INX D
+                    execute!(0x04C8, [0x23, 0x00, 0x00]); // INX H
+                    execute!(0x04C9, [0x3E, 0x13, 0x00]); // MVI A,13H
+                    execute!(0x04CB, [0xB8, 0x00, 0x00]); // CMP B
+                    execute!(0x04CC, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x04CF if is_unchanged(memory, 0x04CF, 0x04D3) => {
+                    execute!(0x04CF, [0xBA, 0x00, 0x00]); // CMP D
+                    execute!(0x04D0, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x04D3 if is_unchanged(memory, 0x04D3, 0x04D7) => {
+                    execute!(0x04D3, [0xBC, 0x00, 0x00]); // CMP H
+                    execute!(0x04D4, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x04D7 if is_unchanged(memory, 0x04D7, 0x04DD) => {
+                    execute!(0x04D7, [0x3E, 0x00, 0x00]); // MVI A,00H
+                    execute!(0x04D9, [0xB9, 0x00, 0x00]); // CMP C
+                    execute!(0x04DA, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x04DD if is_unchanged(memory, 0x04DD, 0x04E1) => {
+                    execute!(0x04DD, [0xBB, 0x00, 0x00]); // CMP E
+                    execute!(0x04DE, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x04E1 if is_unchanged(memory, 0x04E1, 0x04E5) => {
+                    execute!(0x04E1, [0xBD, 0x00, 0x00]); // CMP L
+                    execute!(0x04E2, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x04E5 if is_unchanged(memory, 0x04E5, 0x04EE) => {
+                    execute!(0x04E5, [0x0B, 0x00, 0x00]); // DCX B
+                    execute!(0x04E6, [0x1B, 0x00, 0x00]); // DCX D
+                    execute!(0x04E7, [0x2B, 0x00, 0x00]); // DCX H
+                    execute!(0x04E8, [0x3E, 0x12, 0x00]); // MVI A,12H
+                    execute!(0x04EA, [0xB8, 0x00, 0x00]); // CMP B
+                    execute!(0x04EB, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x04EE if is_unchanged(memory, 0x04EE, 0x04F2) => {
+                    execute!(0x04EE, [0xBA, 0x00, 0x00]); // CMP D
+                    execute!(0x04EF, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x04F2 if is_unchanged(memory, 0x04F2, 0x04F6) => {
+                    execute!(0x04F2, [0xBC, 0x00, 0x00]); // CMP H
+                    execute!(0x04F3, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x04F6 if is_unchanged(memory, 0x04F6, 0x04FC) => {
+                    execute!(0x04F6, [0x3E, 0xFF, 0x00]); // MVI A,0FFH
+                    execute!(0x04F8, [0xB9, 0x00, 0x00]); // CMP C
+                    execute!(0x04F9, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x04FC if is_unchanged(memory, 0x04FC, 0x0500) => {
+                    execute!(0x04FC, [0xBB, 0x00, 0x00]); // CMP E
+                    execute!(0x04FD, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0500 if is_unchanged(memory, 0x0500, 0x0504) => {
+                    execute!(0x0500, [0xBD, 0x00, 0x00]); // CMP L
+                    execute!(0x0501, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0504 if is_unchanged(memory, 0x0504, 0x0510) => {
+                    execute!(0x0504, [0x32, 0xBF, 0x06]); // STA 06BFH
+                    if !is_unchanged(memory, 0x0507, 0x0510) {
+                        continue;
+                    }
+                    execute!(0x0507, [0xAF, 0x00, 0x00]); // XRA A
+                    execute!(0x0508, [0x3A, 0xBF, 0x06]); // LDA 06BFH
+                    execute!(0x050B, [0xFE, 0xFF, 0x00]); // CPI 0FFH
+                    execute!(0x050D, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0510 if is_unchanged(memory, 0x0510, 0x0521) => {
+                    execute!(0x0510, [0x2A, 0xBD, 0x06]); // LHLD 06BDH
+                    execute!(0x0513, [0x22, 0xBF, 0x06]); // SHLD 06BFH
+                    if !is_unchanged(memory, 0x0516, 0x0521) {
+                        continue;
+                    }
+                    execute!(0x0516, [0x3A, 0xBD, 0x06]); // LDA 06BDH
+                    execute!(0x0519, [0x47, 0x00, 0x00]); // MOV B,A
+                    execute!(0x051A, [0x3A, 0xBF, 0x06]); // LDA 06BFH
+                    execute!(0x051D, [0xB8, 0x00, 0x00]); // CMP B
+                    execute!(0x051E, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0521 if is_unchanged(memory, 0x0521, 0x052C) => {
+                    execute!(0x0521, [0x3A, 0xBE, 0x06]); // LDA 06BEH
+                    execute!(0x0524, [0x47, 0x00, 0x00]); // MOV B,A
+                    execute!(0x0525, [0x3A, 0xC0, 0x06]); // LDA 06C0H
+                    execute!(0x0528, [0xB8, 0x00, 0x00]); // CMP B
+                    execute!(0x0529, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x052C if is_unchanged(memory, 0x052C, 0x053A) => {
+                    execute!(0x052C, [0x3E, 0xAA, 0x00]); // MVI A,0AAH
+                    execute!(0x052E, [0x32, 0xBF, 0x06]); // STA 06BFH
+                    if !is_unchanged(memory, 0x0531, 0x053A) {
+                        continue;
+                    }
+                    execute!(0x0531, [0x44, 0x00, 0x00]); // MOV B,H
+                    execute!(0x0532, [0x4D, 0x00, 0x00]); // MOV C,L
+                    execute!(0x0533, [0xAF, 0x00, 0x00]); // XRA A
+                    execute!(0x0534, [0x0A, 0x00, 0x00]); // LDAX B
+                    execute!(0x0535, [0xFE, 0xAA, 0x00]); // CPI 0AAH
+                    execute!(0x0537, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x053A if is_unchanged(memory, 0x053A, 0x0544) => {
+                    execute!(0x053A, [0x3C, 0x00, 0x00]); // INR A
+                    execute!(0x053B, [0x02, 0x00, 0x00]); // STAX B
+                    if !is_unchanged(memory, 0x053C, 0x0544) {
+                        continue;
+                    }
+                    execute!(0x053C, [0x3A, 0xBF, 0x06]); // LDA 06BFH
+                    execute!(0x053F, [0xFE, 0xAB, 0x00]); // CPI 0ABH
+                    execute!(0x0541, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0544 if is_unchanged(memory, 0x0544, 0x0557) => {
+                    execute!(0x0544, [0x3E, 0x77, 0x00]); // MVI A,77H
+                    execute!(0x0546, [0x32, 0xBF, 0x06]); // STA 06BFH
+                    if !is_unchanged(memory, 0x0549, 0x0557) {
+                        continue;
+                    }
+                    execute!(0x0549, [0x2A, 0xBD, 0x06]); // LHLD 06BDH
+                    execute!(0x054C, [0x11, 0x00, 0x00]); // LXI D,0000H
+                    execute!(0x054F, [0xEB, 0x00, 0x00]); // XCHG
+                    execute!(0x0550, [0xAF, 0x00, 0x00]); // XRA A
+                    execute!(0x0551, [0x1A, 0x00, 0x00]); // LDAX D
+                    execute!(0x0552, [0xFE, 0x77, 0x00]); // CPI 77H
+                    execute!(0x0554, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0557 if is_unchanged(memory, 0x0557, 0x055D) => {
+                    execute!(0x0557, [0xAF, 0x00, 0x00]); // XRA A
+                    execute!(0x0558, [0x84, 0x00, 0x00]); // ADD H
+                    execute!(0x0559, [0x85, 0x00, 0x00]); // ADD L
+                    execute!(0x055A, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x055D if is_unchanged(memory, 0x055D, 0x056E) => {
+                    execute!(0x055D, [0x3E, 0xCC, 0x00]); // MVI A,0CCH
+                    execute!(0x055F, [0x12, 0x00, 0x00]); // STAX D
+                    if !is_unchanged(memory, 0x0560, 0x056E) {
+                        continue;
+                    }
+                    execute!(0x0560, [0x3A, 0xBF, 0x06]); // LDA 06BFH
+                    execute!(0x0563, [0xFE, 0xCC, 0x00]); // CPI 0CCH
+                    execute!(0x0565, [0x12, 0x00, 0x00]); // STAX D
+                    if !is_unchanged(memory, 0x0566, 0x056E) {
+                        continue;
+                    }
+                    execute!(0x0566, [0x3A, 0xBF, 0x06]); // LDA 06BFH
+                    execute!(0x0569, [0xFE, 0xCC, 0x00]); // CPI 0CCH
+                    execute!(0x056B, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x056E if is_unchanged(memory, 0x056E, 0x0578) => {
+                    execute!(0x056E, [0x21, 0x77, 0x77]); // LXI H,7777H
+                    execute!(0x0571, [0x29, 0x00, 0x00]); // DAD H
+                    execute!(0x0572, [0x3E, 0xEE, 0x00]); // MVI A,0EEH
+                    execute!(0x0574, [0xBC, 0x00, 0x00]); // CMP H
+                    execute!(0x0575, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0578 if is_unchanged(memory, 0x0578, 0x057C) => {
+                    execute!(0x0578, [0xBD, 0x00, 0x00]); // CMP L
+                    execute!(0x0579, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x057C if is_unchanged(memory, 0x057C, 0x0588) => {
+                    execute!(0x057C, [0x21, 0x55, 0x55]); // LXI H,5555H
+                    execute!(0x057F, [0x01, 0xFF, 0xFF]); // LXI B,0FFFFH
+                    execute!(0x0582, [0x09, 0x00, 0x00]); // DAD B
+                    execute!(0x0583, [0x3E, 0x55, 0x00]); // MVI A,55H
+                    execute!(0x0585, [0xD4, 0xA0, 0x06]); // CNC 06A0H
+                }
+                0x0588 if is_unchanged(memory, 0x0588, 0x058C) => {
+                    execute!(0x0588, [0xBC, 0x00, 0x00]); // CMP H
+                    execute!(0x0589, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x058C if is_unchanged(memory, 0x058C, 0x0592) => {
+                    execute!(0x058C, [0x3E, 0x54, 0x00]); // MVI A,54H
+                    execute!(0x058E, [0xBD, 0x00, 0x00]); // CMP L
+                    execute!(0x058F, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0592 if is_unchanged(memory, 0x0592, 0x059F) => {
+                    execute!(0x0592, [0x21, 0xAA, 0xAA]); // LXI H,0AAAAH
+                    execute!(0x0595, [0x11, 0x33, 0x33]); // LXI D,3333H
+                    execute!(0x0598, [0x19, 0x00, 0x00]); // DAD D
+                    execute!(0x0599, [0x3E, 0xDD, 0x00]); // MVI A,0DDH
+                    execute!(0x059B, [0xBC, 0x00, 0x00]); // CMP H
+                    execute!(0x059C, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x059F if is_unchanged(memory, 0x059F, 0x05A3) => {
+                    execute!(0x059F, [0xBD, 0x00, 0x00]); // CMP L
+                    execute!(0x05A0, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x05A3 if is_unchanged(memory, 0x05A3, 0x05A7) => {
+                    execute!(0x05A3, [0x37, 0x00, 0x00]); // STC
+                    execute!(0x05A4, [0xD4, 0xA0, 0x06]); // CNC 06A0H
+                }
+                0x05A7 if is_unchanged(memory, 0x05A7, 0x05AB) => {
+                    execute!(0x05A7, [0x3F, 0x00, 0x00]); // CMC
+                    execute!(0x05A8, [0xDC, 0xA0, 0x06]); // CC 06A0H
+                }
+                0x05AB if is_unchanged(memory, 0x05AB, 0x05B3) => {
+                    execute!(0x05AB, [0x3E, 0xAA, 0x00]); // MVI A,0AAH
+                    execute!(0x05AD, [0x2F, 0x00, 0x00]); // CMA
+                    execute!(0x05AE, [0xFE, 0x55, 0x00]); // CPI 55H
+                    execute!(0x05B0, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x05B3 if is_unchanged(memory, 0x05B3, 0x05BA) => {
+                    execute!(0x05B3, [0xB7, 0x00, 0x00]); // ORA A
+                    execute!(0x05B4, [0x27, 0x00, 0x00]); // DAA
+                    execute!(0x05B5, [0xFE, 0x55, 0x00]); // CPI 55H
+                    execute!(0x05B7, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x05BA if is_unchanged(memory, 0x05BA, 0x05C3) => {
+                    execute!(0x05BA, [0x3E, 0x88, 0x00]); // MVI A,88H
+                    execute!(0x05BC, [0x87, 0x00, 0x00]); // ADD A
+                    execute!(0x05BD, [0x27, 0x00, 0x00]); // DAA
+                    execute!(0x05BE, [0xFE, 0x76, 0x00]); // CPI 76H
+                    execute!(0x05C0, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x05C3 if is_unchanged(memory, 0x05C3, 0x05CA) => {
+                    execute!(0x05C3, [0xAF, 0x00, 0x00]); // XRA A
+                    execute!(0x05C4, [0x3E, 0xAA, 0x00]); // MVI A,0AAH
+                    execute!(0x05C6, [0x27, 0x00, 0x00]); // DAA
+                    execute!(0x05C7, [0xD4, 0xA0, 0x06]); // CNC 06A0H
+                }
+                0x05CA if is_unchanged(memory, 0x05CA, 0x05CF) => {
+                    execute!(0x05CA, [0xFE, 0x10, 0x00]); // CPI 10H
+                    execute!(0x05CC, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x05CF if is_unchanged(memory, 0x05CF, 0x05D6) => {
+                    execute!(0x05CF, [0xAF, 0x00, 0x00]); // XRA A
+                    execute!(0x05D0, [0x3E, 0x9A, 0x00]); // MVI A,9AH
+                    execute!(0x05D2, [0x27, 0x00, 0x00]); // DAA
+                    execute!(0x05D3, [0xD4, 0xA0, 0x06]); // CNC 06A0H
+                }
+                0x05D6 if is_unchanged(memory, 0x05D6, 0x05D9) => {
+                    execute!(0x05D6, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x05D9 if is_unchanged(memory, 0x05D9, 0x05E0) => {
+                    execute!(0x05D9, [0x37, 0x00, 0x00]); // STC
+                    execute!(0x05DA, [0x3E, 0x42, 0x00]); // MVI A,42H
+                    execute!(0x05DC, [0x07, 0x00, 0x00]); // RLC
+                    execute!(0x05DD, [0xDC, 0xA0, 0x06]); // CC 06A0H
+                }
+                0x05E0 if is_unchanged(memory, 0x05E0, 0x05E4) => {
+                    execute!(0x05E0, [0x07, 0x00, 0x00]); // RLC
+                    execute!(0x05E1, [0xD4, 0xA0, 0x06]); // CNC 06A0H
+                }
+                0x05E4 if is_unchanged(memory, 0x05E4, 0x05E9) => {
+                    execute!(0x05E4, [0xFE, 0x09, 0x00]); // CPI 09H
+                    execute!(0x05E6, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x05E9 if is_unchanged(memory, 0x05E9, 0x05ED) => {
+                    execute!(0x05E9, [0x0F, 0x00, 0x00]); // RRC
+                    execute!(0x05EA, [0xD4, 0xA0, 0x06]); // CNC 06A0H
+                }
+                0x05ED if is_unchanged(memory, 0x05ED, 0x05F3) => {
+                    execute!(0x05ED, [0x0F, 0x00, 0x00]); // RRC
+                    execute!(0x05EE, [0xFE, 0x42, 0x00]); // CPI 42H
+                    execute!(0x05F0, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x05F3 if is_unchanged(memory, 0x05F3, 0x05F8) => {
+                    execute!(0x05F3, [0x17, 0x00, 0x00]); // RAL
+                    execute!(0x05F4, [0x17, 0x00, 0x00]); // RAL
+                    execute!(0x05F5, [0xD4, 0xA0, 0x06]); // CNC 06A0H
+                }
+                0x05F8 if is_unchanged(memory, 0x05F8, 0x05FD) => {
+                    execute!(0x05F8, [0xFE, 0x08, 0x00]); // CPI 08H
+                    execute!(0x05FA, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x05FD if is_unchanged(memory, 0x05FD, 0x0602) => {
+                    execute!(0x05FD, [0x1F, 0x00, 0x00]); // RAR
+                    execute!(0x05FE, [0x1F, 0x00, 0x00]); // RAR
+                    execute!(0x05FF, [0xDC, 0xA0, 0x06]); // CC 06A0H
+                }
+                0x0602 if is_unchanged(memory, 0x0602, 0x0607) => {
+                    execute!(0x0602, [0xFE, 0x02, 0x00]); // CPI 02H
+                    execute!(0x0604, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0607 if is_unchanged(memory, 0x0607, 0x0629) => {
+                    execute!(0x0607, [0x01, 0x34, 0x12]); // LXI B,1234H
+                    execute!(0x060A, [0x11, 0xAA, 0xAA]); // LXI D,0AAAAH
+                    execute!(0x060D, [0x21, 0x55, 0x55]); // LXI H,5555H
+                    execute!(0x0610, [0xAF, 0x00, 0x00]); // XRA A
+                    execute!(0x0611, [0xC5, 0x00, 0x00]); // PUSH B
+                    if !is_unchanged(memory, 0x0612, 0x0629) {
+                        continue;
+                    }
+                    execute!(0x0612, [0xD5, 0x00, 0x00]); // PUSH D
+                    if !is_unchanged(memory, 0x0613, 0x0629) {
+                        continue;
+                    }
+                    execute!(0x0613, [0xE5, 0x00, 0x00]); // PUSH H
+                    if !is_unchanged(memory, 0x0614, 0x0629) {
+                        continue;
+                    }
+                    execute!(0x0614, [0xF5, 0x00, 0x00]); // PUSH PSW
+                    if !is_unchanged(memory, 0x0615, 0x0629) {
+                        continue;
+                    }
+                    execute!(0x0615, [0x01, 0x00, 0x00]); // LXI B,0000H
+                    execute!(0x0618, [0x11, 0x00, 0x00]); // LXI D,0000H
+                    execute!(0x061B, [0x21, 0x00, 0x00]); // LXI H,0000H
+                    execute!(0x061E, [0x3E, 0xC0, 0x00]); // MVI A,0C0H
+                    execute!(0x0620, [0xC6, 0xF0, 0x00]); // ADI 0F0H
+                    execute!(0x0622, [0xF1, 0x00, 0x00]); // POP PSW
+                    execute!(0x0623, [0xE1, 0x00, 0x00]); // POP H
+                    execute!(0x0624, [0xD1, 0x00, 0x00]); // POP D
+                    execute!(0x0625, [0xC1, 0x00, 0x00]); // POP B
+                    execute!(0x0626, [0xDC, 0xA0, 0x06]); // CC 06A0H
+                }
+                0x0629 if is_unchanged(memory, 0x0629, 0x062C) => {
+                    execute!(0x0629, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x062C if is_unchanged(memory, 0x062C, 0x062F) => {
+                    execute!(0x062C, [0xE4, 0xA0, 0x06]); // CPO 06A0H
+                }
+                0x062F if is_unchanged(memory, 0x062F, 0x0632) => {
+                    execute!(0x062F, [0xFC, 0xA0, 0x06]); // CM 06A0H
+                }
+                0x0632 if is_unchanged(memory, 0x0632, 0x0638) => {
+                    execute!(0x0632, [0x3E, 0x12, 0x00]); // MVI A,12H
+                    execute!(0x0634, [0xB8, 0x00, 0x00]); // CMP B
+                    execute!(0x0635, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0638 if is_unchanged(memory, 0x0638, 0x063E) => {
+                    execute!(0x0638, [0x3E, 0x34, 0x00]); // MVI A,34H
+                    execute!(0x063A, [0xB9, 0x00, 0x00]); // CMP C
+                    execute!(0x063B, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x063E if is_unchanged(memory, 0x063E, 0x0644) => {
+                    execute!(0x063E, [0x3E, 0xAA, 0x00]); // MVI A,0AAH
+                    execute!(0x0640, [0xBA, 0x00, 0x00]); // CMP D
+                    execute!(0x0641, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0644 if is_unchanged(memory, 0x0644, 0x0648) => {
+                    execute!(0x0644, [0xBB, 0x00, 0x00]); // CMP E
+                    execute!(0x0645, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0648 if is_unchanged(memory, 0x0648, 0x064E) => {
+                    execute!(0x0648, [0x3E, 0x55, 0x00]); // MVI A,55H
+                    execute!(0x064A, [0xBC, 0x00, 0x00]); // CMP H
+                    execute!(0x064B, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x064E if is_unchanged(memory, 0x064E, 0x0652) => {
+                    execute!(0x064E, [0xBD, 0x00, 0x00]); // CMP L
+                    execute!(0x064F, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0652 if is_unchanged(memory, 0x0652, 0x066E) => {
+                    execute!(0x0652, [0x21, 0x00, 0x00]); // LXI H,0000H
+                    execute!(0x0655, [0x39, 0x00, 0x00]); // DAD SP
+                    execute!(0x0656, [0x22, 0xC4, 0x06]); // SHLD 06C4H
+                    if !is_unchanged(memory, 0x0659, 0x066E) {
+                        continue;
+                    }
+                    execute!(0x0659, [0x31, 0xC3, 0x06]); // LXI SP,06C3H
+                    execute!(0x065C, [0x3B, 0x00, 0x00]); // DCX SP
+                    execute!(0x065D, [0x3B, 0x00, 0x00]); // DCX SP
+                    execute!(0x065E, [0x33, 0x00, 0x00]); // INX SP
+                    execute!(0x065F, [0x3B, 0x00, 0x00]); // DCX SP
+                    execute!(0x0660, [0x3E, 0x55, 0x00]); // MVI A,55H
+                    execute!(0x0662, [0x32, 0xC1, 0x06]); // STA 06C1H
+                    if !is_unchanged(memory, 0x0665, 0x066E) {
+                        continue;
+                    }
+                    execute!(0x0665, [0x2F, 0x00, 0x00]); // CMA
+                    execute!(0x0666, [0x32, 0xC2, 0x06]); // STA 06C2H
+                    if !is_unchanged(memory, 0x0669, 0x066E) {
+                        continue;
+                    }
+                    execute!(0x0669, [0xC1, 0x00, 0x00]); // POP B
+                    execute!(0x066A, [0xB8, 0x00, 0x00]); // CMP B
+                    execute!(0x066B, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x066E if is_unchanged(memory, 0x066E, 0x0673) => {
+                    execute!(0x066E, [0x2F, 0x00, 0x00]); // CMA
+                    execute!(0x066F, [0xB9, 0x00, 0x00]); // CMP C
+                    execute!(0x0670, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0673 if is_unchanged(memory, 0x0673, 0x0685) => {
+                    execute!(0x0673, [0x21, 0xC3, 0x06]); // LXI H,06C3H
+                    execute!(0x0676, [0xF9, 0x00, 0x00]); // SPHL
+                    execute!(0x0677, [0x21, 0x33, 0x77]); // LXI H,7733H
+                    execute!(0x067A, [0x3B, 0x00, 0x00]); // DCX SP
+                    execute!(0x067B, [0x3B, 0x00, 0x00]); // DCX SP
+                    execute!(0x067C, [0xE3, 0x00, 0x00]); // XTHL
+                    if !is_unchanged(memory, 0x067D, 0x0685) {
+                        continue;
+                    }
+                    execute!(0x067D, [0x3A, 0xC2, 0x06]); // LDA 06C2H
+                    execute!(0x0680, [0xFE, 0x77, 0x00]); // CPI 77H
+                    execute!(0x0682, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0685 if is_unchanged(memory, 0x0685, 0x068D) => {
+                    execute!(0x0685, [0x3A, 0xC1, 0x06]); // LDA 06C1H
+                    execute!(0x0688, [0xFE, 0x33, 0x00]); // CPI 33H
+                    execute!(0x068A, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x068D if is_unchanged(memory, 0x068D, 0x0693) => {
+                    execute!(0x068D, [0x3E, 0x55, 0x00]); // MVI A,55H
+                    execute!(0x068F, [0xBD, 0x00, 0x00]); // CMP L
+                    execute!(0x0690, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0693 if is_unchanged(memory, 0x0693, 0x0698) => {
+                    execute!(0x0693, [0x2F, 0x00, 0x00]); // CMA
+                    execute!(0x0694, [0xBC, 0x00, 0x00]); // CMP H
+                    execute!(0x0695, [0xC4, 0xA0, 0x06]); // CNZ 06A0H
+                }
+                0x0698 if is_unchanged(memory, 0x0698, 0x06A0) => {
+                    execute!(0x0698, [0x2A, 0xC4, 0x06]); // LHLD 06C4H
+                    execute!(0x069B, [0xF9, 0x00, 0x00]); // SPHL
+                    execute!(0x069C, [0x21, 0xB4, 0x06]); // LXI H,06B4H
+                    execute!(0x069F, [0xE9, 0x00, 0x00]); // PCHL
+                }
+                0x06A0 if is_unchanged(memory, 0x06A0, 0x06A6) => {
+                    execute!(0x06A0, [0x21, 0x90, 0x01]); // LXI H,0190H
+                    execute!(0x06A3, [0xCD, 0x4B, 0x01]); // CALL 014BH
+                }
+                0x06A6 if is_unchanged(memory, 0x06A6, 0x06AC) => {
+                    execute!(0x06A6, [0xE1, 0x00, 0x00]); // POP H
+                    execute!(0x06A7, [0xE5, 0x00, 0x00]); // PUSH H
+                    if !is_unchanged(memory, 0x06A8, 0x06AC) {
+                        continue;
+                    }
+                    execute!(0x06A8, [0x7C, 0x00, 0x00]); // MOV A,H
+                    execute!(0x06A9, [0xCD, 0x5A, 0x01]); // CALL 015AH
+                }
+                0x06AC if is_unchanged(memory, 0x06AC, 0x06B1) => {
+                    execute!(0x06AC, [0xE1, 0x00, 0x00]); // POP H
+                    execute!(0x06AD, [0x7D, 0x00, 0x00]); // MOV A,L
+                    execute!(0x06AE, [0xCD, 0x5A, 0x01]); // CALL 015AH
+                }
+                0x06B1 if is_unchanged(memory, 0x06B1, 0x06B4) => {
+                    execute!(0x06B1, [0xC3, 0x00, 0x00]); // JMP 0000H
+                }
+                // Computed jumps, modified code, and code outside the image
+                address => {
+                    let instruction = [
+                        memory[address],
+                        memory[address.wrapping_add(1)],
+                        memory[address.wrapping_add(2)],
+                    ];
+                    execute!(address, instruction);
+                }
+            }
+        }
+        elapsed
+    })
+}
+
+// Returns `true` if the code from `start` up to `end` is as it was recompiled.
+fn is_unchanged(memory: &Memory, start: u16, end: u16) -> bool {
+    memory[start..end] == IMAGE[usize::from(start - ORIGIN)..usize::from(end - ORIGIN)]
+}
@@ -0,0 +1,121 @@
+/*
+ * The C API of i8080, an Intel 8080 emulation library in Rust.
+ *
+ * Build the library with the `capi` feature:
+ *
+ *     cargo rustc --release --features capi --lib --crate-type staticlib
+ *     cargo rustc --release --features capi --lib --crate-type cdylib
+ *
+ * Functions that can fail return one of the I8080_* status codes. Every function but i8080_new
+ * and i8080_free takes a valid handle returned by i8080_new.
+ */
+
+#ifndef I8080_H
+#define I8080_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Status codes */
+#define I8080_OK 0
+#define I8080_HALTED 1
+#define I8080_INTERRUPT_NOT_ENABLED 2
+#define I8080_INVALID_ARGUMENT 3
+#define I8080_TOO_LARGE 4
+#define I8080_FILE_NOT_FOUND 5
+#define I8080_IO_ERROR 6
+
+/* Variants */
+#define I8080_VARIANT_INTEL8080 0
+#define I8080_VARIANT_NEC_UPD8080AF 1
+#define I8080_VARIANT_AMD9080 2
+#define I8080_VARIANT_INTEL8085 3
+#define I8080_VARIANT_Z80 4
+
+/* An Intel 8080 system with the callbacks registered for it. */
+typedef struct I8080 I8080;
+
+typedef struct I8080Registers {
+    uint16_t pc;
+    uint16_t sp;
+    uint8_t b;
+    uint8_t c;
+    uint8_t d;
+    uint8_t e;
+    uint8_t h;
+    uint8_t l;
+    uint8_t a;
+    /* The condition flags as PUSH PSW stores them. */
+    uint8_t flags;
+} I8080Registers;
+
+/* Returns the byte that the device connected to input `port` puts on the data bus. */
+typedef uint8_t (*I8080InputCallback)(void *user_data, uint8_t port);
+/* Sends `byte` to the device connected to output `port`. */
+typedef void (*I8080OutputCallback)(void *user_data, uint8_t port, uint8_t byte);
+/* Tells that an instruction has changed the byte at `address` to `byte`. */
+typedef void (*I8080WriteCallback)(void *user_data, uint16_t address, uint8_t byte);
+
+/*
+ * Creates a system of `variant` with the registers in their reset state and the memory cleared.
+ * Returns NULL if `variant` is unknown.
+ */
+I8080 *i8080_new(uint32_t variant);
+/* Destroys `i8080`. NULL is ignored. */
+void i8080_free(I8080 *i8080);
+
+/* Copies `size` bytes from `bytes` into memory starting at `address`. */
+int32_t i8080_load(I8080 *i8080, const uint8_t *bytes, size_t size, uint16_t address);
+/* Loads the file located at `path`, a UTF-8 string, into memory starting at `address`. */
+int32_t i8080_load_file(I8080 *i8080, const char *path, uint16_t address);
+
+/*
+ * Fetches and executes an instruction, storing the number of states taken in `states` unless it
+ * is NULL. Returns I8080_HALTED if the CPU is in the halt state.
+ */
+int32_t i8080_step(I8080 *i8080, uint32_t *states);
+/*
+ * Fetches and executes instructions until the program counter reaches one of the `count`
+ * addresses in `breakpoints`, the CPU halts, or at least `states` states have elapsed, returning
+ * the number of states taken. A write callback makes this as slow as calling i8080_step.
+ */
+uint64_t i8080_run(I8080 *i8080, uint64_t states, const uint16_t *breakpoints, size_t count);
+/*
+ * Escapes from the halt state, if necessary, and executes the 3-byte `instruction`, usually an
+ * RST, with further interrupts disabled, storing the number of states taken in `states` unless it
+ * is NULL. Returns I8080_INTERRUPT_NOT_ENABLED if the interrupt system is disabled.
+ */
+int32_t i8080_interrupt(I8080 *i8080, const uint8_t *instruction, uint32_t *states);
+
+void i8080_get_registers(const I8080 *i8080, I8080Registers *registers);
+void i8080_set_registers(I8080 *i8080, const I8080Registers *registers);
+bool i8080_is_halted(const I8080 *i8080);
+
+uint8_t i8080_read_memory(const I8080 *i8080, uint16_t address);
+void i8080_write_memory(I8080 *i8080, uint16_t address, uint8_t byte);
+/* Returns a pointer to the 65536 bytes of memory, which is valid until `i8080` is destroyed. */
+uint8_t *i8080_memory(I8080 *i8080);
+
+/*
+ * Connects the I/O ports to `input` and `output`, either of which can be NULL, passing
+ * `user_data` to them. Without an input callback, IN reads 0xFF; without an output callback, OUT
+ * does nothing.
+ */
+void i8080_set_io(I8080 *i8080, I8080InputCallback input, I8080OutputCallback output,
+                  void *user_data);
+/*
+ * Calls `write`, unless it is NULL, with `user_data` after each instruction and interrupt for
+ * every byte of memory that it has changed, in the order in which they were written.
+ */
+void i8080_set_write_callback(I8080 *i8080, I8080WriteCallback write, void *user_data);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* I8080_H */
@@ -0,0 +1,280 @@
+"""Python bindings for i8080, an Intel 8080 emulation library in Rust.
+
+The bindings call the C API of the crate through ctypes, so they need the shared library that the
+`capi` feature builds, but no compiler:
+
+    cargo rustc --release --features capi --lib --crate-type cdylib
+
+They find the library in target/release, or at the path in the I8080_LIBRARY environment
+variable.
+
+    from i8080 import Intel8080
+
+    i8080 = Intel8080()
+    i8080.load(bytes([0x3E, 0x42, 0x76]), 0x0000)  # MVI A,42H; HLT
+    i8080.run(1000)
+    assert i8080.a == 0x42 and i8080.is_halted
+"""
+
+import ctypes
+import os
+import sys
+
+__all__ = ["Intel8080", "I8080Error", "VARIANTS"]
+
+# The variants by name, in the order of the I8080_VARIANT_* constants.
+VARIANTS = {
+    "intel8080": 0,
+    "nec_upd8080af": 1,
+    "amd9080": 2,
+    "intel8085": 3,
+    "z80": 4,
+}
+
+_OK = 0
+_HALTED = 1
+_INTERRUPT_NOT_ENABLED = 2
+
+_MESSAGES = {
+    3: "invalid argument",
+    4: "too large to be loaded at the address",
+    5: "file not found",
+    6: "I/O error",
+}
+
+_REGISTERS = ("pc", "sp", "b", "c", "d", "e", "h", "l", "a", "flags")
+
+
+class I8080Error(Exception):
+    """An error that a function of the C API returned as a status code."""
+
+    def __init__(self, status):
+        super().__init__(_MESSAGES.get(status, "status {}".format(status)))
+        self.status = status
+
+
+class _Registers(ctypes.Structure):
+    _fields_ = [("pc", ctypes.c_uint16), ("sp", ctypes.c_uint16)] + [
+        (name, ctypes.c_uint8) for name in _REGISTERS[2:]
+    ]
+
+
+_InputCallback = ctypes.CFUNCTYPE(ctypes.c_uint8, ctypes.c_void_p, ctypes.c_uint8)
+_OutputCallback = ctypes.CFUNCTYPE(None, ctypes.c_void_p, ctypes.c_uint8, ctypes.c_uint8)
+
+
+def _library_path():
+    path = os.environ.get("I8080_LIBRARY")
+    if path:
+        return path
+    if sys.platform == "win32":
+        name = "i8080.dll"
+    elif sys.platform == "darwin":
+        name = "libi8080.dylib"
+    else:
+        name = "libi8080.so"
+    root = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
+    return os.path.join(root, "target", "release", name)
+
+
+def _load_library():
+    lib = ctypes.CDLL(_library_path())
+    handle = ctypes.c_void_p
+    states = ctypes.POINTER(ctypes.c_uint32)
+    registers = ctypes.POINTER(_Registers)
+    signatures = {
+        "i8080_new": (handle, [ctypes.c_uint32]),
+        "i8080_free": (None, [handle]),
+        "i8080_load": (
+            ctypes.c_int32,
+            [handle, ctypes.c_char_p, ctypes.c_size_t, ctypes.c_uint16],
+        ),
+        "i8080_load_file": (ctypes.c_int32, [handle, ctypes.c_char_p, ctypes.c_uint16]),
+        "i8080_step": (ctypes.c_int32, [handle, states]),
+        "i8080_run": (
+            ctypes.c_uint64,
+            [handle, ctypes.c_uint64, ctypes.POINTER(ctypes.c_uint16), ctypes.c_size_t],
+        ),
+        "i8080_interrupt": (ctypes.c_int32, [handle, ctypes.c_char_p, states]),
+        "i8080_get_registers": (None, [handle, registers]),
+        "i8080_set_registers": (None, [handle, registers]),
+        "i8080_is_halted": (ctypes.c_bool, [handle]),
+        "i8080_memory": (ctypes.POINTER(ctypes.c_uint8), [handle]),
+        "i8080_set_io": (None, [handle, _InputCallback, _OutputCallback, ctypes.c_void_p]),
+    }
+    for name, (restype, argtypes) in signatures.items():
+        function = getattr(lib, name)
+        function.restype = restype
+        function.argtypes = argtypes
+    return lib
+
+
+_lib = _load_library()
+
+
+def _check(status):
+    if status != _OK:
+        raise I8080Error(status)
+
+
+class Intel8080:
+    """An Intel 8080 system: a CPU, 64 KiB of memory, and the devices on its I/O ports.
+
+    The registers are attributes: `pc`, `sp`, `a`, `b`, `c`, `d`, `e`, `h`, `l`, and `flags`, the
+    condition flags as PUSH PSW stores them. `memory` is a writable memoryview of the 65536 bytes
+    of memory, which supports the buffer protocol.
+
+    The devices are the `on_input(port)` and `on_output(port, byte)` attributes. By default, IN
+    reads 0xFF and OUT does nothing. An exception that they raise is raised again when the
+    instruction that called them has been executed.
+    """
+
+    _handle = None
+
+    def __init__(self, variant="intel8080"):
+        if variant not in VARIANTS:
+            raise ValueError("unknown variant: {!r}".format(variant))
+        self._handle = _lib.i8080_new(VARIANTS[variant])
+        array = (ctypes.c_uint8 * 0x10000).from_address(
+            ctypes.addressof(_lib.i8080_memory(self._handle).contents)
+        )
+        self.memory = memoryview(array).cast("B")
+        self._hooks = {}
+        self._error = None
+        self.on_input = lambda port: 0xFF
+        self.on_output = lambda port, byte: None
+        # The callbacks are kept alive as long as the system is, as the library holds them.
+        self._callbacks = (_InputCallback(self._input), _OutputCallback(self._output))
+        _lib.i8080_set_io(self._handle, *self._callbacks, None)
+
+    def close(self):
+        """Destroys the system, which must not be used afterwards."""
+        if self._handle:
+            self.memory.release()
+            _lib.i8080_free(self._handle)
+            self._handle = None
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *exc_info):
+        self.close()
+
+    def __del__(self):
+        self.close()
+
+    def load(self, data, address):
+        """Copies the bytes of `data` into memory starting at `address`."""
+        data = bytes(data)
+        _check(_lib.i8080_load(self._handle, data, len(data), address))
+
+    def load_file(self, path, address):
+        """Loads the file located at `path` into memory starting at `address`."""
+        _check(_lib.i8080_load_file(self._handle, os.fsencode(path), address))
+
+    def step(self):
+        """Fetches and executes an instruction, returning the number of states taken, or None if
+        the CPU is in the halt state."""
+        states = ctypes.c_uint32()
+        status = _lib.i8080_step(self._handle, ctypes.byref(states))
+        self._raise_error()
+        if status == _HALTED:
+            return None
+        _check(status)
+        return states.value
+
+    def add_breakpoint(self, address, hook=None):
+        """Makes `run` stop when the program counter reaches `address`, or, if `hook` is given,
+        call `hook(self)` there and carry on. A hook can change the registers and memory, for
+        example to emulate a system call and return from it."""
+        self._hooks[address] = hook
+
+    def remove_breakpoint(self, address):
+        self._hooks.pop(address, None)
+
+    def run(self, states):
+        """Fetches and executes instructions until the program counter reaches a breakpoint
+        without a hook, the CPU halts, or at least `states` states have elapsed, returning the
+        number of states taken. Like `i8080_run`, it executes at least one instruction, even if
+        `states` is 0."""
+        breakpoints = (ctypes.c_uint16 * len(self._hooks))(*self._hooks)
+        elapsed = 0
+        while True:
+            elapsed += _lib.i8080_run(
+                self._handle, states - elapsed, breakpoints, len(breakpoints)
+            )
+            self._raise_error()
+            if self.is_halted or elapsed >= states:
+                return elapsed
+            # `i8080_run` executes the instruction at the program counter before it checks the
+            # breakpoints, so a hook that jumps to another breakpoint is followed here.
+            address = None
+            while self.pc != address and self.pc in self._hooks:
+                address = self.pc
+                hook = self._hooks[address]
+                if hook is None:
+                    return elapsed
+                hook(self)
+            if self._hooks.keys() != set(breakpoints):
+                breakpoints = (ctypes.c_uint16 * len(self._hooks))(*self._hooks)
+
+    def interrupt(self, instruction):
+        """Escapes from the halt state, if necessary, and executes `instruction`, an opcode such
+        as an RST or the bytes of an instruction, with further interrupts disabled. Returns the
+        number of states taken, or None if the interrupt system is disabled."""
+        if isinstance(instruction, int):
+            instruction = [instruction]
+        instruction = bytes(instruction).ljust(3, b"\0")
+        if len(instruction) > 3:
+            raise ValueError("an instruction is at most 3 bytes long")
+        states = ctypes.c_uint32()
+        status = _lib.i8080_interrupt(self._handle, instruction, ctypes.byref(states))
+        self._raise_error()
+        if status == _INTERRUPT_NOT_ENABLED:
+            return None
+        _check(status)
+        return states.value
+
+    @property
+    def is_halted(self):
+        return _lib.i8080_is_halted(self._handle)
+
+    def _registers(self):
+        registers = _Registers()
+        _lib.i8080_get_registers(self._handle, ctypes.byref(registers))
+        return registers
+
+    def _input(self, user_data, port):
+        try:
+            return self.on_input(port) & 0xFF
+        except BaseException as error:
+            self._error = self._error or error
+            return 0xFF
+
+    def _output(self, user_data, port, byte):
+        try:
+            self.on_output(port, byte)
+        except BaseException as error:
+            self._error = self._error or error
+
+    def _raise_error(self):
+        error, self._error = self._error, None
+        if error is not None:
+            raise error
+
+
+def _register_property(name):
+    def get(self):
+        return getattr(self._registers(), name)
+
+    def set(self, value):
+        registers = self._registers()
+        setattr(registers, name, value)
+        _lib.i8080_set_registers(self._handle, ctypes.byref(registers))
+
+    return property(get, set)
+
+
+for _name in _REGISTERS:
+    setattr(Intel8080, _name, _register_property(_name))
+del _name
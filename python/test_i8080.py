@@ -0,0 +1,115 @@
+"""Tests the Python bindings with pytest:
+
+    cargo rustc --release --features capi --lib --crate-type cdylib
+    python -m pytest python
+"""
+
+import os
+
+import pytest
+
+from i8080 import I8080Error, Intel8080
+
+CPU_TESTS = os.path.join(os.path.dirname(__file__), "..", "tests", "cpu_tests")
+
+
+def test_registers_and_memory():
+    with Intel8080() as i8080:
+        i8080.b, i8080.c, i8080.sp = 0x12, 0x34, 0x2000
+        i8080.memory[0x0000:0x0002] = bytes([0xC5, 0x76])  # PUSH B; HLT
+        assert i8080.step() == 11
+        assert bytes(i8080.memory[0x1FFE:0x2000]) == bytes([0x34, 0x12])
+        assert i8080.sp == 0x1FFE
+        assert i8080.run(1000) == 7
+        assert i8080.is_halted
+        assert i8080.step() is None
+
+
+def test_ports():
+    with Intel8080() as i8080:
+        outputs = []
+        i8080.on_input = lambda port: port + 1
+        i8080.on_output = lambda port, byte: outputs.append((port, byte))
+        i8080.load(bytes([0xDB, 0x41, 0xD3, 0x10, 0x76]), 0x0100)  # IN 41H; OUT 10H; HLT
+        i8080.pc = 0x0100
+        i8080.run(1000)
+        assert outputs == [(0x10, 0x42)]
+
+
+def test_exceptions_in_callbacks_are_raised():
+    with Intel8080() as i8080:
+        i8080.on_output = lambda port, byte: 1 / 0
+        i8080.load(bytes([0xD3, 0x10]), 0x0000)  # OUT 10H
+        with pytest.raises(ZeroDivisionError):
+            i8080.step()
+        assert i8080.pc == 0x0002
+
+
+def test_breakpoints_and_interrupts():
+    with Intel8080() as i8080:
+        i8080.load(bytes([0xFB, 0x00, 0x76]), 0x0000)  # EI; NOP; HLT
+        i8080.sp = 0x1000
+        i8080.add_breakpoint(0x0001)
+        assert i8080.run(1000) == 4
+        assert i8080.pc == 0x0001
+        i8080.remove_breakpoint(0x0001)
+        assert i8080.run(1000) == 4 + 7
+        assert i8080.interrupt(0xCF) == 11  # RST 1
+        assert (i8080.pc, i8080.is_halted) == (0x0008, False)
+        assert i8080.interrupt(0xCF) is None
+
+
+def test_hooks():
+    with Intel8080() as i8080:
+        calls = []
+
+        def jump(address):
+            def hook(i8080):
+                calls.append(i8080.pc)
+                i8080.pc = address
+
+            return hook
+
+        # The memory is cleared, so the CPU executes NOPs.
+        i8080.add_breakpoint(0x0001, jump(0x0002))
+        i8080.add_breakpoint(0x0002, jump(0x0003))
+        i8080.add_breakpoint(0x0003)
+        assert i8080.run(1000) == 4
+        assert calls == [0x0001, 0x0002]
+        assert i8080.pc == 0x0003
+        assert i8080.run(0) == 4
+        assert i8080.pc == 0x0004
+
+
+def test_load_errors():
+    with Intel8080() as i8080:
+        with pytest.raises(I8080Error):
+            i8080.load(bytes(2), 0xFFFF)
+        with pytest.raises(I8080Error):
+            i8080.load_file(os.path.join(CPU_TESTS, "MISSING.COM"), 0x0100)
+    with pytest.raises(ValueError):
+        Intel8080("6502")
+
+
+def test_tst8080():
+    with Intel8080() as i8080:
+        i8080.load_file(os.path.join(CPU_TESTS, "TST8080.COM"), 0x0100)
+        i8080.load(bytes([0xC9]), 0x0005)  # RET
+        i8080.pc = 0x0100
+        output = []
+
+        # Prints the characters that the program passes to the console output functions of the
+        # CP/M BDOS.
+        def bdos(i8080):
+            if i8080.c == 2:
+                output.append(chr(i8080.e))
+            elif i8080.c == 9:
+                address = i8080.d << 8 | i8080.e
+                end = bytes(i8080.memory).index(b"$", address)
+                output.append(bytes(i8080.memory[address:end]).decode("ascii"))
+
+        i8080.add_breakpoint(0x0005, bdos)
+        i8080.add_breakpoint(0x0000)
+        i8080.run(10_000_000)
+        assert i8080.pc == 0x0000
+        assert "".join(output).endswith(" CPU IS OPERATIONAL")
@@ -0,0 +1,40 @@
+#![warn(rust_2018_idioms)]
+
+use std::{env, process};
+
+use i8080::{
+    console::StdioConsole,
+    cpm::Bdos,
+    cpu::{Cpu, Variant},
+};
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let variant = if args.first().map(String::as_str) == Some("--z80") {
+        args.remove(0);
+        Variant::Z80
+    } else {
+        Variant::Intel8080
+    };
+    if args.is_empty() {
+        eprintln!("Usage: cpm-run [--z80] PROGRAM.COM [ARGUMENT]...");
+        process::exit(2);
+    }
+    if let Err(err) = run(variant, &args[0], &args[1..]) {
+        eprintln!("Error: {err}");
+        process::exit(1);
+    }
+}
+
+// Runs `program` on a CPU of `variant` with the current directory mapped to drive A until it warm
+// boots.
+fn run(variant: Variant, program: &str, args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut bdos = Bdos::new(StdioConsole::new(), env::current_dir()?);
+    let mut i8080 = bdos.load_program(program, args)?;
+    let Cpu { pc, sp, .. } = i8080.cpu;
+    i8080.cpu = Cpu::new(variant);
+    i8080.cpu.pc = pc;
+    i8080.cpu.sp = sp;
+    bdos.run(&mut i8080)?;
+    Ok(())
+}
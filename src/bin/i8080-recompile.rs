@@ -0,0 +1,35 @@
+#![warn(rust_2018_idioms)]
+
+use std::{env, fs, path::Path, process};
+
+use i8080::recompiler::Program;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() < 3 {
+        eprintln!("Usage: i8080-recompile IMAGE ORIGIN ENTRY_POINT...");
+        eprintln!("Writes Rust source that runs IMAGE, loaded at the hexadecimal address ORIGIN,");
+        eprintln!("from the hexadecimal addresses ENTRY_POINT to standard output.");
+        process::exit(2);
+    }
+    if let Err(err) = recompile(&args[0], &args[1], &args[2..]) {
+        eprintln!("Error: {err}");
+        process::exit(1);
+    }
+}
+
+fn recompile(
+    image: &str,
+    origin: &str,
+    entry_points: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let origin = u16::from_str_radix(origin, 16)?;
+    let entry_points = entry_points
+        .iter()
+        .map(|entry_point| u16::from_str_radix(entry_point, 16))
+        .collect::<Result<Vec<u16>, _>>()?;
+    let program = Program::new(&fs::read(image)?, origin, &entry_points)?;
+    let source = Path::new(image).file_name().map_or(image.into(), |name| name.to_string_lossy());
+    print!("{}", program.to_rust(&source));
+    Ok(())
+}
@@ -0,0 +1,458 @@
+//! The C API, which the `capi` feature enables.
+//!
+//! `include/i8080.h` declares these functions for C and C++. A [`Handle`] is the opaque `I8080`
+//! of the header: [`i8080_new`] creates one and [`i8080_free`] destroys it. Functions that can fail
+//! return one of the `I8080_*` status codes. The I/O ports and memory writes can be observed
+//! through C function pointers, each registered with a `user_data` pointer that is passed back to
+//! it.
+//!
+//! Build the crate as a static or dynamic library with one of
+//!
+//! ```text
+//! cargo rustc --release --features capi --lib --crate-type staticlib
+//! cargo rustc --release --features capi --lib --crate-type cdylib
+//! ```
+//!
+//! [`Handle`]: struct.Handle.html
+//! [`i8080_new`]: fn.i8080_new.html
+//! [`i8080_free`]: fn.i8080_free.html
+
+use std::{
+    ffi::{c_void, CStr},
+    ops::{Index, IndexMut},
+    os::raw::c_char,
+    ptr, slice,
+};
+
+use crate::{
+    cpu::{ConditionFlags, Cpu, Io, Variant},
+    memory::Memory,
+    Error, Intel8080,
+};
+
+/// The operation succeeded.
+pub const I8080_OK: i32 = 0;
+/// The CPU is in the halt state.
+pub const I8080_HALTED: i32 = 1;
+/// An interrupt request arrived when the interrupt system was disabled.
+pub const I8080_INTERRUPT_NOT_ENABLED: i32 = 2;
+/// An argument was invalid, such as an unknown variant or a path that is not UTF-8.
+pub const I8080_INVALID_ARGUMENT: i32 = 3;
+/// The bytes or the file were too large to load at the address.
+pub const I8080_TOO_LARGE: i32 = 4;
+/// The file was not found.
+pub const I8080_FILE_NOT_FOUND: i32 = 5;
+/// An I/O error occurred.
+pub const I8080_IO_ERROR: i32 = 6;
+
+/// The Intel 8080A.
+pub const I8080_VARIANT_INTEL8080: u32 = 0;
+/// The NEC µPD8080AF.
+pub const I8080_VARIANT_NEC_UPD8080AF: u32 = 1;
+/// The AMD Am9080A.
+pub const I8080_VARIANT_AMD9080: u32 = 2;
+/// The Intel 8085A.
+pub const I8080_VARIANT_INTEL8085: u32 = 3;
+/// The Zilog Z80.
+pub const I8080_VARIANT_Z80: u32 = 4;
+
+/// Returns the byte that the device connected to input `port` puts on the data bus.
+pub type InputCallback = extern "C" fn(user_data: *mut c_void, port: u8) -> u8;
+/// Sends `byte` to the device connected to output `port`.
+pub type OutputCallback = extern "C" fn(user_data: *mut c_void, port: u8, byte: u8);
+/// Tells that an instruction has changed the byte at `address` to `byte`.
+pub type WriteCallback = extern "C" fn(user_data: *mut c_void, address: u16, byte: u8);
+
+/// The registers of the 8080: `I8080Registers` in the header.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Registers {
+    pub pc: u16,
+    pub sp: u16,
+    pub b: u8,
+    pub c: u8,
+    pub d: u8,
+    pub e: u8,
+    pub h: u8,
+    pub l: u8,
+    pub a: u8,
+    /// The condition flags as PUSH PSW stores them.
+    pub flags: u8,
+}
+
+/// An Intel 8080 system with the callbacks registered for it: the opaque `I8080` of the header.
+pub struct Handle {
+    i8080: Intel8080,
+    io: Callbacks,
+    write: Option<(WriteCallback, *mut c_void)>,
+    // The addresses written by the current instruction and the bytes that they held before.
+    writes: Vec<(u16, u8)>,
+}
+
+// The I/O callbacks. Reading a port without an input callback returns 0xFF, as an open data bus
+// does.
+struct Callbacks {
+    input: Option<InputCallback>,
+    output: Option<OutputCallback>,
+    user_data: *mut c_void,
+}
+
+impl Io for Callbacks {
+    fn input(&mut self, port: u8) -> u8 {
+        match self.input {
+            Some(input) => input(self.user_data, port),
+            None => 0xFF,
+        }
+    }
+
+    fn output(&mut self, port: u8, byte: u8) {
+        if let Some(output) = self.output {
+            output(self.user_data, port, byte);
+        }
+    }
+}
+
+// The memory seen through `IndexMut`, which logs every write made by an instruction.
+struct WriteLog<'a> {
+    memory: &'a mut Memory,
+    writes: &'a mut Vec<(u16, u8)>,
+}
+
+impl Index<u16> for WriteLog<'_> {
+    type Output = u8;
+
+    fn index(&self, address: u16) -> &u8 {
+        &self.memory[address]
+    }
+}
+
+impl IndexMut<u16> for WriteLog<'_> {
+    fn index_mut(&mut self, address: u16) -> &mut u8 {
+        self.writes.push((address, self.memory[address]));
+        &mut self.memory[address]
+    }
+}
+
+impl Handle {
+    fn step(&mut self) -> Result<u32, Error> {
+        if self.write.is_none() {
+            return self.i8080.fetch_execute_instruction_with_io(&mut self.io).map(|(_, s)| s);
+        }
+        self.log_writes(|cpu, memory, io| {
+            cpu.fetch_execute_instruction_with_bus(memory, io).map(|(_, states)| states)
+        })
+    }
+
+    fn interrupt(&mut self, instruction: [u8; 3]) -> Result<u32, Error> {
+        if self.write.is_none() {
+            return self.i8080.interrupt_with_io(instruction, &mut self.io);
+        }
+        self.log_writes(|cpu, memory, io| cpu.interrupt_with_bus(instruction, memory, io))
+    }
+
+    // Runs `execute` on the memory behind a `WriteLog`, then calls the write callback for every
+    // byte that has changed.
+    fn log_writes<F>(&mut self, execute: F) -> Result<u32, Error>
+    where
+        F: FnOnce(&mut Cpu, &mut WriteLog<'_>, &mut Callbacks) -> Result<u32, Error>,
+    {
+        let Handle { i8080, io, write, writes } = self;
+        writes.clear();
+        let result =
+            execute(&mut i8080.cpu, &mut WriteLog { memory: &mut i8080.memory, writes }, io);
+        if let Some((write, user_data)) = *write {
+            for (index, &(address, byte)) in writes.iter().enumerate() {
+                // The first write to an address logs the byte that it held before the instruction.
+                let is_repeated = writes[..index].iter().any(|&(written, _)| written == address);
+                if i8080.memory[address] != byte && !is_repeated {
+                    write(user_data, address, i8080.memory[address]);
+                }
+            }
+        }
+        result
+    }
+}
+
+fn status(err: &Error) -> i32 {
+    match err {
+        Error::Halted => I8080_HALTED,
+        Error::InterruptNotEnabled => I8080_INTERRUPT_NOT_ENABLED,
+        Error::TooLargeFile { .. } | Error::TooLargeImage { .. } => I8080_TOO_LARGE,
+        Error::FileNotFound { .. } => I8080_FILE_NOT_FOUND,
+        _ => I8080_IO_ERROR,
+    }
+}
+
+/// Creates an 8080 system of `variant`, one of the `I8080_VARIANT_*` constants, with the
+/// registers in their reset state and the memory cleared. Returns a null pointer if `variant` is
+/// unknown.
+#[no_mangle]
+pub extern "C" fn i8080_new(variant: u32) -> *mut Handle {
+    let variant = match variant {
+        I8080_VARIANT_INTEL8080 => Variant::Intel8080,
+        I8080_VARIANT_NEC_UPD8080AF => Variant::NecUpd8080af,
+        I8080_VARIANT_AMD9080 => Variant::Amd9080,
+        I8080_VARIANT_INTEL8085 => Variant::Intel8085,
+        I8080_VARIANT_Z80 => Variant::Z80,
+        _ => return ptr::null_mut(),
+    };
+    let i8080 = Intel8080 { cpu: Cpu::new(variant), ..Intel8080::default() };
+    let io = Callbacks { input: None, output: None, user_data: ptr::null_mut() };
+    Box::into_raw(Box::new(Handle { i8080, io, write: None, writes: Vec::new() }))
+}
+
+/// Destroys `handle`. A null pointer is ignored.
+///
+/// # Safety
+///
+/// `handle` must be null or have been returned by [`i8080_new`] and not destroyed yet.
+///
+/// [`i8080_new`]: fn.i8080_new.html
+#[no_mangle]
+pub unsafe extern "C" fn i8080_free(handle: *mut Handle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Copies `size` bytes from `bytes` into memory starting at `address`.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle, and `bytes` must point to `size` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn i8080_load(
+    handle: *mut Handle,
+    bytes: *const u8,
+    size: usize,
+    address: u16,
+) -> i32 {
+    let bytes = if size == 0 { &[] } else { slice::from_raw_parts(bytes, size) };
+    match (*handle).i8080.memory.load_bytes(bytes, address) {
+        Ok(_) => I8080_OK,
+        Err(err) => status(&err),
+    }
+}
+
+/// Loads the file located at `path`, a NUL-terminated UTF-8 string, into memory starting at
+/// `address`.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle, and `path` must point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn i8080_load_file(
+    handle: *mut Handle,
+    path: *const c_char,
+    address: u16,
+) -> i32 {
+    let path = match CStr::from_ptr(path).to_str() {
+        Ok(path) => path,
+        Err(_) => return I8080_INVALID_ARGUMENT,
+    };
+    match (*handle).i8080.memory.load_file(path, address) {
+        Ok(_) => I8080_OK,
+        Err(err) => status(&err),
+    }
+}
+
+/// Fetches and executes an instruction, storing the number of states taken in `states` unless
+/// it is null. Returns `I8080_HALTED` if the CPU is in the halt state.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle, and `states` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn i8080_step(handle: *mut Handle, states: *mut u32) -> i32 {
+    match (*handle).step() {
+        Ok(instruction_states) => {
+            if !states.is_null() {
+                *states = instruction_states;
+            }
+            I8080_OK
+        }
+        Err(err) => status(&err),
+    }
+}
+
+/// Fetches and executes instructions until the program counter reaches one of the `count`
+/// addresses in `breakpoints`, the CPU halts, or at least `states` states have elapsed, returning
+/// the number of states taken. Registering a write callback makes this as slow as calling
+/// [`i8080_step`] for each instruction.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle, and `breakpoints` must point to `count` addresses unless
+/// `count` is 0.
+///
+/// [`i8080_step`]: fn.i8080_step.html
+#[no_mangle]
+pub unsafe extern "C" fn i8080_run(
+    handle: *mut Handle,
+    states: u64,
+    breakpoints: *const u16,
+    count: usize,
+) -> u64 {
+    let handle = &mut *handle;
+    let breakpoints = if count == 0 { &[] } else { slice::from_raw_parts(breakpoints, count) };
+    if handle.write.is_none() {
+        let Handle { i8080, io, .. } = handle;
+        return i8080.run_with_io(io, states, |pc| breakpoints.contains(&pc));
+    }
+    let mut elapsed = 0;
+    while let Ok(instruction_states) = handle.step() {
+        elapsed += u64::from(instruction_states);
+        if elapsed >= states || breakpoints.contains(&handle.i8080.cpu.pc) {
+            break;
+        }
+    }
+    elapsed
+}
+
+/// Escapes from the halt state, if necessary, and executes the 3-byte `instruction`, usually an
+/// RST, with further interrupts disabled, storing the number of states taken in `states` unless
+/// it is null. Returns `I8080_INTERRUPT_NOT_ENABLED` if the interrupt system is disabled.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle, `instruction` must point to 3 readable bytes, and `states`
+/// must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn i8080_interrupt(
+    handle: *mut Handle,
+    instruction: *const u8,
+    states: *mut u32,
+) -> i32 {
+    let handle = &mut *handle;
+    let mut bytes = [0; 3];
+    bytes.copy_from_slice(slice::from_raw_parts(instruction, 3));
+    match handle.interrupt(bytes) {
+        Ok(interrupt_states) => {
+            if !states.is_null() {
+                *states = interrupt_states;
+            }
+            I8080_OK
+        }
+        Err(err) => status(&err),
+    }
+}
+
+/// Stores the registers in `registers`.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle, and `registers` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn i8080_get_registers(handle: *const Handle, registers: *mut Registers) {
+    let cpu = &(*handle).i8080.cpu;
+    *registers = Registers {
+        pc: cpu.pc,
+        sp: cpu.sp,
+        b: cpu.b,
+        c: cpu.c,
+        d: cpu.d,
+        e: cpu.e,
+        h: cpu.h,
+        l: cpu.l,
+        a: cpu.a,
+        flags: cpu.condition_flags.bits(),
+    };
+}
+
+/// Sets the registers to `registers`.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle, and `registers` must be valid for reads.
+#[no_mangle]
+pub unsafe extern "C" fn i8080_set_registers(handle: *mut Handle, registers: *const Registers) {
+    let cpu = &mut (*handle).i8080.cpu;
+    let registers = *registers;
+    cpu.pc = registers.pc;
+    cpu.sp = registers.sp;
+    cpu.b = registers.b;
+    cpu.c = registers.c;
+    cpu.d = registers.d;
+    cpu.e = registers.e;
+    cpu.h = registers.h;
+    cpu.l = registers.l;
+    cpu.a = registers.a;
+    cpu.condition_flags = ConditionFlags::from_bits_truncate(registers.flags);
+}
+
+/// Returns `true` if the CPU is in the halt state.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn i8080_is_halted(handle: *const Handle) -> bool {
+    (*handle).i8080.cpu.is_halted()
+}
+
+/// Returns the byte at `address`.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn i8080_read_memory(handle: *const Handle, address: u16) -> u8 {
+    let handle = &*handle;
+    handle.i8080.memory[address]
+}
+
+/// Writes `byte` to `address`.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn i8080_write_memory(handle: *mut Handle, address: u16, byte: u8) {
+    let handle = &mut *handle;
+    handle.i8080.memory[address] = byte;
+}
+
+/// Returns a pointer to the 65536 bytes of memory, which is valid until `handle` is destroyed.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn i8080_memory(handle: *mut Handle) -> *mut u8 {
+    (*handle).i8080.memory.as_mut_ptr()
+}
+
+/// Connects the I/O ports to `input` and `output`, either of which can be null, passing
+/// `user_data` to them. Without an input callback, IN reads 0xFF; without an output callback, OUT
+/// does nothing.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn i8080_set_io(
+    handle: *mut Handle,
+    input: Option<InputCallback>,
+    output: Option<OutputCallback>,
+    user_data: *mut c_void,
+) {
+    let io = &mut (*handle).io;
+    io.input = input;
+    io.output = output;
+    io.user_data = user_data;
+}
+
+/// Calls `write`, unless it is null, with `user_data` after each instruction and interrupt for
+/// every byte of memory that it has changed, in the order in which they were written.
+///
+/// # Safety
+///
+/// `handle` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn i8080_set_write_callback(
+    handle: *mut Handle,
+    write: Option<WriteCallback>,
+    user_data: *mut c_void,
+) {
+    (*handle).write = write.map(|write| (write, user_data));
+}
@@ -0,0 +1,281 @@
+//! Cassette tapes recorded in the Kansas City standard and its faster CUTS variant.
+//!
+//! Both formats send bytes as asynchronous serial frames, a start bit (0), eight data bits from
+//! the least significant bit, and stop bits (1), where each bit is a burst of one of two tones. A
+//! [`Cassette`] decodes a WAV recording into the bytes that a UART receives and records the bytes
+//! that a UART sends, so loading and saving tapes is an offline, file-based operation.
+//!
+//! [`Cassette`]: struct.Cassette.html
+
+use std::{
+    collections::VecDeque,
+    f64::consts::PI,
+    fs::File,
+    io::{self, BufWriter},
+    path::Path,
+};
+
+use crate::{console::Console, wav::Wave, Error, Result};
+
+/// The bit rate and the tones of a tape format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Format {
+    /// The number of bits per second.
+    pub baud: u32,
+    /// The frequency of the tone for a 0 (space) in hertz.
+    pub space_frequency: u32,
+    /// The frequency of the tone for a 1 (mark) in hertz.
+    pub mark_frequency: u32,
+    /// The number of stop bits per frame, usually 1 or 2. With none, frames are not checked for
+    /// their stop bits when decoded.
+    pub stop_bits: u32,
+}
+
+impl Format {
+    /// The Kansas City standard (Byte magazine, 1976): 300 baud with four cycles of 1200 Hz for a
+    /// 0 and eight cycles of 2400 Hz for a 1, and two stop bits.
+    pub const KANSAS_CITY: Self =
+        Self { baud: 300, space_frequency: 1200, mark_frequency: 2400, stop_bits: 2 };
+
+    /// The 1200 baud mode of the Processor Technology CUTS board and the SOL-20: half a cycle of
+    /// 600 Hz for a 0 and a cycle of 1200 Hz for a 1, and one stop bit.
+    pub const CUTS: Self =
+        Self { baud: 1200, space_frequency: 600, mark_frequency: 1200, stop_bits: 1 };
+}
+
+/// Encodes `bytes` into a recording at `sample_rate`, preceded and followed by `leader` seconds
+/// of the mark tone.
+pub fn encode(bytes: &[u8], format: Format, sample_rate: u32, leader: f64) -> Wave {
+    let mut bits = Vec::with_capacity(bytes.len() * (9 + format.stop_bits as usize));
+    for &byte in bytes {
+        bits.push(false);
+        bits.extend((0..8).map(|bit| byte >> bit & 1 == 1));
+        bits.extend((0..format.stop_bits).map(|_| true));
+    }
+    let leader_bits = (leader * f64::from(format.baud)).round() as usize;
+    let bits = (0..leader_bits).map(|_| true).chain(bits).chain((0..leader_bits).map(|_| true));
+
+    // Each bit is a whole number of half-cycles, so the tone changes at a zero crossing as it
+    // does in the hardware.
+    let mut samples = Vec::new();
+    let mut half_cycles_before = 0_u64;
+    for (index, bit) in bits.enumerate() {
+        let frequency = f64::from(if bit { format.mark_frequency } else { format.space_frequency });
+        let start = index as f64 / f64::from(format.baud);
+        let end = (index + 1) as f64 / f64::from(format.baud);
+        while (samples.len() as f64 / f64::from(sample_rate)) < end {
+            let time = samples.len() as f64 / f64::from(sample_rate);
+            let half_cycles = (time - start) * 2.0 * frequency;
+            let amplitude = (PI * half_cycles.fract()).sin() * 0.8 * f64::from(i16::MAX);
+            let is_negative = (half_cycles_before + half_cycles as u64) % 2 == 1;
+            samples.push(if is_negative { -amplitude } else { amplitude } as i16);
+        }
+        half_cycles_before += ((end - start) * 2.0 * frequency).round() as u64;
+    }
+    Wave::new(sample_rate, samples)
+}
+
+/// Decodes the bytes recorded in `wave`. Frames whose stop bits are missing are dropped.
+pub fn decode(wave: &Wave, format: Format) -> Vec<u8> {
+    let marks = marks(wave, format);
+    let samples_per_bit = f64::from(wave.sample_rate) / f64::from(format.baud);
+    let at =
+        |start: usize, bits: f64| marks.get(start + (bits * samples_per_bit) as usize).copied();
+
+    let mut bytes = Vec::new();
+    let mut index = 1;
+    while index < marks.len() {
+        // A start bit begins where the mark tone turns into the space tone, and the bits are
+        // sampled in the middle of their cells.
+        let start = index;
+        index += 1;
+        if !marks[start - 1] || marks[start] || at(start, 0.5) != Some(false) {
+            continue;
+        }
+        let mut byte = 0;
+        for bit in 0..8 {
+            if at(start, 1.5 + f64::from(bit)) == Some(true) {
+                byte |= 1 << bit;
+            }
+        }
+        let is_framed =
+            (0..format.stop_bits).all(|bit| at(start, 9.5 + f64::from(bit)).unwrap_or(false));
+        if is_framed {
+            bytes.push(byte);
+            // Resumes from the middle of the last bit of the frame.
+            let last_bit = 8.5 + f64::from(format.stop_bits);
+            index = start + (last_bit * samples_per_bit) as usize;
+        }
+    }
+    bytes
+}
+
+// Classifies every sample as part of the mark tone (`true`) or the space tone (`false`) by the
+// length of the half-cycle that it belongs to.
+fn marks(wave: &Wave, format: Format) -> Vec<bool> {
+    let sample_rate = f64::from(wave.sample_rate);
+    let threshold = (sample_rate / f64::from(2 * format.space_frequency)
+        + sample_rate / f64::from(2 * format.mark_frequency))
+        / 2.0;
+    // Remove any DC offset, and ignore wiggles smaller than a tenth of the peak around 0.
+    let mean = wave.samples.iter().map(|&sample| f64::from(sample)).sum::<f64>()
+        / wave.samples.len().max(1) as f64;
+    let peak =
+        wave.samples.iter().map(|&sample| (f64::from(sample) - mean).abs()).fold(0.0, f64::max);
+    let hysteresis = peak / 10.0;
+
+    let mut marks = vec![true; wave.samples.len()];
+    let mut is_positive = true;
+    let mut half_cycle_start = 0;
+    for (index, &sample) in wave.samples.iter().enumerate() {
+        let sample = f64::from(sample) - mean;
+        let crossed = if is_positive { sample < -hysteresis } else { sample > hysteresis };
+        if crossed {
+            is_positive = !is_positive;
+            let is_mark = ((index - half_cycle_start) as f64) < threshold;
+            for mark in &mut marks[half_cycle_start..index] {
+                *mark = is_mark;
+            }
+            half_cycle_start = index;
+        }
+    }
+    marks
+}
+
+/// A cassette tape that plays back recorded bytes to a UART and records the bytes that the UART
+/// sends.
+///
+/// As a [`Console`], the tape delivers a byte each time a frame has played at the baud rate of
+/// its format. Reading the UART before the next byte arrives returns the last byte again.
+///
+/// [`Console`]: ../console/trait.Console.html
+#[derive(Clone, Debug)]
+pub struct Cassette {
+    format: Format,
+    playback: VecDeque<u8>,
+    recording: Vec<u8>,
+    states_per_byte: u64,
+    // The number of states until the next byte has played.
+    states_until_ready: u64,
+    last_byte: u8,
+}
+
+impl Cassette {
+    /// Creates a blank tape of `format` for a UART attached to a CPU running at `clock_rate`
+    /// hertz.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the baud rate of `format` is 0.
+    pub fn new(format: Format, clock_rate: u32) -> Self {
+        Self::with_playback(format, clock_rate, VecDeque::new())
+    }
+
+    /// Creates a tape that plays back the bytes recorded in `wave`. See [`new`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the baud rate of `format` is 0.
+    ///
+    /// [`new`]: #method.new
+    pub fn from_wave(wave: &Wave, format: Format, clock_rate: u32) -> Self {
+        Self::with_playback(format, clock_rate, decode(wave, format).into())
+    }
+
+    fn with_playback(format: Format, clock_rate: u32, playback: VecDeque<u8>) -> Self {
+        assert!(format.baud > 0, "the tape does not move");
+        // A start bit, eight data bits, and the stop bits.
+        let bits_per_frame = u64::from(9 + format.stop_bits);
+        let states_per_byte = u64::from(clock_rate) * bits_per_frame / u64::from(format.baud);
+        Self {
+            format,
+            playback,
+            recording: Vec::new(),
+            states_per_byte,
+            states_until_ready: states_per_byte,
+            last_byte: 0x00,
+        }
+    }
+
+    /// Loads the tape recorded in the WAV file located at `path`. See [`new`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the baud rate of `format` is 0.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error ([`Error::FileNotFound`] or [`Error::Io`]) if the WAV
+    /// file cannot be read successfully or it is not in a supported format.
+    ///
+    /// [`new`]: #method.new
+    /// [`Error::FileNotFound`]: ../enum.Error.html#variant.FileNotFound
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn load<P: AsRef<Path>>(path: P, format: Format, clock_rate: u32) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|err| Error::from_open(err, path))?;
+        Ok(Self::from_wave(&Wave::read(io::BufReader::new(file))?, format, clock_rate))
+    }
+
+    /// Returns the format of the tape.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Returns the bytes that have yet to be played back.
+    pub fn playback(&self) -> &VecDeque<u8> {
+        &self.playback
+    }
+
+    /// Returns the bytes that have been recorded.
+    pub fn recording(&self) -> &[u8] {
+        &self.recording
+    }
+
+    /// Encodes the recorded bytes at `sample_rate` with a leader of a second.
+    pub fn to_wave(&self, sample_rate: u32) -> Wave {
+        encode(&self.recording, self.format, sample_rate, 1.0)
+    }
+
+    /// Saves the recorded bytes as a WAV file located at `path`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::Io`] error if the WAV file cannot be written
+    /// successfully.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn save<P: AsRef<Path>>(&self, path: P, sample_rate: u32) -> Result<()> {
+        let file = File::create(path)?;
+        self.to_wave(sample_rate).write(BufWriter::new(file))?;
+        Ok(())
+    }
+}
+
+impl Console for Cassette {
+    fn is_input_ready(&mut self) -> bool {
+        !self.playback.is_empty() && self.states_until_ready == 0
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        match self.playback.front() {
+            None => Ok(None),
+            Some(_) if self.states_until_ready > 0 => Ok(Some(self.last_byte)),
+            Some(&byte) => {
+                self.playback.pop_front();
+                self.last_byte = byte;
+                self.states_until_ready = self.states_per_byte;
+                Ok(Some(byte))
+            }
+        }
+    }
+
+    fn write_byte(&mut self, byte: u8) -> io::Result<()> {
+        self.recording.push(byte);
+        Ok(())
+    }
+
+    fn elapse(&mut self, states: u32) {
+        self.states_until_ready = self.states_until_ready.saturating_sub(u64::from(states));
+    }
+}
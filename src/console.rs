@@ -0,0 +1,140 @@
+use std::{
+    collections::VecDeque,
+    io::{self, Read, Write},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+/// A character device, such as a terminal, that is attached to an emulated system.
+pub trait Console {
+    /// Returns `true` if a byte is ready to be read without blocking.
+    fn is_input_ready(&mut self) -> bool;
+
+    /// Reads a byte, blocking until one is available. Returns `None` at the end of input.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the underlying device cannot be read successfully.
+    fn read_byte(&mut self) -> io::Result<Option<u8>>;
+
+    /// Writes a byte.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the underlying device cannot be written
+    /// successfully.
+    fn write_byte(&mut self, byte: u8) -> io::Result<()>;
+
+    /// Tells the device that the CPU has executed `states` states, so that devices with their own
+    /// timing, such as a paper tape reader, can keep pace with it. The default implementation
+    /// does nothing.
+    fn elapse(&mut self, states: u32) {
+        let _ = states;
+    }
+}
+
+impl<C: Console + ?Sized> Console for Box<C> {
+    fn is_input_ready(&mut self) -> bool {
+        (**self).is_input_ready()
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        (**self).read_byte()
+    }
+
+    fn write_byte(&mut self, byte: u8) -> io::Result<()> {
+        (**self).write_byte(byte)
+    }
+
+    fn elapse(&mut self, states: u32) {
+        (**self).elapse(states)
+    }
+}
+
+/// A console that reads input from and writes output to in-memory buffers.
+#[derive(Debug, Default)]
+pub struct BufferConsole {
+    /// The bytes that have yet to be read.
+    pub input: VecDeque<u8>,
+    /// The bytes that have been written.
+    pub output: Vec<u8>,
+}
+
+impl BufferConsole {
+    /// Creates a console that will read `input` and has written nothing yet.
+    pub fn new<I: Into<Vec<u8>>>(input: I) -> Self {
+        Self { input: input.into().into(), output: Vec::new() }
+    }
+}
+
+impl Console for BufferConsole {
+    fn is_input_ready(&mut self) -> bool {
+        !self.input.is_empty()
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        Ok(self.input.pop_front())
+    }
+
+    fn write_byte(&mut self, byte: u8) -> io::Result<()> {
+        self.output.push(byte);
+        Ok(())
+    }
+}
+
+/// A console that is bound to the standard input and output of the host process.
+///
+/// Line feeds read from the standard input are translated into carriage returns, which is what
+/// the 8080 software expects the Return key to produce.
+pub struct StdioConsole {
+    // The standard input is read by a background thread so that `is_input_ready` never blocks.
+    input: Receiver<io::Result<u8>>,
+    peeked: Option<io::Result<u8>>,
+}
+
+impl StdioConsole {
+    /// Creates a console that is bound to the standard input and output.
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for byte in io::stdin().lock().bytes() {
+                let byte = byte.map(|byte| if byte == b'\n' { b'\r' } else { byte });
+                if sender.send(byte).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { input: receiver, peeked: None }
+    }
+}
+
+impl Default for StdioConsole {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Console for StdioConsole {
+    fn is_input_ready(&mut self) -> bool {
+        if self.peeked.is_none() {
+            match self.input.try_recv() {
+                Ok(byte) => self.peeked = Some(byte),
+                Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => return false,
+            }
+        }
+        true
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        match self.peeked.take() {
+            Some(byte) => byte.map(Some),
+            None => self.input.recv().map_or(Ok(None), |byte| byte.map(Some)),
+        }
+    }
+
+    fn write_byte(&mut self, byte: u8) -> io::Result<()> {
+        let mut stdout = io::stdout();
+        stdout.write_all(&[byte])?;
+        stdout.flush()
+    }
+}
@@ -0,0 +1,898 @@
+//! A CP/M 2.2 environment that runs .COM programs out of host directories.
+//!
+//! [`Bdos`] traps calls to the BDOS entry point and the BIOS jump table, and services them on the
+//! host. The files of a CP/M drive are the files of the host directory mapped to the drive.
+//!
+//! [`System`], on the other hand, boots the genuine CCP and BDOS from a disk image, emulating only
+//! the BIOS.
+//!
+//! [`Bdos`]: struct.Bdos.html
+//! [`System`]: struct.System.html
+
+use std::{
+    cmp,
+    collections::{HashMap, VecDeque},
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{console::Console, Error, Intel8080, Result};
+
+pub mod disk;
+mod system;
+pub(crate) use system::Bios;
+pub use system::{System, DEFAULT_BIOS_BASE};
+
+/// The address at which .COM programs are loaded and started.
+pub const TPA_START: u16 = 0x0100;
+/// The address of the BDOS. The BDOS entry point is located 6 bytes after it.
+pub const BDOS_BASE: u16 = 0xFE00;
+/// The address of the BDOS entry point, which location 0x0005 jumps to.
+pub const BDOS_ENTRY: u16 = BDOS_BASE + 6;
+/// The address of the BIOS jump table.
+pub const BIOS_BASE: u16 = 0xFF00;
+/// The address of the default FCB.
+pub const DEFAULT_FCB: u16 = 0x005C;
+/// The address of the default DMA buffer, which initially holds the command tail.
+pub const DEFAULT_DMA: u16 = 0x0080;
+
+const RECORD_SIZE: usize = 128;
+const RECORDS_PER_EXTENT: u32 = 128;
+const DISK_PARAMETER_BLOCK: u16 = BDOS_BASE + 0x10;
+const ALLOCATION_VECTOR: u16 = BDOS_BASE + 0x80;
+const BIOS_ENTRIES: u16 = 17;
+const EOF: u8 = 0x1A; // ^Z
+
+// The number of states after which `run` lets the console catch up with the CPU.
+const STATES_PER_RUN: u64 = 1_000_000;
+
+/// A CP/M 2.2 BDOS that is emulated on the host.
+pub struct Bdos<C> {
+    console: C,
+    drives: [Option<PathBuf>; 16],
+    current_drive: u8,
+    user: u8,
+    dma_address: u16,
+    // The files that have been opened or made, by their drives and names.
+    files: HashMap<(u8, [u8; 11]), File>,
+    search_results: VecDeque<DirectoryEntry>,
+}
+
+impl<C: Console> Bdos<C> {
+    /// Creates a BDOS that talks to `console` and maps `directory` to drive A.
+    pub fn new<P: Into<PathBuf>>(console: C, directory: P) -> Self {
+        let mut bdos = Self {
+            console,
+            drives: Default::default(),
+            current_drive: 0,
+            user: 0,
+            dma_address: DEFAULT_DMA,
+            files: HashMap::new(),
+            search_results: VecDeque::new(),
+        };
+        bdos.drives[0] = Some(directory.into());
+        bdos
+    }
+
+    /// Maps `directory` to `drive` (0 = A, 1 = B, ..., 15 = P).
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `drive` is greater than 15.
+    pub fn mount<P: Into<PathBuf>>(&mut self, drive: u8, directory: P) {
+        self.drives[usize::from(drive)] = Some(directory.into());
+    }
+
+    /// Returns a reference to the console.
+    pub fn console(&self) -> &C {
+        &self.console
+    }
+
+    /// Returns a mutable reference to the console.
+    pub fn console_mut(&mut self) -> &mut C {
+        &mut self.console
+    }
+
+    /// Consumes the BDOS, returning the console.
+    pub fn into_console(self) -> C {
+        self.console
+    }
+
+    /// Creates an Intel 8080 system with the .COM program located at `path` loaded at
+    /// [`TPA_START`], as if the CCP had been given the command line `path args...`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error ([`Error::FileNotFound`], [`Error::Io`], or
+    /// [`Error::TooLargeFile`]) if the program cannot be read successfully or it does not fit in
+    /// the TPA.
+    ///
+    /// [`TPA_START`]: constant.TPA_START.html
+    /// [`Error::FileNotFound`]: ../enum.Error.html#variant.FileNotFound
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    /// [`Error::TooLargeFile`]: ../enum.Error.html#variant.TooLargeFile
+    pub fn load_program<P: AsRef<Path>, S: AsRef<str>>(
+        &mut self,
+        path: P,
+        args: &[S],
+    ) -> Result<Intel8080> {
+        let path = path.as_ref();
+        let mut i8080 = Intel8080::new(&[path], TPA_START)?;
+        let size = fs::metadata(path)?.len();
+        if u64::from(TPA_START) + size > u64::from(BDOS_BASE) {
+            return Err(Error::TooLargeFile {
+                path: path.to_path_buf(),
+                size,
+                start_address: TPA_START,
+            });
+        }
+        self.install(&mut i8080, args);
+        Ok(i8080)
+    }
+
+    /// Sets up the zero page, the BDOS entry point, the BIOS jump table, and a stack whose top
+    /// holds 0x0000 in the memory of `i8080`. The command tail and the default FCBs are built from
+    /// `args`.
+    pub fn install<S: AsRef<str>>(&mut self, i8080: &mut Intel8080, args: &[S]) {
+        let memory = &mut i8080.memory;
+        // JMP WBOOT
+        memory[0x0000] = 0xC3;
+        memory[0x0001..0x0003].copy_from_slice(&(BIOS_BASE + 3).to_le_bytes());
+        memory[0x0003] = 0x00; // IOBYTE
+        memory[0x0004] = (self.user << 4) | self.current_drive;
+        // JMP BDOS
+        memory[0x0005] = 0xC3;
+        memory[0x0006..0x0008].copy_from_slice(&BDOS_ENTRY.to_le_bytes());
+        memory[BDOS_BASE..BDOS_ENTRY].iter_mut().for_each(|byte| *byte = 0);
+        memory[BDOS_ENTRY] = 0xC9; // RET
+
+        // Every entry of the BIOS jump table returns immediately. `trap` services it beforehand.
+        for entry in 0..BIOS_ENTRIES {
+            let address = BIOS_BASE + entry * 3;
+            memory[address..address + 3].copy_from_slice(&[0xC9, 0x00, 0x00]);
+        }
+        self.install_disk_parameters(i8080);
+
+        let tail = args.iter().fold(String::new(), |tail, arg| tail + " " + arg.as_ref());
+        let tail = tail.to_ascii_uppercase();
+        let tail = &tail.as_bytes()[..cmp::min(tail.len(), 127)];
+        let memory = &mut i8080.memory;
+        memory[DEFAULT_FCB..0x0100].iter_mut().for_each(|byte| *byte = 0);
+        memory[DEFAULT_DMA] = tail.len() as u8;
+        memory[DEFAULT_DMA + 1..DEFAULT_DMA + 1 + tail.len() as u16].copy_from_slice(tail);
+        let mut args = args.iter().map(|arg| arg.as_ref().to_ascii_uppercase());
+        memory[DEFAULT_FCB..DEFAULT_FCB + 12].copy_from_slice(&parse_file_name(args.next()));
+        memory[DEFAULT_FCB + 16..DEFAULT_FCB + 28].copy_from_slice(&parse_file_name(args.next()));
+
+        // The stack holds the address of the warm boot so that a program can return to CP/M with
+        // RET.
+        i8080.cpu.sp = BDOS_BASE - 2;
+        i8080.memory[BDOS_BASE - 2] = 0x00;
+        i8080.memory[BDOS_BASE - 1] = 0x00;
+        self.dma_address = DEFAULT_DMA;
+    }
+
+    fn install_disk_parameters(&self, i8080: &mut Intel8080) {
+        // A 2M drive with 2K blocks, none of which is in use.
+        let dpb: [u8; 15] = [
+            0x80, 0x00, // SPT: 128 records per track
+            0x04, // BSH: 2K blocks
+            0x0F, // BLM
+            0x00, // EXM
+            0xFF, 0x03, // DSM: 1024 blocks
+            0xFF, 0x03, // DRM: 1024 directory entries
+            0x00, 0x00, // AL0, AL1
+            0x00, 0x00, // CKS
+            0x00, 0x00, // OFF
+        ];
+        i8080.memory[DISK_PARAMETER_BLOCK..DISK_PARAMETER_BLOCK + 15].copy_from_slice(&dpb);
+        i8080.memory[ALLOCATION_VECTOR..BIOS_BASE].iter_mut().for_each(|byte| *byte = 0);
+    }
+
+    /// Runs the program in `i8080` until it performs a warm boot by transferring control to
+    /// location 0x0000.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the CPU halts ([`Error::Halted`]) or the console
+    /// cannot be read or written successfully ([`Error::Io`]).
+    ///
+    /// [`Error::Halted`]: ../enum.Error.html#variant.Halted
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn run(&mut self, i8080: &mut Intel8080) -> Result<()> {
+        while i8080.cpu.pc != 0x0000 {
+            self.trap(i8080)?;
+            if i8080.cpu.pc == 0x0000 {
+                break;
+            }
+            if i8080.cpu.is_halted() {
+                return Err(Error::Halted);
+            }
+            // Runs up to the next BDOS or BIOS call or the warm boot.
+            let states = i8080
+                .run(STATES_PER_RUN, |pc| pc == 0x0000 || pc == BDOS_ENTRY || is_bios_entry(pc));
+            self.console.elapse(states as u32);
+        }
+        self.files.clear();
+        Ok(())
+    }
+
+    /// Services a BDOS or BIOS call if the program counter is at the BDOS entry point or at an
+    /// entry of the BIOS jump table. The call returns when the RET instruction there is executed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::Io`] error if the console cannot be read or written
+    /// successfully.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn trap(&mut self, i8080: &mut Intel8080) -> Result<()> {
+        let pc = i8080.cpu.pc;
+        if pc == BDOS_ENTRY {
+            let result = self.call(i8080)?;
+            let [low, high] = result.to_le_bytes();
+            i8080.cpu.a = low;
+            i8080.cpu.l = low;
+            i8080.cpu.b = high;
+            i8080.cpu.h = high;
+        } else if is_bios_entry(pc) {
+            self.call_bios((pc - BIOS_BASE) / 3, i8080)?;
+        }
+        Ok(())
+    }
+
+    fn call_bios(&mut self, entry: u16, i8080: &mut Intel8080) -> Result<()> {
+        match entry {
+            // BOOT, WBOOT
+            0 | 1 => i8080.cpu.pc = 0x0000,
+            // CONST
+            2 => i8080.cpu.a = if self.console.is_input_ready() { 0xFF } else { 0x00 },
+            // CONIN
+            3 | 7 => i8080.cpu.a = self.console.read_byte()?.unwrap_or(EOF),
+            // CONOUT, LIST, PUNCH
+            4..=6 => self.console.write_byte(i8080.cpu.c)?,
+            // The other entries access disks, which are not available through the BIOS.
+            _ => i8080.cpu.a = 0xFF,
+        }
+        Ok(())
+    }
+
+    // Performs the BDOS function whose number is in C, returning the value for HL.
+    fn call(&mut self, i8080: &mut Intel8080) -> Result<u16> {
+        let de = u16::from_le_bytes([i8080.cpu.e, i8080.cpu.d]);
+        let e = i8080.cpu.e;
+        let result = match i8080.cpu.c {
+            // System Reset
+            0x00 => {
+                i8080.cpu.pc = 0x0000;
+                0x00
+            }
+            // Console Input
+            0x01 | 0x03 => {
+                let byte = self.console.read_byte()?.unwrap_or(EOF);
+                if i8080.cpu.c == 0x01 && (byte >= b' ' || byte == b'\r' || byte == b'\n') {
+                    self.console.write_byte(byte)?;
+                }
+                byte
+            }
+            // Console Output, Punch Output, List Output
+            0x02 | 0x04 | 0x05 => {
+                self.console.write_byte(e)?;
+                0x00
+            }
+            // Direct Console I/O
+            0x06 => match e {
+                0xFF if self.console.is_input_ready() => self.console.read_byte()?.unwrap_or(EOF),
+                0xFF => 0x00,
+                0xFE => self.console_status(),
+                _ => {
+                    self.console.write_byte(e)?;
+                    0x00
+                }
+            },
+            // Get I/O Byte
+            0x07 => i8080.memory[0x0003],
+            // Set I/O Byte
+            0x08 => {
+                i8080.memory[0x0003] = e;
+                0x00
+            }
+            // Print String
+            0x09 => {
+                // A string without a '$' ends after a pass through memory.
+                let mut address = de;
+                for _ in 0..=u16::MAX {
+                    let byte = i8080.memory[address];
+                    if byte == b'$' {
+                        break;
+                    }
+                    self.console.write_byte(byte)?;
+                    address = address.wrapping_add(1);
+                }
+                0x00
+            }
+            // Read Console Buffer
+            0x0A => {
+                self.read_console_buffer(i8080, de)?;
+                0x00
+            }
+            // Get Console Status
+            0x0B => self.console_status(),
+            // Return Version Number
+            0x0C => return Ok(0x0022),
+            // Reset Disk System
+            0x0D => {
+                self.current_drive = 0;
+                self.dma_address = DEFAULT_DMA;
+                0x00
+            }
+            // Select Disk
+            0x0E => {
+                if let Some(Some(_)) = self.drives.get(usize::from(e)) {
+                    self.current_drive = e;
+                    0x00
+                } else {
+                    0xFF
+                }
+            }
+            // Open File
+            0x0F => self.open_file(i8080, de),
+            // Close File
+            0x10 => self.close_file(i8080, de),
+            // Search for First
+            0x11 => {
+                self.search_results = self.search(i8080, de).into();
+                self.search_next(i8080)
+            }
+            // Search for Next
+            0x12 => self.search_next(i8080),
+            // Delete File
+            0x13 => self.delete_file(i8080, de),
+            // Read Sequential
+            0x14 => self.read_sequential(i8080, de),
+            // Write Sequential
+            0x15 => self.write_sequential(i8080, de),
+            // Make File
+            0x16 => self.make_file(i8080, de),
+            // Rename File
+            0x17 => self.rename_file(i8080, de),
+            // Return Login Vector
+            0x18 => {
+                let vector = self
+                    .drives
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, directory)| directory.is_some())
+                    .fold(0, |vector, (drive, _)| vector | 1 << drive);
+                return Ok(vector);
+            }
+            // Return Current Disk
+            0x19 => self.current_drive,
+            // Set DMA Address
+            0x1A => {
+                self.dma_address = de;
+                0x00
+            }
+            // Get Addr(Alloc)
+            0x1B => return Ok(ALLOCATION_VECTOR),
+            // Write Protect Disk, Set File Attributes, Reset Drive
+            0x1C | 0x1E | 0x25 => 0x00,
+            // Get R/O Vector
+            0x1D => return Ok(0x0000),
+            // Get Addr(DPB Parms)
+            0x1F => return Ok(DISK_PARAMETER_BLOCK),
+            // Set/Get User Code
+            0x20 => {
+                if e == 0xFF {
+                    self.user
+                } else {
+                    self.user = e & 0x0F;
+                    0x00
+                }
+            }
+            // Read Random
+            0x21 => self.read_random(i8080, de),
+            // Write Random, Write Random with Zero Fill
+            0x22 | 0x28 => self.write_random(i8080, de),
+            // Compute File Size
+            0x23 => self.compute_file_size(i8080, de),
+            // Set Random Record
+            0x24 => {
+                let record = Fcb::new(de).sequential_record(i8080);
+                Fcb::new(de).set_random_record(i8080, record);
+                0x00
+            }
+            _ => 0x00,
+        };
+        Ok(u16::from(result))
+    }
+
+    fn console_status(&mut self) -> u8 {
+        if self.console.is_input_ready() {
+            0xFF
+        } else {
+            0x00
+        }
+    }
+
+    fn read_console_buffer(&mut self, i8080: &mut Intel8080, address: u16) -> Result<()> {
+        let capacity = usize::from(i8080.memory[address]);
+        let mut line = Vec::new();
+        loop {
+            let byte = match self.console.read_byte()? {
+                Some(byte) => byte,
+                None if line.is_empty() => {
+                    return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+                }
+                None => break,
+            };
+            match byte {
+                b'\r' | b'\n' => break,
+                // Backspace, Rubout
+                0x08 | 0x7F if line.pop().is_some() => {
+                    for &byte in b"\x08 \x08" {
+                        self.console.write_byte(byte)?;
+                    }
+                }
+                0x08 | 0x7F => (),
+                // ^C at the beginning of a line reboots the system.
+                0x03 if line.is_empty() => {
+                    i8080.cpu.pc = 0x0000;
+                    break;
+                }
+                _ if line.len() < capacity => {
+                    self.console.write_byte(byte)?;
+                    line.push(byte);
+                }
+                _ => (),
+            }
+        }
+        i8080.memory[address.wrapping_add(1)] = line.len() as u8;
+        for (offset, byte) in line.into_iter().enumerate() {
+            i8080.memory[address.wrapping_add(2 + offset as u16)] = byte;
+        }
+        Ok(())
+    }
+
+    fn drive(&self, fcb: Fcb, i8080: &Intel8080) -> u8 {
+        match fcb.byte(i8080, 0) {
+            0 | b'?' => self.current_drive,
+            drive => drive - 1,
+        }
+    }
+
+    fn directory(&self, fcb: Fcb, i8080: &Intel8080) -> Option<&Path> {
+        self.drives.get(usize::from(self.drive(fcb, i8080))).and_then(Option::as_deref)
+    }
+
+    // Returns the key of the file that the FCB names in `files`.
+    fn key(&self, fcb: Fcb, i8080: &Intel8080) -> (u8, [u8; 11]) {
+        (self.drive(fcb, i8080), fcb.name(i8080))
+    }
+
+    // Returns the host files that match the file name, which may contain wildcards, in the FCB.
+    fn search(&self, i8080: &Intel8080, address: u16) -> Vec<DirectoryEntry> {
+        let fcb = Fcb::new(address);
+        let pattern = fcb.name(i8080);
+        let directory = match self.directory(fcb, i8080) {
+            Some(directory) => directory,
+            None => return Vec::new(),
+        };
+        let mut entries: Vec<_> = fs::read_dir(directory)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let metadata = entry.metadata().ok()?;
+                if !metadata.is_file() {
+                    return None;
+                }
+                let name = to_cpm_name(entry.file_name().to_str()?)?;
+                let matches = name.iter().zip(&pattern).all(|(c, p)| *p == b'?' || c == p);
+                if matches {
+                    Some(DirectoryEntry {
+                        name,
+                        path: entry.path(),
+                        records: records(metadata.len()),
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect();
+        entries.sort_by_key(|entry| entry.name);
+        entries
+    }
+
+    fn search_next(&mut self, i8080: &mut Intel8080) -> u8 {
+        let entry = match self.search_results.pop_front() {
+            Some(entry) => entry,
+            None => return 0xFF,
+        };
+        // The last extent of the file is described in the directory entry.
+        let extent = entry.records.saturating_sub(1) / RECORDS_PER_EXTENT;
+        let mut directory_entry = [0u8; 32];
+        directory_entry[0] = self.user;
+        directory_entry[1..12].copy_from_slice(&entry.name);
+        directory_entry[12] = (extent & 0x1F) as u8;
+        directory_entry[14] = (extent >> 5) as u8;
+        directory_entry[15] = (entry.records - extent * RECORDS_PER_EXTENT) as u8;
+        let dma_address = self.dma_address;
+        for (offset, byte) in directory_entry.iter().enumerate() {
+            i8080.memory[dma_address.wrapping_add(offset as u16)] = *byte;
+        }
+        0x00
+    }
+
+    fn find(&self, i8080: &Intel8080, address: u16) -> Option<DirectoryEntry> {
+        self.search(i8080, address).into_iter().next()
+    }
+
+    fn open_file(&mut self, i8080: &mut Intel8080, address: u16) -> u8 {
+        let entry = match self.find(i8080, address) {
+            Some(entry) => entry,
+            None => return 0xFF,
+        };
+        let file = match open(&entry.path) {
+            Ok(file) => file,
+            Err(_) => return 0xFF,
+        };
+        let fcb = Fcb::new(address);
+        fcb.set_name(i8080, &entry.name);
+        fcb.update_record_count(i8080, entry.records);
+        let key = self.key(fcb, i8080);
+        self.files.insert(key, file);
+        0x00
+    }
+
+    fn close_file(&mut self, i8080: &Intel8080, address: u16) -> u8 {
+        match self.files.remove(&self.key(Fcb::new(address), i8080)) {
+            Some(file) if file.sync_all().is_err() => 0xFF,
+            Some(_) => 0x00,
+            None if self.find(i8080, address).is_some() => 0x00,
+            None => 0xFF,
+        }
+    }
+
+    fn delete_file(&mut self, i8080: &Intel8080, address: u16) -> u8 {
+        let entries = self.search(i8080, address);
+        if entries.is_empty() {
+            return 0xFF;
+        }
+        let drive = self.drive(Fcb::new(address), i8080);
+        for entry in entries {
+            self.files.remove(&(drive, entry.name));
+            if fs::remove_file(&entry.path).is_err() {
+                return 0xFF;
+            }
+        }
+        0x00
+    }
+
+    fn make_file(&mut self, i8080: &mut Intel8080, address: u16) -> u8 {
+        let fcb = Fcb::new(address);
+        let directory = match self.directory(fcb, i8080) {
+            Some(directory) => directory,
+            None => return 0xFF,
+        };
+        let path = match self.find(i8080, address) {
+            Some(entry) => entry.path,
+            None => directory.join(to_host_name(&fcb.name(i8080))),
+        };
+        // Making a subsequent extent of a file keeps the preceding extents.
+        let truncate = fcb.sequential_record(i8080) < RECORDS_PER_EXTENT;
+        match OpenOptions::new().read(true).write(true).create(true).truncate(truncate).open(&path)
+        {
+            Ok(file) => {
+                let key = self.key(fcb, i8080);
+                self.files.insert(key, file);
+                fcb.set_byte(i8080, 15, 0x00);
+                0x00
+            }
+            Err(_) => 0xFF,
+        }
+    }
+
+    fn rename_file(&mut self, i8080: &Intel8080, address: u16) -> u8 {
+        let entry = match self.find(i8080, address) {
+            Some(entry) => entry,
+            None => return 0xFF,
+        };
+        let new_name = to_host_name(&Fcb::new(address.wrapping_add(16)).name(i8080));
+        let new_path = entry.path.with_file_name(new_name);
+        let drive = self.drive(Fcb::new(address), i8080);
+        self.files.remove(&(drive, entry.name));
+        match fs::rename(&entry.path, new_path) {
+            Ok(()) => 0x00,
+            Err(_) => 0xFF,
+        }
+    }
+
+    // Returns the file that the FCB names, which is looked up in the directory only if it has not
+    // been opened or made.
+    fn file(&mut self, i8080: &Intel8080, address: u16) -> Option<&mut File> {
+        let key = self.key(Fcb::new(address), i8080);
+        if !self.files.contains_key(&key) {
+            let entry = self.find(i8080, address)?;
+            let file = open(&entry.path).ok()?;
+            self.files.insert(key, file);
+        }
+        self.files.get_mut(&key)
+    }
+
+    // Reads a record from the file into the DMA buffer, returning 0x00 on success or 0x01 if the
+    // record is beyond the end of the file.
+    fn read_record(&mut self, i8080: &mut Intel8080, address: u16, record: u32) -> u8 {
+        let dma_address = self.dma_address;
+        let file = match self.file(i8080, address) {
+            Some(file) => file,
+            None => return 0xFF,
+        };
+        let mut buffer = [EOF; RECORD_SIZE];
+        let mut size = 0;
+        if file.seek(SeekFrom::Start(u64::from(record) * RECORD_SIZE as u64)).is_err() {
+            return 0x01;
+        }
+        while size < RECORD_SIZE {
+            match file.read(&mut buffer[size..]) {
+                Ok(0) => break,
+                Ok(n) => size += n,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(_) => return 0x01,
+            }
+        }
+        if size == 0 {
+            return 0x01;
+        }
+        for (offset, byte) in buffer.iter().enumerate() {
+            i8080.memory[dma_address.wrapping_add(offset as u16)] = *byte;
+        }
+        0x00
+    }
+
+    // Writes a record from the DMA buffer to the file, returning 0x00 on success or 0x02 if the
+    // disk is full.
+    fn write_record(&mut self, i8080: &Intel8080, address: u16, record: u32) -> u8 {
+        let dma_address = self.dma_address;
+        let mut buffer = [0; RECORD_SIZE];
+        for (offset, byte) in buffer.iter_mut().enumerate() {
+            *byte = i8080.memory[dma_address.wrapping_add(offset as u16)];
+        }
+        let file = match self.file(i8080, address) {
+            Some(file) => file,
+            None => return 0xFF,
+        };
+        match file
+            .seek(SeekFrom::Start(u64::from(record) * RECORD_SIZE as u64))
+            .and_then(|_| file.write_all(&buffer))
+        {
+            Ok(()) => 0x00,
+            Err(_) => 0x02,
+        }
+    }
+
+    fn file_records(&mut self, i8080: &Intel8080, address: u16) -> u32 {
+        self.file(i8080, address)
+            .and_then(|file| file.metadata().ok())
+            .map_or(0, |metadata| records(metadata.len()))
+    }
+
+    fn read_sequential(&mut self, i8080: &mut Intel8080, address: u16) -> u8 {
+        let fcb = Fcb::new(address);
+        let record = fcb.sequential_record(i8080);
+        let result = self.read_record(i8080, address, record);
+        if result == 0x00 {
+            fcb.set_sequential_record(i8080, record + 1);
+            let records = self.file_records(i8080, address);
+            fcb.update_record_count(i8080, records);
+        }
+        result
+    }
+
+    fn write_sequential(&mut self, i8080: &mut Intel8080, address: u16) -> u8 {
+        let fcb = Fcb::new(address);
+        let record = fcb.sequential_record(i8080);
+        let result = self.write_record(i8080, address, record);
+        if result == 0x00 {
+            fcb.set_sequential_record(i8080, record + 1);
+            let records = self.file_records(i8080, address);
+            fcb.update_record_count(i8080, records);
+        }
+        result
+    }
+
+    fn read_random(&mut self, i8080: &mut Intel8080, address: u16) -> u8 {
+        let fcb = Fcb::new(address);
+        let record = match fcb.random_record(i8080) {
+            Some(record) => record,
+            None => return 0x06,
+        };
+        // The file is positioned at the record so that a sequential read reads it again.
+        fcb.set_sequential_record(i8080, record);
+        let records = self.file_records(i8080, address);
+        fcb.update_record_count(i8080, records);
+        self.read_record(i8080, address, record)
+    }
+
+    fn write_random(&mut self, i8080: &mut Intel8080, address: u16) -> u8 {
+        let fcb = Fcb::new(address);
+        let record = match fcb.random_record(i8080) {
+            Some(record) => record,
+            None => return 0x06,
+        };
+        fcb.set_sequential_record(i8080, record);
+        let result = self.write_record(i8080, address, record);
+        let records = self.file_records(i8080, address);
+        fcb.update_record_count(i8080, records);
+        result
+    }
+
+    fn compute_file_size(&mut self, i8080: &mut Intel8080, address: u16) -> u8 {
+        match self.find(i8080, address) {
+            Some(entry) => {
+                Fcb::new(address).set_random_record(i8080, entry.records);
+                0x00
+            }
+            None => 0xFF,
+        }
+    }
+}
+
+// A file that matches a search.
+struct DirectoryEntry {
+    name: [u8; 11],
+    path: PathBuf,
+    records: u32,
+}
+
+// A file control block located at an address.
+#[derive(Clone, Copy)]
+struct Fcb(u16);
+
+impl Fcb {
+    fn new(address: u16) -> Self {
+        Self(address)
+    }
+
+    fn byte(self, i8080: &Intel8080, offset: u16) -> u8 {
+        i8080.memory[self.0.wrapping_add(offset)]
+    }
+
+    fn set_byte(self, i8080: &mut Intel8080, offset: u16, byte: u8) {
+        i8080.memory[self.0.wrapping_add(offset)] = byte;
+    }
+
+    // Returns the file name and the file type with the attribute bits cleared.
+    fn name(self, i8080: &Intel8080) -> [u8; 11] {
+        let mut name = [0; 11];
+        for (offset, byte) in name.iter_mut().enumerate() {
+            *byte = (self.byte(i8080, 1 + offset as u16) & 0x7F).to_ascii_uppercase();
+        }
+        name
+    }
+
+    fn set_name(self, i8080: &mut Intel8080, name: &[u8; 11]) {
+        for (offset, byte) in name.iter().enumerate() {
+            let attribute = self.byte(i8080, 1 + offset as u16) & 0x80;
+            self.set_byte(i8080, 1 + offset as u16, byte | attribute);
+        }
+    }
+
+    // Returns the record that the next sequential operation accesses, which is determined by the
+    // module number (S2), the extent number (EX), and the current record (CR).
+    fn sequential_record(self, i8080: &Intel8080) -> u32 {
+        let module = u32::from(self.byte(i8080, 14) & 0x3F);
+        let extent = u32::from(self.byte(i8080, 12) & 0x1F);
+        let current_record = u32::from(self.byte(i8080, 32) & 0x7F);
+        (module * 32 + extent) * RECORDS_PER_EXTENT + current_record
+    }
+
+    fn set_sequential_record(self, i8080: &mut Intel8080, record: u32) {
+        let extent = record / RECORDS_PER_EXTENT;
+        self.set_byte(i8080, 12, (extent & 0x1F) as u8);
+        self.set_byte(i8080, 14, ((extent >> 5) & 0x3F) as u8);
+        self.set_byte(i8080, 32, (record % RECORDS_PER_EXTENT) as u8);
+    }
+
+    // Sets the record count (RC) of the current extent of a file that has `records` records.
+    fn update_record_count(self, i8080: &mut Intel8080, records: u32) {
+        let first_record = self.sequential_record(i8080) / RECORDS_PER_EXTENT * RECORDS_PER_EXTENT;
+        let count = records.saturating_sub(first_record).min(RECORDS_PER_EXTENT);
+        self.set_byte(i8080, 15, count as u8);
+    }
+
+    // Returns the random record number (R0, R1), or `None` if it overflows (R2).
+    fn random_record(self, i8080: &Intel8080) -> Option<u32> {
+        if self.byte(i8080, 35) != 0 {
+            return None;
+        }
+        Some(u32::from(u16::from_le_bytes([self.byte(i8080, 33), self.byte(i8080, 34)])))
+    }
+
+    fn set_random_record(self, i8080: &mut Intel8080, record: u32) {
+        let [r0, r1, r2, _] = record.to_le_bytes();
+        self.set_byte(i8080, 33, r0);
+        self.set_byte(i8080, 34, r1);
+        self.set_byte(i8080, 35, r2);
+    }
+}
+
+// Opens the file located at `path` for reading and writing, or only for reading if it is
+// read-only.
+fn open(path: &Path) -> io::Result<File> {
+    OpenOptions::new().read(true).write(true).open(path).or_else(|_| File::open(path))
+}
+
+// Returns the number of records that a file of `size` bytes occupies.
+fn records(size: u64) -> u32 {
+    let records = size / RECORD_SIZE as u64 + u64::from(size % RECORD_SIZE as u64 != 0);
+    records.min(u64::from(u32::MAX)) as u32
+}
+
+// Returns `true` if `address` is an entry of the BIOS jump table.
+fn is_bios_entry(address: u16) -> bool {
+    (BIOS_BASE..BIOS_BASE + BIOS_ENTRIES * 3).contains(&address) && (address - BIOS_BASE) % 3 == 0
+}
+
+// Parses a file specification such as "B:NAME.TYP" into the drive code, the file name, and the
+// file type of an FCB. `*` is expanded into `?`s.
+fn parse_file_name(spec: Option<String>) -> [u8; 12] {
+    let mut fcb = [b' '; 12];
+    fcb[0] = 0;
+    let spec = match spec {
+        Some(spec) => spec,
+        None => return fcb,
+    };
+    let mut spec = spec.as_bytes();
+    if spec.len() >= 2 && spec[1] == b':' && spec[0].is_ascii_uppercase() {
+        fcb[0] = spec[0] - b'A' + 1;
+        spec = &spec[2..];
+    }
+    let mut parts = spec.splitn(2, |byte| *byte == b'.');
+    let fill = |field: &mut [u8], part: &[u8]| {
+        let wildcard = part.iter().position(|byte| *byte == b'*');
+        let len = cmp::min(wildcard.unwrap_or(part.len()), field.len());
+        field[..len].copy_from_slice(&part[..len]);
+        if wildcard.is_some() {
+            field[len..].iter_mut().for_each(|slot| *slot = b'?');
+        }
+    };
+    fill(&mut fcb[1..9], parts.next().unwrap_or_default());
+    fill(&mut fcb[9..12], parts.next().unwrap_or_default());
+    fcb
+}
+
+// Converts a host file name into the file name and the file type of an FCB, returning `None` if it
+// cannot be represented in CP/M.
+fn to_cpm_name(host_name: &str) -> Option<[u8; 11]> {
+    let host_name = host_name.to_ascii_uppercase();
+    let (name, typ) = match host_name.rfind('.') {
+        Some(0) => return None,
+        Some(dot) => (&host_name[..dot], &host_name[dot + 1..]),
+        None => (&host_name[..], ""),
+    };
+    if name.is_empty()
+        || name.len() > 8
+        || typ.len() > 3
+        || !(name.bytes().chain(typ.bytes())).all(|byte| byte.is_ascii_graphic() && byte != b'.')
+    {
+        return None;
+    }
+    let mut cpm_name = [b' '; 11];
+    cpm_name[..name.len()].copy_from_slice(name.as_bytes());
+    cpm_name[8..8 + typ.len()].copy_from_slice(typ.as_bytes());
+    Some(cpm_name)
+}
+
+// Converts the file name and the file type of an FCB into a host file name.
+fn to_host_name(cpm_name: &[u8; 11]) -> String {
+    let name = String::from_utf8_lossy(&cpm_name[..8]);
+    let typ = String::from_utf8_lossy(&cpm_name[8..]);
+    let (name, typ) = (name.trim_end(), typ.trim_end());
+    if typ.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", name, typ)
+    }
+}
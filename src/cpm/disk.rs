@@ -0,0 +1,243 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use crate::{Error, Result};
+
+/// The size of a sector in bytes. Every disk format uses 128-byte sectors, which the BIOS reads
+/// and writes without blocking or deblocking.
+pub const SECTOR_SIZE: usize = 128;
+
+/// A disk parameter block (DPB), which describes the logical layout of a disk to the BDOS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DiskParameterBlock {
+    /// The number of 128-byte records per track.
+    pub spt: u16,
+    /// The block shift factor.
+    pub bsh: u8,
+    /// The block mask.
+    pub blm: u8,
+    /// The extent mask.
+    pub exm: u8,
+    /// The number of the last block on the disk.
+    pub dsm: u16,
+    /// The number of the last directory entry.
+    pub drm: u16,
+    /// The bitmap of the blocks reserved for the directory (first byte).
+    pub al0: u8,
+    /// The bitmap of the blocks reserved for the directory (second byte).
+    pub al1: u8,
+    /// The size of the directory check vector.
+    pub cks: u16,
+    /// The number of reserved tracks at the beginning of the disk.
+    pub off: u16,
+}
+
+impl DiskParameterBlock {
+    /// Returns the 15 bytes of the DPB in the order that the BDOS expects.
+    pub fn to_bytes(&self) -> [u8; 15] {
+        let [spt_low, spt_high] = self.spt.to_le_bytes();
+        let [dsm_low, dsm_high] = self.dsm.to_le_bytes();
+        let [drm_low, drm_high] = self.drm.to_le_bytes();
+        let [cks_low, cks_high] = self.cks.to_le_bytes();
+        let [off_low, off_high] = self.off.to_le_bytes();
+        #[rustfmt::skip]
+        let bytes = [
+            spt_low, spt_high, self.bsh, self.blm, self.exm, dsm_low, dsm_high, drm_low, drm_high,
+            self.al0, self.al1, cks_low, cks_high, off_low, off_high,
+        ];
+        bytes
+    }
+
+    /// Returns the size of the allocation vector in bytes.
+    pub fn allocation_vector_size(&self) -> usize {
+        usize::from(self.dsm) / 8 + 1
+    }
+}
+
+/// The physical geometry and the logical layout of a disk.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiskFormat {
+    /// The number of tracks.
+    pub tracks: u16,
+    /// The number of sectors per track.
+    pub sectors_per_track: u16,
+    /// The sector translation table, which maps logical sectors (starting at 0) to physical
+    /// sectors (starting at 1). An empty table means that the sectors are not skewed.
+    pub translation: Vec<u8>,
+    /// The disk parameter block.
+    pub dpb: DiskParameterBlock,
+}
+
+impl DiskFormat {
+    /// Returns the format of 8" single-sided single-density IBM 3740 disks, the standard
+    /// distribution format of CP/M: 77 tracks of 26 sectors with a skew factor of 6.
+    pub fn ibm_3740() -> Self {
+        Self {
+            tracks: 77,
+            sectors_per_track: 26,
+            translation: vec![
+                1, 7, 13, 19, 25, 5, 11, 17, 23, 3, 9, 15, 21, 2, 8, 14, 20, 26, 6, 12, 18, 24, 4,
+                10, 16, 22,
+            ],
+            dpb: DiskParameterBlock {
+                spt: 26,
+                bsh: 3,
+                blm: 7,
+                exm: 0,
+                dsm: 242,
+                drm: 63,
+                al0: 0xC0,
+                al1: 0x00,
+                cks: 16,
+                off: 2,
+            },
+        }
+    }
+
+    /// Returns the format of the 4M hard disks of z80pack: 255 tracks of 128 unskewed sectors.
+    pub fn hard_disk_4m() -> Self {
+        Self {
+            tracks: 255,
+            sectors_per_track: 128,
+            translation: Vec::new(),
+            dpb: DiskParameterBlock {
+                spt: 128,
+                bsh: 4,
+                blm: 15,
+                exm: 0,
+                dsm: 2039,
+                drm: 1023,
+                al0: 0xFF,
+                al1: 0xFF,
+                cks: 0,
+                off: 0,
+            },
+        }
+    }
+
+    /// Returns the size of a disk image of this format in bytes.
+    pub fn capacity(&self) -> usize {
+        usize::from(self.tracks) * usize::from(self.sectors_per_track) * SECTOR_SIZE
+    }
+
+    /// Translates a logical sector (starting at 0) into a physical sector (starting at 1).
+    pub fn translate(&self, logical_sector: u16) -> u16 {
+        self.translation
+            .get(usize::from(logical_sector))
+            .map_or(logical_sector + 1, |physical_sector| u16::from(*physical_sector))
+    }
+}
+
+/// A raw disk image, such as a .IMG or .DSK file, that stores the sectors of a disk in physical
+/// order track after track.
+pub struct DiskImage {
+    format: DiskFormat,
+    data: Vec<u8>,
+    file: Option<File>,
+}
+
+impl DiskImage {
+    /// Creates a blank disk image that is not backed by a file.
+    pub fn new(format: DiskFormat) -> Self {
+        let data = vec![0xE5; format.capacity()];
+        Self { format, data, file: None }
+    }
+
+    /// Creates a disk image that holds `bytes` and is not backed by a file. A short image is
+    /// padded with 0xE5, the value of formatted but unused bytes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::InvalidDiskImage`] error if `bytes` is larger than
+    /// the capacity of `format`.
+    ///
+    /// [`Error::InvalidDiskImage`]: ../../enum.Error.html#variant.InvalidDiskImage
+    pub fn from_bytes(format: DiskFormat, mut bytes: Vec<u8>) -> Result<Self> {
+        if bytes.len() > format.capacity() {
+            return Err(Error::InvalidDiskImage { path: None, size: bytes.len() as u64 });
+        }
+        bytes.resize(format.capacity(), 0xE5);
+        Ok(Self { format, data: bytes, file: None })
+    }
+
+    /// Opens the disk image located at `path`. Sectors written later are written through to the
+    /// file unless it is read-only.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error ([`Error::FileNotFound`], [`Error::Io`], or
+    /// [`Error::InvalidDiskImage`]) if the disk image cannot be read successfully or it is larger
+    /// than the capacity of `format`.
+    ///
+    /// [`Error::FileNotFound`]: ../../enum.Error.html#variant.FileNotFound
+    /// [`Error::Io`]: ../../enum.Error.html#variant.Io
+    /// [`Error::InvalidDiskImage`]: ../../enum.Error.html#variant.InvalidDiskImage
+    pub fn open<P: AsRef<Path>>(path: P, format: DiskFormat) -> Result<Self> {
+        let path = path.as_ref();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .or_else(|_| File::open(path))
+            .map_err(|err| Error::from_open(err, path))?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        let size = data.len() as u64;
+        let mut image = Self::from_bytes(format, data)
+            .map_err(|_| Error::InvalidDiskImage { path: Some(path.to_path_buf()), size })?;
+        image.file = Some(file);
+        Ok(image)
+    }
+
+    /// Returns the format of the disk.
+    pub fn format(&self) -> &DiskFormat {
+        &self.format
+    }
+
+    /// Returns the contents of the disk.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the physical `sector` (starting at 1) of `track`, or `None` if there is no such
+    /// sector.
+    pub fn sector(&self, track: u16, sector: u16) -> Option<&[u8]> {
+        let offset = self.offset(track, sector)?;
+        Some(&self.data[offset..offset + SECTOR_SIZE])
+    }
+
+    /// Writes `data` to the physical `sector` (starting at 1) of `track`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is no such sector or the file that backs the
+    /// disk image cannot be written successfully.
+    pub fn write_sector(
+        &mut self,
+        track: u16,
+        sector: u16,
+        data: &[u8; SECTOR_SIZE],
+    ) -> Result<()> {
+        let offset = self
+            .offset(track, sector)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no such sector"))?;
+        self.data[offset..offset + SECTOR_SIZE].copy_from_slice(data);
+        if let Some(file) = &mut self.file {
+            file.seek(SeekFrom::Start(offset as u64))?;
+            file.write_all(data)?;
+        }
+        Ok(())
+    }
+
+    fn offset(&self, track: u16, sector: u16) -> Option<usize> {
+        if track >= self.format.tracks || sector == 0 || sector > self.format.sectors_per_track {
+            return None;
+        }
+        let index = usize::from(track) * usize::from(self.format.sectors_per_track)
+            + usize::from(sector - 1);
+        Some(index * SECTOR_SIZE)
+    }
+}
@@ -0,0 +1,444 @@
+use std::{io, ops::Range};
+
+use super::{
+    disk::{DiskFormat, DiskImage, SECTOR_SIZE},
+    STATES_PER_RUN,
+};
+use crate::{console::Console, memory::Memory, Error, Intel8080, Result};
+
+/// The address of the BIOS of a 64K CP/M 2.2 system.
+pub const DEFAULT_BIOS_BASE: u16 = 0xFA00;
+
+// The sizes of the CCP and the BDOS, which are loaded from the system tracks by a boot.
+const CCP_SIZE: u16 = 0x0800;
+const BDOS_SIZE: u16 = 0x0E00;
+const BIOS_ENTRIES: u16 = 17;
+const DISK_PARAMETER_HEADER_SIZE: u16 = 16;
+
+/// A CP/M 2.2 system that boots the genuine CCP and BDOS from the system tracks of the disk in
+/// drive A.
+///
+/// The BIOS is emulated on the host: every entry of its jump table is trapped and serviced by
+/// [`trap`], and the disk parameter headers of the mounted drives are placed in memory above the
+/// jump table.
+///
+/// [`trap`]: #method.trap
+pub struct System<C> {
+    /// The Intel 8080 system that runs the CCP, the BDOS, and the programs.
+    pub i8080: Intel8080,
+    console: C,
+    bios: Bios,
+}
+
+impl<C: Console> System<C> {
+    /// Creates a CP/M system whose BIOS is located at `bios_base`. The CCP and the BDOS on the
+    /// system tracks must have been configured for the same memory size, e.g.,
+    /// [`DEFAULT_BIOS_BASE`] for a 64K system.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `bios_base` is not a multiple of 128, leaves no room for the
+    /// zero page, the CCP, and the BDOS below it, or leaves no room for the jump table and the
+    /// directory buffer above it.
+    ///
+    /// [`DEFAULT_BIOS_BASE`]: constant.DEFAULT_BIOS_BASE.html
+    pub fn new(console: C, bios_base: u16) -> Self {
+        let mut i8080 = Intel8080::default();
+        let bios = Bios::new(&mut i8080.memory, bios_base, 0x10000);
+        Self { i8080, console, bios }
+    }
+
+    /// Returns the address of the CCP.
+    pub fn ccp_base(&self) -> u16 {
+        self.bios.ccp_base()
+    }
+
+    /// Returns the address of the BDOS.
+    pub fn bdos_base(&self) -> u16 {
+        self.bios.bdos_base()
+    }
+
+    /// Returns the address of the BIOS.
+    pub fn bios_base(&self) -> u16 {
+        self.bios.bios_base
+    }
+
+    /// Returns a reference to the console.
+    pub fn console(&self) -> &C {
+        &self.console
+    }
+
+    /// Returns a mutable reference to the console.
+    pub fn console_mut(&mut self) -> &mut C {
+        &mut self.console
+    }
+
+    /// Inserts `image` into `drive` (0 = A, 1 = B, ..., 15 = P), returning the disk image that
+    /// was in the drive, if any.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `drive` is greater than 15 or the disk parameter header,
+    /// the disk parameter block, the check vector, and the allocation vector of the drive do not
+    /// fit in memory above the BIOS jump table.
+    pub fn mount(&mut self, drive: u8, image: DiskImage) -> Option<DiskImage> {
+        self.bios.mount(&mut self.i8080.memory, drive, image)
+    }
+
+    /// Removes the disk image from `drive`, returning it.
+    pub fn unmount(&mut self, drive: u8) -> Option<DiskImage> {
+        self.bios.unmount(drive)
+    }
+
+    /// Returns a reference to the disk image in `drive`.
+    pub fn disk(&self, drive: u8) -> Option<&DiskImage> {
+        self.bios.disk(drive)
+    }
+
+    /// Performs a cold boot: loads the CCP and the BDOS from the system tracks of the disk in
+    /// drive A, sets up the zero page, and transfers control to the CCP.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::NoDisk`] error if there is no disk in drive A, or an
+    /// [`Error::InvalidDiskImage`] error if the disk has no room for the CCP and the BDOS on its
+    /// system tracks.
+    ///
+    /// [`Error::NoDisk`]: ../../enum.Error.html#variant.NoDisk
+    /// [`Error::InvalidDiskImage`]: ../../enum.Error.html#variant.InvalidDiskImage
+    pub fn boot(&mut self) -> Result<()> {
+        self.bios.boot(&mut self.i8080)
+    }
+
+    /// Runs the system until an error occurs, e.g., the CPU halts ([`Error::Halted`]) or the
+    /// console reaches the end of its input ([`Error::Io`]).
+    ///
+    /// [`Error::Halted`]: ../../enum.Error.html#variant.Halted
+    /// [`Error::Io`]: ../../enum.Error.html#variant.Io
+    pub fn run(&mut self) -> Result<()> {
+        let bios_table = self.bios.jump_table();
+        loop {
+            self.trap()?;
+            if self.i8080.cpu.is_halted() {
+                return Err(Error::Halted);
+            }
+            // Runs up to the next BIOS call.
+            let states = self.i8080.run(STATES_PER_RUN, |pc| bios_table.contains(&pc));
+            self.console.elapse(states as u32);
+        }
+    }
+
+    /// Services a BIOS call if the program counter is at an entry of the BIOS jump table. The call
+    /// returns when the RET instruction there is executed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a boot fails ([`Error::NoDisk`] or
+    /// [`Error::InvalidDiskImage`]) or the console cannot be read or written successfully
+    /// ([`Error::Io`]).
+    ///
+    /// [`Error::NoDisk`]: ../../enum.Error.html#variant.NoDisk
+    /// [`Error::InvalidDiskImage`]: ../../enum.Error.html#variant.InvalidDiskImage
+    /// [`Error::Io`]: ../../enum.Error.html#variant.Io
+    pub fn trap(&mut self) -> Result<()> {
+        self.bios.trap(&mut self.i8080, &mut self.console)
+    }
+}
+
+// The BIOS emulated on the host and the drives that it serves, which `System` and the machines
+// that boot CP/M install in their memory.
+pub(crate) struct Bios {
+    bios_base: u16,
+    // The end of the memory available for the disk parameters.
+    memory_end: u32,
+    drives: Vec<Option<Drive>>,
+    // The address and the size of the memory allocated for the disk parameters of each drive,
+    // which are kept for the next disk in the drive.
+    allocations: Vec<Option<(u16, u32)>>,
+    // The first address that is not used for the disk parameters yet.
+    free_address: u32,
+    directory_buffer: u16,
+    selected_drive: u8,
+    track: u16,
+    sector: u16,
+    dma_address: u16,
+}
+
+struct Drive {
+    image: DiskImage,
+    disk_parameter_header: u16,
+}
+
+impl Bios {
+    // Installs the jump table of a BIOS at `bios_base` in `memory`, placing the disk parameters
+    // above it up to `memory_end`. Panics as `System::new` does.
+    pub(crate) fn new(memory: &mut Memory, bios_base: u16, memory_end: u32) -> Self {
+        assert!(bios_base % SECTOR_SIZE as u16 == 0, "the BIOS is not aligned on a sector");
+        assert!(bios_base >= 0x0100 + CCP_SIZE + BDOS_SIZE, "no room for the CCP and the BDOS");
+        let directory_buffer = u32::from(bios_base) + u32::from(BIOS_ENTRIES * 3);
+        assert!(
+            directory_buffer + SECTOR_SIZE as u32 <= memory_end,
+            "no room for the BIOS jump table and the directory buffer"
+        );
+        // Every entry of the BIOS jump table returns immediately. `trap` services it beforehand.
+        for entry in 0..BIOS_ENTRIES {
+            let address = bios_base + entry * 3;
+            memory[address..address + 3].copy_from_slice(&[0xC9, 0x00, 0x00]);
+        }
+        Self {
+            bios_base,
+            memory_end,
+            drives: (0..16).map(|_| None).collect(),
+            allocations: vec![None; 16],
+            free_address: directory_buffer + SECTOR_SIZE as u32,
+            directory_buffer: directory_buffer as u16,
+            selected_drive: 0,
+            track: 0,
+            sector: 1,
+            dma_address: 0x0080,
+        }
+    }
+
+    pub(crate) fn ccp_base(&self) -> u16 {
+        self.bios_base - BDOS_SIZE - CCP_SIZE
+    }
+
+    pub(crate) fn bdos_base(&self) -> u16 {
+        self.bios_base - BDOS_SIZE
+    }
+
+    // Returns the addresses of the jump table.
+    pub(crate) fn jump_table(&self) -> Range<u16> {
+        self.bios_base..self.bios_base + BIOS_ENTRIES * 3
+    }
+
+    pub(crate) fn mount(
+        &mut self,
+        memory: &mut Memory,
+        drive: u8,
+        image: DiskImage,
+    ) -> Option<DiskImage> {
+        let disk_parameter_header = self.allocate_disk_parameters(drive, image.format());
+        self.write_disk_parameters(memory, disk_parameter_header, image.format());
+        self.drives[usize::from(drive)]
+            .replace(Drive { image, disk_parameter_header })
+            .map(|drive| drive.image)
+    }
+
+    pub(crate) fn unmount(&mut self, drive: u8) -> Option<DiskImage> {
+        self.drives.get_mut(usize::from(drive))?.take().map(|drive| drive.image)
+    }
+
+    pub(crate) fn disk(&self, drive: u8) -> Option<&DiskImage> {
+        self.drives.get(usize::from(drive))?.as_ref().map(|drive| &drive.image)
+    }
+
+    // Returns the address of the memory for the disk parameters of `format` in `drive`, reusing
+    // the memory allocated for the previous disks in the drive if it is large enough or can grow.
+    fn allocate_disk_parameters(&mut self, drive: u8, format: &DiskFormat) -> u16 {
+        let size = disk_parameters_size(format);
+        let allocation = &mut self.allocations[usize::from(drive)];
+        let address = match *allocation {
+            Some((address, allocated)) if size <= allocated => return address,
+            // The last allocation grows in place.
+            Some((address, allocated)) if u32::from(address) + allocated == self.free_address => {
+                u32::from(address)
+            }
+            _ => self.free_address,
+        };
+        assert!(
+            address + size <= self.memory_end,
+            "the disk parameters of the drive do not fit in memory above the BIOS jump table"
+        );
+        self.free_address = address + size;
+        *allocation = Some((address as u16, size));
+        address as u16
+    }
+
+    // Writes the disk parameter header of `format` to `dph`, followed by the disk parameter
+    // block, the sector translation table, the check vector, and the allocation vector.
+    fn write_disk_parameters(&self, memory: &mut Memory, dph: u16, format: &DiskFormat) {
+        let translation_size = format.translation.len() as u16;
+        let dpb = dph + DISK_PARAMETER_HEADER_SIZE;
+        let translation = dpb + 15;
+        let check_vector = translation + translation_size;
+        let allocation_vector = check_vector + format.dpb.cks;
+
+        let translation_address = if format.translation.is_empty() { 0 } else { translation };
+        let words = [
+            translation_address,
+            0,
+            0,
+            0,
+            self.directory_buffer,
+            dpb,
+            check_vector,
+            allocation_vector,
+        ];
+        for (index, word) in words.iter().enumerate() {
+            let address = dph + 2 * index as u16;
+            memory[address..address + 2].copy_from_slice(&word.to_le_bytes());
+        }
+        memory[dpb..dpb + 15].copy_from_slice(&format.dpb.to_bytes());
+        memory[translation..translation + translation_size].copy_from_slice(&format.translation);
+    }
+
+    pub(crate) fn boot(&mut self, i8080: &mut Intel8080) -> Result<()> {
+        i8080.memory[0x0003] = 0x00; // IOBYTE
+        i8080.memory[0x0004] = 0x00; // current drive and user
+        self.warm_boot(i8080)
+    }
+
+    fn warm_boot(&mut self, i8080: &mut Intel8080) -> Result<()> {
+        let image = &self.drives[0].as_ref().ok_or(Error::NoDisk { drive: 0 })?.image;
+        let format = image.format();
+        let mut address = self.ccp_base();
+        // The CCP and the BDOS occupy the system tracks from the second sector of track 0, the
+        // first one being the cold start loader.
+        let (mut track, mut sector) = (0, 2);
+        while address < self.bios_base {
+            let data = image.sector(track, sector).ok_or(Error::InvalidDiskImage {
+                path: None,
+                size: image.as_bytes().len() as u64,
+            })?;
+            i8080.memory[address..address + SECTOR_SIZE as u16].copy_from_slice(data);
+            address += SECTOR_SIZE as u16;
+            sector += 1;
+            if sector > format.sectors_per_track {
+                track += 1;
+                sector = 1;
+            }
+        }
+
+        let bdos_entry = self.bdos_base() + 6;
+        let memory = &mut i8080.memory;
+        // JMP WBOOT
+        memory[0x0000] = 0xC3;
+        memory[0x0001..0x0003].copy_from_slice(&(self.bios_base + 3).to_le_bytes());
+        // JMP BDOS
+        memory[0x0005] = 0xC3;
+        memory[0x0006..0x0008].copy_from_slice(&bdos_entry.to_le_bytes());
+        self.dma_address = 0x0080;
+        // The CCP expects the current drive and user in C.
+        i8080.cpu.c = i8080.memory[0x0004];
+        i8080.cpu.sp = 0x0100;
+        i8080.cpu.pc = self.ccp_base();
+        Ok(())
+    }
+
+    // Services a BIOS call with `console` as the console, the reader, the punch, and the list
+    // device if the program counter is at an entry of the jump table.
+    pub(crate) fn trap<C: Console + ?Sized>(
+        &mut self,
+        i8080: &mut Intel8080,
+        console: &mut C,
+    ) -> Result<()> {
+        let pc = i8080.cpu.pc;
+        if !self.jump_table().contains(&pc) || (pc - self.bios_base) % 3 != 0 {
+            return Ok(());
+        }
+        let cpu = &i8080.cpu;
+        let bc = u16::from_le_bytes([cpu.c, cpu.b]);
+        let de = u16::from_le_bytes([cpu.e, cpu.d]);
+        let c = cpu.c;
+        match (pc - self.bios_base) / 3 {
+            // BOOT
+            0 => self.boot(i8080)?,
+            // WBOOT
+            1 => self.warm_boot(i8080)?,
+            // CONST
+            2 => i8080.cpu.a = if console.is_input_ready() { 0xFF } else { 0x00 },
+            // CONIN, READER
+            3 | 7 => {
+                let byte = console.read_byte()?;
+                let byte = byte.ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+                i8080.cpu.a = byte & 0x7F;
+            }
+            // CONOUT, LIST, PUNCH
+            4..=6 => console.write_byte(c)?,
+            // HOME
+            8 => self.track = 0,
+            // SELDSK
+            9 => {
+                let dph = match self.drives.get(usize::from(c)) {
+                    Some(Some(drive)) => {
+                        self.selected_drive = c;
+                        drive.disk_parameter_header
+                    }
+                    _ => 0x0000,
+                };
+                set_hl(i8080, dph);
+            }
+            // SETTRK
+            10 => self.track = bc,
+            // SETSEC
+            11 => self.sector = bc,
+            // SETDMA
+            12 => self.dma_address = bc,
+            // READ
+            13 => i8080.cpu.a = self.read(&mut i8080.memory),
+            // WRITE
+            14 => i8080.cpu.a = self.write(&i8080.memory),
+            // LISTST
+            15 => i8080.cpu.a = 0xFF,
+            // SECTRAN
+            16 => {
+                // A drive without a sector translation table has its sectors numbered from 1
+                // consecutively.
+                let sector =
+                    if de == 0 { bc + 1 } else { u16::from(i8080.memory[de.wrapping_add(bc)]) };
+                set_hl(i8080, sector);
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    // Reads the selected sector into the DMA buffer, returning 0x00 on success or 0x01 on error.
+    fn read(&self, memory: &mut Memory) -> u8 {
+        let drive = match &self.drives[usize::from(self.selected_drive)] {
+            Some(drive) => drive,
+            None => return 0x01,
+        };
+        match drive.image.sector(self.track, self.sector) {
+            Some(data) => {
+                for (offset, byte) in data.iter().enumerate() {
+                    memory[self.dma_address.wrapping_add(offset as u16)] = *byte;
+                }
+                0x00
+            }
+            None => 0x01,
+        }
+    }
+
+    // Writes the DMA buffer to the selected sector, returning 0x00 on success or 0x01 on error.
+    fn write(&mut self, memory: &Memory) -> u8 {
+        let mut data = [0; SECTOR_SIZE];
+        for (offset, byte) in data.iter_mut().enumerate() {
+            *byte = memory[self.dma_address.wrapping_add(offset as u16)];
+        }
+        let drive = match &mut self.drives[usize::from(self.selected_drive)] {
+            Some(drive) => drive,
+            None => return 0x01,
+        };
+        match drive.image.write_sector(self.track, self.sector, &data) {
+            Ok(()) => 0x00,
+            Err(_) => 0x01,
+        }
+    }
+}
+
+fn set_hl(i8080: &mut Intel8080, value: u16) {
+    let [low, high] = value.to_le_bytes();
+    i8080.cpu.l = low;
+    i8080.cpu.h = high;
+}
+
+// Returns the size of the disk parameters of `format` in memory.
+fn disk_parameters_size(format: &DiskFormat) -> u32 {
+    u32::from(DISK_PARAMETER_HEADER_SIZE)
+        + 15
+        + format.translation.len() as u32
+        + u32::from(format.dpb.cks)
+        + format.dpb.allocation_vector_size() as u32
+}
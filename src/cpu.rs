@@ -1,10 +1,15 @@
-use std::mem;
+use core::mem;
 
 use bitflags::bitflags;
 
-use crate::{memory::Memory, Error, Result};
+use crate::{
+    memory::{Bus, Memory},
+    Error, Result,
+};
 
-/// An Intel 8080 CPU.
+/// An Intel 8080 CPU, or one of the compatible CPUs listed in [`Variant`].
+///
+/// [`Variant`]: enum.Variant.html
 #[derive(Default)]
 pub struct Cpu {
     /// Program counter.
@@ -30,12 +35,45 @@ pub struct Cpu {
     /// Condition flags.
     pub condition_flags: ConditionFlags,
 
+    /// The registers that only the Z80 has.
+    pub z80: Z80Registers,
+
     interruptable: Interruptable,
     is_halted: bool,
+    variant: Variant,
+    pins: intel8085::Pins,
+    // The result from which the parity, zero, and sign flags are yet to be set. The 8080 and its
+    // clones defer these flags, which are mostly overwritten before they are read, until a
+    // conditional instruction or PUSH PSW reads them or control returns to the caller.
+    parity_zero_sign: Option<u8>,
+    #[cfg(feature = "std")]
+    block_cache: Option<Box<BlockCache>>,
 }
 
 impl Cpu {
-    /// Fetches and executes an instruction, returning it with the number of states taken.
+    /// Creates a CPU of `variant` in its reset state. `Cpu::default()` creates an Intel 8080.
+    pub fn new(variant: Variant) -> Self {
+        let mut cpu = Self { variant, ..Self::default() };
+        match variant {
+            // Bit 1 is the V flag of the 8085, which is clear after a reset.
+            Variant::Intel8085 => {
+                cpu.pins = intel8085::Pins::reset();
+                cpu.condition_flags = ConditionFlags::empty();
+            }
+            // The Z80 has no bit that is always 1.
+            Variant::Z80 => cpu.condition_flags = ConditionFlags::empty(),
+            _ => {}
+        }
+        cpu
+    }
+
+    /// Returns the variant of the CPU.
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Fetches and executes an instruction, returning it with the number of states taken. No I/O
+    /// device is connected: IN leaves the accumulator unchanged and OUT does nothing.
     ///
     /// # Errors
     ///
@@ -43,12 +81,68 @@ impl Cpu {
     ///
     /// [`Error::Halted`]: ../enum.Error.html#variant.Halted
     pub fn fetch_execute_instruction(&mut self, memory: &mut Memory) -> Result<(Instruction, u32)> {
-        if self.is_halted {
-            return Err(Error::Halted);
-        }
-        let instruction = self.fetch_instruction(memory);
-        let interruptable = self.interruptable;
-        let states = self.execute_instruction(instruction, memory);
+        self.fetch_execute_instruction_with_io(memory, &mut Unconnected(self.a))
+    }
+
+    /// Fetches and executes an instruction with the I/O devices `io` connected to the I/O ports,
+    /// returning the instruction with the number of states taken.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::Halted`] error if the CPU is in the halted state.
+    ///
+    /// [`Error::Halted`]: ../enum.Error.html#variant.Halted
+    pub fn fetch_execute_instruction_with_io<I: Io + ?Sized>(
+        &mut self,
+        memory: &mut Memory,
+        io: &mut I,
+    ) -> Result<(Instruction, u32)> {
+        self.fetch_execute_instruction_with_bus(memory, io)
+    }
+
+    // Does what `fetch_execute_instruction_with_io` does on `memory`, which may be a wrapper
+    // around `Memory`.
+    pub(crate) fn fetch_execute_instruction_with_bus<M: Bus + ?Sized, I: Io + ?Sized>(
+        &mut self,
+        memory: &mut M,
+        io: &mut I,
+    ) -> Result<(Instruction, u32)> {
+        // Each variant is dispatched on once, as this runs for every instruction.
+        let (interruptable, instruction, states) = match self.variant {
+            Variant::Intel8085 => {
+                let interrupt_states = self.acknowledge_intel8085_interrupt(memory);
+                if self.is_halted {
+                    return Err(Error::Halted);
+                }
+                let interruptable = self.interruptable;
+                let opcode = memory[self.pc];
+                let instruction = self.fetch_instruction_of_length(
+                    memory,
+                    Self::intel8085_instruction_length(opcode),
+                );
+                let states = self.execute_intel8085_instruction(instruction, memory, io);
+                (interruptable, instruction, interrupt_states + states)
+            }
+            Variant::Z80 => {
+                if self.is_halted {
+                    return Err(Error::Halted);
+                }
+                let interruptable = self.interruptable;
+                let (instruction, states) = self.execute_z80_instruction(memory, io);
+                (interruptable, instruction, states)
+            }
+            _ => {
+                if self.is_halted {
+                    return Err(Error::Halted);
+                }
+                let interruptable = self.interruptable;
+                let length = INTEL8080_OPCODES[usize::from(memory[self.pc])].length;
+                let instruction = self.fetch_instruction_of_length(memory, length);
+                let states = self.execute_intel8080_instruction(instruction, memory, io);
+                (interruptable, instruction, states)
+            }
+        };
+        self.flags();
         // XXX: If two EI instructions occur consecutively, the interrupt system is enabled
         // immediately following the execution of the second EI instruction.
         if let (Interruptable::Enabling, Interruptable::Enabling) =
@@ -59,8 +153,154 @@ impl Cpu {
         Ok((instruction, states))
     }
 
+    /// Returns the engine that executes the instructions of the 8080 and its clones in [`run`] and
+    /// [`run_with_io`].
+    ///
+    /// [`run`]: #method.run
+    /// [`run_with_io`]: #method.run_with_io
+    #[cfg(feature = "std")]
+    pub fn engine(&self) -> Engine {
+        if self.block_cache.is_some() {
+            Engine::BlockCache
+        } else {
+            Engine::Interpreter
+        }
+    }
+
+    /// Selects the engine that executes the instructions of the 8080 and its clones in [`run`]
+    /// and [`run_with_io`]. The interpreter is selected by default.
+    ///
+    /// [`run`]: #method.run
+    /// [`run_with_io`]: #method.run_with_io
+    #[cfg(feature = "std")]
+    pub fn set_engine(&mut self, engine: Engine) {
+        match engine {
+            Engine::Interpreter => self.block_cache = None,
+            Engine::BlockCache => {
+                if self.block_cache.is_none() {
+                    self.block_cache = Some(Box::new(BlockCache::new()));
+                }
+            }
+        }
+    }
+
+    /// Fetches and executes instructions until the program counter reaches an address for which
+    /// `is_breakpoint` returns `true`, the CPU halts, or at least `states` states have elapsed,
+    /// returning the number of states taken. At least one instruction is executed unless the CPU
+    /// is in the halt state. No I/O device is connected.
+    ///
+    /// This is much faster than calling [`fetch_execute_instruction`] for each instruction.
+    ///
+    /// [`fetch_execute_instruction`]: #method.fetch_execute_instruction
+    pub fn run<F: FnMut(u16) -> bool>(
+        &mut self,
+        memory: &mut Memory,
+        states: u64,
+        is_breakpoint: F,
+    ) -> u64 {
+        match self.variant {
+            Variant::Intel8085 | Variant::Z80 => self.run_steps(states, is_breakpoint, |cpu| {
+                cpu.fetch_execute_instruction(memory).ok().map(|(_, states)| states)
+            }),
+            #[cfg(feature = "std")]
+            _ if self.block_cache.is_some() => {
+                let mut cache = self.block_cache.take().unwrap();
+                let states =
+                    self.run_blocks(&mut cache, memory, states, is_breakpoint, |cpu, memory, i| {
+                        cpu.execute_intel8080_instruction(i, memory, &mut Unconnected(cpu.a))
+                    });
+                self.block_cache = Some(cache);
+                states
+            }
+            _ => self.run_steps(states, is_breakpoint, |cpu| {
+                cpu.fetch_execute_intel8080_instruction(memory, &mut Unconnected(cpu.a))
+            }),
+        }
+    }
+
+    /// Fetches and executes instructions with the I/O devices `io` connected to the I/O ports
+    /// until the program counter reaches an address for which `is_breakpoint` returns `true`, the
+    /// CPU halts, or at least `states` states have elapsed, returning the number of states taken.
+    /// At least one instruction is executed unless the CPU is in the halt state.
+    ///
+    /// This is much faster than calling [`fetch_execute_instruction_with_io`] for each
+    /// instruction.
+    ///
+    /// [`fetch_execute_instruction_with_io`]: #method.fetch_execute_instruction_with_io
+    pub fn run_with_io<I: Io + ?Sized, F: FnMut(u16) -> bool>(
+        &mut self,
+        memory: &mut Memory,
+        io: &mut I,
+        states: u64,
+        is_breakpoint: F,
+    ) -> u64 {
+        match self.variant {
+            Variant::Intel8085 | Variant::Z80 => self.run_steps(states, is_breakpoint, |cpu| {
+                cpu.fetch_execute_instruction_with_io(memory, io).ok().map(|(_, states)| states)
+            }),
+            #[cfg(feature = "std")]
+            _ if self.block_cache.is_some() => {
+                let mut cache = self.block_cache.take().unwrap();
+                let states =
+                    self.run_blocks(&mut cache, memory, states, is_breakpoint, |cpu, memory, i| {
+                        cpu.execute_intel8080_instruction(i, memory, io)
+                    });
+                self.block_cache = Some(cache);
+                states
+            }
+            _ => self.run_steps(states, is_breakpoint, |cpu| {
+                cpu.fetch_execute_intel8080_instruction(memory, io)
+            }),
+        }
+    }
+
+    /// Runs `code` on a copy of the CPU whose registers the compiler can keep out of memory,
+    /// returning what `code` returns, which is the number of states taken. This is how the Rust
+    /// code that [`recompiler`] generates runs: `code` executes the instructions of the 8080 and
+    /// its clones with [`execute_recompiled_instruction`].
+    ///
+    /// [`recompiler`]: ../recompiler/index.html
+    /// [`execute_recompiled_instruction`]: #method.execute_recompiled_instruction
+    pub fn run_recompiled<F: FnOnce(&mut Self) -> u64>(&mut self, code: F) -> u64 {
+        let mut cpu = mem::take(self);
+        let elapsed = code(&mut cpu);
+        cpu.flags();
+        *self = cpu;
+        elapsed
+    }
+
+    /// Executes `instruction` as the 8080 and its clones do when they fetch it from `address`,
+    /// with the I/O devices `io` connected to the I/O ports, returning the number of states taken.
+    /// The program counter is set to the address that follows the instruction before it is
+    /// executed.
+    ///
+    /// Call this only from the code that [`run_recompiled`] runs, which brings the parity, zero,
+    /// and sign flags in `condition_flags` up to date when it returns. Given a constant
+    /// `instruction`, the compiler leaves only the code that the instruction needs.
+    ///
+    /// [`run_recompiled`]: #method.run_recompiled
+    #[inline(always)]
+    pub fn execute_recompiled_instruction<I: Io + ?Sized>(
+        &mut self,
+        address: u16,
+        instruction: Instruction,
+        memory: &mut Memory,
+        io: &mut I,
+    ) -> u32 {
+        let interruptable = self.interruptable;
+        let length = INTEL8080_OPCODES[usize::from(instruction[0])].length;
+        self.pc = address.wrapping_add(u16::from(length));
+        let states = self.execute_intel8080_instruction(instruction, memory, io);
+        if let (Interruptable::Enabling, Interruptable::Enabling) =
+            (interruptable, self.interruptable)
+        {
+            self.interruptable = Interruptable::Enabled;
+        }
+        states
+    }
+
     /// Escapes from the halt state, if necessary, and executes `instruction` with further
-    /// interrupts disabled.
+    /// interrupts disabled. No I/O device is connected.
     ///
     /// # Errors
     ///
@@ -69,107 +309,176 @@ impl Cpu {
     ///
     /// [`Error::InterruptNotEnabled`]: ../enum.Error.html#variant.InterruptNotEnabled
     pub fn interrupt(&mut self, instruction: Instruction, memory: &mut Memory) -> Result<u32> {
+        self.interrupt_with_io(instruction, memory, &mut Unconnected(self.a))
+    }
+
+    /// Escapes from the halt state, if necessary, and executes `instruction` with further
+    /// interrupts disabled and the I/O devices `io` connected to the I/O ports. The Z80 handles the
+    /// interrupt in the mode set by IM: `instruction` is only used in mode 0, and its first byte is
+    /// the low byte of the vector address in mode 2.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::InterruptNotEnabled`] error if the interrupt system
+    /// is already disabled.
+    ///
+    /// [`Error::InterruptNotEnabled`]: ../enum.Error.html#variant.InterruptNotEnabled
+    pub fn interrupt_with_io<I: Io + ?Sized>(
+        &mut self,
+        instruction: Instruction,
+        memory: &mut Memory,
+        io: &mut I,
+    ) -> Result<u32> {
+        self.interrupt_with_bus(instruction, memory, io)
+    }
+
+    // Does what `interrupt_with_io` does on `memory`, which may be a wrapper around `Memory`.
+    pub(crate) fn interrupt_with_bus<M: Bus + ?Sized, I: Io + ?Sized>(
+        &mut self,
+        instruction: Instruction,
+        memory: &mut M,
+        io: &mut I,
+    ) -> Result<u32> {
         if let Interruptable::Enabled = self.interruptable {
             self.is_halted = false;
             self.interruptable = Interruptable::Disabled;
-            Ok(self.execute_instruction(instruction, memory))
+            if let Variant::Z80 = self.variant {
+                Ok(self.acknowledge_z80_interrupt(instruction, memory, io))
+            } else {
+                let states = self.execute_instruction_with_io(instruction, memory, io);
+                self.flags();
+                Ok(states)
+            }
         } else {
             Err(Error::InterruptNotEnabled)
         }
     }
 
-    fn fetch_instruction(&mut self, memory: &Memory) -> Instruction {
-        match memory[self.pc] {
-            | 0x00 /* NOP */ | 0x02 /* STAX B */ | 0x03 /* INX B */ | 0x04 /* INR B */
-            | 0x05 /* DCR B */ | 0x07 /* RLC */ | 0x09 /* DAD */ | 0x0A /* LDAX B */
-            | 0x0B /* DCX B */ | 0x0C /* INR C */ | 0x0D /* DCR C */ | 0x0F /* RRC */
-            | 0x12 /* STAX D */ | 0x13 /* INX D */ | 0x14 /* INR D */ | 0x15 /* DCR D */
-            | 0x17 /* RAL */ | 0x19 /* DAD D */ | 0x1A /* LDAX D */ | 0x1B /* DCX D */
-            | 0x1C /* INR E */ | 0x1D /* DCR E */ | 0x1F /* RAR */
-            | 0x23 /* INX H */ | 0x24 /* INR H */ | 0x25 /* DCR H */ | 0x27 /* DAA */
-            | 0x29 /* DAD H */ | 0x2B /* DCX H */ | 0x2C /* INR L */ | 0x2D /* DCR L */
-            | 0x2F /* CMA */
-            | 0x33 /* INX SP */ | 0x34 /* INR M */ | 0x35 /* DCR M */ | 0x37 /* STC */
-            | 0x39 /* DAD SP */ | 0x3B /* DCX SP */ | 0x3C /* INR A */ | 0x3D /* DCR A */
-            | 0x3F /* CMC */
-            | 0x40 /* MOV B,B */ | 0x41 /* MOV B,C */ | 0x42 /* MOV B,D */ | 0x43 /* MOV B,E */
-            | 0x44 /* MOV B,H */ | 0x45 /* MOV B,L */ | 0x46 /* MOV B,M */ | 0x47 /* MOV B,A */
-            | 0x48 /* MOV C,B */ | 0x49 /* MOV C,C */ | 0x4A /* MOV C,D */ | 0x4B /* MOV C,E */
-            | 0x4C /* MOV C,H */ | 0x4D /* MOV C,L */ | 0x4E /* MOV C,M */ | 0x4F /* MOV C,A */
-            | 0x50 /* MOV D,B */ | 0x51 /* MOV D,C */ | 0x52 /* MOV D,D */ | 0x53 /* MOV D,E */
-            | 0x54 /* MOV D,H */ | 0x55 /* MOV D,L */ | 0x56 /* MOV D,M */ | 0x57 /* MOV D,A */
-            | 0x58 /* MOV E,B */ | 0x59 /* MOV E,C */ | 0x5A /* MOV E,D */ | 0x5B /* MOV E,E */
-            | 0x5C /* MOV E,H */ | 0x5D /* MOV E,L */ | 0x5E /* MOV E,M */ | 0x5F /* MOV E,A */
-            | 0x60 /* MOV H,B */ | 0x61 /* MOV H,C */ | 0x62 /* MOV H,D */ | 0x63 /* MOV H,E */
-            | 0x64 /* MOV H,H */ | 0x65 /* MOV H,L */ | 0x66 /* MOV H,M */ | 0x67 /* MOV H,A */
-            | 0x68 /* MOV L,B */ | 0x69 /* MOV L,C */ | 0x6A /* MOV L,D */ | 0x6B /* MOV L,E */
-            | 0x6C /* MOV L,H */ | 0x6D /* MOV L,L */ | 0x6E /* MOV L,M */ | 0x6F /* MOV L,A */
-            | 0x70 /* MOV M,B */ | 0x71 /* MOV M,C */ | 0x72 /* MOV M,D */ | 0x73 /* MOV M,E */
-            | 0x74 /* MOV M,H */ | 0x75 /* MOV M,L */ | 0x76 /* HLT */ | 0x77 /* MOV M,A */
-            | 0x78 /* MOV A,B */ | 0x79 /* MOV A,C */ | 0x7A /* MOV A,D */ | 0x7B /* MOV A,E */
-            | 0x7C /* MOV A,H */ | 0x7D /* MOV A,L */ | 0x7E /* MOV A,M */ | 0x7F /* MOV A,A */
-            | 0x80 /* ADD B */ | 0x81 /* ADD C */ | 0x82 /* ADD D */ | 0x83 /* ADD E */
-            | 0x84 /* ADD H */ | 0x85 /* ADD L */ | 0x86 /* ADD M */ | 0x87 /* ADD A */
-            | 0x88 /* ADC B */ | 0x89 /* ADC C */ | 0x8A /* ADC D */ | 0x8B /* ADC E */
-            | 0x8C /* ADC H */ | 0x8D /* ADC L */ | 0x8E /* ADC M */ | 0x8F /* ADC A */
-            | 0x90 /* SUB B */ | 0x91 /* SUB C */ | 0x92 /* SUB D */ | 0x93 /* SUB E */
-            | 0x94 /* SUB H */ | 0x95 /* SUB L */ | 0x96 /* SUB M */ | 0x97 /* SUB A */
-            | 0x98 /* SBB B */ | 0x99 /* SBB C */ | 0x9A /* SBB D */ | 0x9B /* SBB E */
-            | 0x9C /* SBB H */ | 0x9D /* SBB L */ | 0x9E /* SBB M */ | 0x9F /* SBB A */
-            | 0xA0 /* ANA B */ | 0xA1 /* ANA C */ | 0xA2 /* ANA D */ | 0xA3 /* ANA E */
-            | 0xA4 /* ANA H */ | 0xA5 /* ANA L */ | 0xA6 /* ANA M */ | 0xA7 /* ANA A */
-            | 0xA8 /* XRA B */ | 0xA9 /* XRA C */ | 0xAA /* XRA D */ | 0xAB /* XRA E */
-            | 0xAC /* XRA H */ | 0xAD /* XRA L */ | 0xAE /* XRA M */ | 0xAF /* XRA A */
-            | 0xB0 /* ORA B */ | 0xB1 /* ORA C */ | 0xB2 /* ORA D */ | 0xB3 /* ORA E */
-            | 0xB4 /* ORA H */ | 0xB5 /* ORA L */ | 0xB6 /* ORA M */ | 0xB7 /* ORA A */
-            | 0xB8 /* CMP B */ | 0xB9 /* CMP C */ | 0xBA /* CMP D */ | 0xBB /* CMP E */
-            | 0xBC /* CMP H */ | 0xBD /* CMP L */ | 0xBE /* CMP M */ | 0xBF /* CMP A */
-            | 0xC0 /* RNZ */ | 0xC1 /* POP B */ | 0xC5 /* PUSH B */ | 0xC7 /* RST 0 */
-            | 0xC8 /* RZ */ | 0xC9 /* RET */ | 0xCF /* RST 1 */
-            | 0xD0 /* RNC */ | 0xD1 /* POP D */ | 0xD5 /* PUSH D */ | 0xD7 /* RST 2 */
-            | 0xD8 /* RC */ | 0xDF /* RST 3 */
-            | 0xE0 /* RPO */ | 0xE1 /* POP H */ | 0xE3 /* XTHL */ | 0xE5 /* PUSH H */
-            | 0xE7 /* RST 4 */ | 0xE8 /* RPE */ | 0xE9 /* PCHL */ | 0xEB /* XCHG */
-            | 0xEF /* RST 5 */
-            | 0xF0 /* RP */ | 0xF1 /* POP PSW */ | 0xF3 /* DI */ | 0xF5 /* PUSH PSW */
-            | 0xF7 /* RST 6 */ | 0xF8 /* RM */ | 0xF9 /* SPHL */ | 0xFB /* EI */
-            | 0xFF /* RST 7 */ => {
-                let instruction = [memory[self.pc], 0, 0];
-                self.pc += 1;
-                instruction
-            }
-            | 0x06 /* MVI B */ | 0x0E /* MVI C */
-            | 0x16 /* MVI D */ | 0x1E /* MVI E */
-            | 0x26 /* MVI H */ | 0x2E /* MVI L */
-            | 0x36 /* MVI M */ | 0x3E /* MVI A */
-            | 0xC6 /* ADI */ | 0xCE /* ACI */
-            | 0xD3 /* OUT */ | 0xD6 /* SUI */ | 0xDB /* IN */ | 0xDE /* SBI */
-            | 0xE6 /* ANI */ | 0xEE /* XRI */
-            | 0xF6 /* ORI */ | 0xFE /* CPI */ => {
-                let instruction = [memory[self.pc], memory[self.pc + 1], 0];
-                self.pc += 2;
-                instruction
-            }
-            | 0x01 /* LXI B */
-            | 0x11 /* LXI D */
-            | 0x21 /* LXI H */ | 0x22 /* SHLD */ | 0x2A /* LHLD */
-            | 0x31 /* LXI SP */ | 0x32 /* STA */ | 0x3A /* LDA */
-            | 0xC2 /* JNZ */ | 0xC3 /* JMP */ | 0xC4 /* CNZ */ | 0xCA /* JZ */
-            | 0xCC /* CZ */ | 0xCD /* CALL */
-            | 0xD2 /* JNC */ | 0xD4 /* CNC */ | 0xDA /* JC */ | 0xDC /* CC */
-            | 0xE2 /* JPO */ | 0xE4 /* CPO */ | 0xEA /* JPE */ | 0xEC /* CPE */
-            | 0xF2 /* JP */ | 0xF4 /* CP */ | 0xFA /* JM */ | 0xFC /* CM */ => {
-                let instruction =
-                    [memory[self.pc], memory[self.pc + 1], memory[self.pc + 2]];
-                self.pc += 3;
-                instruction
-            }
-            instruction => unimplemented!("{:#04X?} (undocumented)", instruction),
+    /// Returns `true` if the CPU is in the halt state.
+    pub fn is_halted(&self) -> bool {
+        self.is_halted
+    }
+
+    /// Returns `true` if the interrupt system is enabled, which is the state of the INTE pin.
+    /// Interrupts are enabled one instruction after EI, whereas INTE goes high immediately.
+    pub fn is_interrupt_enabled(&self) -> bool {
+        !matches!(self.interruptable, Interruptable::Disabled)
+    }
+
+    /// Returns the status word that the CPU sends out at the start of its next machine cycle:
+    /// an instruction fetch, or a halt acknowledge in the halt state.
+    pub fn status_word(&self) -> StatusWord {
+        if self.is_halted {
+            StatusWord::MEMR | StatusWord::HLTA | StatusWord::WO
+        } else {
+            StatusWord::MEMR | StatusWord::M1 | StatusWord::WO
+        }
+    }
+
+    // Runs `step` until it returns `None` or the CPU reaches a breakpoint or takes `states` states.
+    // `step` works on a local copy of the CPU, whose registers the compiler can keep out of memory.
+    #[inline(always)]
+    fn run_steps<F, S>(&mut self, states: u64, mut is_breakpoint: F, mut step: S) -> u64
+    where
+        F: FnMut(u16) -> bool,
+        S: FnMut(&mut Self) -> Option<u32>,
+    {
+        let mut cpu = mem::take(self);
+        let mut elapsed = 0;
+        while let Some(instruction_states) = step(&mut cpu) {
+            elapsed += u64::from(instruction_states);
+            if elapsed >= states || is_breakpoint(cpu.pc) {
+                break;
+            }
+        }
+        cpu.flags();
+        *self = cpu;
+        elapsed
+    }
+
+    // Does what `fetch_execute_instruction_with_io` does for the Intel 8080 and its clones, but
+    // returns `None` in the halt state.
+    #[inline(always)]
+    fn fetch_execute_intel8080_instruction<M: Bus + ?Sized, I: Io + ?Sized>(
+        &mut self,
+        memory: &mut M,
+        io: &mut I,
+    ) -> Option<u32> {
+        if self.is_halted {
+            return None;
+        }
+        let interruptable = self.interruptable;
+        // Reading the bytes that follow a shorter instruction is harmless and saves branches.
+        let instruction =
+            [memory[self.pc], memory[self.pc.wrapping_add(1)], memory[self.pc.wrapping_add(2)]];
+        let length = INTEL8080_OPCODES[usize::from(instruction[0])].length;
+        self.pc = self.pc.wrapping_add(u16::from(length));
+        let states = self.execute_intel8080_instruction(instruction, memory, io);
+        if let (Interruptable::Enabling, Interruptable::Enabling) =
+            (interruptable, self.interruptable)
+        {
+            self.interruptable = Interruptable::Enabled;
+        }
+        Some(states)
+    }
+
+    #[cfg(test)]
+    fn fetch_instruction<M: Bus + ?Sized>(&mut self, memory: &M) -> Instruction {
+        let opcode = memory[self.pc];
+        let length = match self.variant {
+            Variant::Intel8085 => Self::intel8085_instruction_length(opcode),
+            _ => INTEL8080_OPCODES[usize::from(opcode)].length,
+        };
+        self.fetch_instruction_of_length(memory, length)
+    }
+
+    #[inline(always)]
+    fn fetch_instruction_of_length<M: Bus + ?Sized>(
+        &mut self,
+        memory: &M,
+        length: u8,
+    ) -> Instruction {
+        let mut instruction = [memory[self.pc], 0, 0];
+        for offset in 1..length {
+            instruction[usize::from(offset)] = memory[self.pc.wrapping_add(u16::from(offset))];
+        }
+        self.pc = self.pc.wrapping_add(u16::from(length));
+        instruction
+    }
+
+    #[cfg(test)]
+    fn execute_instruction<M: Bus + ?Sized>(
+        &mut self,
+        instruction: Instruction,
+        memory: &mut M,
+    ) -> u32 {
+        let states =
+            self.execute_instruction_with_io(instruction, memory, &mut Unconnected(self.a));
+        self.flags();
+        states
+    }
+
+    fn execute_instruction_with_io<M: Bus + ?Sized, I: Io + ?Sized>(
+        &mut self,
+        instruction: Instruction,
+        memory: &mut M,
+        io: &mut I,
+    ) -> u32 {
+        match self.variant {
+            Variant::Intel8085 => self.execute_intel8085_instruction(instruction, memory, io),
+            Variant::Z80 => self.execute_z80_bus_instruction(instruction, memory, io),
+            _ => self.execute_intel8080_instruction(instruction, memory, io),
         }
     }
 
     #[allow(clippy::cognitive_complexity)]
-    fn execute_instruction(&mut self, instruction: Instruction, memory: &mut Memory) -> u32 {
+    #[inline(always)]
+    fn execute_intel8080_instruction<M: Bus + ?Sized, I: Io + ?Sized>(
+        &mut self,
+        instruction: Instruction,
+        memory: &mut M,
+        io: &mut I,
+    ) -> u32 {
         match instruction[0] {
             // ACI (Add immediate to A with carry)
             0xCE => {
@@ -439,7 +748,7 @@ impl Cpu {
 
             // CNZ (Call on no zero)
             0xC4 => {
-                if !self.condition_flags.contains(ConditionFlags::ZERO) {
+                if !self.flags().contains(ConditionFlags::ZERO) {
                     self.call(instruction, memory);
                     17
                 } else {
@@ -448,7 +757,7 @@ impl Cpu {
             }
             // CZ (Call on zero)
             0xCC => {
-                if self.condition_flags.contains(ConditionFlags::ZERO) {
+                if self.flags().contains(ConditionFlags::ZERO) {
                     self.call(instruction, memory);
                     17
                 } else {
@@ -475,7 +784,7 @@ impl Cpu {
             }
             // CPO (Call on parity odd)
             0xE4 => {
-                if !self.condition_flags.contains(ConditionFlags::PARITY) {
+                if !self.flags().contains(ConditionFlags::PARITY) {
                     self.call(instruction, memory);
                     17
                 } else {
@@ -484,7 +793,7 @@ impl Cpu {
             }
             // CPE (Call on parity even)
             0xEC => {
-                if self.condition_flags.contains(ConditionFlags::PARITY) {
+                if self.flags().contains(ConditionFlags::PARITY) {
                     self.call(instruction, memory);
                     17
                 } else {
@@ -493,7 +802,7 @@ impl Cpu {
             }
             // CP (Call on postive)
             0xF4 => {
-                if !self.condition_flags.contains(ConditionFlags::SIGN) {
+                if !self.flags().contains(ConditionFlags::SIGN) {
                     self.call(instruction, memory);
                     17
                 } else {
@@ -502,7 +811,7 @@ impl Cpu {
             }
             // CM (Call on minus)
             0xFC => {
-                if self.condition_flags.contains(ConditionFlags::SIGN) {
+                if self.flags().contains(ConditionFlags::SIGN) {
                     self.call(instruction, memory);
                     17
                 } else {
@@ -674,7 +983,10 @@ impl Cpu {
             }
 
             // IN port (Initiate input operation)
-            0xDB => 10,
+            0xDB => {
+                self.a = io.input(instruction[1]);
+                10
+            }
 
             // INR M (Increment memory)
             0x34 => {
@@ -767,14 +1079,14 @@ impl Cpu {
 
             // JNZ (Jump on no zero)
             0xC2 => {
-                if !self.condition_flags.contains(ConditionFlags::ZERO) {
+                if !self.flags().contains(ConditionFlags::ZERO) {
                     self.pc = u16::from_le_bytes([instruction[1], instruction[2]]);
                 }
                 10
             }
             // JZ (Jump on zero)
             0xCA => {
-                if self.condition_flags.contains(ConditionFlags::ZERO) {
+                if self.flags().contains(ConditionFlags::ZERO) {
                     self.pc = u16::from_le_bytes([instruction[1], instruction[2]]);
                 }
                 10
@@ -795,28 +1107,28 @@ impl Cpu {
             }
             // JPO (Jump on parity odd)
             0xE2 => {
-                if !self.condition_flags.contains(ConditionFlags::PARITY) {
+                if !self.flags().contains(ConditionFlags::PARITY) {
                     self.pc = u16::from_le_bytes([instruction[1], instruction[2]]);
                 }
                 10
             }
             // JPE (Jump on parity even)
             0xEA => {
-                if self.condition_flags.contains(ConditionFlags::PARITY) {
+                if self.flags().contains(ConditionFlags::PARITY) {
                     self.pc = u16::from_le_bytes([instruction[1], instruction[2]]);
                 }
                 10
             }
             // JP (Jump on positive)
             0xF2 => {
-                if !self.condition_flags.contains(ConditionFlags::SIGN) {
+                if !self.flags().contains(ConditionFlags::SIGN) {
                     self.pc = u16::from_le_bytes([instruction[1], instruction[2]]);
                 }
                 10
             }
             // JM (Jump on minus)
             0xFA => {
-                if self.condition_flags.contains(ConditionFlags::SIGN) {
+                if self.flags().contains(ConditionFlags::SIGN) {
                     self.pc = u16::from_le_bytes([instruction[1], instruction[2]]);
                 }
                 10
@@ -1284,7 +1596,10 @@ impl Cpu {
             }
 
             // OUT port (Initiate output operation)
-            0xD3 => 10,
+            0xD3 => {
+                io.output(instruction[1], self.a);
+                10
+            }
 
             // PCHL (H & L to program counter)
             0xE9 => {
@@ -1294,8 +1609,10 @@ impl Cpu {
 
             // POP PSW (Pop A and Flags off stack)
             0xF1 => {
+                self.parity_zero_sign = None;
                 self.condition_flags = ConditionFlags::from_bits_truncate(
-                    memory[self.sp] | ConditionFlags::ALWAYS_ONE.bits(),
+                    memory[self.sp] & !ConditionFlags::UNDERFLOW.bits()
+                        | ConditionFlags::ALWAYS_ONE.bits(),
                 );
                 self.a = memory[self.sp.wrapping_add(1)];
                 self.sp = self.sp.wrapping_add(2);
@@ -1327,7 +1644,7 @@ impl Cpu {
             // PUSH PSW (Push A and Flags on stack)
             0xF5 => {
                 memory[self.sp.wrapping_sub(1)] = self.a;
-                memory[self.sp.wrapping_sub(2)] = self.condition_flags.bits();
+                memory[self.sp.wrapping_sub(2)] = self.flags().bits();
                 self.sp = self.sp.wrapping_sub(2);
                 11
             }
@@ -1393,7 +1710,7 @@ impl Cpu {
 
             // RNZ (Return on no zero)
             0xC0 => {
-                if !self.condition_flags.contains(ConditionFlags::ZERO) {
+                if !self.flags().contains(ConditionFlags::ZERO) {
                     self.ret(memory);
                     11
                 } else {
@@ -1402,7 +1719,7 @@ impl Cpu {
             }
             // RZ (Return on zero)
             0xC8 => {
-                if self.condition_flags.contains(ConditionFlags::ZERO) {
+                if self.flags().contains(ConditionFlags::ZERO) {
                     self.ret(memory);
                     11
                 } else {
@@ -1429,7 +1746,7 @@ impl Cpu {
             }
             // RPO (Return on parity odd)
             0xE0 => {
-                if !self.condition_flags.contains(ConditionFlags::PARITY) {
+                if !self.flags().contains(ConditionFlags::PARITY) {
                     self.ret(memory);
                     11
                 } else {
@@ -1438,7 +1755,7 @@ impl Cpu {
             }
             // RPE (Return on parity even)
             0xE8 => {
-                if self.condition_flags.contains(ConditionFlags::PARITY) {
+                if self.flags().contains(ConditionFlags::PARITY) {
                     self.ret(memory);
                     11
                 } else {
@@ -1447,7 +1764,7 @@ impl Cpu {
             }
             // RP (Return on positive)
             0xF0 => {
-                if !self.condition_flags.contains(ConditionFlags::SIGN) {
+                if !self.flags().contains(ConditionFlags::SIGN) {
                     self.ret(memory);
                     11
                 } else {
@@ -1456,7 +1773,7 @@ impl Cpu {
             }
             // RM (Return on minus)
             0xF8 => {
-                if self.condition_flags.contains(ConditionFlags::SIGN) {
+                if self.flags().contains(ConditionFlags::SIGN) {
                     self.ret(memory);
                     11
                 } else {
@@ -1725,6 +2042,7 @@ impl Cpu {
         }
     }
 
+    #[inline]
     fn add(&mut self, x: u8, y: u8, carry_in: bool) -> (u8, bool) {
         self.condition_flags.set(
             ConditionFlags::AUX_CARRY,
@@ -1732,16 +2050,29 @@ impl Cpu {
         );
         let result = if carry_in { x.wrapping_add(y).wrapping_add(1) } else { x.wrapping_add(y) };
         self.update_parity_zero_sign_flags(result);
+        let overflow = (x ^ result) & (y ^ result) & 0x80 != 0;
+        match self.variant {
+            Variant::Intel8085 => self.condition_flags.set(ConditionFlags::OVERFLOW, overflow),
+            // The parity flag of the NEC µPD8080AF indicates overflow after an arithmetic
+            // instruction, as on the Z80.
+            Variant::NecUpd8080af => {
+                self.flags();
+                self.condition_flags.set(ConditionFlags::PARITY, overflow);
+            }
+            _ => {}
+        }
         (result, if carry_in { x >= 0xFF - y } else { x > 0xFF - y })
     }
 
-    fn call(&mut self, instruction: Instruction, memory: &mut Memory) {
+    #[inline]
+    fn call<M: Bus + ?Sized>(&mut self, instruction: Instruction, memory: &mut M) {
         memory[self.sp.wrapping_sub(1)] = ((self.pc & 0xFF00) >> 8) as u8;
         memory[self.sp.wrapping_sub(2)] = (self.pc & 0x00FF) as u8;
         self.sp = self.sp.wrapping_sub(2);
         self.pc = u16::from_le_bytes([instruction[1], instruction[2]]);
     }
 
+    #[inline]
     fn logical_and(&mut self, byte: u8) {
         self.condition_flags.remove(ConditionFlags::CARRY);
         // There is a discrepancy in the behavior of the auxiliary carry flag between the manuals.
@@ -1757,13 +2088,24 @@ impl Cpu {
         // described in "Intel 8080/8085 Assembly Language Programming Manual."
         //
         // See also https://github.com/superzazu/8080/issues/1.
-        self.condition_flags.set(ConditionFlags::AUX_CARRY, ((self.a | byte) & 0x08) > 0);
+        //
+        // > The 8085 logical AND instructions always set the auxiliary flag ON.
+        // > (Intel 8080/8085 Assembly Language Programming Manual, 1981, p. 1-12)
+        //
+        // The AMD Am9080A clears the flag as the earlier manual describes.
+        let aux_carry = match self.variant {
+            Variant::Intel8085 | Variant::Z80 => true,
+            Variant::Amd9080 => false,
+            _ => ((self.a | byte) & 0x08) > 0,
+        };
+        self.condition_flags.set(ConditionFlags::AUX_CARRY, aux_carry);
         let result = self.a & byte;
         self.update_parity_zero_sign_flags(result);
         self.a = result;
     }
 
     // Or byte with A.
+    #[inline]
     fn logical_or(&mut self, byte: u8) {
         self.condition_flags.remove(ConditionFlags::CARRY);
         self.condition_flags.remove(ConditionFlags::AUX_CARRY);
@@ -1773,6 +2115,7 @@ impl Cpu {
     }
 
     // Exclusive Or byte with A.
+    #[inline]
     fn logical_xor(&mut self, byte: u8) {
         self.condition_flags.remove(ConditionFlags::CARRY);
         self.condition_flags.remove(ConditionFlags::AUX_CARRY);
@@ -1781,18 +2124,21 @@ impl Cpu {
         self.a = result;
     }
 
-    fn restart(&mut self, instruction: u8, memory: &mut Memory) {
+    #[inline]
+    fn restart<M: Bus + ?Sized>(&mut self, instruction: u8, memory: &mut M) {
         memory[self.sp.wrapping_sub(1)] = ((self.pc & 0xFF00) >> 8) as u8;
         memory[self.sp.wrapping_sub(2)] = (self.pc & 0x00FF) as u8;
         self.sp = self.sp.wrapping_sub(2);
         self.pc = u16::from(instruction & 0x38);
     }
 
-    fn ret(&mut self, memory: &Memory) {
+    #[inline]
+    fn ret<M: Bus + ?Sized>(&mut self, memory: &M) {
         self.pc = u16::from_le_bytes([memory[self.sp], memory[self.sp.wrapping_add(1)]]);
         self.sp = self.sp.wrapping_add(2);
     }
 
+    #[inline]
     fn subtract(&mut self, x: u8, y: u8, borrow_in: bool) -> (u8, bool) {
         // Refer to https://retrocomputing.stackexchange.com/q/12558 for details on the behavior of
         // the auxiliary carry flag in subtraction.
@@ -1801,13 +2147,105 @@ impl Cpu {
         (result, borrow_out)
     }
 
+    #[inline]
     fn update_parity_zero_sign_flags(&mut self, result: u8) {
-        self.condition_flags.set(ConditionFlags::PARITY, result.count_ones() % 2 == 0);
-        self.condition_flags.set(ConditionFlags::ZERO, result == 0);
-        self.condition_flags.set(ConditionFlags::SIGN, result & 0x80 > 0);
+        match self.variant {
+            Variant::Intel8085 | Variant::Z80 => self.set_parity_zero_sign_flags(result),
+            _ => self.parity_zero_sign = Some(result),
+        }
+    }
+
+    // Sets the parity, zero, and sign flags that have been deferred, returning the flags.
+    #[inline]
+    fn flags(&mut self) -> ConditionFlags {
+        if let Some(result) = self.parity_zero_sign.take() {
+            self.set_parity_zero_sign_flags(result);
+        }
+        self.condition_flags
+    }
+
+    #[inline]
+    fn set_parity_zero_sign_flags(&mut self, result: u8) {
+        let parity_zero_sign = ConditionFlags::PARITY | ConditionFlags::ZERO | ConditionFlags::SIGN;
+        self.condition_flags = ConditionFlags::from_bits_truncate(
+            self.condition_flags.bits() & !parity_zero_sign.bits()
+                | PARITY_ZERO_SIGN_FLAGS[usize::from(result)],
+        );
     }
 }
 
+/// The I/O devices that are connected to the 256 input ports and the 256 output ports.
+pub trait Io {
+    /// Returns the byte that the device connected to input `port` puts on the data bus.
+    fn input(&mut self, port: u8) -> u8;
+
+    /// Sends `byte` to the device connected to output `port`.
+    fn output(&mut self, port: u8, byte: u8);
+}
+
+impl<I: Io + ?Sized> Io for &mut I {
+    fn input(&mut self, port: u8) -> u8 {
+        (**self).input(port)
+    }
+
+    fn output(&mut self, port: u8, byte: u8) {
+        (**self).output(port, byte)
+    }
+}
+
+// No I/O device. Reading a port returns the accumulator before the IN instruction so that the
+// instruction leaves it unchanged.
+struct Unconnected(u8);
+
+impl Io for Unconnected {
+    fn input(&mut self, _port: u8) -> u8 {
+        self.0
+    }
+
+    fn output(&mut self, _port: u8, _byte: u8) {}
+}
+
+/// The CPUs that can be emulated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Variant {
+    /// The Intel 8080A.
+    ///
+    /// The KR580VM80A, the Soviet clone used in the Radio-86RK and the Vector-06C, is not
+    /// emulated as a variant of its own. It passes the test programs in `tests/cpu_tests` as the
+    /// 8080A does, and its reported differences have not been verified, so this variant is used
+    /// for it.
+    #[default]
+    Intel8080,
+    /// The NEC µPD8080AF, whose parity flag indicates two's complement overflow after an
+    /// arithmetic instruction (ADD, ADC, SUB, SBB, CMP, INR, DCR, and their immediate forms).
+    NecUpd8080af,
+    /// The AMD Am9080A, whose logical AND instructions clear the auxiliary carry flag as the
+    /// 8080 manual of 1976 describes.
+    Amd9080,
+    /// The Intel 8085A, which adds RIM and SIM, the TRAP and RST 5.5/6.5/7.5 interrupt inputs,
+    /// the SID and SOD serial lines, and the undocumented instructions and V and K flags, and
+    /// differs in the timings of some instructions.
+    Intel8085,
+    /// The Zilog Z80, which adds the CB, DD, ED, and FD prefixed instructions, the index
+    /// registers, the alternate registers, interrupt modes 0, 1, and 2, the non-maskable
+    /// interrupt, and the I and R registers, and uses its own timings and flags. The undocumented
+    /// bits 3 and 5 of the flags are not emulated.
+    Z80,
+}
+
+/// The interrupt inputs of the 8085 other than INTR.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InterruptPin {
+    /// TRAP, which cannot be masked, vectors to 0x0024.
+    Trap,
+    /// RST 5.5, a level-sensitive input that vectors to 0x002C.
+    Rst5_5,
+    /// RST 6.5, a level-sensitive input that vectors to 0x0034.
+    Rst6_5,
+    /// RST 7.5, an edge-triggered input that vectors to 0x003C.
+    Rst7_5,
+}
+
 /// A type alias for `[u8; 3]` that represents an instruction. If the instruction is shorter than 3
 /// bytes, it is padded with null bytes at the end.
 pub type Instruction = [u8; 3];
@@ -1816,19 +2254,22 @@ bitflags! {
     /// A byte that holds the settings of the condition flags:
     ///
     /// <table>
-    /// <tr> <th>Bit</th> <th>Condition Flag</th> </tr>
-    /// <tr> <td>7</td>   <td>Sign</td> </tr>
-    /// <tr> <td>6</td>   <td>Zero</td> </tr>
-    /// <tr> <td>5</td>   <td>0</td> </tr>
-    /// <tr> <td>4</td>   <td>Auxiliary Carry</td> </tr>
-    /// <tr> <td>3</td>   <td>0</td> </tr>
-    /// <tr> <td>2</td>   <td>Parity</td> </tr>
-    /// <tr> <td>1</td>   <td>1</td> </tr>
-    /// <tr> <td>0</td>   <td>Carry</td> </tr>
+    /// <tr> <th>Bit</th> <th>Condition Flag</th> <th>8085</th>          <th>Z80</th> </tr>
+    /// <tr> <td>7</td>   <td>Sign</td>            <td></td>              <td></td> </tr>
+    /// <tr> <td>6</td>   <td>Zero</td>            <td></td>              <td></td> </tr>
+    /// <tr> <td>5</td>   <td>0</td>               <td>K (Underflow)</td> <td></td> </tr>
+    /// <tr> <td>4</td>   <td>Auxiliary Carry</td> <td></td>              <td>H (Half Carry)</td> </tr>
+    /// <tr> <td>3</td>   <td>0</td>               <td></td>              <td></td> </tr>
+    /// <tr> <td>2</td>   <td>Parity</td>          <td></td>              <td>P/V (Parity/Overflow)</td> </tr>
+    /// <tr> <td>1</td>   <td>1</td>               <td>V (Overflow)</td>  <td>N (Subtract)</td> </tr>
+    /// <tr> <td>0</td>   <td>Carry</td>           <td></td>              <td></td> </tr>
     /// </table>
     pub struct ConditionFlags: u8 {
         const CARRY = 0b0000_0001;
         const ALWAYS_ONE = 0b0000_0010;
+        const OVERFLOW = 0b0000_0010; // 8085 only
+        const UNDERFLOW = 0b0010_0000; // 8085 only
+        const SUBTRACT = 0b0000_0010; // Z80 only
         const PARITY = 0b0000_0100;
         const AUX_CARRY = 0b0001_0000; // auxiliary carry
         const ZERO = 0b0100_0000;
@@ -1836,6 +2277,21 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// The status word that the CPU puts on the data bus at the start of each machine cycle to
+    /// describe the cycle. `WO` is active low: it is set unless the cycle writes.
+    pub struct StatusWord: u8 {
+        const INTA = 0b0000_0001; // interrupt acknowledge
+        const WO = 0b0000_0010; // not write output
+        const STACK = 0b0000_0100;
+        const HLTA = 0b0000_1000; // halt acknowledge
+        const OUT = 0b0001_0000;
+        const M1 = 0b0010_0000; // instruction fetch
+        const INP = 0b0100_0000;
+        const MEMR = 0b1000_0000; // memory read
+    }
+}
+
 impl Default for ConditionFlags {
     fn default() -> Self {
         ConditionFlags::ALWAYS_ONE
@@ -1850,5 +2306,18 @@ enum Interruptable {
     Enabled,
 }
 
+#[cfg(feature = "std")]
+mod block_cache;
+#[cfg(feature = "std")]
+use block_cache::BlockCache;
+#[cfg(feature = "std")]
+pub use block_cache::Engine;
+mod intel8085;
+mod opcodes;
+use opcodes::PARITY_ZERO_SIGN_FLAGS;
+pub use opcodes::{MemoryAccess, OpcodeInfo, INTEL8080_OPCODES};
+mod z80;
+pub use z80::{InterruptMode, Z80Registers};
+
 #[cfg(test)]
 mod tests;
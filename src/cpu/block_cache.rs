@@ -0,0 +1,408 @@
+// A translation cache that decodes code into blocks of instructions once, so that `Cpu::run`
+// executes them without fetching and decoding each instruction again.
+//
+// A block follows the path that the code is likely to take: through jumps, calls, and restarts,
+// through conditional jumps backwards as loops take them, and past the other conditional
+// instructions. It ends where the next address is unknown, at a return, PCHL, or HLT, or after
+// `MAX_BLOCK_LENGTH` instructions, and the CPU leaves it early when an instruction goes elsewhere.
+//
+// The cache counts the blocks that cover each byte, and drops the blocks that cover a byte when it
+// changes: after an instruction that writes it, which the cache checks from the registers, or
+// between runs, which the cache finds by comparing memory with its copy when a run starts. Code
+// that the program itself rewrites is interpreted from then on, as it is likely to be rewritten
+// again, which would make the cache decode it again and again.
+
+use core::{convert::TryInto, mem};
+
+use super::{Cpu, Instruction, Interruptable, MemoryAccess, INTEL8080_OPCODES};
+use crate::memory::Memory;
+
+const MEMORY_SIZE: usize = 0x10000;
+// The bytes compared at once with the copy of memory, and over which the blocks that cover them
+// are listed.
+const LINE_SIZE: usize = 64;
+const MAX_BLOCK_LENGTH: usize = 64;
+
+/// The engines that execute the instructions of the Intel 8080 and its clones in [`Cpu::run`] and
+/// [`Cpu::run_with_io`]. The Intel 8085 and the Z80 are always interpreted.
+///
+/// [`Cpu::run`]: struct.Cpu.html#method.run
+/// [`Cpu::run_with_io`]: struct.Cpu.html#method.run_with_io
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Engine {
+    /// Fetches and decodes every instruction that it executes.
+    Interpreter,
+    /// Decodes the code into blocks once and executes the cached blocks, dropping them when the
+    /// memory that they were decoded from is written. It asks `is_breakpoint` about the addresses
+    /// in a block once in each run, so the answers must not change during a run.
+    BlockCache,
+}
+
+pub(super) struct BlockCache {
+    // The block that starts at each address.
+    blocks: Vec<Option<Box<Block>>>,
+    // The start addresses of the blocks that cover bytes in each line.
+    lines: Vec<Vec<u16>>,
+    // The number of cached blocks that cover each byte.
+    coverage: Box<[u32; MEMORY_SIZE]>,
+    // The bytes that cached blocks cover.
+    code: Bitmap,
+    // The bytes of code that the program has rewritten, which blocks no longer cover.
+    rewritten: Bitmap,
+    // The memory as of the start of the last run, updated where blocks are decoded.
+    copy: Box<[u8; MEMORY_SIZE]>,
+    // The number of runs so far.
+    run: u64,
+}
+
+struct Block {
+    operations: Vec<Operation>,
+    // The start addresses and sizes of the runs of bytes that the instructions were decoded from.
+    ranges: Vec<(u16, u16)>,
+    // The run in which `breakpoints` was found.
+    run: u64,
+    // Whether the successor of each instruction is a breakpoint in that run, one bit each.
+    breakpoints: u64,
+}
+
+struct Operation {
+    instruction: Instruction,
+    // The address that follows the instruction.
+    next_address: u16,
+    // The address of the next instruction in the block.
+    successor: u16,
+    memory_access: MemoryAccess,
+}
+
+// One bit for each byte of memory, which is small enough to stay in the data cache of the host.
+struct Bitmap(Box<[u64; MEMORY_SIZE / 64]>);
+
+impl BlockCache {
+    pub(super) fn new() -> Self {
+        Self {
+            blocks: (0..MEMORY_SIZE).map(|_| None).collect(),
+            lines: vec![Vec::new(); MEMORY_SIZE / LINE_SIZE],
+            coverage: vec![0; MEMORY_SIZE].into_boxed_slice().try_into().unwrap(),
+            code: Bitmap::new(),
+            rewritten: Bitmap::new(),
+            copy: vec![0; MEMORY_SIZE].into_boxed_slice().try_into().unwrap(),
+            run: 0,
+        }
+    }
+
+    // Drops the blocks that cover the bytes written since the last run.
+    fn synchronize(&mut self, memory: &Memory) {
+        let memory: &[u8; MEMORY_SIZE] = memory;
+        for start in (0..MEMORY_SIZE).step_by(LINE_SIZE) {
+            let line = start..start + LINE_SIZE;
+            if memory[line.clone()] != self.copy[line.clone()] {
+                for address in line {
+                    if memory[address] != self.copy[address] {
+                        self.copy[address] = memory[address];
+                        self.invalidate(address as u16);
+                    }
+                }
+            }
+        }
+    }
+
+    // Decodes the block that starts at `address` unless it is cached, and finds its breakpoints
+    // unless they have been found in this run.
+    #[inline]
+    fn prepare<F>(&mut self, address: u16, memory: &Memory, mut is_breakpoint: F)
+    where
+        F: FnMut(u16) -> bool,
+    {
+        let run = self.run;
+        let block = match &mut self.blocks[usize::from(address)] {
+            Some(block) if block.run == run => return,
+            Some(block) => block,
+            None => self.insert(address, Block::decode(address, memory, &self.rewritten), memory),
+        };
+        block.run = run;
+        block.breakpoints = 0;
+        for (index, operation) in block.operations.iter().enumerate() {
+            if is_breakpoint(operation.successor) {
+                block.breakpoints |= 1 << index;
+            }
+        }
+    }
+
+    fn insert(&mut self, address: u16, block: Block, memory: &Memory) -> &mut Block {
+        for covered in block.addresses() {
+            self.coverage[usize::from(covered)] += 1;
+            self.code.set(covered);
+            self.copy[usize::from(covered)] = memory[covered];
+        }
+        for line in block.lines() {
+            if !self.lines[line].contains(&address) {
+                self.lines[line].push(address);
+            }
+        }
+        self.blocks[usize::from(address)].get_or_insert(Box::new(block))
+    }
+
+    // Returns the bytes of code that `operation` has changed, if any, given the registers after
+    // it was executed.
+    #[inline]
+    fn written_code(&self, operation: &Operation, cpu: &Cpu, memory: &Memory) -> Option<[u16; 2]> {
+        let addresses = operation.written_addresses(cpu)?;
+        let is_changed = |&address: &u16| {
+            self.code.get(address) && memory[address] != self.copy[usize::from(address)]
+        };
+        if addresses.iter().any(is_changed) {
+            Some(addresses)
+        } else {
+            None
+        }
+    }
+
+    // Drops the blocks that cover the bytes that the program has rewritten, and leaves the bytes
+    // to the interpreter.
+    fn rewrite(&mut self, addresses: [u16; 2]) {
+        for &address in &addresses {
+            if self.code.get(address) {
+                self.rewritten.set(address);
+                self.invalidate(address);
+            }
+        }
+    }
+
+    // Drops the blocks that cover `address`.
+    fn invalidate(&mut self, address: u16) {
+        if self.coverage[usize::from(address)] == 0 {
+            return;
+        }
+        let line = usize::from(address) / LINE_SIZE;
+        let mut index = 0;
+        while index < self.lines[line].len() {
+            let start = self.lines[line][index];
+            if covers(&self.blocks[usize::from(start)].as_ref().unwrap().ranges, address) {
+                // This removes `start` from the line.
+                self.remove(start);
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    fn remove(&mut self, start: u16) {
+        let block = self.blocks[usize::from(start)].take().unwrap();
+        for covered in block.addresses() {
+            self.coverage[usize::from(covered)] -= 1;
+            if self.coverage[usize::from(covered)] == 0 {
+                self.code.clear(covered);
+            }
+        }
+        for line in block.lines() {
+            self.lines[line].retain(|&address| address != start);
+        }
+    }
+}
+
+impl Block {
+    // Decodes the instructions that start at `start_address` up to the first one that covers
+    // `rewritten` bytes, which leaves the block empty if it is the first.
+    fn decode(start_address: u16, memory: &Memory, rewritten: &Bitmap) -> Self {
+        let mut operations = Vec::new();
+        let mut ranges: Vec<(u16, u16)> = Vec::new();
+        let mut address = start_address;
+        while operations.len() < MAX_BLOCK_LENGTH {
+            let (operation, successor) = Operation::decode(address, memory);
+            let length = operation.next_address.wrapping_sub(address);
+            let covered = (0..length).map(|offset| address.wrapping_add(offset));
+            if covered.clone().any(|address| rewritten.get(address)) {
+                break;
+            }
+            // A loop goes over the same bytes again.
+            for covered in covered {
+                if covers(&ranges, covered) {
+                    continue;
+                }
+                match ranges.last_mut() {
+                    Some((start, size)) if start.wrapping_add(*size) == covered => *size += 1,
+                    _ => ranges.push((covered, 1)),
+                }
+            }
+            operations.push(operation);
+            match successor {
+                Some(successor) => address = successor,
+                None => break,
+            }
+        }
+        Self { operations, ranges, run: 0, breakpoints: 0 }
+    }
+
+    fn addresses(&self) -> impl Iterator<Item = u16> + '_ {
+        self.ranges
+            .iter()
+            .flat_map(|&(start, size)| (0..size).map(move |offset| start.wrapping_add(offset)))
+    }
+
+    // Returns the lines that the block covers bytes in, some of them more than once.
+    fn lines(&self) -> impl Iterator<Item = usize> + '_ {
+        self.addresses().map(|address| usize::from(address) / LINE_SIZE)
+    }
+}
+
+impl Operation {
+    // Decodes the instruction at `address`, returning it with the address that the CPU is likely
+    // to execute next, or `None` if it cannot be known until the instruction is executed.
+    fn decode(address: u16, memory: &Memory) -> (Self, Option<u16>) {
+        let opcode = memory[address];
+        let info = &INTEL8080_OPCODES[usize::from(opcode)];
+        let mut instruction = [opcode, 0, 0];
+        for offset in 1..info.length {
+            instruction[usize::from(offset)] = memory[address.wrapping_add(u16::from(offset))];
+        }
+        let next_address = address.wrapping_add(u16::from(info.length));
+        let target = u16::from_le_bytes([instruction[1], instruction[2]]);
+        let successor = match opcode {
+            // HLT, RET, and PCHL, including the undocumented RET
+            0x76 | 0xC9 | 0xD9 | 0xE9 => None,
+            // JMP and CALL, including the undocumented ones
+            0xC3 | 0xCB | 0xCD | 0xDD | 0xED | 0xFD => Some(target),
+            // RST
+            0xC7..=0xFF if opcode & 0x07 == 0b111 => Some(u16::from(opcode & 0x38)),
+            // Jcc backwards
+            0xC2..=0xFA if opcode & 0x07 == 0b010 && target <= address => Some(target),
+            _ => Some(next_address),
+        };
+        let operation = Self {
+            instruction,
+            next_address,
+            successor: successor.unwrap_or(next_address),
+            memory_access: info.memory_access,
+        };
+        (operation, successor)
+    }
+
+    // Returns the addresses that the instruction may have written, given the registers after it
+    // was executed.
+    #[inline]
+    fn written_addresses(&self, cpu: &Cpu) -> Option<[u16; 2]> {
+        let access = self.memory_access;
+        if access.contains(MemoryAccess::WRITE_M) {
+            let hl = u16::from_le_bytes([cpu.l, cpu.h]);
+            Some([hl, hl])
+        } else if access.contains(MemoryAccess::WRITE_INDIRECT) {
+            // STAX B or STAX D
+            let address = match self.instruction[0] {
+                0x02 => u16::from_le_bytes([cpu.c, cpu.b]),
+                _ => u16::from_le_bytes([cpu.e, cpu.d]),
+            };
+            Some([address, address])
+        } else if access.contains(MemoryAccess::WRITE_DIRECT) {
+            let address = u16::from_le_bytes([self.instruction[1], self.instruction[2]]);
+            Some([address, address.wrapping_add(1)])
+        } else if access.contains(MemoryAccess::WRITE_STACK) {
+            Some([cpu.sp, cpu.sp.wrapping_add(1)])
+        } else {
+            None
+        }
+    }
+}
+
+impl Bitmap {
+    fn new() -> Self {
+        Self(vec![0; MEMORY_SIZE / 64].into_boxed_slice().try_into().unwrap())
+    }
+
+    #[inline]
+    fn get(&self, address: u16) -> bool {
+        self.0[usize::from(address / 64)] & 1 << (address % 64) != 0
+    }
+
+    fn set(&mut self, address: u16) {
+        self.0[usize::from(address / 64)] |= 1 << (address % 64);
+    }
+
+    fn clear(&mut self, address: u16) {
+        self.0[usize::from(address / 64)] &= !(1 << (address % 64));
+    }
+}
+
+// Returns `true` if `ranges` of bytes, given by their start addresses and sizes, cover `address`.
+fn covers(ranges: &[(u16, u16)], address: u16) -> bool {
+    ranges.iter().any(|&(start, size)| address.wrapping_sub(start) < size)
+}
+
+impl Cpu {
+    // Does what `run_steps` does for the Intel 8080 and its clones, executing the blocks in
+    // `cache` with `execute`.
+    #[inline(always)]
+    pub(super) fn run_blocks<F, E>(
+        &mut self,
+        cache: &mut BlockCache,
+        memory: &mut Memory,
+        states: u64,
+        mut is_breakpoint: F,
+        mut execute: E,
+    ) -> u64
+    where
+        F: FnMut(u16) -> bool,
+        E: FnMut(&mut Self, &mut Memory, Instruction) -> u32,
+    {
+        cache.synchronize(memory);
+        cache.run += 1;
+        let mut cpu = mem::take(self);
+        let mut elapsed = 0;
+        while !cpu.is_halted {
+            let address = cpu.pc;
+            cache.prepare(address, memory, &mut is_breakpoint);
+            let operations = &cache.blocks[usize::from(address)].as_ref().unwrap().operations;
+            let breakpoints = cache.blocks[usize::from(address)].as_ref().unwrap().breakpoints;
+            let mut is_stopped = false;
+            let mut written_code = None;
+            if operations.is_empty() {
+                let (operation, _) = Operation::decode(address, memory);
+                elapsed += u64::from(cpu.execute_operation(&operation, memory, &mut execute));
+                written_code = cache.written_code(&operation, &cpu, memory);
+                is_stopped = elapsed >= states || is_breakpoint(cpu.pc);
+            }
+            for (index, operation) in operations.iter().enumerate() {
+                elapsed += u64::from(cpu.execute_operation(operation, memory, &mut execute));
+                written_code = cache.written_code(operation, &cpu, memory);
+                // The instruction may have gone elsewhere or overwritten the rest of the block.
+                if cpu.pc != operation.successor || written_code.is_some() {
+                    is_stopped = elapsed >= states || is_breakpoint(cpu.pc);
+                    break;
+                }
+                if elapsed >= states || breakpoints & 1 << index != 0 {
+                    is_stopped = true;
+                    break;
+                }
+            }
+            if let Some(addresses) = written_code {
+                cache.rewrite(addresses);
+            }
+            if is_stopped {
+                break;
+            }
+        }
+        cpu.flags();
+        *self = cpu;
+        elapsed
+    }
+
+    #[inline(always)]
+    fn execute_operation<E>(
+        &mut self,
+        operation: &Operation,
+        memory: &mut Memory,
+        execute: E,
+    ) -> u32
+    where
+        E: FnOnce(&mut Self, &mut Memory, Instruction) -> u32,
+    {
+        let interruptable = self.interruptable;
+        self.pc = operation.next_address;
+        let states = execute(self, memory, operation.instruction);
+        if let (Interruptable::Enabling, Interruptable::Enabling) =
+            (interruptable, self.interruptable)
+        {
+            self.interruptable = Interruptable::Enabled;
+        }
+        states
+    }
+}
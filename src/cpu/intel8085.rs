@@ -0,0 +1,334 @@
+// The instructions, timings, interrupt inputs, and serial lines of the Intel 8085.
+//
+// The undocumented instructions and the V and K flags follow "Unspecified 8085 op codes enhance
+// programming" (W. Dehnhardt and V. M. Sorensen, Electronics, January 18, 1979).
+
+use super::*;
+
+// The bits of the accumulator for RIM and SIM.
+const MASKS: u8 = 0x07;
+const INTERRUPT_ENABLE: u8 = 0x08;
+const MASK_SET_ENABLE: u8 = 0x08;
+const RESET_RST7_5: u8 = 0x10;
+const SERIAL_OUTPUT_ENABLE: u8 = 0x40;
+const SERIAL_DATA: u8 = 0x80;
+
+// The interrupt inputs and the serial lines.
+#[derive(Clone, Copy, Default)]
+pub(super) struct Pins {
+    // The RST 5.5, RST 6.5, and RST 7.5 masks set by SIM.
+    masks: u8,
+    rst5_5: bool,
+    rst6_5: bool,
+    rst7_5: bool,
+    // RST 7.5 and TRAP are latched on the rising edge.
+    is_rst7_5_pending: bool,
+    trap: bool,
+    is_trap_pending: bool,
+    // The state of the interrupt system when TRAP was acknowledged, which the next RIM reads.
+    interrupt_enabled_before_trap: Option<bool>,
+    sid: bool,
+    sod: bool,
+}
+
+impl Pins {
+    // RESET masks RST 5.5, 6.5, and 7.5.
+    pub(super) fn reset() -> Self {
+        Self { masks: MASKS, ..Self::default() }
+    }
+}
+
+impl Cpu {
+    /// Sets the level of an interrupt input of the 8085. An interrupt is acknowledged before the
+    /// next instruction if the interrupt system is enabled and the input is not masked by SIM,
+    /// except for TRAP, which is always acknowledged. The inputs are ignored by other variants.
+    pub fn set_interrupt_pin(&mut self, pin: InterruptPin, level: bool) {
+        let pins = &mut self.pins;
+        match pin {
+            InterruptPin::Trap => {
+                pins.is_trap_pending |= level && !pins.trap;
+                pins.trap = level;
+            }
+            InterruptPin::Rst5_5 => pins.rst5_5 = level,
+            InterruptPin::Rst6_5 => pins.rst6_5 = level,
+            InterruptPin::Rst7_5 => {
+                pins.is_rst7_5_pending |= level && !pins.rst7_5;
+                pins.rst7_5 = level;
+            }
+        }
+    }
+
+    /// Sets the level of the SID (serial input data) line of the 8085, which RIM reads.
+    pub fn set_sid(&mut self, level: bool) {
+        self.pins.sid = level;
+    }
+
+    /// Returns the level of the SOD (serial output data) line of the 8085, which SIM sets.
+    pub fn sod(&self) -> bool {
+        self.pins.sod
+    }
+
+    // Returns the length of an instruction, which differs from that on the 8080 for the
+    // undocumented opcodes.
+    pub(super) fn intel8085_instruction_length(opcode: u8) -> u8 {
+        match opcode {
+            0x28 /* LDHI */ | 0x38 /* LDSI */ => 2,
+            0xDD /* JNK */ | 0xFD /* JK */ => 3,
+            0x08 /* DSUB */ | 0x10 /* ARHL */ | 0x18 /* RDEL */ | 0x20 /* RIM */ | 0x30 /* SIM */
+            | 0xCB /* RSTV */ | 0xD9 /* SHLX */ | 0xED /* LHLX */ => 1,
+            _ => INTEL8080_OPCODES[usize::from(opcode)].length,
+        }
+    }
+
+    // Acknowledges the interrupt with the highest priority, if any, returning the number of
+    // states taken.
+    pub(super) fn acknowledge_intel8085_interrupt<M: Bus + ?Sized>(
+        &mut self,
+        memory: &mut M,
+    ) -> u32 {
+        let pins = &mut self.pins;
+        let is_enabled = matches!(self.interruptable, Interruptable::Enabled);
+        let vector = if pins.is_trap_pending {
+            pins.is_trap_pending = false;
+            pins.interrupt_enabled_before_trap = Some(is_enabled);
+            0x24
+        } else if is_enabled && pins.is_rst7_5_pending && pins.masks & 0x04 == 0 {
+            pins.is_rst7_5_pending = false;
+            0x3C
+        } else if is_enabled && pins.rst6_5 && pins.masks & 0x02 == 0 {
+            0x34
+        } else if is_enabled && pins.rst5_5 && pins.masks & 0x01 == 0 {
+            0x2C
+        } else {
+            return 0;
+        };
+        self.is_halted = false;
+        self.interruptable = Interruptable::Disabled;
+        self.call([0xCD, vector, 0x00], memory);
+        12
+    }
+
+    pub(super) fn execute_intel8085_instruction<M: Bus + ?Sized, I: Io + ?Sized>(
+        &mut self,
+        instruction: Instruction,
+        memory: &mut M,
+        io: &mut I,
+    ) -> u32 {
+        let hl = u16::from_le_bytes([self.l, self.h]);
+        let de = u16::from_le_bytes([self.e, self.d]);
+        match instruction[0] {
+            // DSUB (Subtract BC from HL, undocumented)
+            0x08 => {
+                let bc = u16::from_le_bytes([self.c, self.b]);
+                let (result, borrow) = hl.overflowing_sub(bc);
+                let overflow = (hl ^ bc) & (hl ^ result) & 0x8000 != 0;
+                self.set_hl(result);
+                self.update_parity_zero_sign_flags(self.l);
+                self.condition_flags.set(ConditionFlags::ZERO, result == 0);
+                self.condition_flags.set(ConditionFlags::SIGN, result & 0x8000 != 0);
+                self.condition_flags.set(ConditionFlags::CARRY, borrow);
+                self.condition_flags.set(ConditionFlags::OVERFLOW, overflow);
+                10
+            }
+
+            // ARHL (Arithmetic shift HL right, undocumented)
+            0x10 => {
+                self.condition_flags.set(ConditionFlags::CARRY, hl & 0x0001 != 0);
+                self.set_hl(hl >> 1 | hl & 0x8000);
+                7
+            }
+
+            // RDEL (Rotate DE left through carry, undocumented)
+            0x18 => {
+                let carry = self.condition_flags.contains(ConditionFlags::CARRY);
+                self.condition_flags.set(ConditionFlags::CARRY, de & 0x8000 != 0);
+                self.condition_flags.set(ConditionFlags::OVERFLOW, (de ^ de << 1) & 0x8000 != 0);
+                let [e, d] = (de << 1 | u16::from(carry)).to_le_bytes();
+                self.d = d;
+                self.e = e;
+                10
+            }
+
+            // RIM (Read interrupt masks)
+            0x20 => {
+                let is_enabled = matches!(self.interruptable, Interruptable::Enabled);
+                let pins = &mut self.pins;
+                let interrupt_enabled =
+                    pins.interrupt_enabled_before_trap.take().unwrap_or(is_enabled);
+                self.a = pins.masks
+                    | if interrupt_enabled { INTERRUPT_ENABLE } else { 0 }
+                    | u8::from(pins.rst5_5) << 4
+                    | u8::from(pins.rst6_5) << 5
+                    | u8::from(pins.is_rst7_5_pending) << 6
+                    | if pins.sid { SERIAL_DATA } else { 0 };
+                4
+            }
+
+            // LDHI (Load DE with HL plus immediate byte, undocumented)
+            0x28 => {
+                let [e, d] = hl.wrapping_add(u16::from(instruction[1])).to_le_bytes();
+                self.d = d;
+                self.e = e;
+                10
+            }
+
+            // SIM (Set interrupt masks)
+            0x30 => {
+                let pins = &mut self.pins;
+                if self.a & MASK_SET_ENABLE != 0 {
+                    pins.masks = self.a & MASKS;
+                }
+                if self.a & RESET_RST7_5 != 0 {
+                    pins.is_rst7_5_pending = false;
+                }
+                if self.a & SERIAL_OUTPUT_ENABLE != 0 {
+                    pins.sod = self.a & SERIAL_DATA != 0;
+                }
+                4
+            }
+
+            // LDSI (Load DE with SP plus immediate byte, undocumented)
+            0x38 => {
+                let [e, d] = self.sp.wrapping_add(u16::from(instruction[1])).to_le_bytes();
+                self.d = d;
+                self.e = e;
+                10
+            }
+
+            // RSTV (Restart 8 on overflow, undocumented)
+            0xCB => {
+                if self.condition_flags.contains(ConditionFlags::OVERFLOW) {
+                    self.call([0xCD, 0x40, 0x00], memory);
+                    12
+                } else {
+                    6
+                }
+            }
+
+            // SHLX (Store HL indirect through DE, undocumented)
+            0xD9 => {
+                memory[de] = self.l;
+                memory[de.wrapping_add(1)] = self.h;
+                10
+            }
+
+            // JNK (Jump on no underflow, undocumented)
+            0xDD => {
+                if self.condition_flags.contains(ConditionFlags::UNDERFLOW) {
+                    7
+                } else {
+                    self.pc = u16::from_le_bytes([instruction[1], instruction[2]]);
+                    10
+                }
+            }
+
+            // LHLX (Load HL indirect through DE, undocumented)
+            0xED => {
+                self.l = memory[de];
+                self.h = memory[de.wrapping_add(1)];
+                10
+            }
+
+            // JK (Jump on underflow, undocumented)
+            0xFD => {
+                if self.condition_flags.contains(ConditionFlags::UNDERFLOW) {
+                    self.pc = u16::from_le_bytes([instruction[1], instruction[2]]);
+                    10
+                } else {
+                    7
+                }
+            }
+
+            // POP PSW (Pop A and Flags off stack), which restores V and K
+            0xF1 => {
+                self.condition_flags = ConditionFlags::from_bits_truncate(memory[self.sp]);
+                self.a = memory[self.sp.wrapping_add(1)];
+                self.sp = self.sp.wrapping_add(2);
+                10
+            }
+
+            opcode => {
+                let states = self.execute_intel8080_instruction(instruction, memory, io);
+                self.update_underflow_flag(opcode);
+                self.intel8085_states(opcode, states)
+            }
+        }
+    }
+
+    // K is set when INX wraps around to 0x0000 or DCX wraps around to 0xFFFF.
+    fn update_underflow_flag(&mut self, opcode: u8) {
+        let pair = match opcode & 0x30 {
+            0x00 => u16::from_le_bytes([self.c, self.b]),
+            0x10 => u16::from_le_bytes([self.e, self.d]),
+            0x20 => u16::from_le_bytes([self.l, self.h]),
+            _ => self.sp,
+        };
+        match opcode & 0xCF {
+            // INX
+            0x03 => self.condition_flags.set(ConditionFlags::UNDERFLOW, pair == 0x0000),
+            // DCX
+            0x0B => self.condition_flags.set(ConditionFlags::UNDERFLOW, pair == 0xFFFF),
+            _ => {}
+        }
+    }
+
+    // Converts the number of states that an instruction takes on the 8080 into that on the 8085.
+    fn intel8085_states(&self, opcode: u8, states: u32) -> u32 {
+        match opcode {
+            // HLT
+            0x76 => 5,
+            // MOV r,r
+            0x40..=0x7F if opcode & 0x07 != 0x06 && opcode & 0xF8 != 0x70 => 4,
+            // INR r and DCR r
+            0x04..=0x3D if opcode & 0x06 == 0x04 && opcode & 0x38 != 0x30 => 4,
+            // INX, DCX, SPHL, and PCHL
+            0x03 | 0x13 | 0x23 | 0x33 | 0x0B | 0x1B | 0x2B | 0x3B | 0xE9 | 0xF9 => 6,
+            // Jcond
+            0xC2 | 0xCA | 0xD2 | 0xDA | 0xE2 | 0xEA | 0xF2 | 0xFA => {
+                if self.condition(opcode) {
+                    10
+                } else {
+                    7
+                }
+            }
+            // Ccond
+            0xC4 | 0xCC | 0xD4 | 0xDC | 0xE4 | 0xEC | 0xF4 | 0xFC => {
+                if states == 17 {
+                    18
+                } else {
+                    9
+                }
+            }
+            // Rcond
+            0xC0 | 0xC8 | 0xD0 | 0xD8 | 0xE0 | 0xE8 | 0xF0 | 0xF8 => states + 1,
+            // PUSH and RST
+            0xC5 | 0xD5 | 0xE5 | 0xF5 | 0xC7 | 0xCF | 0xD7 | 0xDF | 0xE7 | 0xEF | 0xF7 | 0xFF => 12,
+            // CALL
+            0xCD => 18,
+            // XTHL
+            0xE3 => 16,
+            _ => states,
+        }
+    }
+
+    // Returns whether the condition of a conditional instruction is true.
+    fn condition(&self, opcode: u8) -> bool {
+        let flags = self.condition_flags;
+        match opcode & 0x38 {
+            0x00 => !flags.contains(ConditionFlags::ZERO),
+            0x08 => flags.contains(ConditionFlags::ZERO),
+            0x10 => !flags.contains(ConditionFlags::CARRY),
+            0x18 => flags.contains(ConditionFlags::CARRY),
+            0x20 => !flags.contains(ConditionFlags::PARITY),
+            0x28 => flags.contains(ConditionFlags::PARITY),
+            0x30 => !flags.contains(ConditionFlags::SIGN),
+            _ => flags.contains(ConditionFlags::SIGN),
+        }
+    }
+
+    fn set_hl(&mut self, hl: u16) {
+        let [l, h] = hl.to_le_bytes();
+        self.h = h;
+        self.l = l;
+    }
+}
@@ -0,0 +1,365 @@
+// Static per-opcode metadata of the Intel 8080 and the precomputed flag table shared by the
+// instructions that set the parity, zero, and sign flags by their result.
+
+use bitflags::bitflags;
+
+use super::ConditionFlags;
+
+/// What the CPU knows about an opcode before executing it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpcodeInfo {
+    /// The mnemonic with the register operands, to which a disassembler appends the immediate
+    /// operand, if any: `"MVI B"` for MVI B,0FFH and `"JMP"` for JMP 1000H.
+    pub mnemonic: &'static str,
+    /// The length of the instruction in bytes.
+    pub length: u8,
+    /// The number of states taken, or taken when the condition of a conditional instruction is
+    /// false.
+    pub states: u8,
+    /// The number of states taken when the condition of a conditional instruction is true, which
+    /// equals `states` for other instructions.
+    pub states_taken: u8,
+    /// The condition flags that the instruction can change.
+    pub flags: ConditionFlags,
+    /// The memory that the instruction can read or write besides fetching itself.
+    pub memory_access: MemoryAccess,
+}
+
+bitflags! {
+    /// The memory that an instruction can read or write besides fetching itself.
+    pub struct MemoryAccess: u8 {
+        /// The byte addressed by HL (M).
+        const READ_M = 0b0000_0001;
+        const WRITE_M = 0b0000_0010;
+        /// The byte addressed by BC or DE (LDAX and STAX).
+        const READ_INDIRECT = 0b0000_0100;
+        const WRITE_INDIRECT = 0b0000_1000;
+        /// The bytes addressed by the operand (LDA, STA, LHLD, and SHLD).
+        const READ_DIRECT = 0b0001_0000;
+        const WRITE_DIRECT = 0b0010_0000;
+        /// The stack.
+        const READ_STACK = 0b0100_0000;
+        const WRITE_STACK = 0b1000_0000;
+    }
+}
+
+const NONE: u8 = 0;
+const CARRY: u8 = ConditionFlags::CARRY.bits();
+const SZAP: u8 = ConditionFlags::SIGN.bits()
+    | ConditionFlags::ZERO.bits()
+    | ConditionFlags::AUX_CARRY.bits()
+    | ConditionFlags::PARITY.bits();
+const ALL: u8 = SZAP | CARRY;
+
+const READ_M: u8 = MemoryAccess::READ_M.bits();
+const WRITE_M: u8 = MemoryAccess::WRITE_M.bits();
+const READ_WRITE_M: u8 = READ_M | WRITE_M;
+const READ_INDIRECT: u8 = MemoryAccess::READ_INDIRECT.bits();
+const WRITE_INDIRECT: u8 = MemoryAccess::WRITE_INDIRECT.bits();
+const READ_DIRECT: u8 = MemoryAccess::READ_DIRECT.bits();
+const WRITE_DIRECT: u8 = MemoryAccess::WRITE_DIRECT.bits();
+const READ_STACK: u8 = MemoryAccess::READ_STACK.bits();
+const WRITE_STACK: u8 = MemoryAccess::WRITE_STACK.bits();
+const READ_WRITE_STACK: u8 = READ_STACK | WRITE_STACK;
+
+const fn opcode(
+    mnemonic: &'static str,
+    length: u8,
+    states: u8,
+    states_taken: u8,
+    flags: u8,
+    memory_access: u8,
+) -> OpcodeInfo {
+    OpcodeInfo {
+        mnemonic,
+        length,
+        states,
+        states_taken,
+        flags: ConditionFlags::from_bits_truncate(flags),
+        memory_access: MemoryAccess::from_bits_truncate(memory_access),
+    }
+}
+
+/// The metadata of the 256 opcodes of the Intel 8080, indexed by opcode. The undocumented opcodes
+/// are listed as the instructions that they act as.
+#[rustfmt::skip]
+pub static INTEL8080_OPCODES: [OpcodeInfo; 256] = [
+    /* 0x00 */ opcode("NOP", 1, 4, 4, NONE, NONE),
+    /* 0x01 */ opcode("LXI B", 3, 10, 10, NONE, NONE),
+    /* 0x02 */ opcode("STAX B", 1, 7, 7, NONE, WRITE_INDIRECT),
+    /* 0x03 */ opcode("INX B", 1, 5, 5, NONE, NONE),
+    /* 0x04 */ opcode("INR B", 1, 5, 5, SZAP, NONE),
+    /* 0x05 */ opcode("DCR B", 1, 5, 5, SZAP, NONE),
+    /* 0x06 */ opcode("MVI B", 2, 7, 7, NONE, NONE),
+    /* 0x07 */ opcode("RLC", 1, 4, 4, CARRY, NONE),
+    /* 0x08 */ opcode("NOP", 1, 4, 4, NONE, NONE), // undocumented
+    /* 0x09 */ opcode("DAD B", 1, 10, 10, CARRY, NONE),
+    /* 0x0A */ opcode("LDAX B", 1, 7, 7, NONE, READ_INDIRECT),
+    /* 0x0B */ opcode("DCX B", 1, 5, 5, NONE, NONE),
+    /* 0x0C */ opcode("INR C", 1, 5, 5, SZAP, NONE),
+    /* 0x0D */ opcode("DCR C", 1, 5, 5, SZAP, NONE),
+    /* 0x0E */ opcode("MVI C", 2, 7, 7, NONE, NONE),
+    /* 0x0F */ opcode("RRC", 1, 4, 4, CARRY, NONE),
+    /* 0x10 */ opcode("NOP", 1, 4, 4, NONE, NONE), // undocumented
+    /* 0x11 */ opcode("LXI D", 3, 10, 10, NONE, NONE),
+    /* 0x12 */ opcode("STAX D", 1, 7, 7, NONE, WRITE_INDIRECT),
+    /* 0x13 */ opcode("INX D", 1, 5, 5, NONE, NONE),
+    /* 0x14 */ opcode("INR D", 1, 5, 5, SZAP, NONE),
+    /* 0x15 */ opcode("DCR D", 1, 5, 5, SZAP, NONE),
+    /* 0x16 */ opcode("MVI D", 2, 7, 7, NONE, NONE),
+    /* 0x17 */ opcode("RAL", 1, 4, 4, CARRY, NONE),
+    /* 0x18 */ opcode("NOP", 1, 4, 4, NONE, NONE), // undocumented
+    /* 0x19 */ opcode("DAD D", 1, 10, 10, CARRY, NONE),
+    /* 0x1A */ opcode("LDAX D", 1, 7, 7, NONE, READ_INDIRECT),
+    /* 0x1B */ opcode("DCX D", 1, 5, 5, NONE, NONE),
+    /* 0x1C */ opcode("INR E", 1, 5, 5, SZAP, NONE),
+    /* 0x1D */ opcode("DCR E", 1, 5, 5, SZAP, NONE),
+    /* 0x1E */ opcode("MVI E", 2, 7, 7, NONE, NONE),
+    /* 0x1F */ opcode("RAR", 1, 4, 4, CARRY, NONE),
+    /* 0x20 */ opcode("NOP", 1, 4, 4, NONE, NONE), // undocumented
+    /* 0x21 */ opcode("LXI H", 3, 10, 10, NONE, NONE),
+    /* 0x22 */ opcode("SHLD", 3, 16, 16, NONE, WRITE_DIRECT),
+    /* 0x23 */ opcode("INX H", 1, 5, 5, NONE, NONE),
+    /* 0x24 */ opcode("INR H", 1, 5, 5, SZAP, NONE),
+    /* 0x25 */ opcode("DCR H", 1, 5, 5, SZAP, NONE),
+    /* 0x26 */ opcode("MVI H", 2, 7, 7, NONE, NONE),
+    /* 0x27 */ opcode("DAA", 1, 4, 4, ALL, NONE),
+    /* 0x28 */ opcode("NOP", 1, 4, 4, NONE, NONE), // undocumented
+    /* 0x29 */ opcode("DAD H", 1, 10, 10, CARRY, NONE),
+    /* 0x2A */ opcode("LHLD", 3, 16, 16, NONE, READ_DIRECT),
+    /* 0x2B */ opcode("DCX H", 1, 5, 5, NONE, NONE),
+    /* 0x2C */ opcode("INR L", 1, 5, 5, SZAP, NONE),
+    /* 0x2D */ opcode("DCR L", 1, 5, 5, SZAP, NONE),
+    /* 0x2E */ opcode("MVI L", 2, 7, 7, NONE, NONE),
+    /* 0x2F */ opcode("CMA", 1, 4, 4, NONE, NONE),
+    /* 0x30 */ opcode("NOP", 1, 4, 4, NONE, NONE), // undocumented
+    /* 0x31 */ opcode("LXI SP", 3, 10, 10, NONE, NONE),
+    /* 0x32 */ opcode("STA", 3, 13, 13, NONE, WRITE_DIRECT),
+    /* 0x33 */ opcode("INX SP", 1, 5, 5, NONE, NONE),
+    /* 0x34 */ opcode("INR M", 1, 10, 10, SZAP, READ_WRITE_M),
+    /* 0x35 */ opcode("DCR M", 1, 10, 10, SZAP, READ_WRITE_M),
+    /* 0x36 */ opcode("MVI M", 2, 10, 10, NONE, WRITE_M),
+    /* 0x37 */ opcode("STC", 1, 4, 4, CARRY, NONE),
+    /* 0x38 */ opcode("NOP", 1, 4, 4, NONE, NONE), // undocumented
+    /* 0x39 */ opcode("DAD SP", 1, 10, 10, CARRY, NONE),
+    /* 0x3A */ opcode("LDA", 3, 13, 13, NONE, READ_DIRECT),
+    /* 0x3B */ opcode("DCX SP", 1, 5, 5, NONE, NONE),
+    /* 0x3C */ opcode("INR A", 1, 5, 5, SZAP, NONE),
+    /* 0x3D */ opcode("DCR A", 1, 5, 5, SZAP, NONE),
+    /* 0x3E */ opcode("MVI A", 2, 7, 7, NONE, NONE),
+    /* 0x3F */ opcode("CMC", 1, 4, 4, CARRY, NONE),
+    /* 0x40 */ opcode("MOV B,B", 1, 5, 5, NONE, NONE),
+    /* 0x41 */ opcode("MOV B,C", 1, 5, 5, NONE, NONE),
+    /* 0x42 */ opcode("MOV B,D", 1, 5, 5, NONE, NONE),
+    /* 0x43 */ opcode("MOV B,E", 1, 5, 5, NONE, NONE),
+    /* 0x44 */ opcode("MOV B,H", 1, 5, 5, NONE, NONE),
+    /* 0x45 */ opcode("MOV B,L", 1, 5, 5, NONE, NONE),
+    /* 0x46 */ opcode("MOV B,M", 1, 7, 7, NONE, READ_M),
+    /* 0x47 */ opcode("MOV B,A", 1, 5, 5, NONE, NONE),
+    /* 0x48 */ opcode("MOV C,B", 1, 5, 5, NONE, NONE),
+    /* 0x49 */ opcode("MOV C,C", 1, 5, 5, NONE, NONE),
+    /* 0x4A */ opcode("MOV C,D", 1, 5, 5, NONE, NONE),
+    /* 0x4B */ opcode("MOV C,E", 1, 5, 5, NONE, NONE),
+    /* 0x4C */ opcode("MOV C,H", 1, 5, 5, NONE, NONE),
+    /* 0x4D */ opcode("MOV C,L", 1, 5, 5, NONE, NONE),
+    /* 0x4E */ opcode("MOV C,M", 1, 7, 7, NONE, READ_M),
+    /* 0x4F */ opcode("MOV C,A", 1, 5, 5, NONE, NONE),
+    /* 0x50 */ opcode("MOV D,B", 1, 5, 5, NONE, NONE),
+    /* 0x51 */ opcode("MOV D,C", 1, 5, 5, NONE, NONE),
+    /* 0x52 */ opcode("MOV D,D", 1, 5, 5, NONE, NONE),
+    /* 0x53 */ opcode("MOV D,E", 1, 5, 5, NONE, NONE),
+    /* 0x54 */ opcode("MOV D,H", 1, 5, 5, NONE, NONE),
+    /* 0x55 */ opcode("MOV D,L", 1, 5, 5, NONE, NONE),
+    /* 0x56 */ opcode("MOV D,M", 1, 7, 7, NONE, READ_M),
+    /* 0x57 */ opcode("MOV D,A", 1, 5, 5, NONE, NONE),
+    /* 0x58 */ opcode("MOV E,B", 1, 5, 5, NONE, NONE),
+    /* 0x59 */ opcode("MOV E,C", 1, 5, 5, NONE, NONE),
+    /* 0x5A */ opcode("MOV E,D", 1, 5, 5, NONE, NONE),
+    /* 0x5B */ opcode("MOV E,E", 1, 5, 5, NONE, NONE),
+    /* 0x5C */ opcode("MOV E,H", 1, 5, 5, NONE, NONE),
+    /* 0x5D */ opcode("MOV E,L", 1, 5, 5, NONE, NONE),
+    /* 0x5E */ opcode("MOV E,M", 1, 7, 7, NONE, READ_M),
+    /* 0x5F */ opcode("MOV E,A", 1, 5, 5, NONE, NONE),
+    /* 0x60 */ opcode("MOV H,B", 1, 5, 5, NONE, NONE),
+    /* 0x61 */ opcode("MOV H,C", 1, 5, 5, NONE, NONE),
+    /* 0x62 */ opcode("MOV H,D", 1, 5, 5, NONE, NONE),
+    /* 0x63 */ opcode("MOV H,E", 1, 5, 5, NONE, NONE),
+    /* 0x64 */ opcode("MOV H,H", 1, 5, 5, NONE, NONE),
+    /* 0x65 */ opcode("MOV H,L", 1, 5, 5, NONE, NONE),
+    /* 0x66 */ opcode("MOV H,M", 1, 7, 7, NONE, READ_M),
+    /* 0x67 */ opcode("MOV H,A", 1, 5, 5, NONE, NONE),
+    /* 0x68 */ opcode("MOV L,B", 1, 5, 5, NONE, NONE),
+    /* 0x69 */ opcode("MOV L,C", 1, 5, 5, NONE, NONE),
+    /* 0x6A */ opcode("MOV L,D", 1, 5, 5, NONE, NONE),
+    /* 0x6B */ opcode("MOV L,E", 1, 5, 5, NONE, NONE),
+    /* 0x6C */ opcode("MOV L,H", 1, 5, 5, NONE, NONE),
+    /* 0x6D */ opcode("MOV L,L", 1, 5, 5, NONE, NONE),
+    /* 0x6E */ opcode("MOV L,M", 1, 7, 7, NONE, READ_M),
+    /* 0x6F */ opcode("MOV L,A", 1, 5, 5, NONE, NONE),
+    /* 0x70 */ opcode("MOV M,B", 1, 7, 7, NONE, WRITE_M),
+    /* 0x71 */ opcode("MOV M,C", 1, 7, 7, NONE, WRITE_M),
+    /* 0x72 */ opcode("MOV M,D", 1, 7, 7, NONE, WRITE_M),
+    /* 0x73 */ opcode("MOV M,E", 1, 7, 7, NONE, WRITE_M),
+    /* 0x74 */ opcode("MOV M,H", 1, 7, 7, NONE, WRITE_M),
+    /* 0x75 */ opcode("MOV M,L", 1, 7, 7, NONE, WRITE_M),
+    /* 0x76 */ opcode("HLT", 1, 7, 7, NONE, NONE),
+    /* 0x77 */ opcode("MOV M,A", 1, 7, 7, NONE, WRITE_M),
+    /* 0x78 */ opcode("MOV A,B", 1, 5, 5, NONE, NONE),
+    /* 0x79 */ opcode("MOV A,C", 1, 5, 5, NONE, NONE),
+    /* 0x7A */ opcode("MOV A,D", 1, 5, 5, NONE, NONE),
+    /* 0x7B */ opcode("MOV A,E", 1, 5, 5, NONE, NONE),
+    /* 0x7C */ opcode("MOV A,H", 1, 5, 5, NONE, NONE),
+    /* 0x7D */ opcode("MOV A,L", 1, 5, 5, NONE, NONE),
+    /* 0x7E */ opcode("MOV A,M", 1, 7, 7, NONE, READ_M),
+    /* 0x7F */ opcode("MOV A,A", 1, 5, 5, NONE, NONE),
+    /* 0x80 */ opcode("ADD B", 1, 4, 4, ALL, NONE),
+    /* 0x81 */ opcode("ADD C", 1, 4, 4, ALL, NONE),
+    /* 0x82 */ opcode("ADD D", 1, 4, 4, ALL, NONE),
+    /* 0x83 */ opcode("ADD E", 1, 4, 4, ALL, NONE),
+    /* 0x84 */ opcode("ADD H", 1, 4, 4, ALL, NONE),
+    /* 0x85 */ opcode("ADD L", 1, 4, 4, ALL, NONE),
+    /* 0x86 */ opcode("ADD M", 1, 7, 7, ALL, READ_M),
+    /* 0x87 */ opcode("ADD A", 1, 4, 4, ALL, NONE),
+    /* 0x88 */ opcode("ADC B", 1, 4, 4, ALL, NONE),
+    /* 0x89 */ opcode("ADC C", 1, 4, 4, ALL, NONE),
+    /* 0x8A */ opcode("ADC D", 1, 4, 4, ALL, NONE),
+    /* 0x8B */ opcode("ADC E", 1, 4, 4, ALL, NONE),
+    /* 0x8C */ opcode("ADC H", 1, 4, 4, ALL, NONE),
+    /* 0x8D */ opcode("ADC L", 1, 4, 4, ALL, NONE),
+    /* 0x8E */ opcode("ADC M", 1, 7, 7, ALL, READ_M),
+    /* 0x8F */ opcode("ADC A", 1, 4, 4, ALL, NONE),
+    /* 0x90 */ opcode("SUB B", 1, 4, 4, ALL, NONE),
+    /* 0x91 */ opcode("SUB C", 1, 4, 4, ALL, NONE),
+    /* 0x92 */ opcode("SUB D", 1, 4, 4, ALL, NONE),
+    /* 0x93 */ opcode("SUB E", 1, 4, 4, ALL, NONE),
+    /* 0x94 */ opcode("SUB H", 1, 4, 4, ALL, NONE),
+    /* 0x95 */ opcode("SUB L", 1, 4, 4, ALL, NONE),
+    /* 0x96 */ opcode("SUB M", 1, 7, 7, ALL, READ_M),
+    /* 0x97 */ opcode("SUB A", 1, 4, 4, ALL, NONE),
+    /* 0x98 */ opcode("SBB B", 1, 4, 4, ALL, NONE),
+    /* 0x99 */ opcode("SBB C", 1, 4, 4, ALL, NONE),
+    /* 0x9A */ opcode("SBB D", 1, 4, 4, ALL, NONE),
+    /* 0x9B */ opcode("SBB E", 1, 4, 4, ALL, NONE),
+    /* 0x9C */ opcode("SBB H", 1, 4, 4, ALL, NONE),
+    /* 0x9D */ opcode("SBB L", 1, 4, 4, ALL, NONE),
+    /* 0x9E */ opcode("SBB M", 1, 7, 7, ALL, READ_M),
+    /* 0x9F */ opcode("SBB A", 1, 4, 4, ALL, NONE),
+    /* 0xA0 */ opcode("ANA B", 1, 4, 4, ALL, NONE),
+    /* 0xA1 */ opcode("ANA C", 1, 4, 4, ALL, NONE),
+    /* 0xA2 */ opcode("ANA D", 1, 4, 4, ALL, NONE),
+    /* 0xA3 */ opcode("ANA E", 1, 4, 4, ALL, NONE),
+    /* 0xA4 */ opcode("ANA H", 1, 4, 4, ALL, NONE),
+    /* 0xA5 */ opcode("ANA L", 1, 4, 4, ALL, NONE),
+    /* 0xA6 */ opcode("ANA M", 1, 7, 7, ALL, READ_M),
+    /* 0xA7 */ opcode("ANA A", 1, 4, 4, ALL, NONE),
+    /* 0xA8 */ opcode("XRA B", 1, 4, 4, ALL, NONE),
+    /* 0xA9 */ opcode("XRA C", 1, 4, 4, ALL, NONE),
+    /* 0xAA */ opcode("XRA D", 1, 4, 4, ALL, NONE),
+    /* 0xAB */ opcode("XRA E", 1, 4, 4, ALL, NONE),
+    /* 0xAC */ opcode("XRA H", 1, 4, 4, ALL, NONE),
+    /* 0xAD */ opcode("XRA L", 1, 4, 4, ALL, NONE),
+    /* 0xAE */ opcode("XRA M", 1, 7, 7, ALL, READ_M),
+    /* 0xAF */ opcode("XRA A", 1, 4, 4, ALL, NONE),
+    /* 0xB0 */ opcode("ORA B", 1, 4, 4, ALL, NONE),
+    /* 0xB1 */ opcode("ORA C", 1, 4, 4, ALL, NONE),
+    /* 0xB2 */ opcode("ORA D", 1, 4, 4, ALL, NONE),
+    /* 0xB3 */ opcode("ORA E", 1, 4, 4, ALL, NONE),
+    /* 0xB4 */ opcode("ORA H", 1, 4, 4, ALL, NONE),
+    /* 0xB5 */ opcode("ORA L", 1, 4, 4, ALL, NONE),
+    /* 0xB6 */ opcode("ORA M", 1, 7, 7, ALL, READ_M),
+    /* 0xB7 */ opcode("ORA A", 1, 4, 4, ALL, NONE),
+    /* 0xB8 */ opcode("CMP B", 1, 4, 4, ALL, NONE),
+    /* 0xB9 */ opcode("CMP C", 1, 4, 4, ALL, NONE),
+    /* 0xBA */ opcode("CMP D", 1, 4, 4, ALL, NONE),
+    /* 0xBB */ opcode("CMP E", 1, 4, 4, ALL, NONE),
+    /* 0xBC */ opcode("CMP H", 1, 4, 4, ALL, NONE),
+    /* 0xBD */ opcode("CMP L", 1, 4, 4, ALL, NONE),
+    /* 0xBE */ opcode("CMP M", 1, 7, 7, ALL, READ_M),
+    /* 0xBF */ opcode("CMP A", 1, 4, 4, ALL, NONE),
+    /* 0xC0 */ opcode("RNZ", 1, 5, 11, NONE, READ_STACK),
+    /* 0xC1 */ opcode("POP B", 1, 10, 10, NONE, READ_STACK),
+    /* 0xC2 */ opcode("JNZ", 3, 10, 10, NONE, NONE),
+    /* 0xC3 */ opcode("JMP", 3, 10, 10, NONE, NONE),
+    /* 0xC4 */ opcode("CNZ", 3, 11, 17, NONE, WRITE_STACK),
+    /* 0xC5 */ opcode("PUSH B", 1, 11, 11, NONE, WRITE_STACK),
+    /* 0xC6 */ opcode("ADI", 2, 7, 7, ALL, NONE),
+    /* 0xC7 */ opcode("RST 0", 1, 11, 11, NONE, WRITE_STACK),
+    /* 0xC8 */ opcode("RZ", 1, 5, 11, NONE, READ_STACK),
+    /* 0xC9 */ opcode("RET", 1, 10, 10, NONE, READ_STACK),
+    /* 0xCA */ opcode("JZ", 3, 10, 10, NONE, NONE),
+    /* 0xCB */ opcode("JMP", 3, 10, 10, NONE, NONE), // undocumented
+    /* 0xCC */ opcode("CZ", 3, 11, 17, NONE, WRITE_STACK),
+    /* 0xCD */ opcode("CALL", 3, 17, 17, NONE, WRITE_STACK),
+    /* 0xCE */ opcode("ACI", 2, 7, 7, ALL, NONE),
+    /* 0xCF */ opcode("RST 1", 1, 11, 11, NONE, WRITE_STACK),
+    /* 0xD0 */ opcode("RNC", 1, 5, 11, NONE, READ_STACK),
+    /* 0xD1 */ opcode("POP D", 1, 10, 10, NONE, READ_STACK),
+    /* 0xD2 */ opcode("JNC", 3, 10, 10, NONE, NONE),
+    /* 0xD3 */ opcode("OUT", 2, 10, 10, NONE, NONE),
+    /* 0xD4 */ opcode("CNC", 3, 11, 17, NONE, WRITE_STACK),
+    /* 0xD5 */ opcode("PUSH D", 1, 11, 11, NONE, WRITE_STACK),
+    /* 0xD6 */ opcode("SUI", 2, 7, 7, ALL, NONE),
+    /* 0xD7 */ opcode("RST 2", 1, 11, 11, NONE, WRITE_STACK),
+    /* 0xD8 */ opcode("RC", 1, 5, 11, NONE, READ_STACK),
+    /* 0xD9 */ opcode("RET", 1, 10, 10, NONE, READ_STACK), // undocumented
+    /* 0xDA */ opcode("JC", 3, 10, 10, NONE, NONE),
+    /* 0xDB */ opcode("IN", 2, 10, 10, NONE, NONE),
+    /* 0xDC */ opcode("CC", 3, 11, 17, NONE, WRITE_STACK),
+    /* 0xDD */ opcode("CALL", 3, 17, 17, NONE, WRITE_STACK), // undocumented
+    /* 0xDE */ opcode("SBI", 2, 7, 7, ALL, NONE),
+    /* 0xDF */ opcode("RST 3", 1, 11, 11, NONE, WRITE_STACK),
+    /* 0xE0 */ opcode("RPO", 1, 5, 11, NONE, READ_STACK),
+    /* 0xE1 */ opcode("POP H", 1, 10, 10, NONE, READ_STACK),
+    /* 0xE2 */ opcode("JPO", 3, 10, 10, NONE, NONE),
+    /* 0xE3 */ opcode("XTHL", 1, 18, 18, NONE, READ_WRITE_STACK),
+    /* 0xE4 */ opcode("CPO", 3, 11, 17, NONE, WRITE_STACK),
+    /* 0xE5 */ opcode("PUSH H", 1, 11, 11, NONE, WRITE_STACK),
+    /* 0xE6 */ opcode("ANI", 2, 7, 7, ALL, NONE),
+    /* 0xE7 */ opcode("RST 4", 1, 11, 11, NONE, WRITE_STACK),
+    /* 0xE8 */ opcode("RPE", 1, 5, 11, NONE, READ_STACK),
+    /* 0xE9 */ opcode("PCHL", 1, 5, 5, NONE, NONE),
+    /* 0xEA */ opcode("JPE", 3, 10, 10, NONE, NONE),
+    /* 0xEB */ opcode("XCHG", 1, 4, 4, NONE, NONE),
+    /* 0xEC */ opcode("CPE", 3, 11, 17, NONE, WRITE_STACK),
+    /* 0xED */ opcode("CALL", 3, 17, 17, NONE, WRITE_STACK), // undocumented
+    /* 0xEE */ opcode("XRI", 2, 7, 7, ALL, NONE),
+    /* 0xEF */ opcode("RST 5", 1, 11, 11, NONE, WRITE_STACK),
+    /* 0xF0 */ opcode("RP", 1, 5, 11, NONE, READ_STACK),
+    /* 0xF1 */ opcode("POP PSW", 1, 10, 10, ALL, READ_STACK),
+    /* 0xF2 */ opcode("JP", 3, 10, 10, NONE, NONE),
+    /* 0xF3 */ opcode("DI", 1, 4, 4, NONE, NONE),
+    /* 0xF4 */ opcode("CP", 3, 11, 17, NONE, WRITE_STACK),
+    /* 0xF5 */ opcode("PUSH PSW", 1, 11, 11, NONE, WRITE_STACK),
+    /* 0xF6 */ opcode("ORI", 2, 7, 7, ALL, NONE),
+    /* 0xF7 */ opcode("RST 6", 1, 11, 11, NONE, WRITE_STACK),
+    /* 0xF8 */ opcode("RM", 1, 5, 11, NONE, READ_STACK),
+    /* 0xF9 */ opcode("SPHL", 1, 5, 5, NONE, NONE),
+    /* 0xFA */ opcode("JM", 3, 10, 10, NONE, NONE),
+    /* 0xFB */ opcode("EI", 1, 4, 4, NONE, NONE),
+    /* 0xFC */ opcode("CM", 3, 11, 17, NONE, WRITE_STACK),
+    /* 0xFD */ opcode("CALL", 3, 17, 17, NONE, WRITE_STACK), // undocumented
+    /* 0xFE */ opcode("CPI", 2, 7, 7, ALL, NONE),
+    /* 0xFF */ opcode("RST 7", 1, 11, 11, NONE, WRITE_STACK),
+
+];
+
+// The parity, zero, and sign flags for each result.
+pub(super) static PARITY_ZERO_SIGN_FLAGS: [u8; 256] = parity_zero_sign_flags();
+
+const fn parity_zero_sign_flags() -> [u8; 256] {
+    let mut flags = [0; 256];
+    let mut result = 0;
+    while result < 256 {
+        if (result as u8).count_ones() % 2 == 0 {
+            flags[result] |= ConditionFlags::PARITY.bits();
+        }
+        if result == 0 {
+            flags[result] |= ConditionFlags::ZERO.bits();
+        }
+        if result & 0x80 != 0 {
+            flags[result] |= ConditionFlags::SIGN.bits();
+        }
+        result += 1;
+    }
+    flags
+}
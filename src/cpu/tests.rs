@@ -201,3 +201,311 @@ fn sui() {
     assert!(!i8080.cpu.condition_flags.contains(ConditionFlags::ZERO));
     assert!(!i8080.cpu.condition_flags.contains(ConditionFlags::SIGN));
 }
+
+// The opcode table agrees with the lengths, timings, flags, and memory writes of the
+// instructions.
+#[test]
+fn opcode_table() {
+    for (opcode, info) in INTEL8080_OPCODES.iter().enumerate() {
+        let mut observed_states = Vec::new();
+        for &flags in &[0x02, 0xD7] {
+            let mut i8080 = Intel8080::default();
+            let cpu = &mut i8080.cpu;
+            cpu.pc = 0x1000;
+            cpu.sp = 0x5000;
+            cpu.b = 0x20;
+            cpu.d = 0x30;
+            cpu.h = 0x40;
+            cpu.condition_flags = ConditionFlags::from_bits_truncate(flags);
+            i8080.memory[0x1000..0x1003].copy_from_slice(&[opcode as u8, 0x00, 0x60]);
+            let before = i8080.memory[0x0000..0xFFFF].to_vec();
+
+            let instruction = i8080.cpu.fetch_instruction(&i8080.memory);
+            assert_eq!(i8080.cpu.pc, 0x1000 + u16::from(info.length), "{}", info.mnemonic);
+            observed_states.push(i8080.cpu.execute_instruction(instruction, &mut i8080.memory));
+
+            let changed = i8080.cpu.condition_flags.bits() ^ flags;
+            assert_eq!(changed & !info.flags.bits(), 0, "{}", info.mnemonic);
+            for (address, (&old, &new)) in
+                before.iter().zip(&i8080.memory[0x0000..0xFFFF]).enumerate()
+            {
+                if old == new {
+                    continue;
+                }
+                let access = match address {
+                    0x4000 => MemoryAccess::WRITE_M,
+                    0x2000 | 0x3000 => MemoryAccess::WRITE_INDIRECT,
+                    0x6000 | 0x6001 => MemoryAccess::WRITE_DIRECT,
+                    0x4FFE..=0x5001 => MemoryAccess::WRITE_STACK,
+                    _ => MemoryAccess::empty(),
+                };
+                assert!(!access.is_empty(), "{} writes to {:#06X}", info.mnemonic, address);
+                assert!(info.memory_access.contains(access), "{}", info.mnemonic);
+            }
+        }
+        observed_states.sort_unstable();
+        observed_states.dedup();
+        let expected: Vec<u32> = if info.states == info.states_taken {
+            vec![info.states.into()]
+        } else {
+            vec![info.states.into(), info.states_taken.into()]
+        };
+        assert_eq!(observed_states, expected, "{}", info.mnemonic);
+    }
+}
+
+fn intel8085() -> Intel8080 {
+    Intel8080 { cpu: Cpu::new(Variant::Intel8085), memory: Memory::new() }
+}
+
+// Undocumented instructions of the 8080
+#[test]
+fn undocumented_8080() {
+    let mut i8080 = Intel8080::default();
+
+    i8080.memory[0x0000] = 0x08; // NOP
+    i8080.memory[0x0001] = 0xCB; // JMP 0x1234
+    i8080.memory[0x0002] = 0x34;
+    i8080.memory[0x0003] = 0x12;
+    assert_eq!(i8080.fetch_execute_instruction().unwrap(), ([0x08, 0, 0], 4));
+    assert_eq!(i8080.fetch_execute_instruction().unwrap(), ([0xCB, 0x34, 0x12], 10));
+    assert_eq!(i8080.cpu.pc, 0x1234);
+}
+
+// Instruction timings of the 8085
+#[test]
+fn timings_8085() {
+    let mut i8080 = intel8085();
+    let Intel8080 { cpu, memory } = &mut i8080;
+
+    assert_eq!(cpu.execute_instruction([0x41, 0, 0], memory), 4); // MOV B,C
+    assert_eq!(cpu.execute_instruction([0x46, 0, 0], memory), 7); // MOV B,M
+    assert_eq!(cpu.execute_instruction([0x04, 0, 0], memory), 4); // INR B
+    assert_eq!(cpu.execute_instruction([0x34, 0, 0], memory), 10); // INR M
+    assert_eq!(cpu.execute_instruction([0x03, 0, 0], memory), 6); // INX B
+    assert_eq!(cpu.execute_instruction([0xC5, 0, 0], memory), 12); // PUSH B
+    assert_eq!(cpu.execute_instruction([0xCD, 0, 0], memory), 18); // CALL 0
+    assert_eq!(cpu.execute_instruction([0xE3, 0, 0], memory), 16); // XTHL
+
+    cpu.condition_flags.insert(ConditionFlags::ZERO);
+    assert_eq!(cpu.execute_instruction([0xC2, 0, 0], memory), 7); // JNZ 0
+    assert_eq!(cpu.execute_instruction([0xCA, 0, 0], memory), 10); // JZ 0
+    assert_eq!(cpu.execute_instruction([0xC4, 0, 0], memory), 9); // CNZ 0
+    assert_eq!(cpu.execute_instruction([0xC0, 0, 0], memory), 6); // RNZ
+    assert_eq!(cpu.execute_instruction([0xC8, 0, 0], memory), 12); // RZ
+    assert_eq!(cpu.execute_instruction([0x76, 0, 0], memory), 5); // HLT
+}
+
+// ANA r (And register with A) on the 8085
+#[test]
+fn ana_r_8085() {
+    let mut i8080 = intel8085();
+
+    // Intel 8080/8085 Assembly Language Programming Manual, p. 1-12.
+    i8080.cpu.a = 0x01;
+    i8080.cpu.b = 0x01;
+    i8080.cpu.execute_instruction([0xA0, 0, 0], &mut i8080.memory); // ANA B
+    assert!(i8080.cpu.condition_flags.contains(ConditionFlags::AUX_CARRY));
+}
+
+// The V and K flags of the 8085
+#[test]
+fn overflow_underflow_8085() {
+    let mut i8080 = intel8085();
+
+    i8080.cpu.a = 0x7F;
+    i8080.cpu.execute_instruction([0xC6, 0x01, 0], &mut i8080.memory); // ADI 1
+    assert!(i8080.cpu.condition_flags.contains(ConditionFlags::OVERFLOW));
+    i8080.cpu.execute_instruction([0xD6, 0x01, 0], &mut i8080.memory); // SUI 1
+    assert!(i8080.cpu.condition_flags.contains(ConditionFlags::OVERFLOW));
+    i8080.cpu.execute_instruction([0xD6, 0x01, 0], &mut i8080.memory); // SUI 1
+    assert!(!i8080.cpu.condition_flags.contains(ConditionFlags::OVERFLOW));
+
+    i8080.cpu.b = 0x00;
+    i8080.cpu.c = 0x01;
+    i8080.cpu.execute_instruction([0x0B, 0, 0], &mut i8080.memory); // DCX B
+    assert!(!i8080.cpu.condition_flags.contains(ConditionFlags::UNDERFLOW));
+    i8080.cpu.execute_instruction([0xFD, 0x34, 0x12], &mut i8080.memory); // JK 0x1234
+    assert_ne!(i8080.cpu.pc, 0x1234);
+    i8080.cpu.execute_instruction([0x0B, 0, 0], &mut i8080.memory); // DCX B
+    assert!(i8080.cpu.condition_flags.contains(ConditionFlags::UNDERFLOW));
+    i8080.cpu.execute_instruction([0xFD, 0x34, 0x12], &mut i8080.memory); // JK 0x1234
+    assert_eq!(i8080.cpu.pc, 0x1234);
+
+    // PUSH PSW and POP PSW keep the flags.
+    i8080.cpu.sp = 0x1000;
+    i8080.cpu.execute_instruction([0xF5, 0, 0], &mut i8080.memory); // PUSH PSW
+    assert_eq!(i8080.memory[0x0FFE] & 0x22, 0x20);
+    i8080.memory[0x0FFE] = 0x00;
+    i8080.cpu.execute_instruction([0xF1, 0, 0], &mut i8080.memory); // POP PSW
+    assert!(i8080.cpu.condition_flags.is_empty());
+}
+
+// Undocumented instructions of the 8085
+#[test]
+fn undocumented_8085() {
+    let mut i8080 = intel8085();
+
+    i8080.cpu.h = 0x12;
+    i8080.cpu.l = 0x34;
+    i8080.cpu.b = 0x02;
+    i8080.cpu.c = 0x35;
+    let cycle = i8080.cpu.execute_instruction([0x08, 0, 0], &mut i8080.memory); // DSUB
+    assert_eq!((i8080.cpu.h, i8080.cpu.l, cycle), (0x0F, 0xFF, 10));
+    assert!(!i8080.cpu.condition_flags.contains(ConditionFlags::CARRY));
+
+    i8080.cpu.h = 0x80;
+    i8080.cpu.l = 0x03;
+    let cycle = i8080.cpu.execute_instruction([0x10, 0, 0], &mut i8080.memory); // ARHL
+    assert_eq!((i8080.cpu.h, i8080.cpu.l, cycle), (0xC0, 0x01, 7));
+    assert!(i8080.cpu.condition_flags.contains(ConditionFlags::CARRY));
+
+    i8080.cpu.d = 0x80;
+    i8080.cpu.e = 0x01;
+    i8080.cpu.execute_instruction([0x18, 0, 0], &mut i8080.memory); // RDEL
+    assert_eq!((i8080.cpu.d, i8080.cpu.e), (0x00, 0x03));
+    assert!(i8080.cpu.condition_flags.contains(ConditionFlags::CARRY));
+
+    i8080.cpu.execute_instruction([0x28, 0x10, 0], &mut i8080.memory); // LDHI 0x10
+    assert_eq!((i8080.cpu.d, i8080.cpu.e), (0xC0, 0x11));
+    i8080.cpu.sp = 0x2000;
+    i8080.cpu.execute_instruction([0x38, 0x02, 0], &mut i8080.memory); // LDSI 0x02
+    assert_eq!((i8080.cpu.d, i8080.cpu.e), (0x20, 0x02));
+
+    i8080.cpu.execute_instruction([0xD9, 0, 0], &mut i8080.memory); // SHLX
+    assert_eq!((i8080.memory[0x2002], i8080.memory[0x2003]), (0x01, 0xC0));
+    i8080.memory[0x2002] = 0xCD;
+    i8080.memory[0x2003] = 0xAB;
+    i8080.cpu.execute_instruction([0xED, 0, 0], &mut i8080.memory); // LHLX
+    assert_eq!((i8080.cpu.h, i8080.cpu.l), (0xAB, 0xCD));
+
+    i8080.cpu.condition_flags.remove(ConditionFlags::OVERFLOW);
+    let cycle = i8080.cpu.execute_instruction([0xCB, 0, 0], &mut i8080.memory); // RSTV
+    assert_eq!(cycle, 6);
+    i8080.cpu.condition_flags.insert(ConditionFlags::OVERFLOW);
+    let cycle = i8080.cpu.execute_instruction([0xCB, 0, 0], &mut i8080.memory); // RSTV
+    assert_eq!((i8080.cpu.pc, cycle), (0x0040, 12));
+}
+
+// The V flag of the 8085 shares bit 1 with the bit that is always 1 on the 8080.
+#[test]
+fn rstv_after_reset_8085() {
+    let mut i8080 = intel8085();
+
+    assert!(!i8080.cpu.condition_flags.contains(ConditionFlags::OVERFLOW));
+    i8080.cpu.pc = 0x0100;
+    i8080.memory[0x0100] = 0xCB; // RSTV
+    assert_eq!(i8080.fetch_execute_instruction().unwrap(), ([0xCB, 0, 0], 6));
+    assert_eq!(i8080.cpu.pc, 0x0101);
+}
+
+// RIM (Read interrupt masks) and SIM (Set interrupt masks)
+#[test]
+fn rim_sim() {
+    let mut i8080 = intel8085();
+
+    i8080.cpu.set_sid(true);
+    i8080.cpu.set_interrupt_pin(InterruptPin::Rst6_5, true);
+    i8080.cpu.set_interrupt_pin(InterruptPin::Rst7_5, true);
+    i8080.cpu.execute_instruction([0x20, 0, 0], &mut i8080.memory); // RIM
+    assert_eq!(i8080.cpu.a, 0x80 | 0x40 | 0x20 | 0x07);
+
+    i8080.cpu.a = 0x40 | 0x80 | 0x10 | 0x08 | 0x05;
+    i8080.cpu.execute_instruction([0x30, 0, 0], &mut i8080.memory); // SIM
+    assert!(i8080.cpu.sod());
+    i8080.cpu.execute_instruction([0x20, 0, 0], &mut i8080.memory); // RIM
+    assert_eq!(i8080.cpu.a, 0x80 | 0x20 | 0x05);
+}
+
+// TRAP and RST 5.5, 6.5, and 7.5
+#[test]
+fn interrupt_pins() {
+    let mut i8080 = intel8085();
+
+    i8080.cpu.sp = 0x1000;
+    i8080.memory[0x0000] = 0x3E; // MVI A,0x08
+    i8080.memory[0x0001] = 0x08;
+    i8080.memory[0x0002] = 0x30; // SIM
+    i8080.memory[0x0003] = 0xFB; // EI
+    i8080.memory[0x0004] = 0x76; // HLT
+    i8080.memory[0x0024] = 0x20; // RIM
+    i8080.memory[0x0034] = 0x00; // NOP
+    i8080.memory[0x003C] = 0x76; // HLT
+    for _ in 0..4 {
+        i8080.fetch_execute_instruction().unwrap();
+    }
+    assert!(matches!(i8080.fetch_execute_instruction(), Err(Error::Halted)));
+
+    // RST 7.5 is latched, and it takes priority over RST 5.5.
+    i8080.cpu.set_interrupt_pin(InterruptPin::Rst5_5, true);
+    i8080.cpu.set_interrupt_pin(InterruptPin::Rst7_5, true);
+    i8080.cpu.set_interrupt_pin(InterruptPin::Rst7_5, false);
+    assert_eq!(i8080.fetch_execute_instruction().unwrap(), ([0x76, 0, 0], 12 + 5));
+    assert_eq!(i8080.cpu.pc, 0x003D);
+    assert!(!i8080.cpu.is_interrupt_enabled());
+
+    // TRAP is acknowledged with the interrupt system disabled, and RIM reads its prior state.
+    i8080.cpu.set_interrupt_pin(InterruptPin::Trap, true);
+    assert_eq!(i8080.fetch_execute_instruction().unwrap(), ([0x20, 0, 0], 12 + 4));
+    assert_eq!(i8080.cpu.a & 0x08, 0x00);
+    assert_eq!(i8080.cpu.pc, 0x0025);
+}
+
+#[test]
+fn run() {
+    let mut i8080 = Intel8080::default();
+    i8080.memory[0x0000] = 0x06; // MVI B,0x03
+    i8080.memory[0x0001] = 0x03;
+    i8080.memory[0x0002] = 0x05; // DCR B
+    i8080.memory[0x0003] = 0xC2; // JNZ 0x0002
+    i8080.memory[0x0004] = 0x02;
+    i8080.memory[0x0005] = 0x00;
+    i8080.memory[0x0006] = 0xFB; // EI
+    i8080.memory[0x0007] = 0x76; // HLT
+
+    // At least `states` states.
+    assert_eq!(i8080.run(10, |_| false), 7 + 5);
+    assert_eq!(i8080.cpu.pc, 0x0003);
+
+    // The breakpoint is checked after each instruction.
+    assert_eq!(i8080.run(u64::MAX, |pc| pc == 0x0002 || pc == 0x0006), 10);
+    assert_eq!(i8080.cpu.pc, 0x0002);
+    assert_eq!(i8080.run(u64::MAX, |pc| pc == 0x0006), 5 + 10 + 5 + 10);
+    assert_eq!(i8080.cpu.b, 0x00);
+
+    // The CPU halts with interrupts enabled.
+    assert_eq!(i8080.run(u64::MAX, |_| false), 4 + 7);
+    assert!(i8080.cpu.is_halted());
+    assert!(i8080.cpu.is_interrupt_enabled());
+    assert_eq!(i8080.run(u64::MAX, |_| false), 0);
+}
+
+// The parity, zero, and sign flags that are deferred
+#[test]
+fn deferred_flags() {
+    let mut i8080 = Intel8080::default();
+    i8080.cpu.sp = 0x1000;
+    i8080.memory[0x0000] = 0xAF; // XRA A
+    i8080.memory[0x0001] = 0xF5; // PUSH PSW
+    i8080.memory[0x0002] = 0x3C; // INR A
+    i8080.memory[0x0003] = 0xF1; // POP PSW
+    i8080.memory[0x0004] = 0xCA; // JZ 0x0000
+    i8080.memory[0x0005] = 0x00;
+    i8080.memory[0x0006] = 0x00;
+
+    // They are set when control returns.
+    i8080.run(u64::MAX, |pc| pc == 0x0001);
+    let parity_zero = ConditionFlags::PARITY | ConditionFlags::ZERO;
+    assert_eq!(i8080.cpu.condition_flags, parity_zero | ConditionFlags::ALWAYS_ONE);
+
+    // They are pushed by PUSH PSW, and POP PSW overrides them.
+    i8080.run(u64::MAX, |pc| pc == 0x0004);
+    assert_eq!(i8080.memory[0x0FFE], (parity_zero | ConditionFlags::ALWAYS_ONE).bits());
+    assert_eq!(i8080.cpu.a, 0x00);
+    assert_eq!(i8080.cpu.condition_flags, parity_zero | ConditionFlags::ALWAYS_ONE);
+
+    // They are read by conditional instructions after being changed by the caller.
+    i8080.cpu.condition_flags.remove(ConditionFlags::ZERO);
+    i8080.fetch_execute_instruction().unwrap();
+    assert_eq!(i8080.cpu.pc, 0x0007);
+}
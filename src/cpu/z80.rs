@@ -0,0 +1,1085 @@
+// The Zilog Z80, which runs 8080 programs and adds the CB, DD, ED, and FD prefixed instruction
+// groups, the index registers, the alternate registers, and interrupt modes 0, 1, and 2.
+//
+// Opcodes are decoded by their bit fields as described in "Decoding Z80 Opcodes" (C. Dinu). The
+// undocumented flag bits 3 and 5 are not emulated.
+
+use super::*;
+
+/// The registers of the Z80 that the 8080 does not have.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Z80Registers {
+    /// Index register IX.
+    pub ix: u16,
+    /// Index register IY.
+    pub iy: u16,
+    /// Interrupt page address register.
+    pub i: u8,
+    /// Memory refresh register, whose lower seven bits are incremented on each opcode fetch.
+    pub r: u8,
+    /// The alternate register pair AF'.
+    pub af_alternate: u16,
+    /// The alternate register pair BC'.
+    pub bc_alternate: u16,
+    /// The alternate register pair DE'.
+    pub de_alternate: u16,
+    /// The alternate register pair HL'.
+    pub hl_alternate: u16,
+    /// The interrupt mode set by IM.
+    pub interrupt_mode: InterruptMode,
+    // IFF2, which keeps the state of the interrupt system during a non-maskable interrupt.
+    iff2: bool,
+    // The instruction that an interrupting device puts on the data bus in mode 0, and the number
+    // of its bytes fetched so far.
+    bus: Option<(Instruction, usize)>,
+    // The first bytes of the current instruction, and the number of bytes fetched.
+    instruction: Instruction,
+    fetched: usize,
+}
+
+/// The interrupt modes of the Z80.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InterruptMode {
+    /// The interrupting device puts an instruction, usually RST, on the data bus as on the 8080.
+    #[default]
+    Mode0,
+    /// The CPU restarts at 0x0038.
+    Mode1,
+    /// The CPU calls the address stored in the vector table at I × 256 plus the byte that the
+    /// interrupting device puts on the data bus.
+    Mode2,
+}
+
+// The register pair that HL stands for after a DD or FD prefix.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Index {
+    Hl,
+    Ix,
+    Iy,
+}
+
+impl Cpu {
+    /// Escapes from the halt state, if necessary, and restarts at 0x0066 with further maskable
+    /// interrupts disabled, returning the number of states taken. RETN returns from the
+    /// interrupt and restores the interrupt system.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the CPU is not a Z80, which has the NMI input.
+    pub fn non_maskable_interrupt(&mut self, memory: &mut Memory) -> u32 {
+        assert_eq!(self.variant, Variant::Z80, "only the Z80 has the NMI input");
+        self.is_halted = false;
+        self.interruptable = Interruptable::Disabled;
+        self.increment_r();
+        self.call([0xCD, 0x66, 0x00], memory);
+        11
+    }
+
+    // Acknowledges a maskable interrupt, for which the interrupting device puts `instruction` on
+    // the data bus.
+    pub(super) fn acknowledge_z80_interrupt<M: Bus + ?Sized, I: Io + ?Sized>(
+        &mut self,
+        instruction: Instruction,
+        memory: &mut M,
+        io: &mut I,
+    ) -> u32 {
+        self.z80.iff2 = false;
+        match self.z80.interrupt_mode {
+            // The instruction is fetched in the acknowledge cycle, which takes 2 more states.
+            InterruptMode::Mode0 => 2 + self.execute_z80_bus_instruction(instruction, memory, io),
+            InterruptMode::Mode1 => {
+                self.increment_r();
+                self.call([0xCD, 0x38, 0x00], memory);
+                13
+            }
+            InterruptMode::Mode2 => {
+                self.increment_r();
+                let vector = u16::from_le_bytes([instruction[0], self.z80.i]);
+                let address = [memory[vector], memory[vector.wrapping_add(1)]];
+                self.call([0xCD, address[0], address[1]], memory);
+                19
+            }
+        }
+    }
+
+    // Executes `instruction` as if an interrupting device put it on the data bus.
+    pub(super) fn execute_z80_bus_instruction<M: Bus + ?Sized, I: Io + ?Sized>(
+        &mut self,
+        instruction: Instruction,
+        memory: &mut M,
+        io: &mut I,
+    ) -> u32 {
+        self.z80.bus = Some((instruction, 0));
+        let (_, states) = self.execute_z80_instruction(memory, io);
+        self.z80.bus = None;
+        states
+    }
+
+    // Fetches and executes an instruction, returning its first three bytes with the number of
+    // states taken.
+    pub(super) fn execute_z80_instruction<M: Bus + ?Sized, I: Io + ?Sized>(
+        &mut self,
+        memory: &mut M,
+        io: &mut I,
+    ) -> (Instruction, u32) {
+        self.z80.instruction = [0; 3];
+        self.z80.fetched = 0;
+        let states = match self.fetch_opcode(memory) {
+            0xCB => self.execute_z80_bit_instruction(memory),
+            0xDD => self.execute_z80_index_instruction(Index::Ix, memory, io),
+            0xED => self.execute_z80_extended_instruction(memory, io),
+            0xFD => self.execute_z80_index_instruction(Index::Iy, memory, io),
+            opcode => self.execute_z80_main_instruction(opcode, Index::Hl, memory, io),
+        };
+        (self.z80.instruction, states)
+    }
+
+    // Executes an instruction with a DD or FD prefix.
+    fn execute_z80_index_instruction<M: Bus + ?Sized, I: Io + ?Sized>(
+        &mut self,
+        index: Index,
+        memory: &mut M,
+        io: &mut I,
+    ) -> u32 {
+        match self.fetch_opcode(memory) {
+            0xCB => {
+                let address = self.index_address(index, memory);
+                let opcode = self.fetch(memory);
+                self.execute_z80_indexed_bit_instruction(opcode, address, memory)
+            }
+            // Another prefix cancels this one, which then takes the time of a NOP.
+            0xDD | 0xED | 0xFD => {
+                self.unfetch_opcode();
+                4
+            }
+            opcode => 4 + self.execute_z80_main_instruction(opcode, index, memory, io),
+        }
+    }
+
+    #[allow(clippy::cognitive_complexity)]
+    fn execute_z80_main_instruction<M: Bus + ?Sized, I: Io + ?Sized>(
+        &mut self,
+        opcode: u8,
+        index: Index,
+        memory: &mut M,
+        io: &mut I,
+    ) -> u32 {
+        let (x, y, z) = (opcode >> 6, opcode >> 3 & 0x07, opcode & 0x07);
+        let (p, q) = (y >> 1, y & 0x01);
+        // The extra states that an indexed memory operand takes to compute its address.
+        let displacement_states = if index == Index::Hl { 0 } else { 8 };
+        match (x, z) {
+            (0, 0) => match y {
+                // NOP
+                0 => 4,
+                // EX AF,AF'
+                1 => {
+                    let af = self.af();
+                    self.set_af(self.z80.af_alternate);
+                    self.z80.af_alternate = af;
+                    4
+                }
+                // DJNZ d
+                2 => {
+                    let displacement = self.fetch(memory);
+                    self.b = self.b.wrapping_sub(1);
+                    if self.b != 0 {
+                        self.jump_relative(displacement);
+                        13
+                    } else {
+                        8
+                    }
+                }
+                // JR d
+                3 => {
+                    let displacement = self.fetch(memory);
+                    self.jump_relative(displacement);
+                    12
+                }
+                // JR cc,d
+                _ => {
+                    let displacement = self.fetch(memory);
+                    if self.z80_condition(y - 4) {
+                        self.jump_relative(displacement);
+                        12
+                    } else {
+                        7
+                    }
+                }
+            },
+            // LD rp,nn
+            (0, 1) if q == 0 => {
+                let word = self.fetch_word(memory);
+                self.set_register_pair(p, index, word);
+                10
+            }
+            // ADD HL,rp
+            (0, 1) => {
+                let x = self.register_pair(2, index);
+                let y = self.register_pair(p, index);
+                let result = x.wrapping_add(y);
+                self.condition_flags.set(ConditionFlags::AUX_CARRY, (x ^ y ^ result) & 0x1000 != 0);
+                self.condition_flags
+                    .set(ConditionFlags::CARRY, u32::from(x) + u32::from(y) > 0xFFFF);
+                self.condition_flags.remove(ConditionFlags::SUBTRACT);
+                self.set_register_pair(2, index, result);
+                11
+            }
+            (0, 2) => match (p, q) {
+                // LD (BC),A
+                (0, 0) => {
+                    memory[u16::from_le_bytes([self.c, self.b])] = self.a;
+                    7
+                }
+                // LD (DE),A
+                (1, 0) => {
+                    memory[u16::from_le_bytes([self.e, self.d])] = self.a;
+                    7
+                }
+                // LD (nn),HL
+                (2, 0) => {
+                    let address = self.fetch_word(memory);
+                    self.store_word(address, self.register_pair(2, index), memory);
+                    16
+                }
+                // LD (nn),A
+                (3, 0) => {
+                    let address = self.fetch_word(memory);
+                    memory[address] = self.a;
+                    13
+                }
+                // LD A,(BC)
+                (0, _) => {
+                    self.a = memory[u16::from_le_bytes([self.c, self.b])];
+                    7
+                }
+                // LD A,(DE)
+                (1, _) => {
+                    self.a = memory[u16::from_le_bytes([self.e, self.d])];
+                    7
+                }
+                // LD HL,(nn)
+                (2, _) => {
+                    let address = self.fetch_word(memory);
+                    let word = Self::load_word(address, memory);
+                    self.set_register_pair(2, index, word);
+                    16
+                }
+                // LD A,(nn)
+                _ => {
+                    let address = self.fetch_word(memory);
+                    self.a = memory[address];
+                    13
+                }
+            },
+            // INC rp and DEC rp
+            (0, 3) => {
+                let pair = self.register_pair(p, index);
+                let pair = if q == 0 { pair.wrapping_add(1) } else { pair.wrapping_sub(1) };
+                self.set_register_pair(p, index, pair);
+                6
+            }
+            // INC r and DEC r
+            (0, 4) | (0, 5) => {
+                let address = if y == 6 { self.index_address(index, memory) } else { 0 };
+                let value = self.register(y, index, address, memory);
+                let result =
+                    if z == 4 { self.z80_increment(value) } else { self.z80_decrement(value) };
+                self.set_register(y, index, address, result, memory);
+                if y == 6 {
+                    11 + displacement_states
+                } else {
+                    4
+                }
+            }
+            // LD r,n
+            (0, 6) => {
+                let address = if y == 6 { self.index_address(index, memory) } else { 0 };
+                let value = self.fetch(memory);
+                self.set_register(y, index, address, value, memory);
+                if y == 6 {
+                    10 + displacement_states / 8 * 5
+                } else {
+                    7
+                }
+            }
+            (0, _) => {
+                match y {
+                    // RLCA
+                    0 => {
+                        self.condition_flags.set(ConditionFlags::CARRY, self.a & 0x80 != 0);
+                        self.a = self.a.rotate_left(1);
+                    }
+                    // RRCA
+                    1 => {
+                        self.condition_flags.set(ConditionFlags::CARRY, self.a & 0x01 != 0);
+                        self.a = self.a.rotate_right(1);
+                    }
+                    // RLA
+                    2 => {
+                        let carry = self.condition_flags.contains(ConditionFlags::CARRY);
+                        self.condition_flags.set(ConditionFlags::CARRY, self.a & 0x80 != 0);
+                        self.a = self.a << 1 | u8::from(carry);
+                    }
+                    // RRA
+                    3 => {
+                        let carry = self.condition_flags.contains(ConditionFlags::CARRY);
+                        self.condition_flags.set(ConditionFlags::CARRY, self.a & 0x01 != 0);
+                        self.a = self.a >> 1 | u8::from(carry) << 7;
+                    }
+                    // DAA
+                    4 => {
+                        self.z80_decimal_adjust();
+                        return 4;
+                    }
+                    // CPL
+                    5 => {
+                        self.a = !self.a;
+                        self.condition_flags.insert(ConditionFlags::AUX_CARRY);
+                        self.condition_flags.insert(ConditionFlags::SUBTRACT);
+                        return 4;
+                    }
+                    // SCF
+                    6 => self.condition_flags.insert(ConditionFlags::CARRY),
+                    // CCF
+                    _ => {
+                        let carry = self.condition_flags.contains(ConditionFlags::CARRY);
+                        self.condition_flags.set(ConditionFlags::CARRY, !carry);
+                        self.condition_flags.remove(ConditionFlags::SUBTRACT);
+                        self.condition_flags.set(ConditionFlags::AUX_CARRY, carry);
+                        return 4;
+                    }
+                }
+                self.condition_flags.remove(ConditionFlags::AUX_CARRY);
+                self.condition_flags.remove(ConditionFlags::SUBTRACT);
+                4
+            }
+
+            // HALT
+            (1, 6) if y == 6 => {
+                self.is_halted = true;
+                4
+            }
+            // LD r,r'
+            (1, _) => {
+                if y == 6 || z == 6 {
+                    // The other operand is H or L even after a prefix.
+                    let address = self.index_address(index, memory);
+                    let value = self.register(z, Index::Hl, address, memory);
+                    self.set_register(y, Index::Hl, address, value, memory);
+                    7 + displacement_states
+                } else {
+                    let value = self.register(z, index, 0, memory);
+                    self.set_register(y, index, 0, value, memory);
+                    4
+                }
+            }
+
+            // ALU r
+            (2, _) => {
+                let address = if z == 6 { self.index_address(index, memory) } else { 0 };
+                let value = self.register(z, index, address, memory);
+                self.z80_alu(y, value);
+                if z == 6 {
+                    7 + displacement_states
+                } else {
+                    4
+                }
+            }
+
+            // RET cc
+            (3, 0) => {
+                if self.z80_condition(y) {
+                    self.ret(memory);
+                    11
+                } else {
+                    5
+                }
+            }
+            (3, 1) => match (q, p) {
+                // POP rp2
+                (0, _) => {
+                    let word = Self::load_word(self.sp, memory);
+                    self.sp = self.sp.wrapping_add(2);
+                    if p == 3 {
+                        self.set_af(word);
+                    } else {
+                        self.set_register_pair(p, index, word);
+                    }
+                    10
+                }
+                // RET
+                (_, 0) => {
+                    self.ret(memory);
+                    10
+                }
+                // EXX
+                (_, 1) => {
+                    let alternates =
+                        [self.z80.bc_alternate, self.z80.de_alternate, self.z80.hl_alternate];
+                    let pairs = [0, 1, 2].map(|pair| self.register_pair(pair, Index::Hl));
+                    for (pair, &alternate) in alternates.iter().enumerate() {
+                        self.set_register_pair(pair as u8, Index::Hl, alternate);
+                    }
+                    let [bc, de, hl] = pairs;
+                    self.z80.bc_alternate = bc;
+                    self.z80.de_alternate = de;
+                    self.z80.hl_alternate = hl;
+                    4
+                }
+                // JP (HL)
+                (_, 2) => {
+                    self.pc = self.register_pair(2, index);
+                    4
+                }
+                // LD SP,HL
+                _ => {
+                    self.sp = self.register_pair(2, index);
+                    6
+                }
+            },
+            // JP cc,nn
+            (3, 2) => {
+                let address = self.fetch_word(memory);
+                if self.z80_condition(y) {
+                    self.pc = address;
+                }
+                10
+            }
+            (3, 3) => match y {
+                // JP nn
+                0 => {
+                    self.pc = self.fetch_word(memory);
+                    10
+                }
+                // The CB prefix, which is handled before
+                1 => self.execute_z80_bit_instruction(memory),
+                // OUT (n),A
+                2 => {
+                    let port = self.fetch(memory);
+                    io.output(port, self.a);
+                    11
+                }
+                // IN A,(n)
+                3 => {
+                    let port = self.fetch(memory);
+                    self.a = io.input(port);
+                    11
+                }
+                // EX (SP),HL
+                4 => {
+                    let word = Self::load_word(self.sp, memory);
+                    self.store_word(self.sp, self.register_pair(2, index), memory);
+                    self.set_register_pair(2, index, word);
+                    19
+                }
+                // EX DE,HL
+                5 => {
+                    mem::swap(&mut self.d, &mut self.h);
+                    mem::swap(&mut self.e, &mut self.l);
+                    4
+                }
+                // DI
+                6 => {
+                    self.interruptable = Interruptable::Disabled;
+                    self.z80.iff2 = false;
+                    4
+                }
+                // EI
+                _ => {
+                    if let Interruptable::Disabled = self.interruptable {
+                        self.interruptable = Interruptable::Enabling;
+                    }
+                    self.z80.iff2 = true;
+                    4
+                }
+            },
+            // CALL cc,nn
+            (3, 4) => {
+                let address = self.fetch_word(memory);
+                if self.z80_condition(y) {
+                    let [low, high] = address.to_le_bytes();
+                    self.call([0xCD, low, high], memory);
+                    17
+                } else {
+                    10
+                }
+            }
+            // PUSH rp2
+            (3, 5) if q == 0 => {
+                let word = if p == 3 { self.af() } else { self.register_pair(p, index) };
+                self.sp = self.sp.wrapping_sub(2);
+                self.store_word(self.sp, word, memory);
+                11
+            }
+            // CALL nn, and the prefixes, which are handled before
+            (3, 5) => match p {
+                0 => {
+                    let [low, high] = self.fetch_word(memory).to_le_bytes();
+                    self.call([0xCD, low, high], memory);
+                    17
+                }
+                1 => self.execute_z80_index_instruction(Index::Ix, memory, io),
+                2 => self.execute_z80_extended_instruction(memory, io),
+                _ => self.execute_z80_index_instruction(Index::Iy, memory, io),
+            },
+            // ALU n
+            (3, 6) => {
+                let value = self.fetch(memory);
+                self.z80_alu(y, value);
+                7
+            }
+            // RST
+            _ => {
+                self.restart(opcode, memory);
+                11
+            }
+        }
+    }
+
+    // Executes an instruction with a CB prefix.
+    fn execute_z80_bit_instruction<M: Bus + ?Sized>(&mut self, memory: &mut M) -> u32 {
+        let opcode = self.fetch_opcode(memory);
+        let (x, y, z) = (opcode >> 6, opcode >> 3 & 0x07, opcode & 0x07);
+        let address = u16::from_le_bytes([self.l, self.h]);
+        let value = self.register(z, Index::Hl, address, memory);
+        match x {
+            // BIT b,r
+            1 => {
+                self.z80_test_bit(y, value);
+                if z == 6 {
+                    12
+                } else {
+                    8
+                }
+            }
+            _ => {
+                let result = match x {
+                    0 => self.z80_rotate(y, value),
+                    2 => value & !(1 << y),
+                    _ => value | 1 << y,
+                };
+                self.set_register(z, Index::Hl, address, result, memory);
+                if z == 6 {
+                    15
+                } else {
+                    8
+                }
+            }
+        }
+    }
+
+    // Executes an instruction with a DD CB or FD CB prefix, which operates on (IX+d) or (IY+d).
+    // Except for BIT, the result is also copied to register `z` unless it is (HL).
+    fn execute_z80_indexed_bit_instruction<M: Bus + ?Sized>(
+        &mut self,
+        opcode: u8,
+        address: u16,
+        memory: &mut M,
+    ) -> u32 {
+        let (x, y, z) = (opcode >> 6, opcode >> 3 & 0x07, opcode & 0x07);
+        let value = memory[address];
+        let result = match x {
+            1 => {
+                self.z80_test_bit(y, value);
+                return 20;
+            }
+            0 => self.z80_rotate(y, value),
+            2 => value & !(1 << y),
+            _ => value | 1 << y,
+        };
+        memory[address] = result;
+        if z != 6 {
+            self.set_register(z, Index::Hl, address, result, memory);
+        }
+        23
+    }
+
+    // Executes an instruction with an ED prefix.
+    #[allow(clippy::cognitive_complexity)]
+    fn execute_z80_extended_instruction<M: Bus + ?Sized, I: Io + ?Sized>(
+        &mut self,
+        memory: &mut M,
+        io: &mut I,
+    ) -> u32 {
+        let opcode = self.fetch_opcode(memory);
+        let (x, y, z) = (opcode >> 6, opcode >> 3 & 0x07, opcode & 0x07);
+        let (p, q) = (y >> 1, y & 0x01);
+        match (x, z) {
+            // IN r,(C), which only sets the flags for r = (HL)
+            (1, 0) => {
+                let value = io.input(self.c);
+                self.update_z80_logical_flags(value, false);
+                if y != 6 {
+                    self.set_register(y, Index::Hl, 0, value, memory);
+                }
+                12
+            }
+            // OUT (C),r, which outputs 0 for r = (HL)
+            (1, 1) => {
+                let value = if y == 6 { 0 } else { self.register(y, Index::Hl, 0, memory) };
+                io.output(self.c, value);
+                12
+            }
+            // SBC HL,rp and ADC HL,rp
+            (1, 2) => {
+                let hl = u16::from_le_bytes([self.l, self.h]);
+                let operand = self.register_pair(p, Index::Hl);
+                let carry = u32::from(self.condition_flags.contains(ConditionFlags::CARRY));
+                let (result, overflow, carry_out) = if q == 0 {
+                    let result = u32::from(hl).wrapping_sub(u32::from(operand)).wrapping_sub(carry);
+                    let overflow = (hl ^ operand) & (hl ^ result as u16) & 0x8000 != 0;
+                    (result, overflow, result > 0xFFFF)
+                } else {
+                    let result = u32::from(hl) + u32::from(operand) + carry;
+                    let overflow = !(hl ^ operand) & (hl ^ result as u16) & 0x8000 != 0;
+                    (result, overflow, result > 0xFFFF)
+                };
+                let result = result as u16;
+                let flags = &mut self.condition_flags;
+                flags.set(ConditionFlags::SIGN, result & 0x8000 != 0);
+                flags.set(ConditionFlags::ZERO, result == 0);
+                flags.set(ConditionFlags::AUX_CARRY, (hl ^ operand ^ result) & 0x1000 != 0);
+                flags.set(ConditionFlags::PARITY, overflow);
+                flags.set(ConditionFlags::SUBTRACT, q == 0);
+                flags.set(ConditionFlags::CARRY, carry_out);
+                let [l, h] = result.to_le_bytes();
+                self.h = h;
+                self.l = l;
+                15
+            }
+            // LD (nn),rp and LD rp,(nn)
+            (1, 3) => {
+                let address = self.fetch_word(memory);
+                if q == 0 {
+                    self.store_word(address, self.register_pair(p, Index::Hl), memory);
+                } else {
+                    let word = Self::load_word(address, memory);
+                    self.set_register_pair(p, Index::Hl, word);
+                }
+                20
+            }
+            // NEG
+            (1, 4) => {
+                let value = self.a;
+                self.a = 0;
+                self.z80_alu(2, value);
+                8
+            }
+            // RETN and RETI, which both copy IFF2 back to IFF1
+            (1, 5) => {
+                self.ret(memory);
+                self.interruptable =
+                    if self.z80.iff2 { Interruptable::Enabled } else { Interruptable::Disabled };
+                14
+            }
+            // IM 0, IM 1, and IM 2
+            (1, 6) => {
+                self.z80.interrupt_mode = match y & 0x03 {
+                    2 => InterruptMode::Mode1,
+                    3 => InterruptMode::Mode2,
+                    _ => InterruptMode::Mode0,
+                };
+                8
+            }
+            (1, 7) => match y {
+                // LD I,A
+                0 => {
+                    self.z80.i = self.a;
+                    9
+                }
+                // LD R,A
+                1 => {
+                    self.z80.r = self.a;
+                    9
+                }
+                // LD A,I and LD A,R
+                2 | 3 => {
+                    self.a = if y == 2 { self.z80.i } else { self.z80.r };
+                    let flags = &mut self.condition_flags;
+                    flags.set(ConditionFlags::SIGN, self.a & 0x80 != 0);
+                    flags.set(ConditionFlags::ZERO, self.a == 0);
+                    flags.remove(ConditionFlags::AUX_CARRY | ConditionFlags::SUBTRACT);
+                    flags.set(ConditionFlags::PARITY, self.z80.iff2);
+                    9
+                }
+                // RRD and RLD
+                4 | 5 => {
+                    let address = u16::from_le_bytes([self.l, self.h]);
+                    let value = memory[address];
+                    if y == 4 {
+                        memory[address] = self.a << 4 | value >> 4;
+                        self.a = self.a & 0xF0 | value & 0x0F;
+                    } else {
+                        memory[address] = value << 4 | self.a & 0x0F;
+                        self.a = self.a & 0xF0 | value >> 4;
+                    }
+                    self.update_z80_logical_flags(self.a, false);
+                    18
+                }
+                _ => 8,
+            },
+            // The block instructions
+            (2, 0..=3) if y >= 4 => self.execute_z80_block_instruction(y, z, memory, io),
+            // The other opcodes act as two NOPs.
+            _ => 8,
+        }
+    }
+
+    // Executes LDI, CPI, INI, OUTI, their decrementing forms, and their repeating forms, which
+    // execute again until they are done.
+    fn execute_z80_block_instruction<M: Bus + ?Sized, I: Io + ?Sized>(
+        &mut self,
+        y: u8,
+        z: u8,
+        memory: &mut M,
+        io: &mut I,
+    ) -> u32 {
+        let is_decrement = y & 0x01 != 0;
+        let is_repeat = y >= 6;
+        let step =
+            |pair: u16| if is_decrement { pair.wrapping_sub(1) } else { pair.wrapping_add(1) };
+        let hl = u16::from_le_bytes([self.l, self.h]);
+        let is_done = match z {
+            // LDI
+            0 => {
+                let de = u16::from_le_bytes([self.e, self.d]);
+                memory[de] = memory[hl];
+                self.set_register_pair(1, Index::Hl, step(de));
+                let bc = u16::from_le_bytes([self.c, self.b]).wrapping_sub(1);
+                self.set_register_pair(0, Index::Hl, bc);
+                self.condition_flags.remove(ConditionFlags::AUX_CARRY | ConditionFlags::SUBTRACT);
+                self.condition_flags.set(ConditionFlags::PARITY, bc != 0);
+                bc == 0
+            }
+            // CPI
+            1 => {
+                let value = memory[hl];
+                let result = self.a.wrapping_sub(value);
+                let bc = u16::from_le_bytes([self.c, self.b]).wrapping_sub(1);
+                self.set_register_pair(0, Index::Hl, bc);
+                let flags = &mut self.condition_flags;
+                flags.set(ConditionFlags::SIGN, result & 0x80 != 0);
+                flags.set(ConditionFlags::ZERO, result == 0);
+                flags.set(ConditionFlags::AUX_CARRY, (self.a ^ value ^ result) & 0x10 != 0);
+                flags.set(ConditionFlags::PARITY, bc != 0);
+                flags.insert(ConditionFlags::SUBTRACT);
+                bc == 0 || result == 0
+            }
+            // INI
+            2 => {
+                memory[hl] = io.input(self.c);
+                self.b = self.b.wrapping_sub(1);
+                self.condition_flags.set(ConditionFlags::ZERO, self.b == 0);
+                self.condition_flags.insert(ConditionFlags::SUBTRACT);
+                self.b == 0
+            }
+            // OUTI
+            _ => {
+                self.b = self.b.wrapping_sub(1);
+                io.output(self.c, memory[hl]);
+                self.condition_flags.set(ConditionFlags::ZERO, self.b == 0);
+                self.condition_flags.insert(ConditionFlags::SUBTRACT);
+                self.b == 0
+            }
+        };
+        self.set_register_pair(2, Index::Hl, step(hl));
+        if is_repeat && !is_done {
+            self.pc = self.pc.wrapping_sub(2);
+            21
+        } else {
+            16
+        }
+    }
+
+    fn fetch<M: Bus + ?Sized>(&mut self, memory: &M) -> u8 {
+        let byte = match &mut self.z80.bus {
+            Some((instruction, fetched)) => {
+                let byte = instruction.get(*fetched).copied().unwrap_or(0x00);
+                *fetched += 1;
+                byte
+            }
+            None => {
+                let byte = memory[self.pc];
+                self.pc = self.pc.wrapping_add(1);
+                byte
+            }
+        };
+        if let Some(slot) = self.z80.instruction.get_mut(self.z80.fetched) {
+            *slot = byte;
+        }
+        self.z80.fetched += 1;
+        byte
+    }
+
+    fn fetch_opcode<M: Bus + ?Sized>(&mut self, memory: &M) -> u8 {
+        self.increment_r();
+        self.fetch(memory)
+    }
+
+    // Puts back the opcode just fetched so that it starts the next instruction.
+    fn unfetch_opcode(&mut self) {
+        match &mut self.z80.bus {
+            Some((_, fetched)) => *fetched -= 1,
+            None => self.pc = self.pc.wrapping_sub(1),
+        }
+        self.z80.fetched -= 1;
+        self.z80.instruction[self.z80.fetched] = 0;
+        self.z80.r = self.z80.r & 0x80 | self.z80.r.wrapping_sub(1) & 0x7F;
+    }
+
+    fn fetch_word<M: Bus + ?Sized>(&mut self, memory: &M) -> u16 {
+        u16::from_le_bytes([self.fetch(memory), self.fetch(memory)])
+    }
+
+    fn increment_r(&mut self) {
+        self.z80.r = self.z80.r & 0x80 | self.z80.r.wrapping_add(1) & 0x7F;
+    }
+
+    fn load_word<M: Bus + ?Sized>(address: u16, memory: &M) -> u16 {
+        u16::from_le_bytes([memory[address], memory[address.wrapping_add(1)]])
+    }
+
+    fn store_word<M: Bus + ?Sized>(&self, address: u16, word: u16, memory: &mut M) {
+        let [low, high] = word.to_le_bytes();
+        memory[address] = low;
+        memory[address.wrapping_add(1)] = high;
+    }
+
+    fn jump_relative(&mut self, displacement: u8) {
+        self.pc = self.pc.wrapping_add(displacement as i8 as u16);
+    }
+
+    // Fetches the displacement of (IX+d) or (IY+d), returning the address of the memory operand.
+    fn index_address<M: Bus + ?Sized>(&mut self, index: Index, memory: &M) -> u16 {
+        match index {
+            Index::Hl => u16::from_le_bytes([self.l, self.h]),
+            Index::Ix => self.z80.ix.wrapping_add(self.fetch(memory) as i8 as u16),
+            Index::Iy => self.z80.iy.wrapping_add(self.fetch(memory) as i8 as u16),
+        }
+    }
+
+    fn af(&self) -> u16 {
+        u16::from_le_bytes([self.condition_flags.bits(), self.a])
+    }
+
+    fn set_af(&mut self, af: u16) {
+        let [flags, a] = af.to_le_bytes();
+        self.a = a;
+        self.condition_flags = ConditionFlags::from_bits_truncate(flags);
+    }
+
+    // Returns BC, DE, HL (or the index register), or SP.
+    fn register_pair(&self, pair: u8, index: Index) -> u16 {
+        match (pair, index) {
+            (0, _) => u16::from_le_bytes([self.c, self.b]),
+            (1, _) => u16::from_le_bytes([self.e, self.d]),
+            (2, Index::Hl) => u16::from_le_bytes([self.l, self.h]),
+            (2, Index::Ix) => self.z80.ix,
+            (2, Index::Iy) => self.z80.iy,
+            _ => self.sp,
+        }
+    }
+
+    fn set_register_pair(&mut self, pair: u8, index: Index, word: u16) {
+        let [low, high] = word.to_le_bytes();
+        match (pair, index) {
+            (0, _) => {
+                self.b = high;
+                self.c = low;
+            }
+            (1, _) => {
+                self.d = high;
+                self.e = low;
+            }
+            (2, Index::Hl) => {
+                self.h = high;
+                self.l = low;
+            }
+            (2, Index::Ix) => self.z80.ix = word,
+            (2, Index::Iy) => self.z80.iy = word,
+            _ => self.sp = word,
+        }
+    }
+
+    // Returns B, C, D, E, H, L, the memory operand at `address`, or A. H and L stand for the
+    // halves of the index register after a prefix.
+    fn register<M: Bus + ?Sized>(
+        &self,
+        register: u8,
+        index: Index,
+        address: u16,
+        memory: &M,
+    ) -> u8 {
+        match register {
+            0 => self.b,
+            1 => self.c,
+            2 => self.d,
+            3 => self.e,
+            4 => self.register_pair(2, index).to_le_bytes()[1],
+            5 => self.register_pair(2, index).to_le_bytes()[0],
+            6 => memory[address],
+            _ => self.a,
+        }
+    }
+
+    fn set_register<M: Bus + ?Sized>(
+        &mut self,
+        register: u8,
+        index: Index,
+        address: u16,
+        value: u8,
+        memory: &mut M,
+    ) {
+        match register {
+            0 => self.b = value,
+            1 => self.c = value,
+            2 => self.d = value,
+            3 => self.e = value,
+            4 | 5 => {
+                let mut bytes = self.register_pair(2, index).to_le_bytes();
+                bytes[usize::from(5 - register)] = value;
+                self.set_register_pair(2, index, u16::from_le_bytes(bytes));
+            }
+            6 => memory[address] = value,
+            _ => self.a = value,
+        }
+    }
+
+    // Returns whether condition NZ, Z, NC, C, PO, PE, P, or M is true.
+    fn z80_condition(&self, condition: u8) -> bool {
+        let flags = self.condition_flags;
+        match condition {
+            0 => !flags.contains(ConditionFlags::ZERO),
+            1 => flags.contains(ConditionFlags::ZERO),
+            2 => !flags.contains(ConditionFlags::CARRY),
+            3 => flags.contains(ConditionFlags::CARRY),
+            4 => !flags.contains(ConditionFlags::PARITY),
+            5 => flags.contains(ConditionFlags::PARITY),
+            6 => !flags.contains(ConditionFlags::SIGN),
+            _ => flags.contains(ConditionFlags::SIGN),
+        }
+    }
+
+    // Executes ADD, ADC, SUB, SBC, AND, XOR, OR, or CP with A and `value`.
+    fn z80_alu(&mut self, operation: u8, value: u8) {
+        let carry = u8::from(self.condition_flags.contains(ConditionFlags::CARRY));
+        match operation {
+            0 | 1 | 2 | 3 | 7 => {
+                let is_subtract = operation >= 2;
+                let carry = if operation & 0x05 == 0x01 { carry } else { 0 };
+                let a = u16::from(self.a);
+                let operand = u16::from(value) + u16::from(carry);
+                let result = if is_subtract { a.wrapping_sub(operand) } else { a + operand };
+                let byte = result as u8;
+                let overflow = if is_subtract {
+                    (self.a ^ value) & (self.a ^ byte) & 0x80 != 0
+                } else {
+                    !(self.a ^ value) & (self.a ^ byte) & 0x80 != 0
+                };
+                let flags = &mut self.condition_flags;
+                flags.set(ConditionFlags::SIGN, byte & 0x80 != 0);
+                flags.set(ConditionFlags::ZERO, byte == 0);
+                flags.set(ConditionFlags::AUX_CARRY, (self.a ^ value ^ byte) & 0x10 != 0);
+                flags.set(ConditionFlags::PARITY, overflow);
+                flags.set(ConditionFlags::SUBTRACT, is_subtract);
+                flags.set(ConditionFlags::CARRY, result > 0xFF);
+                if operation != 7 {
+                    self.a = byte;
+                }
+            }
+            _ => {
+                self.a = match operation {
+                    4 => self.a & value,
+                    5 => self.a ^ value,
+                    _ => self.a | value,
+                };
+                self.update_z80_logical_flags(self.a, operation == 4);
+                self.condition_flags.remove(ConditionFlags::CARRY);
+            }
+        }
+    }
+
+    // Sets S, Z, and P/V (as parity) by `result`, sets H to `aux_carry`, and clears N, leaving C
+    // alone.
+    fn update_z80_logical_flags(&mut self, result: u8, aux_carry: bool) {
+        self.update_parity_zero_sign_flags(result);
+        self.condition_flags.set(ConditionFlags::AUX_CARRY, aux_carry);
+        self.condition_flags.remove(ConditionFlags::SUBTRACT);
+    }
+
+    fn z80_increment(&mut self, value: u8) -> u8 {
+        let result = value.wrapping_add(1);
+        let flags = &mut self.condition_flags;
+        flags.set(ConditionFlags::SIGN, result & 0x80 != 0);
+        flags.set(ConditionFlags::ZERO, result == 0);
+        flags.set(ConditionFlags::AUX_CARRY, result & 0x0F == 0);
+        flags.set(ConditionFlags::PARITY, result == 0x80);
+        flags.remove(ConditionFlags::SUBTRACT);
+        result
+    }
+
+    fn z80_decrement(&mut self, value: u8) -> u8 {
+        let result = value.wrapping_sub(1);
+        let flags = &mut self.condition_flags;
+        flags.set(ConditionFlags::SIGN, result & 0x80 != 0);
+        flags.set(ConditionFlags::ZERO, result == 0);
+        flags.set(ConditionFlags::AUX_CARRY, result & 0x0F == 0x0F);
+        flags.set(ConditionFlags::PARITY, result == 0x7F);
+        flags.insert(ConditionFlags::SUBTRACT);
+        result
+    }
+
+    // Executes RLC, RRC, RL, RR, SLA, SRA, SLL (undocumented), or SRL on `value`.
+    fn z80_rotate(&mut self, operation: u8, value: u8) -> u8 {
+        let carry = u8::from(self.condition_flags.contains(ConditionFlags::CARRY));
+        let (result, carry_out) = match operation {
+            0 => (value.rotate_left(1), value & 0x80),
+            1 => (value.rotate_right(1), value & 0x01),
+            2 => (value << 1 | carry, value & 0x80),
+            3 => (value >> 1 | carry << 7, value & 0x01),
+            4 => (value << 1, value & 0x80),
+            5 => (value >> 1 | value & 0x80, value & 0x01),
+            6 => (value << 1 | 0x01, value & 0x80),
+            _ => (value >> 1, value & 0x01),
+        };
+        self.update_z80_logical_flags(result, false);
+        self.condition_flags.set(ConditionFlags::CARRY, carry_out != 0);
+        result
+    }
+
+    fn z80_test_bit(&mut self, bit: u8, value: u8) {
+        let is_zero = value & 1 << bit == 0;
+        let flags = &mut self.condition_flags;
+        flags.set(ConditionFlags::ZERO, is_zero);
+        flags.set(ConditionFlags::PARITY, is_zero);
+        flags.set(ConditionFlags::SIGN, bit == 7 && !is_zero);
+        flags.insert(ConditionFlags::AUX_CARRY);
+        flags.remove(ConditionFlags::SUBTRACT);
+    }
+
+    fn z80_decimal_adjust(&mut self) {
+        let flags = self.condition_flags;
+        let mut correction = 0;
+        let mut carry = flags.contains(ConditionFlags::CARRY);
+        if flags.contains(ConditionFlags::AUX_CARRY) || self.a & 0x0F > 0x09 {
+            correction |= 0x06;
+        }
+        if carry || self.a > 0x99 {
+            correction |= 0x60;
+            carry = true;
+        }
+        let aux_carry = if flags.contains(ConditionFlags::SUBTRACT) {
+            let aux_carry = flags.contains(ConditionFlags::AUX_CARRY) && self.a & 0x0F < 0x06;
+            self.a = self.a.wrapping_sub(correction);
+            aux_carry
+        } else {
+            let aux_carry = self.a & 0x0F > 0x09;
+            self.a = self.a.wrapping_add(correction);
+            aux_carry
+        };
+        self.update_parity_zero_sign_flags(self.a);
+        self.condition_flags.set(ConditionFlags::AUX_CARRY, aux_carry);
+        self.condition_flags.set(ConditionFlags::CARRY, carry);
+    }
+}
@@ -1,42 +1,113 @@
+//! i8080 is an Intel 8080 emulation library.
+//!
+//! The CPU and the memory only need `core`. Disabling the default `std` feature removes the file
+//! loaders, the backtraces in [`Error`], and the modules that emulate devices and machines, so that
+//! the CPU can run on a microcontroller.
+//!
+//! [`Error`]: enum.Error.html
+
 #![doc(html_root_url = "https://dkim.github.io/i8080/1.0.0")]
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(rust_2018_idioms)]
 
+use core::fmt::{self, Display, Formatter};
+#[cfg(feature = "std")]
 use std::{
-    fmt::{self, Display, Formatter},
+    backtrace::Backtrace,
     io,
     path::{Path, PathBuf},
 };
 
-use backtrace::Backtrace;
-
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "std")]
+pub mod cassette;
+#[cfg(feature = "std")]
+pub mod console;
+#[cfg(feature = "std")]
+pub mod cpm;
 pub mod cpu;
-use cpu::{Cpu, Instruction};
+use cpu::{Cpu, Instruction, Io};
+#[cfg(feature = "std")]
+pub mod machine;
 pub mod memory;
 use memory::Memory;
+#[cfg(feature = "std")]
+pub mod paper_tape;
+#[cfg(feature = "std")]
+pub mod recompiler;
+#[cfg(feature = "std")]
+pub mod sound;
+#[cfg(feature = "std")]
+pub mod video;
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub mod wasm;
+#[cfg(feature = "std")]
+pub mod wav;
 
 /// An error that can occur in this crate.
+///
+/// The backtraces of [`Error::FileNotFound`] and [`Error::Io`] are captured only if the
+/// `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` environment variable enables them, and the errors that
+/// executing instructions returns carry none, so that handling them costs nothing.
+///
+/// [`Error::FileNotFound`]: enum.Error.html#variant.FileNotFound
+/// [`Error::Io`]: enum.Error.html#variant.Io
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// The specified file was not found.
+    #[cfg(feature = "std")]
     FileNotFound { path: PathBuf, source: io::Error, backtrace: Backtrace },
     /// An interrupt request arrived when the interrupt system was disabled.
     InterruptNotEnabled,
+    /// The size of the disk image does not match its format.
+    #[cfg(feature = "std")]
+    InvalidDiskImage { path: Option<PathBuf>, size: u64 },
+    /// The paper tape does not hold records in the format of the MITS checksum loader.
+    #[cfg(feature = "std")]
+    InvalidPaperTape,
     /// An I/O error.
+    #[cfg(feature = "std")]
     Io { source: io::Error, backtrace: Backtrace },
     /// The specified file was too large to load at the specified memory address.
+    #[cfg(feature = "std")]
     TooLargeFile { path: PathBuf, size: u64, start_address: u16 },
+    /// The specified bytes were too many to load at the specified memory address.
+    TooLargeImage { size: usize, start_address: u16 },
     /// An attempt to fetch and execute an instruction was made when the CPU was stopped.
     Halted,
+    /// There was no disk in the specified drive (0 = A, 1 = B, ..., 15 = P).
+    #[cfg(feature = "std")]
+    NoDisk { drive: u8 },
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
+            #[cfg(feature = "std")]
             Error::FileNotFound { path, source, .. } => {
                 write!(f, "{}: '{}'", source, path.display())
             }
             Error::InterruptNotEnabled => write!(f, "interrupt not enabled"),
+            #[cfg(feature = "std")]
+            Error::InvalidDiskImage { path: Some(path), size } => {
+                write!(
+                    f,
+                    "Disk image {} ({} bytes) does not match its format",
+                    path.display(),
+                    size
+                )
+            }
+            #[cfg(feature = "std")]
+            Error::InvalidDiskImage { path: None, size } => {
+                write!(f, "Disk image ({} bytes) does not match its format", size)
+            }
+            #[cfg(feature = "std")]
+            Error::InvalidPaperTape => write!(f, "Paper tape has no valid checksum records"),
+            #[cfg(feature = "std")]
             Error::Io { source, .. } => source.fmt(f),
+            #[cfg(feature = "std")]
             Error::TooLargeFile { path, size, start_address } => write!(
                 f,
                 "File {} ({} bytes) is too large to load at address {:#06X}",
@@ -44,29 +115,58 @@ impl Display for Error {
                 size,
                 start_address
             ),
+            Error::TooLargeImage { size, start_address } => write!(
+                f,
+                "Image ({} bytes) is too large to load at address {:#06X}",
+                size, start_address
+            ),
             Error::Halted => write!(f, "halted"),
+            #[cfg(feature = "std")]
+            Error::NoDisk { drive } => write!(f, "No disk in drive {}", char::from(b'A' + drive)),
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Error::FileNotFound { source, .. } | Error::Io { source, .. } => Some(source),
-            Error::InterruptNotEnabled | Error::TooLargeFile { .. } => None,
-            Error::Halted => None,
+            Error::InterruptNotEnabled
+            | Error::InvalidDiskImage { .. }
+            | Error::InvalidPaperTape
+            | Error::TooLargeFile { .. }
+            | Error::TooLargeImage { .. } => None,
+            Error::Halted | Error::NoDisk { .. } => None,
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl From<io::Error> for Error {
     fn from(e: io::Error) -> Self {
-        Error::Io { source: e, backtrace: Backtrace::new() }
+        Error::Io { source: e, backtrace: Backtrace::capture() }
     }
 }
 
-/// A specialized `std::result::Result` type for this crate.
-pub type Result<T> = std::result::Result<T, Error>;
+#[cfg(feature = "std")]
+impl Error {
+    // Converts an error that occurred while opening the file located at `path`.
+    pub(crate) fn from_open(err: io::Error, path: &Path) -> Self {
+        if let io::ErrorKind::NotFound = err.kind() {
+            Error::FileNotFound {
+                path: path.to_path_buf(),
+                source: err,
+                backtrace: Backtrace::capture(),
+            }
+        } else {
+            Error::Io { source: err, backtrace: Backtrace::capture() }
+        }
+    }
+}
+
+/// A specialized `core::result::Result` type for this crate.
+pub type Result<T> = core::result::Result<T, Error>;
 
 /// An Intel 8080 system.
 #[derive(Default)]
@@ -90,6 +190,7 @@ impl Intel8080 {
     /// [`Error::FileNotFound`]: enum.Error.html#variant.FileNotFound
     /// [`Error::Io`]: enum.Error.html#variant.Io
     /// [`Error::TooLargeFile`]: enum.Error.html#variant.TooLargeFile
+    #[cfg(feature = "std")]
     pub fn new<P: AsRef<Path>>(paths: &[P], start_address: u16) -> Result<Self> {
         let mut cpu = Cpu::default();
         cpu.pc = start_address;
@@ -109,6 +210,40 @@ impl Intel8080 {
         self.cpu.fetch_execute_instruction(&mut self.memory)
     }
 
+    /// Fetches and executes an instruction with the I/O devices `io` connected to the I/O ports,
+    /// returning the instruction with the number of states taken.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::Halted`] error if the CPU is in the halted state.
+    ///
+    /// [`Error::Halted`]: enum.Error.html#variant.Halted
+    pub fn fetch_execute_instruction_with_io<I: Io + ?Sized>(
+        &mut self,
+        io: &mut I,
+    ) -> Result<(Instruction, u32)> {
+        self.cpu.fetch_execute_instruction_with_io(&mut self.memory, io)
+    }
+
+    /// Fetches and executes instructions until the program counter reaches an address for which
+    /// `is_breakpoint` returns `true`, the CPU halts, or at least `states` states have elapsed,
+    /// returning the number of states taken.
+    pub fn run<F: FnMut(u16) -> bool>(&mut self, states: u64, is_breakpoint: F) -> u64 {
+        self.cpu.run(&mut self.memory, states, is_breakpoint)
+    }
+
+    /// Fetches and executes instructions with the I/O devices `io` connected to the I/O ports
+    /// until the program counter reaches an address for which `is_breakpoint` returns `true`, the
+    /// CPU halts, or at least `states` states have elapsed, returning the number of states taken.
+    pub fn run_with_io<I: Io + ?Sized, F: FnMut(u16) -> bool>(
+        &mut self,
+        io: &mut I,
+        states: u64,
+        is_breakpoint: F,
+    ) -> u64 {
+        self.cpu.run_with_io(&mut self.memory, io, states, is_breakpoint)
+    }
+
     /// Escapes from the halt state, if necessary, and executes `instruction` with further
     /// interrupts disabled.
     ///
@@ -121,4 +256,21 @@ impl Intel8080 {
     pub fn interrupt(&mut self, instruction: Instruction) -> Result<u32> {
         self.cpu.interrupt(instruction, &mut self.memory)
     }
+
+    /// Escapes from the halt state, if necessary, and executes `instruction` with further
+    /// interrupts disabled and the I/O devices `io` connected to the I/O ports.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::InterruptNotEnabled`] error if the interrupt system
+    /// is already disabled.
+    ///
+    /// [`Error::InterruptNotEnabled`]: enum.Error.html#variant.InterruptNotEnabled
+    pub fn interrupt_with_io<I: Io + ?Sized>(
+        &mut self,
+        instruction: Instruction,
+        io: &mut I,
+    ) -> Result<u32> {
+        self.cpu.interrupt_with_io(instruction, &mut self.memory, io)
+    }
 }
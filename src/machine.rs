@@ -0,0 +1,200 @@
+//! Emulated machines that are built around an Intel 8080 CPU.
+
+use core::ops::{Index, IndexMut};
+use std::io;
+
+use crate::{
+    console::Console,
+    cpu::{Cpu, Instruction, Io},
+    memory::Memory,
+    Error, Intel8080, Result,
+};
+
+pub mod altair;
+pub mod imsai;
+pub mod sol20;
+pub mod space_invaders;
+
+// The memory of a machine seen through `IndexMut`, in which writes to the addresses for which
+// `is_writable` returns `false`, such as ROM or unpopulated memory, go to a byte that is thrown
+// away.
+struct Protected<'a, F> {
+    memory: &'a mut Memory,
+    is_writable: F,
+    discarded: u8,
+}
+
+impl<F: Fn(u16) -> bool> Index<u16> for Protected<'_, F> {
+    type Output = u8;
+
+    fn index(&self, address: u16) -> &u8 {
+        &self.memory[address]
+    }
+}
+
+impl<F: Fn(u16) -> bool> IndexMut<u16> for Protected<'_, F> {
+    fn index_mut(&mut self, address: u16) -> &mut u8 {
+        if (self.is_writable)(address) {
+            &mut self.memory[address]
+        } else {
+            // An instruction such as INR M may read the byte through the reference.
+            self.discarded = self.memory[address];
+            &mut self.discarded
+        }
+    }
+}
+
+// Fetches and executes an instruction with `io` connected to the I/O ports, ignoring writes to
+// the addresses for which `is_writable` returns `false`. Returns the number of states taken.
+pub(crate) fn execute_protected<I, F>(
+    i8080: &mut Intel8080,
+    io: &mut I,
+    is_writable: F,
+) -> Result<u32>
+where
+    I: Io + ?Sized,
+    F: Fn(u16) -> bool,
+{
+    let Intel8080 { cpu, memory } = i8080;
+    let mut memory = Protected { memory, is_writable, discarded: 0 };
+    cpu.fetch_execute_instruction_with_bus(&mut memory, io).map(|(_, states)| states)
+}
+
+// Does what `execute_protected` does for an interrupt that puts `instruction` on the data bus.
+pub(crate) fn interrupt_protected<I, F>(
+    i8080: &mut Intel8080,
+    instruction: Instruction,
+    io: &mut I,
+    is_writable: F,
+) -> Result<u32>
+where
+    I: Io + ?Sized,
+    F: Fn(u16) -> bool,
+{
+    let Intel8080 { cpu, memory } = i8080;
+    let mut memory = Protected { memory, is_writable, discarded: 0 };
+    cpu.interrupt_with_bus(instruction, &mut memory, io)
+}
+
+// The address/data switches of the front panel of an Altair or an IMSAI, and the RUN/STOP state
+// of the CPU that the panel controls.
+#[derive(Default)]
+pub(crate) struct FrontPanel {
+    pub(crate) switches: u16,
+    pub(crate) is_running: bool,
+}
+
+impl FrontPanel {
+    // Returns the positions of the sense switches, which are the upper eight address switches.
+    pub(crate) fn sense_switches(&self) -> u8 {
+        self.switches.to_le_bytes()[1]
+    }
+
+    // EXAMINE: jumps to the address set on the switches.
+    pub(crate) fn examine(&self, cpu: &mut Cpu) {
+        if !self.is_running {
+            cpu.pc = self.switches;
+        }
+    }
+
+    // EXAMINE NEXT: advances to the next address.
+    pub(crate) fn examine_next(&self, cpu: &mut Cpu) {
+        if !self.is_running {
+            cpu.pc = cpu.pc.wrapping_add(1);
+        }
+    }
+
+    // DEPOSIT: stores the byte set on the data switches (A0-A7) at the current address unless
+    // `is_writable` returns `false` for it.
+    pub(crate) fn deposit<F: Fn(u16) -> bool>(&self, i8080: &mut Intel8080, is_writable: F) {
+        let address = i8080.cpu.pc;
+        if !self.is_running && is_writable(address) {
+            i8080.memory[address] = self.switches.to_le_bytes()[0];
+        }
+    }
+
+    // DEPOSIT NEXT: advances to the next address and deposits there.
+    pub(crate) fn deposit_next<F: Fn(u16) -> bool>(&self, i8080: &mut Intel8080, is_writable: F) {
+        self.examine_next(&mut i8080.cpu);
+        self.deposit(i8080, is_writable);
+    }
+
+    // SINGLE STEP: executes an instruction with `step` while the CPU is stopped.
+    pub(crate) fn single_step<F>(&self, step: F) -> Result<()>
+    where
+        F: FnOnce(&Self) -> Result<u32>,
+    {
+        if !self.is_running {
+            step(self)?;
+        }
+        Ok(())
+    }
+
+    // Starts the CPU and executes instructions with `step`, which returns the number of states
+    // taken, until the CPU halts or executes at least `states` states. The CPU is left running in
+    // the latter case.
+    pub(crate) fn run_for<F>(&mut self, states: u64, mut step: F) -> Result<()>
+    where
+        F: FnMut(&Self) -> Result<u32>,
+    {
+        self.is_running = true;
+        let mut elapsed = 0;
+        while elapsed < states {
+            match step(self) {
+                Ok(states) => elapsed += u64::from(states),
+                Err(Error::Halted) => {
+                    self.is_running = false;
+                    break;
+                }
+                Err(err) => {
+                    self.is_running = false;
+                    return Err(err);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// A channel of a serial board with `console` attached, whose status register has the
+// `receiver_ready` bits set while the console has input ready and the `transmitter_ready` bits
+// set always.
+pub(crate) struct SerialChannel<C> {
+    pub(crate) console: C,
+    receiver_ready: u8,
+    transmitter_ready: u8,
+}
+
+impl<C: Console> SerialChannel<C> {
+    pub(crate) fn new(console: C, receiver_ready: u8, transmitter_ready: u8) -> Self {
+        Self { console, receiver_ready, transmitter_ready }
+    }
+
+    pub(crate) fn status(&mut self) -> u8 {
+        let ready = self.console.is_input_ready();
+        self.transmitter_ready | if ready { self.receiver_ready } else { 0 }
+    }
+
+    // Reads a byte from the console. Reaching the end of the input and failing to read are
+    // recorded in `error` unless it already holds an error from the same instruction.
+    pub(crate) fn read_data(&mut self, error: &mut Option<Error>) -> u8 {
+        match self.console.read_byte() {
+            Ok(Some(byte)) => byte,
+            Ok(None) => {
+                error.get_or_insert(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+                0x00
+            }
+            Err(err) => {
+                error.get_or_insert(err.into());
+                0x00
+            }
+        }
+    }
+
+    // Writes `byte` to the console, recording a failure in `error` as `read_data` does.
+    pub(crate) fn write_data(&mut self, byte: u8, error: &mut Option<Error>) {
+        if let Err(err) = self.console.write_byte(byte) {
+            error.get_or_insert(err.into());
+        }
+    }
+}
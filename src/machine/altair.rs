@@ -0,0 +1,484 @@
+//! The MITS Altair 8800 microcomputer.
+//!
+//! The machine is operated through its front panel: the address/data switches select an address
+//! to EXAMINE or a byte to DEPOSIT, and RUN, STOP, and SINGLE STEP control the CPU. The status
+//! and address LEDs show what the CPU is doing. A terminal is attached to the first port of an
+//! 88-2SIO serial card, and the sense switches (the upper eight address switches) can be read
+//! from port 0xFF.
+//!
+//! Up to 16 disks can be mounted on the drives of an [88-DCDD] floppy disk controller, and a
+//! cassette, such as a [`Cassette`] in the Kansas City format, can be attached to the 88-ACR audio
+//! cassette interface.
+//!
+//! Programs distributed on paper tape, such as Altair BASIC, can be loaded directly with
+//! [`Altair::load_tape`], or booted through their bootstrap loaders from a [`PaperTape`] attached
+//! as the console.
+//!
+//! [88-DCDD]: dcdd/index.html
+//! [`Cassette`]: ../../cassette/struct.Cassette.html
+//! [`Altair::load_tape`]: struct.Altair.html#method.load_tape
+//! [`PaperTape`]: ../../paper_tape/struct.PaperTape.html
+
+use std::{fs, path::Path};
+
+use super::{FrontPanel, SerialChannel};
+use crate::{
+    console::Console,
+    cpu::{Instruction, Io, StatusWord},
+    Error, Intel8080, Result,
+};
+
+pub mod dcdd;
+use dcdd::{Controller, Disk};
+
+/// The status/control port of the first channel of the 88-2SIO.
+pub const SIO_STATUS_PORT: u8 = 0x10;
+
+/// The data port of the first channel of the 88-2SIO.
+pub const SIO_DATA_PORT: u8 = 0x11;
+
+/// The status port of the 88-SIO of the 88-ACR.
+pub const ACR_STATUS_PORT: u8 = 0x06;
+
+/// The data port of the 88-SIO of the 88-ACR.
+pub const ACR_DATA_PORT: u8 = 0x07;
+
+/// The port from which the sense switches are read.
+pub const SENSE_SWITCHES_PORT: u8 = 0xFF;
+
+// The bits of the status register of the 6850 ACIA on the 88-2SIO.
+const RECEIVE_DATA_REGISTER_FULL: u8 = 0x01;
+const TRANSMIT_DATA_REGISTER_EMPTY: u8 = 0x02;
+
+// The bits of the status register of the 88-SIO, which are active low.
+const INPUT_DEVICE_READY: u8 = 0x01;
+const OUTPUT_DEVICE_READY: u8 = 0x80;
+
+// The sync bytes of the records that the MITS checksum loader reads.
+const DATA_RECORD: u8 = 0x3C;
+const END_RECORD: u8 = 0x78;
+
+/// The lights of the front panel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Leds {
+    /// A0-A15.
+    pub address: u16,
+    /// D0-D7.
+    pub data: u8,
+    /// The status lights driven by the status word of the CPU.
+    pub status: StatusWord,
+    /// INTE: the interrupt system is enabled.
+    pub inte: bool,
+    /// PROT: the memory at the address is protected. Memory protection is not emulated.
+    pub prot: bool,
+    /// WAIT: the CPU is stopped.
+    pub wait: bool,
+    /// HLDA: the CPU has released the bus for DMA. DMA is not emulated.
+    pub hlda: bool,
+}
+
+/// An Altair 8800.
+pub struct Altair<C> {
+    /// The CPU and the memory. Instructions executed and interrupts sent through it directly, not
+    /// through the methods of the Altair, can write to unpopulated memory.
+    pub i8080: Intel8080,
+    panel: FrontPanel,
+    devices: Devices<C>,
+    memory_size: usize,
+}
+
+impl<C: Console> Altair<C> {
+    /// Creates a stopped Altair with `memory_size` bytes of memory from address 0 and `console`
+    /// attached to the 88-2SIO. Reading from unpopulated addresses returns 0xFF, and writing to
+    /// them has no effect. A boot ROM, such as the disk boot loader at 0xFF00, can be installed
+    /// above the memory by writing it to `i8080.memory` directly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `memory_size` is larger than 64K.
+    pub fn new(console: C, memory_size: usize) -> Self {
+        assert!(memory_size <= 0x10000, "memory size larger than 64K");
+        let mut i8080 = Intel8080::default();
+        for byte in i8080.memory.iter_mut().skip(memory_size) {
+            *byte = 0xFF;
+        }
+        Self {
+            i8080,
+            panel: FrontPanel::default(),
+            devices: Devices {
+                sio: SerialChannel::new(
+                    console,
+                    RECEIVE_DATA_REGISTER_FULL,
+                    TRANSMIT_DATA_REGISTER_EMPTY,
+                ),
+                sense_switches: 0,
+                dcdd: Controller::default(),
+                acr: None,
+                states: 0,
+                error: None,
+            },
+            memory_size,
+        }
+    }
+
+    /// Returns the size of the populated memory in bytes.
+    pub fn memory_size(&self) -> usize {
+        self.memory_size
+    }
+
+    /// Returns a reference to the console.
+    pub fn console(&self) -> &C {
+        &self.devices.sio.console
+    }
+
+    /// Returns a mutable reference to the console.
+    pub fn console_mut(&mut self) -> &mut C {
+        &mut self.devices.sio.console
+    }
+
+    /// Mounts `disk` on `drive` (0-15) of the 88-DCDD, returning the disk that was mounted there.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `drive` is greater than 15.
+    pub fn mount(&mut self, drive: usize, disk: Disk) -> Option<Disk> {
+        self.devices.dcdd.mount(drive, disk)
+    }
+
+    /// Removes the disk from `drive` (0-15) of the 88-DCDD and returns it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `drive` is greater than 15.
+    pub fn unmount(&mut self, drive: usize) -> Option<Disk> {
+        self.devices.dcdd.unmount(drive)
+    }
+
+    /// Returns the disk mounted on `drive` (0-15) of the 88-DCDD.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `drive` is greater than 15.
+    pub fn disk(&self, drive: usize) -> Option<&Disk> {
+        self.devices.dcdd.disk(drive)
+    }
+
+    /// Attaches `cassette` to the 88-ACR, returning the cassette that was attached there.
+    pub fn attach_cassette(&mut self, cassette: Box<dyn Console>) -> Option<Box<dyn Console>> {
+        self.devices.acr.replace(cassette)
+    }
+
+    /// Removes the cassette from the 88-ACR and returns it.
+    pub fn detach_cassette(&mut self) -> Option<Box<dyn Console>> {
+        self.devices.acr.take()
+    }
+
+    /// Returns the number of states that the CPU has executed.
+    pub fn states(&self) -> u64 {
+        self.devices.states
+    }
+
+    /// Returns the positions of the 16 address/data switches. A set bit means that the switch is
+    /// up.
+    pub fn switches(&self) -> u16 {
+        self.panel.switches
+    }
+
+    /// Sets the positions of the address/data switches.
+    pub fn set_switches(&mut self, switches: u16) {
+        self.panel.switches = switches;
+    }
+
+    /// Returns `true` if the CPU is running.
+    pub fn is_running(&self) -> bool {
+        self.panel.is_running
+    }
+
+    /// Returns the state of the front panel lights.
+    pub fn leds(&self) -> Leds {
+        let cpu = &self.i8080.cpu;
+        Leds {
+            address: cpu.pc,
+            data: self.i8080.memory[cpu.pc],
+            status: cpu.status_word(),
+            inte: cpu.is_interrupt_enabled(),
+            prot: false,
+            wait: !self.panel.is_running,
+            hlda: false,
+        }
+    }
+
+    /// Operates the RESET switch, which sets the program counter to 0.
+    pub fn reset(&mut self) {
+        self.i8080.cpu.pc = 0x0000;
+    }
+
+    /// Operates the EXAMINE switch, which jumps to the address set on the switches.
+    pub fn examine(&mut self) {
+        self.panel.examine(&mut self.i8080.cpu);
+    }
+
+    /// Operates the EXAMINE NEXT switch, which advances to the next address.
+    pub fn examine_next(&mut self) {
+        self.panel.examine_next(&mut self.i8080.cpu);
+    }
+
+    /// Operates the DEPOSIT switch, which stores the byte set on the data switches (A0-A7) at the
+    /// current address.
+    pub fn deposit(&mut self) {
+        let memory_size = self.memory_size;
+        self.panel.deposit(&mut self.i8080, |address| usize::from(address) < memory_size);
+    }
+
+    /// Operates the DEPOSIT NEXT switch, which advances to the next address and deposits there.
+    pub fn deposit_next(&mut self) {
+        let memory_size = self.memory_size;
+        self.panel.deposit_next(&mut self.i8080, |address| usize::from(address) < memory_size);
+    }
+
+    /// Operates the RUN switch. The CPU runs when [`run`] or [`run_for`] is called.
+    ///
+    /// [`run`]: #method.run
+    /// [`run_for`]: #method.run_for
+    pub fn start(&mut self) {
+        self.panel.is_running = true;
+    }
+
+    /// Operates the STOP switch.
+    pub fn stop(&mut self) {
+        self.panel.is_running = false;
+    }
+
+    /// Operates the SINGLE STEP switch, which executes one instruction while the CPU is stopped.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::Halted`] error if the CPU is in the halt state, or
+    /// an [`Error::Io`] error if the console cannot be read or written successfully.
+    ///
+    /// [`Error::Halted`]: ../../enum.Error.html#variant.Halted
+    /// [`Error::Io`]: ../../enum.Error.html#variant.Io
+    pub fn single_step(&mut self) -> Result<()> {
+        let Self { i8080, panel, devices, memory_size } = self;
+        panel.single_step(|panel| devices.step(i8080, panel, *memory_size))
+    }
+
+    /// Starts the CPU and runs it until it halts.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::Io`] error if the console cannot be read or written
+    /// successfully, or the console has reached the end of its input.
+    ///
+    /// [`Error::Io`]: ../../enum.Error.html#variant.Io
+    pub fn run(&mut self) -> Result<()> {
+        self.run_for(u64::MAX)
+    }
+
+    /// Starts the CPU and runs it until it halts or executes at least `states` states. The CPU is
+    /// left running in the latter case.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::Io`] error if the console cannot be read or written
+    /// successfully, or the console has reached the end of its input.
+    ///
+    /// [`Error::Io`]: ../../enum.Error.html#variant.Io
+    pub fn run_for(&mut self, states: u64) -> Result<()> {
+        let Self { i8080, panel, devices, memory_size } = self;
+        panel.run_for(states, |panel| devices.step(i8080, panel, *memory_size))
+    }
+
+    /// Sends `instruction`, usually an RST, to the CPU as an interrupting device does. Like the
+    /// instructions of the program, the interrupt cannot write to unpopulated memory.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::InterruptNotEnabled`] error if the interrupt system
+    /// is disabled.
+    ///
+    /// [`Error::InterruptNotEnabled`]: ../../enum.Error.html#variant.InterruptNotEnabled
+    pub fn interrupt(&mut self, instruction: Instruction) -> Result<()> {
+        self.devices.sense_switches = self.panel.sense_switches();
+        let memory_size = self.memory_size;
+        let states = super::interrupt_protected(
+            &mut self.i8080,
+            instruction,
+            &mut self.devices,
+            |address| usize::from(address) < memory_size,
+        )?;
+        self.devices.elapse(states)
+    }
+
+    /// Loads a paper tape in the format of the MITS checksum loader, such as an Altair BASIC tape,
+    /// and sets the program counter to its start address. The bootstrap and checksum loaders at
+    /// the start of the tape are skipped.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::InvalidPaperTape`] error if the tape does not hold
+    /// checksum records that end with a start address.
+    ///
+    /// [`Error::InvalidPaperTape`]: ../../enum.Error.html#variant.InvalidPaperTape
+    pub fn load_tape(&mut self, tape: &[u8]) -> Result<u16> {
+        let (records, start_address) = tape
+            .iter()
+            .enumerate()
+            .filter(|(_, &byte)| byte == DATA_RECORD)
+            .find_map(|(offset, _)| parse_checksum_records(&tape[offset..]))
+            .ok_or(Error::InvalidPaperTape)?;
+        for (address, data) in records {
+            for (offset, byte) in data.iter().enumerate() {
+                self.write(address.wrapping_add(offset as u16), *byte);
+            }
+        }
+        self.i8080.cpu.pc = start_address;
+        Ok(start_address)
+    }
+
+    /// Loads the paper tape image located at `path`. See [`load_tape`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error ([`Error::FileNotFound`], [`Error::Io`], or
+    /// [`Error::InvalidPaperTape`]) if the tape image cannot be read successfully or it is not in
+    /// the format of the checksum loader.
+    ///
+    /// [`load_tape`]: #method.load_tape
+    /// [`Error::FileNotFound`]: ../../enum.Error.html#variant.FileNotFound
+    /// [`Error::Io`]: ../../enum.Error.html#variant.Io
+    /// [`Error::InvalidPaperTape`]: ../../enum.Error.html#variant.InvalidPaperTape
+    pub fn load_tape_file<P: AsRef<Path>>(&mut self, path: P) -> Result<u16> {
+        let path = path.as_ref();
+        let tape = fs::read(path).map_err(|err| Error::from_open(err, path))?;
+        self.load_tape(&tape)
+    }
+
+    fn write(&mut self, address: u16, byte: u8) {
+        if usize::from(address) < self.memory_size {
+            self.i8080.memory[address] = byte;
+        }
+    }
+}
+
+// The load address and the data of a checksum record.
+type Record<'a> = (u16, &'a [u8]);
+
+// Parses checksum records from the start of `tape` until an end record. Bytes between records,
+// such as leaders of nulls, are skipped as the checksum loader does.
+fn parse_checksum_records(mut tape: &[u8]) -> Option<(Vec<Record<'_>>, u16)> {
+    let mut records = Vec::new();
+    loop {
+        match tape.split_first()? {
+            (&DATA_RECORD, rest) => {
+                let (&count, rest) = rest.split_first()?;
+                let count = usize::from(count);
+                if rest.len() < 3 + count {
+                    return None;
+                }
+                let address = u16::from_le_bytes([rest[0], rest[1]]);
+                let data = &rest[2..2 + count];
+                let checksum =
+                    rest[..2 + count].iter().fold(0_u8, |sum, byte| sum.wrapping_add(*byte));
+                if checksum != rest[2 + count] {
+                    return None;
+                }
+                records.push((address, data));
+                tape = &rest[3 + count..];
+            }
+            (&END_RECORD, rest) if !records.is_empty() && rest.len() >= 2 => {
+                return Some((records, u16::from_le_bytes([rest[0], rest[1]])));
+            }
+            (_, rest) => tape = rest,
+        }
+    }
+}
+
+// The devices on the I/O ports.
+struct Devices<C> {
+    sio: SerialChannel<C>,
+    // The sense switches of the front panel as of the current instruction.
+    sense_switches: u8,
+    dcdd: Controller,
+    acr: Option<Box<dyn Console>>,
+    // The number of states elapsed before the current instruction.
+    states: u64,
+    // The first device error since the last instruction.
+    error: Option<Error>,
+}
+
+impl<C: Console> Devices<C> {
+    // Executes an instruction, ignoring writes to the memory above `memory_size`, and returns the
+    // number of states taken.
+    fn step(
+        &mut self,
+        i8080: &mut Intel8080,
+        panel: &FrontPanel,
+        memory_size: usize,
+    ) -> Result<u32> {
+        self.sense_switches = panel.sense_switches();
+        let states =
+            super::execute_protected(i8080, self, |address| usize::from(address) < memory_size)?;
+        self.elapse(states)?;
+        Ok(states)
+    }
+
+    // Advances the devices by `states`, returning the error that a device has met.
+    fn elapse(&mut self, states: u32) -> Result<()> {
+        self.states += u64::from(states);
+        self.sio.console.elapse(states);
+        if let Some(cassette) = &mut self.acr {
+            cassette.elapse(states);
+        }
+        match self.error.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<C: Console> Io for Devices<C> {
+    fn input(&mut self, port: u8) -> u8 {
+        match port {
+            SIO_STATUS_PORT => self.sio.status(),
+            SIO_DATA_PORT => self.sio.read_data(&mut self.error),
+            dcdd::DRIVE_PORT..=dcdd::DATA_PORT => self.dcdd.input(port, self.states),
+            ACR_STATUS_PORT => match self.acr.as_mut().map(|cassette| cassette.is_input_ready()) {
+                Some(true) => !(INPUT_DEVICE_READY | OUTPUT_DEVICE_READY),
+                _ => !OUTPUT_DEVICE_READY,
+            },
+            // Reading past the end of the tape is not an error; the program sees a silent tape.
+            ACR_DATA_PORT => match self.acr.as_mut().map(|cassette| cassette.read_byte()) {
+                Some(Ok(Some(byte))) => byte,
+                Some(Err(err)) => {
+                    self.error.get_or_insert(err.into());
+                    0x00
+                }
+                _ => 0x00,
+            },
+            // The second channel, which has nothing attached.
+            0x12 => TRANSMIT_DATA_REGISTER_EMPTY,
+            SENSE_SWITCHES_PORT => self.sense_switches,
+            // The data bus floats high.
+            _ => 0xFF,
+        }
+    }
+
+    fn output(&mut self, port: u8, byte: u8) {
+        let result = match port {
+            SIO_DATA_PORT => {
+                self.sio.write_data(byte, &mut self.error);
+                Ok(())
+            }
+            dcdd::DRIVE_PORT..=dcdd::DATA_PORT => self.dcdd.output(port, byte, self.states),
+            ACR_DATA_PORT => match &mut self.acr {
+                Some(cassette) => cassette.write_byte(byte).map_err(Error::from),
+                None => Ok(()),
+            },
+            _ => Ok(()),
+        };
+        if let Err(err) = result {
+            self.error.get_or_insert(err);
+        }
+    }
+}
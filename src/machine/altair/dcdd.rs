@@ -0,0 +1,330 @@
+//! The MITS 88-DCDD floppy disk controller and its 8" hard-sectored disks.
+//!
+//! The controller is programmed through three ports:
+//!
+//! <table>
+//! <tr> <th>Port</th> <th>IN</th>              <th>OUT</th> </tr>
+//! <tr> <td>0x08</td> <td>Drive status</td>    <td>Drive select</td> </tr>
+//! <tr> <td>0x09</td> <td>Sector position</td> <td>Drive control</td> </tr>
+//! <tr> <td>0x0A</td> <td>Read data</td>       <td>Write data</td> </tr>
+//! </table>
+//!
+//! The bits of the status are active low. The disk spins at 360 RPM, and the sector position is
+//! derived from the number of states that the CPU has executed, so software that waits for a
+//! sector sees it come around at the speed of a real disk.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use crate::{Error, Result};
+
+/// The drive select port.
+pub const DRIVE_PORT: u8 = 0x08;
+
+/// The drive control and sector position port.
+pub const CONTROL_PORT: u8 = 0x09;
+
+/// The data port.
+pub const DATA_PORT: u8 = 0x0A;
+
+/// The number of tracks on a disk.
+pub const TRACKS: u16 = 77;
+
+/// The number of hard sectors per track.
+pub const SECTORS_PER_TRACK: u8 = 32;
+
+/// The size of a sector in bytes.
+pub const SECTOR_SIZE: usize = 137;
+
+/// The size of a disk image in bytes.
+pub const DISK_SIZE: usize = TRACKS as usize * SECTORS_PER_TRACK as usize * SECTOR_SIZE;
+
+// The timing of the drive in states of a 2 MHz CPU.
+const REVOLUTION_STATES: u64 = 2_000_000 / 6;
+const SECTOR_STATES: u64 = REVOLUTION_STATES / SECTORS_PER_TRACK as u64;
+const SECTOR_TRUE_STATES: u64 = 60;
+const STEP_STATES: u64 = 20_000;
+const HEAD_SETTLE_STATES: u64 = 80_000;
+
+// The status bits in positive logic. The complement is put on the data bus.
+const ENTER_NEW_WRITE_DATA: u8 = 0x01;
+const MOVE_HEAD: u8 = 0x02;
+const HEAD_STATUS: u8 = 0x04;
+const INTERRUPTS_ENABLED: u8 = 0x20;
+const TRACK_0: u8 = 0x40;
+const NEW_READ_DATA_AVAILABLE: u8 = 0x80;
+
+// The bits of the drive control byte.
+const STEP_IN: u8 = 0x01;
+const STEP_OUT: u8 = 0x02;
+const HEAD_LOAD: u8 = 0x04;
+const HEAD_UNLOAD: u8 = 0x08;
+const INTERRUPT_ENABLE: u8 = 0x10;
+const INTERRUPT_DISABLE: u8 = 0x20;
+const WRITE_ENABLE: u8 = 0x80;
+
+/// A disk image that stores the 137-byte sectors of a disk in order track after track.
+pub struct Disk {
+    data: Vec<u8>,
+    file: Option<File>,
+}
+
+impl Disk {
+    /// Creates a blank disk that is not backed by a file.
+    pub fn new() -> Self {
+        Self { data: vec![0xE5; DISK_SIZE], file: None }
+    }
+
+    /// Creates a disk that holds `bytes` and is not backed by a file. A short image is padded
+    /// with 0xE5.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::InvalidDiskImage`] error if `bytes` is larger than
+    /// [`DISK_SIZE`].
+    ///
+    /// [`Error::InvalidDiskImage`]: ../../../enum.Error.html#variant.InvalidDiskImage
+    /// [`DISK_SIZE`]: constant.DISK_SIZE.html
+    pub fn from_bytes(mut bytes: Vec<u8>) -> Result<Self> {
+        if bytes.len() > DISK_SIZE {
+            return Err(Error::InvalidDiskImage { path: None, size: bytes.len() as u64 });
+        }
+        bytes.resize(DISK_SIZE, 0xE5);
+        Ok(Self { data: bytes, file: None })
+    }
+
+    /// Opens the disk image located at `path`. Sectors written later are written through to the
+    /// file unless it is read-only.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error ([`Error::FileNotFound`], [`Error::Io`], or
+    /// [`Error::InvalidDiskImage`]) if the disk image cannot be read successfully or it is larger
+    /// than [`DISK_SIZE`].
+    ///
+    /// [`Error::FileNotFound`]: ../../../enum.Error.html#variant.FileNotFound
+    /// [`Error::Io`]: ../../../enum.Error.html#variant.Io
+    /// [`Error::InvalidDiskImage`]: ../../../enum.Error.html#variant.InvalidDiskImage
+    /// [`DISK_SIZE`]: constant.DISK_SIZE.html
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .or_else(|_| File::open(path))
+            .map_err(|err| Error::from_open(err, path))?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        let size = data.len() as u64;
+        let mut disk = Self::from_bytes(data)
+            .map_err(|_| Error::InvalidDiskImage { path: Some(path.to_path_buf()), size })?;
+        disk.file = Some(file);
+        Ok(disk)
+    }
+
+    /// Returns the contents of the disk.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns `sector` (starting at 0) of `track`, or `None` if there is no such sector.
+    pub fn sector(&self, track: u16, sector: u8) -> Option<&[u8]> {
+        let offset = Self::offset(track, sector)?;
+        Some(&self.data[offset..offset + SECTOR_SIZE])
+    }
+
+    /// Writes `data` to `sector` (starting at 0) of `track`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is no such sector or the file that backs the
+    /// disk cannot be written successfully.
+    pub fn write_sector(&mut self, track: u16, sector: u8, data: &[u8; SECTOR_SIZE]) -> Result<()> {
+        let offset = Self::offset(track, sector)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no such sector"))?;
+        self.data[offset..offset + SECTOR_SIZE].copy_from_slice(data);
+        if let Some(file) = &mut self.file {
+            file.seek(SeekFrom::Start(offset as u64))?;
+            file.write_all(data)?;
+        }
+        Ok(())
+    }
+
+    fn offset(track: u16, sector: u8) -> Option<usize> {
+        if track >= TRACKS || sector >= SECTORS_PER_TRACK {
+            return None;
+        }
+        let index = usize::from(track) * usize::from(SECTORS_PER_TRACK) + usize::from(sector);
+        Some(index * SECTOR_SIZE)
+    }
+}
+
+impl Default for Disk {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Default)]
+struct Drive {
+    disk: Option<Disk>,
+    track: u16,
+}
+
+// The controller and up to 16 drives.
+#[derive(Default)]
+pub(super) struct Controller {
+    drives: [Drive; 16],
+    selected: Option<usize>,
+    is_head_loaded: bool,
+    head_ready_at: u64,
+    move_ready_at: u64,
+    interrupts_enabled: bool,
+    is_writing: bool,
+    // The sector that the last sector true pulse was read for, and the position in it.
+    sector: Option<u8>,
+    position: usize,
+    buffer: Vec<u8>,
+}
+
+impl Controller {
+    pub(super) fn mount(&mut self, drive: usize, disk: Disk) -> Option<Disk> {
+        self.drives[drive].disk.replace(disk)
+    }
+
+    pub(super) fn unmount(&mut self, drive: usize) -> Option<Disk> {
+        if self.selected == Some(drive) {
+            self.select(None);
+        }
+        self.drives[drive].disk.take()
+    }
+
+    pub(super) fn disk(&self, drive: usize) -> Option<&Disk> {
+        self.drives[drive].disk.as_ref()
+    }
+
+    pub(super) fn input(&mut self, port: u8, now: u64) -> u8 {
+        let drive = match self.selected {
+            Some(drive) => drive,
+            None => return 0xFF,
+        };
+        match port {
+            DRIVE_PORT => {
+                let mut status = 0;
+                if self.is_writing && self.position < SECTOR_SIZE {
+                    status |= ENTER_NEW_WRITE_DATA;
+                }
+                if now >= self.move_ready_at {
+                    status |= MOVE_HEAD;
+                }
+                let is_head_ready = self.is_head_loaded && now >= self.head_ready_at;
+                if is_head_ready {
+                    status |= HEAD_STATUS;
+                }
+                if self.interrupts_enabled {
+                    status |= INTERRUPTS_ENABLED;
+                }
+                if self.drives[drive].track == 0 {
+                    status |= TRACK_0;
+                }
+                if is_head_ready
+                    && !self.is_writing
+                    && self.sector.is_some()
+                    && self.position < SECTOR_SIZE
+                {
+                    status |= NEW_READ_DATA_AVAILABLE;
+                }
+                !status
+            }
+            CONTROL_PORT if self.is_head_loaded => {
+                let offset = now % REVOLUTION_STATES;
+                let sector = ((offset / SECTOR_STATES) as u8).min(SECTORS_PER_TRACK - 1);
+                let is_sector_true = offset % SECTOR_STATES < SECTOR_TRUE_STATES;
+                if is_sector_true && !self.is_writing {
+                    self.sector = Some(sector);
+                    self.position = 0;
+                }
+                0xC0 | sector << 1 | u8::from(!is_sector_true)
+            }
+            DATA_PORT if self.is_head_loaded && !self.is_writing => {
+                let track = self.drives[drive].track;
+                let byte = self
+                    .sector
+                    .and_then(|sector| self.drives[drive].disk.as_ref()?.sector(track, sector))
+                    .and_then(|data| data.get(self.position).copied())
+                    .unwrap_or(0x00);
+                self.position += 1;
+                byte
+            }
+            _ => 0xFF,
+        }
+    }
+
+    pub(super) fn output(&mut self, port: u8, byte: u8, now: u64) -> Result<()> {
+        match port {
+            DRIVE_PORT if byte & 0x80 != 0 => self.select(None),
+            DRIVE_PORT => self.select(Some(usize::from(byte & 0x0F))),
+            CONTROL_PORT => {
+                let drive = match self.selected {
+                    Some(drive) => &mut self.drives[drive],
+                    None => return Ok(()),
+                };
+                if byte & (STEP_IN | STEP_OUT) != 0 && now >= self.move_ready_at {
+                    if byte & STEP_IN != 0 && drive.track < TRACKS - 1 {
+                        drive.track += 1;
+                    } else if byte & STEP_OUT != 0 && drive.track > 0 {
+                        drive.track -= 1;
+                    }
+                    self.move_ready_at = now + STEP_STATES;
+                    self.sector = None;
+                }
+                if byte & HEAD_LOAD != 0 && !self.is_head_loaded {
+                    self.is_head_loaded = true;
+                    self.head_ready_at = now + HEAD_SETTLE_STATES;
+                }
+                if byte & HEAD_UNLOAD != 0 {
+                    self.is_head_loaded = false;
+                }
+                if byte & INTERRUPT_ENABLE != 0 {
+                    self.interrupts_enabled = true;
+                }
+                if byte & INTERRUPT_DISABLE != 0 {
+                    self.interrupts_enabled = false;
+                }
+                if byte & WRITE_ENABLE != 0 && self.sector.is_some() {
+                    self.is_writing = true;
+                    self.position = 0;
+                    self.buffer.clear();
+                }
+            }
+            DATA_PORT if self.is_writing => {
+                self.buffer.push(byte);
+                self.position += 1;
+                if self.position == SECTOR_SIZE {
+                    self.is_writing = false;
+                    let mut data = [0; SECTOR_SIZE];
+                    data.copy_from_slice(&self.buffer);
+                    if let (Some(drive), Some(sector)) = (self.selected, self.sector) {
+                        let drive = &mut self.drives[drive];
+                        if let Some(disk) = &mut drive.disk {
+                            disk.write_sector(drive.track, sector, &data)?;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn select(&mut self, drive: Option<usize>) {
+        self.selected = drive.filter(|&drive| self.drives[drive].disk.is_some());
+        self.is_head_loaded = false;
+        self.is_writing = false;
+        self.sector = None;
+    }
+}
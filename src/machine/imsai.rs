@@ -0,0 +1,421 @@
+//! The IMSAI 8080 microcomputer with a VIO video board.
+//!
+//! Like the Altair, the machine is operated through its front panel. In addition, the panel has
+//! eight programmed output LEDs that are driven by OUT 0xFF, and the sense switches (the upper
+//! eight address switches) can be read with IN 0xFF. A terminal is attached to the first channel
+//! of an SIO-2 serial board, and the VIO board displays 24 lines of 80 characters from the video
+//! RAM at 0xF000.
+//!
+//! CP/M 2.2 boots from the disk images mounted on the drives of the disk system. As in
+//! [`System`], the BIOS is emulated on the host in place of a disk controller: its jump table is
+//! installed at [`BIOS_BASE`], below the video RAM, and the console output of CP/M goes to both
+//! the terminal and the VIO screen.
+//!
+//! [`System`]: ../../cpm/struct.System.html
+//! [`BIOS_BASE`]: constant.BIOS_BASE.html
+
+use std::io;
+
+use super::{FrontPanel, SerialChannel};
+use crate::{
+    console::Console,
+    cpm::{disk::DiskImage, Bios},
+    cpu::{Io, StatusWord},
+    memory::Memory,
+    Error, Intel8080, Result,
+};
+
+/// The data port of channel A of the SIO-2.
+pub const SIO_DATA_PORT: u8 = 0x02;
+
+/// The status/control port of channel A of the SIO-2.
+pub const SIO_STATUS_PORT: u8 = 0x03;
+
+/// The port of the programmed output LEDs (OUT) and the sense switches (IN).
+pub const FRONT_PANEL_PORT: u8 = 0xFF;
+
+/// The start address of the video RAM of the VIO.
+pub const VIO_START: u16 = 0xF000;
+
+/// The number of characters per line on the VIO.
+pub const VIO_COLUMNS: usize = 80;
+
+/// The number of lines on the VIO.
+pub const VIO_ROWS: usize = 24;
+
+/// The address of the BIOS of the CP/M system, which is configured for 59K so that the system
+/// stays below the video RAM.
+pub const BIOS_BASE: u16 = 0xE600;
+
+// The bits of the status register of the 8251 USARTs on the SIO-2.
+const TRANSMITTER_READY: u8 = 0x01;
+const RECEIVER_READY: u8 = 0x02;
+const TRANSMITTER_EMPTY: u8 = 0x04;
+
+/// The lights of the front panel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Leds {
+    /// A0-A15.
+    pub address: u16,
+    /// D0-D7.
+    pub data: u8,
+    /// The status lights driven by the status word of the CPU.
+    pub status: StatusWord,
+    /// INTE: the interrupt system is enabled.
+    pub inte: bool,
+    /// The byte latched by the last OUT 0xFF. The LEDs are wired so that a lamp is lit for each
+    /// bit that is 0.
+    pub programmed_output: u8,
+    /// RUN: the CPU is running.
+    pub run: bool,
+    /// WAIT: the CPU is stopped.
+    pub wait: bool,
+}
+
+/// An IMSAI 8080.
+pub struct Imsai<C> {
+    /// The CPU and the 64K memory.
+    pub i8080: Intel8080,
+    panel: FrontPanel,
+    devices: Devices<C>,
+}
+
+impl<C: Console> Imsai<C> {
+    /// Creates a stopped IMSAI with `console` attached to channel A of the SIO-2. The programs to
+    /// run, such as a monitor ROM, can be loaded by writing them to `i8080.memory`.
+    pub fn new(console: C) -> Self {
+        let mut i8080 = Intel8080::default();
+        for byte in &mut i8080.memory[VIO_START..VIO_START + (VIO_COLUMNS * VIO_ROWS) as u16] {
+            *byte = b' ';
+        }
+        Self {
+            i8080,
+            panel: FrontPanel::default(),
+            devices: Devices {
+                sio: SerialChannel::new(
+                    console,
+                    RECEIVER_READY,
+                    TRANSMITTER_READY | TRANSMITTER_EMPTY,
+                ),
+                sense_switches: 0,
+                programmed_output: 0xFF,
+                bios: None,
+                cursor: 0,
+                states: 0,
+                error: None,
+            },
+        }
+    }
+
+    /// Returns a reference to the console.
+    pub fn console(&self) -> &C {
+        &self.devices.sio.console
+    }
+
+    /// Returns a mutable reference to the console.
+    pub fn console_mut(&mut self) -> &mut C {
+        &mut self.devices.sio.console
+    }
+
+    /// Inserts `image` into `drive` (0 = A, 1 = B, ..., 15 = P), returning the disk image that
+    /// was in the drive, if any. Mounting the first disk installs the BIOS at [`BIOS_BASE`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `drive` is greater than 15 or the disk parameters of the drive
+    /// do not fit in memory between the BIOS jump table and the video RAM.
+    ///
+    /// [`BIOS_BASE`]: constant.BIOS_BASE.html
+    pub fn mount(&mut self, drive: u8, image: DiskImage) -> Option<DiskImage> {
+        let memory = &mut self.i8080.memory;
+        let bios = self
+            .devices
+            .bios
+            .get_or_insert_with(|| Bios::new(memory, BIOS_BASE, u32::from(VIO_START)));
+        bios.mount(memory, drive, image)
+    }
+
+    /// Removes the disk image from `drive`, returning it.
+    pub fn unmount(&mut self, drive: u8) -> Option<DiskImage> {
+        self.devices.bios.as_mut()?.unmount(drive)
+    }
+
+    /// Returns a reference to the disk image in `drive`.
+    pub fn disk(&self, drive: u8) -> Option<&DiskImage> {
+        self.devices.bios.as_ref()?.disk(drive)
+    }
+
+    /// Boots CP/M: loads the CCP and the BDOS from the system tracks of the disk in drive A, sets
+    /// up the zero page, and sets the program counter to the CCP. The CPU runs when [`run`] or
+    /// [`run_for`] is called.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::NoDisk`] error if there is no disk in drive A, or an
+    /// [`Error::InvalidDiskImage`] error if the disk has no room for the CCP and the BDOS on its
+    /// system tracks.
+    ///
+    /// [`run`]: #method.run
+    /// [`run_for`]: #method.run_for
+    /// [`Error::NoDisk`]: ../../enum.Error.html#variant.NoDisk
+    /// [`Error::InvalidDiskImage`]: ../../enum.Error.html#variant.InvalidDiskImage
+    pub fn boot(&mut self) -> Result<()> {
+        match &mut self.devices.bios {
+            Some(bios) => bios.boot(&mut self.i8080),
+            None => Err(Error::NoDisk { drive: 0 }),
+        }
+    }
+
+    /// Returns the number of states that the CPU has executed.
+    pub fn states(&self) -> u64 {
+        self.devices.states
+    }
+
+    /// Returns the positions of the 16 address/data switches. A set bit means that the switch is
+    /// up.
+    pub fn switches(&self) -> u16 {
+        self.panel.switches
+    }
+
+    /// Sets the positions of the address/data switches.
+    pub fn set_switches(&mut self, switches: u16) {
+        self.panel.switches = switches;
+    }
+
+    /// Returns `true` if the CPU is running.
+    pub fn is_running(&self) -> bool {
+        self.panel.is_running
+    }
+
+    /// Returns the state of the front panel lights.
+    pub fn leds(&self) -> Leds {
+        let cpu = &self.i8080.cpu;
+        Leds {
+            address: cpu.pc,
+            data: self.i8080.memory[cpu.pc],
+            status: cpu.status_word(),
+            inte: cpu.is_interrupt_enabled(),
+            programmed_output: self.devices.programmed_output,
+            run: self.panel.is_running,
+            wait: !self.panel.is_running,
+        }
+    }
+
+    /// Operates the RESET switch, which sets the program counter to 0.
+    pub fn reset(&mut self) {
+        self.i8080.cpu.pc = 0x0000;
+    }
+
+    /// Operates the EXAMINE switch, which jumps to the address set on the switches.
+    pub fn examine(&mut self) {
+        self.panel.examine(&mut self.i8080.cpu);
+    }
+
+    /// Operates the EXAMINE NEXT switch, which advances to the next address.
+    pub fn examine_next(&mut self) {
+        self.panel.examine_next(&mut self.i8080.cpu);
+    }
+
+    /// Operates the DEPOSIT switch, which stores the byte set on the data switches (A0-A7) at the
+    /// current address.
+    pub fn deposit(&mut self) {
+        self.panel.deposit(&mut self.i8080, |_| true);
+    }
+
+    /// Operates the DEPOSIT NEXT switch, which advances to the next address and deposits there.
+    pub fn deposit_next(&mut self) {
+        self.panel.deposit_next(&mut self.i8080, |_| true);
+    }
+
+    /// Operates the RUN switch. The CPU runs when [`run`] or [`run_for`] is called.
+    ///
+    /// [`run`]: #method.run
+    /// [`run_for`]: #method.run_for
+    pub fn start(&mut self) {
+        self.panel.is_running = true;
+    }
+
+    /// Operates the STOP switch.
+    pub fn stop(&mut self) {
+        self.panel.is_running = false;
+    }
+
+    /// Operates the SINGLE STEP switch, which executes one instruction while the CPU is stopped.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::Halted`] error if the CPU is in the halt state, or
+    /// an [`Error::Io`] error if the console cannot be read or written successfully.
+    ///
+    /// [`Error::Halted`]: ../../enum.Error.html#variant.Halted
+    /// [`Error::Io`]: ../../enum.Error.html#variant.Io
+    pub fn single_step(&mut self) -> Result<()> {
+        let Self { i8080, panel, devices } = self;
+        panel.single_step(|panel| devices.step(i8080, panel))
+    }
+
+    /// Starts the CPU and runs it until it halts.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::Io`] error if the console cannot be read or written
+    /// successfully, or the console has reached the end of its input, or an error that a boot
+    /// from a BIOS call returns.
+    ///
+    /// [`Error::Io`]: ../../enum.Error.html#variant.Io
+    pub fn run(&mut self) -> Result<()> {
+        self.run_for(u64::MAX)
+    }
+
+    /// Starts the CPU and runs it until it halts or executes at least `states` states. The CPU is
+    /// left running in the latter case.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::Io`] error if the console cannot be read or written
+    /// successfully, or the console has reached the end of its input, or an error that a boot
+    /// from a BIOS call returns.
+    ///
+    /// [`Error::Io`]: ../../enum.Error.html#variant.Io
+    pub fn run_for(&mut self, states: u64) -> Result<()> {
+        let Self { i8080, panel, devices } = self;
+        panel.run_for(states, |panel| devices.step(i8080, panel))
+    }
+
+    /// Returns line `row` (starting at 0) of the VIO screen. Inverse video (bit 7) is ignored,
+    /// and control characters are shown as spaces.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row` is not less than [`VIO_ROWS`].
+    ///
+    /// [`VIO_ROWS`]: constant.VIO_ROWS.html
+    pub fn vio_line(&self, row: usize) -> String {
+        assert!(row < VIO_ROWS, "no such line on the VIO");
+        let start = VIO_START + (row * VIO_COLUMNS) as u16;
+        self.i8080.memory[start..start + VIO_COLUMNS as u16]
+            .iter()
+            .map(|&byte| match byte & 0x7F {
+                0x20..=0x7E => char::from(byte & 0x7F),
+                _ => ' ',
+            })
+            .collect()
+    }
+
+    /// Returns the VIO screen as text: [`VIO_ROWS`] lines with trailing spaces removed, each
+    /// ending in a line feed.
+    ///
+    /// [`VIO_ROWS`]: constant.VIO_ROWS.html
+    pub fn vio_text(&self) -> String {
+        (0..VIO_ROWS).map(|row| format!("{}\n", self.vio_line(row).trim_end())).collect()
+    }
+}
+
+// The devices on the I/O ports, and the disk system.
+struct Devices<C> {
+    sio: SerialChannel<C>,
+    // The sense switches of the front panel as of the current instruction.
+    sense_switches: u8,
+    programmed_output: u8,
+    bios: Option<Bios>,
+    // The position on the VIO screen at which CP/M writes the next character.
+    cursor: usize,
+    states: u64,
+    // The first device error since the last instruction.
+    error: Option<Error>,
+}
+
+impl<C: Console> Devices<C> {
+    // Services a BIOS call if the program counter is at one, then executes an instruction and
+    // returns the number of states taken.
+    fn step(&mut self, i8080: &mut Intel8080, panel: &FrontPanel) -> Result<u32> {
+        self.sense_switches = panel.sense_switches();
+        if let Some(bios) = &mut self.bios {
+            let mut console = CpmConsole { terminal: &mut self.sio.console, output: Vec::new() };
+            bios.trap(i8080, &mut console)?;
+            for byte in console.output {
+                self.cursor = write_vio(&mut i8080.memory, self.cursor, byte);
+            }
+        }
+        let (_, states) = i8080.fetch_execute_instruction_with_io(self)?;
+        self.states += u64::from(states);
+        self.sio.console.elapse(states);
+        match self.error.take() {
+            Some(err) => Err(err),
+            None => Ok(states),
+        }
+    }
+}
+
+impl<C: Console> Io for Devices<C> {
+    fn input(&mut self, port: u8) -> u8 {
+        match port {
+            SIO_DATA_PORT => self.sio.read_data(&mut self.error),
+            SIO_STATUS_PORT => self.sio.status(),
+            // Channel B, which has nothing attached.
+            0x05 => TRANSMITTER_READY | TRANSMITTER_EMPTY,
+            FRONT_PANEL_PORT => self.sense_switches,
+            // The data bus floats high.
+            _ => 0xFF,
+        }
+    }
+
+    fn output(&mut self, port: u8, byte: u8) {
+        match port {
+            SIO_DATA_PORT => self.sio.write_data(byte, &mut self.error),
+            FRONT_PANEL_PORT => self.programmed_output = byte,
+            // Mode and command words for the USARTs are accepted and ignored.
+            _ => {}
+        }
+    }
+}
+
+// The console of CP/M, which reads from the terminal and writes to both the terminal and the VIO
+// screen. The bytes for the screen are kept in `output` until the BIOS call returns.
+struct CpmConsole<'a, C> {
+    terminal: &'a mut C,
+    output: Vec<u8>,
+}
+
+impl<C: Console> Console for CpmConsole<'_, C> {
+    fn is_input_ready(&mut self) -> bool {
+        self.terminal.is_input_ready()
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        self.terminal.read_byte()
+    }
+
+    fn write_byte(&mut self, byte: u8) -> io::Result<()> {
+        self.output.push(byte);
+        self.terminal.write_byte(byte)
+    }
+}
+
+// Writes `byte` to the VIO screen at `cursor` as a glass terminal does, scrolling the screen up
+// when a line feed leaves the last line. Returns the new position of the cursor.
+fn write_vio(memory: &mut Memory, cursor: usize, byte: u8) -> usize {
+    let (row, column) = (cursor / VIO_COLUMNS, cursor % VIO_COLUMNS);
+    let cursor = match byte {
+        b'\r' => row * VIO_COLUMNS,
+        b'\n' => cursor + VIO_COLUMNS,
+        // Backspace
+        0x08 => cursor - usize::from(column > 0),
+        0x20..=0x7E => {
+            memory[VIO_START + cursor as u16] = byte;
+            cursor + 1
+        }
+        _ => cursor,
+    };
+    if cursor < VIO_COLUMNS * VIO_ROWS {
+        return cursor;
+    }
+    let end = VIO_START + (VIO_COLUMNS * VIO_ROWS) as u16;
+    for address in VIO_START..end - VIO_COLUMNS as u16 {
+        memory[address] = memory[address + VIO_COLUMNS as u16];
+    }
+    for byte in &mut memory[end - VIO_COLUMNS as u16..end] {
+        *byte = b' ';
+    }
+    cursor - VIO_COLUMNS
+}
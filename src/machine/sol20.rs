@@ -0,0 +1,332 @@
+//! The Processor Technology SOL-20 terminal computer.
+//!
+//! The SOLOS personality module, a 2K ROM, is mapped at 0xC000, followed by 1K of system RAM at
+//! 0xC800 and the 1K display RAM of the built-in VDM-1 at 0xCC00, which shows 16 lines of 64
+//! characters. The CPU starts at 0xC000 on reset.
+//!
+//! The keyboard is connected to the parallel keyboard port and the cassette interface to the
+//! tape UART, each as a [`Console`].
+//!
+//! [`Console`]: ../../console/trait.Console.html
+
+use std::{fs, path::Path};
+
+use crate::{
+    console::Console,
+    cpu::{Instruction, Io},
+    Error, Intel8080, Result,
+};
+
+/// The start address of the SOLOS ROM.
+pub const ROM_START: u16 = 0xC000;
+
+/// The size of the SOLOS ROM in bytes.
+pub const ROM_SIZE: usize = 0x0800;
+
+/// The start address of the display RAM of the VDM-1.
+pub const VDM_START: u16 = 0xCC00;
+
+/// The number of characters per line on the VDM-1.
+pub const VDM_COLUMNS: usize = 64;
+
+/// The number of lines on the VDM-1.
+pub const VDM_ROWS: usize = 16;
+
+/// The status port of the keyboard and the cassette interface (IN), and the cassette control port
+/// (OUT).
+pub const STATUS_PORT: u8 = 0xFA;
+
+/// The data port of the cassette interface.
+pub const TAPE_DATA_PORT: u8 = 0xFB;
+
+/// The data port of the keyboard.
+pub const KEYBOARD_DATA_PORT: u8 = 0xFC;
+
+/// The display control port of the VDM-1.
+pub const DISPLAY_CONTROL_PORT: u8 = 0xFE;
+
+// The bits of the status port. Keyboard data ready is active low.
+const KEYBOARD_DATA_READY: u8 = 0x01;
+const TAPE_DATA_READY: u8 = 0x40;
+const TAPE_TRANSMITTER_BUFFER_EMPTY: u8 = 0x80;
+
+// The bits of the cassette control port.
+const TAPE_1_MOTOR: u8 = 0x80;
+const TAPE_2_MOTOR: u8 = 0x40;
+
+/// A SOL-20.
+pub struct Sol20<K, T> {
+    /// The CPU and the memory. Instructions executed and interrupts sent through it directly, not
+    /// through the methods of the SOL-20, can write to the ROM.
+    pub i8080: Intel8080,
+    devices: Devices<K, T>,
+    states: u64,
+}
+
+impl<K: Console, T: Console> Sol20<K, T> {
+    /// Creates a SOL-20 with `keyboard` on the keyboard port and `cassette` on the cassette
+    /// interface. The SOLOS ROM is empty until it is loaded with [`load_rom`] or
+    /// [`load_rom_file`].
+    ///
+    /// [`load_rom`]: #method.load_rom
+    /// [`load_rom_file`]: #method.load_rom_file
+    pub fn new(keyboard: K, cassette: T) -> Self {
+        let mut i8080 = Intel8080::default();
+        i8080.cpu.pc = ROM_START;
+        for byte in &mut i8080.memory[VDM_START..VDM_START + (VDM_COLUMNS * VDM_ROWS) as u16] {
+            *byte = b' ';
+        }
+        Self {
+            i8080,
+            devices: Devices {
+                keyboard,
+                cassette,
+                keyboard_data: 0x00,
+                tape_data: 0x00,
+                tape_control: 0x00,
+                display_control: 0x00,
+                error: None,
+            },
+            states: 0,
+        }
+    }
+
+    /// Loads `rom` into the SOLOS ROM.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rom` is larger than [`ROM_SIZE`].
+    ///
+    /// [`ROM_SIZE`]: constant.ROM_SIZE.html
+    pub fn load_rom(&mut self, rom: &[u8]) {
+        assert!(rom.len() <= ROM_SIZE, "the ROM is larger than 2K");
+        self.i8080.memory[ROM_START..ROM_START + rom.len() as u16].copy_from_slice(rom);
+    }
+
+    /// Loads the ROM file located at `path` into the SOLOS ROM.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error ([`Error::FileNotFound`], [`Error::Io`], or
+    /// [`Error::TooLargeFile`]) if the ROM file cannot be read successfully or it is larger than
+    /// [`ROM_SIZE`].
+    ///
+    /// [`Error::FileNotFound`]: ../../enum.Error.html#variant.FileNotFound
+    /// [`Error::Io`]: ../../enum.Error.html#variant.Io
+    /// [`Error::TooLargeFile`]: ../../enum.Error.html#variant.TooLargeFile
+    /// [`ROM_SIZE`]: constant.ROM_SIZE.html
+    pub fn load_rom_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let rom = fs::read(path).map_err(|err| Error::from_open(err, path))?;
+        if rom.len() > ROM_SIZE {
+            let size = rom.len() as u64;
+            return Err(Error::TooLargeFile {
+                path: path.to_path_buf(),
+                size,
+                start_address: ROM_START,
+            });
+        }
+        self.load_rom(&rom);
+        Ok(())
+    }
+
+    /// Returns a reference to the keyboard.
+    pub fn keyboard(&self) -> &K {
+        &self.devices.keyboard
+    }
+
+    /// Returns a mutable reference to the keyboard.
+    pub fn keyboard_mut(&mut self) -> &mut K {
+        &mut self.devices.keyboard
+    }
+
+    /// Returns a reference to the cassette interface.
+    pub fn cassette(&self) -> &T {
+        &self.devices.cassette
+    }
+
+    /// Returns a mutable reference to the cassette interface.
+    pub fn cassette_mut(&mut self) -> &mut T {
+        &mut self.devices.cassette
+    }
+
+    /// Returns whether the motors of cassette recorders 1 and 2 are turned on.
+    pub fn tape_motors(&self) -> (bool, bool) {
+        let control = self.devices.tape_control;
+        (control & TAPE_1_MOTOR != 0, control & TAPE_2_MOTOR != 0)
+    }
+
+    /// Returns the number of states that the CPU has executed.
+    pub fn states(&self) -> u64 {
+        self.states
+    }
+
+    /// Operates the reset key, which restarts SOLOS.
+    pub fn reset(&mut self) {
+        self.i8080.cpu.pc = ROM_START;
+    }
+
+    /// Runs the CPU until it halts.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::Io`] error if the keyboard or the cassette interface
+    /// cannot be read or written successfully.
+    ///
+    /// [`Error::Io`]: ../../enum.Error.html#variant.Io
+    pub fn run(&mut self) -> Result<()> {
+        self.run_for(u64::MAX)
+    }
+
+    /// Runs the CPU until it halts or executes at least `states` states.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::Io`] error if the keyboard or the cassette interface
+    /// cannot be read or written successfully.
+    ///
+    /// [`Error::Io`]: ../../enum.Error.html#variant.Io
+    pub fn run_for(&mut self, states: u64) -> Result<()> {
+        let end = self.states.saturating_add(states);
+        while self.states < end {
+            match super::execute_protected(&mut self.i8080, &mut self.devices, is_writable) {
+                Ok(states) => self.elapse(states)?,
+                Err(Error::Halted) => break,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends `instruction`, usually an RST, to the CPU as an interrupting device does. Like the
+    /// instructions of the program, the interrupt cannot write to the ROM.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::InterruptNotEnabled`] error if the interrupt system
+    /// is disabled.
+    ///
+    /// [`Error::InterruptNotEnabled`]: ../../enum.Error.html#variant.InterruptNotEnabled
+    pub fn interrupt(&mut self, instruction: Instruction) -> Result<()> {
+        let states = super::interrupt_protected(
+            &mut self.i8080,
+            instruction,
+            &mut self.devices,
+            is_writable,
+        )?;
+        self.elapse(states)
+    }
+
+    /// Returns line `row` (starting at 0) of the screen, taking the scrolling set through the
+    /// display control port into account. The cursor and inverse video (bit 7) are ignored, and
+    /// control characters are shown as spaces.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row` is not less than [`VDM_ROWS`].
+    ///
+    /// [`VDM_ROWS`]: constant.VDM_ROWS.html
+    pub fn screen_line(&self, row: usize) -> String {
+        assert!(row < VDM_ROWS, "no such line on the VDM-1");
+        // The upper four bits blank the lines above them, and the lower four bits select the
+        // line of the display RAM that is shown at the top.
+        let control = usize::from(self.devices.display_control);
+        if row < control >> 4 {
+            return " ".repeat(VDM_COLUMNS);
+        }
+        let line = (row + (control & 0x0F)) % VDM_ROWS;
+        let start = VDM_START + (line * VDM_COLUMNS) as u16;
+        self.i8080.memory[start..start + VDM_COLUMNS as u16]
+            .iter()
+            .map(|&byte| match byte & 0x7F {
+                0x20..=0x7E => char::from(byte & 0x7F),
+                _ => ' ',
+            })
+            .collect()
+    }
+
+    /// Returns the screen as text: [`VDM_ROWS`] lines with trailing spaces removed, each ending in
+    /// a line feed.
+    ///
+    /// [`VDM_ROWS`]: constant.VDM_ROWS.html
+    pub fn screen_text(&self) -> String {
+        (0..VDM_ROWS).map(|row| format!("{}\n", self.screen_line(row).trim_end())).collect()
+    }
+
+    // Advances the devices by `states`, returning the error that a device has met.
+    fn elapse(&mut self, states: u32) -> Result<()> {
+        self.states += u64::from(states);
+        self.devices.keyboard.elapse(states);
+        self.devices.cassette.elapse(states);
+        match self.devices.error.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+// Returns `false` for the addresses of the ROM.
+fn is_writable(address: u16) -> bool {
+    !(usize::from(ROM_START)..usize::from(ROM_START) + ROM_SIZE).contains(&usize::from(address))
+}
+
+// The devices on the I/O ports.
+struct Devices<K, T> {
+    keyboard: K,
+    cassette: T,
+    // The latched bytes of the keyboard and the tape UART.
+    keyboard_data: u8,
+    tape_data: u8,
+    tape_control: u8,
+    display_control: u8,
+    // The first device error since the last instruction.
+    error: Option<Error>,
+}
+
+// Reads a byte from `console` into `latch`, which keeps its value at the end of the input.
+fn read<C: Console>(console: &mut C, error: &mut Option<Error>, latch: &mut u8) -> u8 {
+    match console.read_byte() {
+        Ok(Some(byte)) => *latch = byte,
+        Ok(None) => {}
+        Err(err) => {
+            error.get_or_insert(err.into());
+        }
+    }
+    *latch
+}
+
+impl<K: Console, T: Console> Io for Devices<K, T> {
+    fn input(&mut self, port: u8) -> u8 {
+        match port {
+            STATUS_PORT => {
+                let mut status = TAPE_TRANSMITTER_BUFFER_EMPTY;
+                if !self.keyboard.is_input_ready() {
+                    status |= KEYBOARD_DATA_READY;
+                }
+                if self.cassette.is_input_ready() {
+                    status |= TAPE_DATA_READY;
+                }
+                status
+            }
+            TAPE_DATA_PORT => read(&mut self.cassette, &mut self.error, &mut self.tape_data),
+            KEYBOARD_DATA_PORT => {
+                read(&mut self.keyboard, &mut self.error, &mut self.keyboard_data)
+            }
+            // The data bus floats high.
+            _ => 0xFF,
+        }
+    }
+
+    fn output(&mut self, port: u8, byte: u8) {
+        match port {
+            STATUS_PORT => self.tape_control = byte,
+            TAPE_DATA_PORT => {
+                if let Err(err) = self.cassette.write_byte(byte) {
+                    self.error.get_or_insert(err.into());
+                }
+            }
+            DISPLAY_CONTROL_PORT => self.display_control = byte,
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,387 @@
+//! Taito's Space Invaders (1978) arcade machine.
+//!
+//! The program ROMs occupy 0x0000-0x1FFF and the RAM 0x2000-0x3FFF, of which 0x2400-0x3FFF is the
+//! video RAM. The monitor is rotated 90 degrees counterclockwise, so the 256×224 frame buffer is
+//! seen as a 224×256 screen.
+
+use std::{fs::File, path::Path};
+
+use crate::{
+    cpu::{Instruction, Io},
+    sound::{Sound, SoundLog, Voice, Waveform},
+    video::{Bitmap, Frame, Overlay, Rotation, BLACK, WHITE},
+    wav::Wave,
+    Error, Intel8080, Result,
+};
+
+/// The names of the ROM files in the order of the addresses that they are loaded at.
+pub const ROM_FILES: [&str; 4] = ["invaders.h", "invaders.g", "invaders.f", "invaders.e"];
+
+/// The width of the screen in pixels.
+pub const SCREEN_WIDTH: usize = 224;
+
+/// The height of the screen in pixels.
+pub const SCREEN_HEIGHT: usize = 256;
+
+/// The start address of the video RAM.
+pub const VIDEO_RAM_START: u16 = 0x2400;
+
+/// The clock rate of the CPU in hertz.
+pub const CLOCK_RATE: u32 = 2_000_000;
+
+/// The number of states that the CPU executes per 60 Hz frame.
+pub const STATES_PER_FRAME: u64 = CLOCK_RATE as u64 / 60;
+
+/// The names of the sample files of the sounds in the order of [`sample_voices`], which follow
+/// the common naming of Space Invaders sample sets.
+///
+/// [`sample_voices`]: fn.sample_voices.html
+pub const SAMPLE_FILES: [&str; 10] =
+    ["0.wav", "1.wav", "2.wav", "3.wav", "4.wav", "5.wav", "6.wav", "7.wav", "8.wav", "9.wav"];
+
+// The port bits that trigger the sounds, and whether the sounds repeat while their bits are on:
+// the UFO, the shot, the player's death, the invader's death, the four notes of the fleet
+// movement, the UFO hit, and the extra ship.
+const SOUND_TRIGGERS: [(u8, u8, bool); 10] = [
+    (3, 0x01, true),
+    (3, 0x02, false),
+    (3, 0x04, false),
+    (3, 0x08, false),
+    (5, 0x01, false),
+    (5, 0x02, false),
+    (5, 0x04, false),
+    (5, 0x08, false),
+    (5, 0x10, false),
+    (3, 0x10, false),
+];
+
+// The interrupt that the hardware raises when the beam reaches the middle of the screen.
+const MID_SCREEN_INTERRUPT: Instruction = [0xCF, 0, 0]; // RST 1
+
+// The interrupt that the hardware raises when the beam reaches the bottom of the screen.
+const VBLANK_INTERRUPT: Instruction = [0xD7, 0, 0]; // RST 2
+
+/// The controls of a player.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Controls {
+    /// The fire button.
+    pub shot: bool,
+    /// The joystick is pushed left.
+    pub left: bool,
+    /// The joystick is pushed right.
+    pub right: bool,
+}
+
+impl Controls {
+    fn bits(self) -> u8 {
+        u8::from(self.shot) << 4 | u8::from(self.left) << 5 | u8::from(self.right) << 6
+    }
+}
+
+/// The state of the coin slot, the buttons, the joysticks, and the tilt switch. `true` means that
+/// the switch is closed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Inputs {
+    /// A coin is being inserted.
+    pub coin: bool,
+    /// The 1 player start button.
+    pub player1_start: bool,
+    /// The 2 player start button.
+    pub player2_start: bool,
+    /// The controls of player 1.
+    pub player1: Controls,
+    /// The controls of player 2. Upright cabinets share player 1's controls between players.
+    pub player2: Controls,
+    /// The tilt switch.
+    pub tilt: bool,
+}
+
+/// The DIP switches on the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DipSwitches {
+    /// The number of ships per game, which is 3, 4, 5, or 6.
+    pub ships: u8,
+    /// Awards the extra ship at 1,000 points instead of 1,500 points.
+    pub extra_ship_at_1000: bool,
+    /// Shows the coin information in the demo screen.
+    pub coin_info: bool,
+}
+
+impl Default for DipSwitches {
+    fn default() -> Self {
+        Self { ships: 3, extra_ship_at_1000: false, coin_info: true }
+    }
+}
+
+/// A Space Invaders machine.
+pub struct SpaceInvaders {
+    /// The CPU and the memory.
+    pub i8080: Intel8080,
+    /// The cabinet controls.
+    pub inputs: Inputs,
+    /// The DIP switches, which the game reads at any time.
+    pub dip_switches: DipSwitches,
+    hardware: Hardware,
+    states: u64,
+}
+
+impl SpaceInvaders {
+    /// Creates a machine with the ROM files in [`ROM_FILES`] located in `rom_dir` loaded.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error ([`Error::FileNotFound`], [`Error::Io`], or
+    /// [`Error::TooLargeFile`]) if a ROM file cannot be read successfully or the ROM files do not
+    /// fit into 0x0000-0x1FFF.
+    ///
+    /// [`ROM_FILES`]: constant.ROM_FILES.html
+    /// [`Error::FileNotFound`]: ../../enum.Error.html#variant.FileNotFound
+    /// [`Error::Io`]: ../../enum.Error.html#variant.Io
+    /// [`Error::TooLargeFile`]: ../../enum.Error.html#variant.TooLargeFile
+    pub fn new<P: AsRef<Path>>(rom_dir: P) -> Result<Self> {
+        let rom_dir = rom_dir.as_ref();
+        let paths: Vec<_> = ROM_FILES.iter().map(|name| rom_dir.join(name)).collect();
+        let mut i8080 = Intel8080::default();
+        let mut start_address = 0x0000;
+        for path in paths {
+            let end_address = i8080.memory.load_file(&path, start_address)?;
+            if end_address > VIDEO_RAM_START - 0x0400 {
+                let size = u64::from(end_address - start_address);
+                return Err(Error::TooLargeFile { path, size, start_address });
+            }
+            start_address = end_address;
+        }
+        Ok(Self::with_i8080(i8080))
+    }
+
+    /// Creates a machine with `rom`, the concatenation of the ROM files, loaded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rom` is larger than 8K.
+    pub fn from_rom(rom: &[u8]) -> Self {
+        assert!(rom.len() <= 0x2000, "the ROM is larger than 8K");
+        let mut i8080 = Intel8080::default();
+        i8080.memory[0x0000..rom.len() as u16].copy_from_slice(rom);
+        Self::with_i8080(i8080)
+    }
+
+    fn with_i8080(i8080: Intel8080) -> Self {
+        Self {
+            i8080,
+            inputs: Inputs::default(),
+            dip_switches: DipSwitches::default(),
+            hardware: Hardware::default(),
+            states: 0,
+        }
+    }
+
+    /// Returns the number of states that have elapsed since the machine was created.
+    pub fn states(&self) -> u64 {
+        self.states
+    }
+
+    /// Returns the latest bytes written to the sound ports 3 and 5.
+    pub fn sound_ports(&self) -> (u8, u8) {
+        (self.hardware.sound_log.latch(3), self.hardware.sound_log.latch(5))
+    }
+
+    /// Returns the record of the writes to the sound ports 3 and 5, whose timestamps can be
+    /// converted into time with [`CLOCK_RATE`].
+    ///
+    /// [`CLOCK_RATE`]: constant.CLOCK_RATE.html
+    pub fn sound_log(&self) -> &SoundLog {
+        &self.hardware.sound_log
+    }
+
+    /// Returns a mutable reference to the record of the writes to the sound ports, through which
+    /// rendered events can be cleared.
+    pub fn sound_log_mut(&mut self) -> &mut SoundLog {
+        &mut self.hardware.sound_log
+    }
+
+    /// Runs the CPU for a frame, raising the mid-screen interrupt (RST 1) halfway through and the
+    /// vertical blank interrupt (RST 2) at the end. Interrupts that arrive while the game has
+    /// interrupts disabled are dropped, as on the real hardware.
+    pub fn run_frame(&mut self) {
+        let frame_start = self.states - self.states % STATES_PER_FRAME;
+        self.run_until(frame_start + STATES_PER_FRAME / 2);
+        self.interrupt(MID_SCREEN_INTERRUPT);
+        self.run_until(frame_start + STATES_PER_FRAME);
+        self.interrupt(VBLANK_INTERRUPT);
+    }
+
+    fn run_until(&mut self, states: u64) {
+        self.hardware.update_inputs(self.inputs, self.dip_switches);
+        while self.states < states {
+            self.hardware.states = self.states;
+            match self.i8080.fetch_execute_instruction_with_io(&mut self.hardware) {
+                Ok((_, instruction_states)) => self.states += u64::from(instruction_states),
+                // The CPU idles until the next interrupt.
+                Err(_) => self.states = states,
+            }
+        }
+    }
+
+    fn interrupt(&mut self, instruction: Instruction) {
+        if let Ok(states) = self.i8080.interrupt_with_io(instruction, &mut self.hardware) {
+            self.states += u64::from(states);
+        }
+    }
+
+    /// Returns the video RAM, 0x2400-0x3FFF, as the hardware scans it: each run of 32 bytes is
+    /// a scan line of 256 pixels with the least significant bit of each byte on the left.
+    pub fn video_ram(&self) -> &[u8] {
+        &self.i8080.memory[VIDEO_RAM_START..0x4000]
+    }
+
+    /// Returns the layout of the frame buffer, with the red and green strips of cellophane that
+    /// upright cabinets have glued to the monitor.
+    pub fn bitmap() -> Bitmap {
+        const RED: [u8; 3] = [0xFF, 0x20, 0x20];
+        const GREEN: [u8; 3] = [0x20, 0xFF, 0x20];
+        Bitmap {
+            start: VIDEO_RAM_START,
+            width: SCREEN_HEIGHT,
+            height: SCREEN_WIDTH,
+            bits_per_pixel: 1,
+            stride: SCREEN_HEIGHT / 8,
+            lsb_first: true,
+            rotation: Rotation::Counterclockwise90,
+            palette: vec![BLACK, WHITE],
+            overlays: vec![
+                // The UFO.
+                Overlay { x: 0, y: 32, width: SCREEN_WIDTH, height: 32, color: RED },
+                // The shields and the player's cannon.
+                Overlay { x: 0, y: 184, width: SCREEN_WIDTH, height: 56, color: GREEN },
+                // The reserve cannons, but not the credit count.
+                Overlay { x: 16, y: 240, width: 118, height: 16, color: GREEN },
+            ],
+        }
+    }
+
+    /// Renders the screen in color, as seen through the overlays of [`bitmap`].
+    ///
+    /// [`bitmap`]: #method.bitmap
+    pub fn render(&self) -> Frame {
+        Self::bitmap().render(&self.i8080.memory)
+    }
+
+    /// Returns the pixels of the screen as seen in the cabinet, in row-major order from the top
+    /// left corner. There are [`SCREEN_WIDTH`] × [`SCREEN_HEIGHT`] pixels, and `true` means that a
+    /// pixel is lit.
+    ///
+    /// [`SCREEN_WIDTH`]: constant.SCREEN_WIDTH.html
+    /// [`SCREEN_HEIGHT`]: constant.SCREEN_HEIGHT.html
+    pub fn pixels(&self) -> Vec<bool> {
+        let mut pixels = vec![false; SCREEN_WIDTH * SCREEN_HEIGHT];
+        for (index, byte) in self.video_ram().iter().enumerate() {
+            // A scan line of the frame buffer is a column of the screen drawn from the bottom.
+            let x = index / 32;
+            for bit in 0..8 {
+                let y = SCREEN_HEIGHT - 1 - (index % 32 * 8 + bit);
+                pixels[y * SCREEN_WIDTH + x] = byte & 1 << bit != 0;
+            }
+        }
+        pixels
+    }
+}
+
+// The devices on the I/O ports.
+#[derive(Default)]
+struct Hardware {
+    ports: [u8; 3],
+    shift_register: u16,
+    shift_offset: u8,
+    sound_log: SoundLog,
+    // The number of states elapsed before the current instruction.
+    states: u64,
+}
+
+impl Hardware {
+    fn update_inputs(&mut self, inputs: Inputs, dip_switches: DipSwitches) {
+        // Port 0 is not read by the game. Bits 1-3 are always 1.
+        self.ports[0] = 0x0E | inputs.player1.bits();
+        self.ports[1] = u8::from(inputs.coin)
+            | u8::from(inputs.player2_start) << 1
+            | u8::from(inputs.player1_start) << 2
+            | 0x08
+            | inputs.player1.bits();
+        self.ports[2] = (dip_switches.ships.clamp(3, 6) - 3)
+            | u8::from(inputs.tilt) << 2
+            | u8::from(dip_switches.extra_ship_at_1000) << 3
+            | inputs.player2.bits()
+            | u8::from(!dip_switches.coin_info) << 7;
+    }
+}
+
+impl Io for Hardware {
+    fn input(&mut self, port: u8) -> u8 {
+        match port {
+            0..=2 => self.ports[usize::from(port)],
+            3 => (self.shift_register >> (8 - self.shift_offset)) as u8,
+            _ => 0,
+        }
+    }
+
+    fn output(&mut self, port: u8, byte: u8) {
+        match port {
+            2 => self.shift_offset = byte & 0x07,
+            3 | 5 => self.sound_log.record(self.states, port, byte),
+            4 => self.shift_register = u16::from(byte) << 8 | self.shift_register >> 8,
+            // Port 6 resets the watchdog timer, which is not emulated.
+            _ => {}
+        }
+    }
+}
+
+/// Returns the voices that approximate the sound circuits with synthesized tones and noise.
+pub fn synthesized_voices() -> Vec<Voice> {
+    let tone = |waveform, start_frequency, end_frequency, duration| Sound::Tone {
+        waveform,
+        start_frequency,
+        end_frequency,
+        duration,
+    };
+    let sounds = [
+        tone(Waveform::Triangle, 500.0, 1000.0, 0.1),
+        tone(Waveform::Noise, 4000.0, 1000.0, 0.35),
+        tone(Waveform::Noise, 2000.0, 200.0, 1.0),
+        tone(Waveform::Noise, 3000.0, 600.0, 0.25),
+        tone(Waveform::Square, 110.0, 100.0, 0.1),
+        tone(Waveform::Square, 98.0, 90.0, 0.1),
+        tone(Waveform::Square, 87.0, 80.0, 0.1),
+        tone(Waveform::Square, 82.0, 75.0, 0.1),
+        tone(Waveform::Square, 1200.0, 300.0, 1.0),
+        tone(Waveform::Square, 1000.0, 1000.0, 0.8),
+    ];
+    voices(sounds.iter().cloned())
+}
+
+/// Returns the voices that play the samples in [`SAMPLE_FILES`] located in `sample_dir`.
+///
+/// # Errors
+///
+/// This function will return an error ([`Error::FileNotFound`] or [`Error::Io`]) if a sample file
+/// cannot be read successfully or it is not a WAV file of 8-bit or 16-bit PCM audio.
+///
+/// [`SAMPLE_FILES`]: constant.SAMPLE_FILES.html
+/// [`Error::FileNotFound`]: ../../enum.Error.html#variant.FileNotFound
+/// [`Error::Io`]: ../../enum.Error.html#variant.Io
+pub fn sample_voices<P: AsRef<Path>>(sample_dir: P) -> Result<Vec<Voice>> {
+    let mut sounds = Vec::with_capacity(SAMPLE_FILES.len());
+    for name in &SAMPLE_FILES {
+        let path = sample_dir.as_ref().join(name);
+        let file = File::open(&path).map_err(|err| Error::from_open(err, &path))?;
+        sounds.push(Sound::Sample(Wave::read(file)?));
+    }
+    Ok(voices(sounds.into_iter()))
+}
+
+fn voices<I: Iterator<Item = Sound>>(sounds: I) -> Vec<Voice> {
+    SOUND_TRIGGERS
+        .iter()
+        .zip(sounds)
+        .map(|(&(port, mask, looping), sound)| Voice { port, mask, sound, looping, volume: 0.25 })
+        .collect()
+}
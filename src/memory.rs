@@ -1,16 +1,17 @@
-use std::{
-    fs::File,
-    io::{self, Read},
-    ops::{Deref, DerefMut, Index, IndexMut, Range, RangeFrom},
-    path::Path,
-};
-
-use backtrace::Backtrace;
+use core::ops::{Deref, DerefMut, Index, IndexMut, Range, RangeFrom};
+#[cfg(feature = "std")]
+use std::{fs::File, io::Read, path::Path};
 
 use crate::{Error, Result};
 
 const MEMORY_SIZE: usize = 65536;
 
+// The memory that the CPU reads and writes: `Memory`, or a wrapper around it that watches or refuses
+// the writes made through `IndexMut`.
+pub(crate) trait Bus: IndexMut<u16, Output = u8> {}
+
+impl<M: IndexMut<u16, Output = u8> + ?Sized> Bus for M {}
+
 /// A 64K memory.
 pub struct Memory([u8; MEMORY_SIZE]);
 
@@ -20,6 +21,25 @@ impl Memory {
         Self([0; MEMORY_SIZE])
     }
 
+    /// Loads `bytes` into memory starting at `start_address`, returning the address that follows
+    /// them.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::TooLargeImage`] error if `bytes` are too many to be
+    /// loaded into memory starting at `start_address`.
+    ///
+    /// [`Error::TooLargeImage`]: ../enum.Error.html#variant.TooLargeImage
+    pub fn load_bytes(&mut self, bytes: &[u8], start_address: u16) -> Result<u16> {
+        if usize::from(start_address) + bytes.len() > self.len() {
+            return Err(Error::TooLargeImage { size: bytes.len(), start_address });
+        }
+        let end_address = start_address.wrapping_add(bytes.len() as u16);
+        self.0[usize::from(start_address)..usize::from(start_address) + bytes.len()]
+            .copy_from_slice(bytes);
+        Ok(end_address)
+    }
+
     /// Loads ROM files located at `paths` into memory starting at `start_address`.
     ///
     /// # Errors
@@ -31,6 +51,7 @@ impl Memory {
     /// [`Error::FileNotFound`]: ../enum.Error.html#variant.FileNotFound
     /// [`Error::Io`]: ../enum.Error.html#variant.Io
     /// [`Error::TooLargeFile`]: ../enum.Error.html#variant.TooLargeFile
+    #[cfg(feature = "std")]
     pub fn load_files<P: AsRef<Path>>(
         &mut self,
         paths: &[P],
@@ -53,19 +74,10 @@ impl Memory {
     /// [`Error::FileNotFound`]: ../enum.Error.html#variant.FileNotFound
     /// [`Error::Io`]: ../enum.Error.html#variant.Io
     /// [`Error::TooLargeFile`]: ../enum.Error.html#variant.TooLargeFile
+    #[cfg(feature = "std")]
     pub fn load_file<P: AsRef<Path>>(&mut self, path: P, start_address: u16) -> Result<u16> {
         let path = path.as_ref();
-        let mut file = File::open(path).map_err(|err| {
-            if let io::ErrorKind::NotFound = err.kind() {
-                Error::FileNotFound {
-                    path: path.to_path_buf(),
-                    source: err,
-                    backtrace: Backtrace::new(),
-                }
-            } else {
-                Error::Io { source: err, backtrace: Backtrace::new() }
-            }
-        })?;
+        let mut file = File::open(path).map_err(|err| Error::from_open(err, path))?;
         let size = file.metadata()?.len();
         if u64::from(start_address) + size > self.len() as u64 {
             return Err(Error::TooLargeFile { path: path.to_path_buf(), size, start_address });
@@ -99,12 +111,14 @@ impl DerefMut for Memory {
 impl Index<u16> for Memory {
     type Output = u8;
 
+    #[inline]
     fn index(&self, address: u16) -> &Self::Output {
         &self.0[usize::from(address)]
     }
 }
 
 impl IndexMut<u16> for Memory {
+    #[inline]
     fn index_mut(&mut self, address: u16) -> &mut Self::Output {
         &mut self.0[usize::from(address)]
     }
@@ -0,0 +1,133 @@
+//! Paper tape readers and punches.
+//!
+//! A [`PaperTape`] is a [`Console`] that reads the tape in its reader at a fixed number of
+//! characters per second and punches the bytes written to it. Attached to the serial port of a
+//! machine, it feeds the bootstrap loaders of Altair BASIC and other MITS software exactly as a
+//! Teletype ASR-33 or a high-speed reader did.
+//!
+//! [`PaperTape`]: struct.PaperTape.html
+//! [`Console`]: ../console/trait.Console.html
+
+use std::{
+    collections::VecDeque,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{console::Console, Error, Result};
+
+/// The speed of the reader of a Teletype ASR-33 in characters per second.
+pub const TELETYPE_SPEED: u32 = 10;
+
+/// A paper tape reader and punch.
+///
+/// Reading the reader before the next character is under the read head returns the last
+/// character read again, as the UART that the reader is attached to does.
+#[derive(Debug)]
+pub struct PaperTape {
+    reader: VecDeque<u8>,
+    punched: Vec<u8>,
+    punch_file: Option<File>,
+    states_per_character: u64,
+    // The number of states until the next character is under the read head.
+    states_until_ready: u64,
+    last_character: u8,
+}
+
+impl PaperTape {
+    /// Creates a reader and punch with no tape loaded, which reads `characters_per_second`
+    /// characters per second when attached to a CPU running at `clock_rate` hertz.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `characters_per_second` is 0.
+    pub fn new(clock_rate: u32, characters_per_second: u32) -> Self {
+        assert!(characters_per_second > 0, "the reader does not move");
+        Self {
+            reader: VecDeque::new(),
+            punched: Vec::new(),
+            punch_file: None,
+            states_per_character: u64::from(clock_rate / characters_per_second),
+            states_until_ready: 0,
+            last_character: 0x00,
+        }
+    }
+
+    /// Loads `tape` into the reader, replacing the rest of the tape that was there.
+    pub fn load(&mut self, tape: &[u8]) {
+        self.reader = tape.iter().copied().collect();
+        self.states_until_ready = self.states_per_character;
+    }
+
+    /// Loads the paper tape image located at `path` into the reader.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error ([`Error::FileNotFound`] or [`Error::Io`]) if the tape
+    /// image cannot be read successfully.
+    ///
+    /// [`Error::FileNotFound`]: ../enum.Error.html#variant.FileNotFound
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let tape = fs::read(path).map_err(|err| Error::from_open(err, path))?;
+        self.load(&tape);
+        Ok(())
+    }
+
+    /// Appends the bytes punched from now on to the file located at `path`, which is created if
+    /// it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::Io`] error if the file cannot be opened
+    /// successfully.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn punch_to_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        self.punch_file = Some(OpenOptions::new().append(true).create(true).open(path)?);
+        Ok(())
+    }
+
+    /// Returns the bytes that have yet to be read.
+    pub fn reader(&self) -> &VecDeque<u8> {
+        &self.reader
+    }
+
+    /// Returns the bytes that have been punched.
+    pub fn punched(&self) -> &[u8] {
+        &self.punched
+    }
+}
+
+impl Console for PaperTape {
+    fn is_input_ready(&mut self) -> bool {
+        !self.reader.is_empty() && self.states_until_ready == 0
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        match self.reader.front() {
+            None => Ok(None),
+            Some(_) if self.states_until_ready > 0 => Ok(Some(self.last_character)),
+            Some(&byte) => {
+                self.reader.pop_front();
+                self.last_character = byte;
+                self.states_until_ready = self.states_per_character;
+                Ok(Some(byte))
+            }
+        }
+    }
+
+    fn write_byte(&mut self, byte: u8) -> io::Result<()> {
+        self.punched.push(byte);
+        if let Some(file) = &mut self.punch_file {
+            file.write_all(&[byte])?;
+        }
+        Ok(())
+    }
+
+    fn elapse(&mut self, states: u32) {
+        self.states_until_ready = self.states_until_ready.saturating_sub(u64::from(states));
+    }
+}
@@ -0,0 +1,321 @@
+//! A static recompiler, which translates the 8080 code of a ROM image into Rust source.
+//!
+//! [`Program::new`] disassembles the image by recursive descent, following jumps, calls, and
+//! restarts from the entry points, and splits the code that it reaches into blocks of
+//! straight-line code. [`Program::to_rust`] emits a module whose `run_with_io` function executes
+//! the blocks natively on the same [`Cpu`] and [`Memory`] as [`Cpu::run_with_io`] does, with the
+//! same results:
+//!
+//! ```text
+//! pub fn run_with_io<I: Io + ?Sized, F: FnMut(u16) -> bool>(
+//!     cpu: &mut Cpu,
+//!     memory: &mut Memory,
+//!     io: &mut I,
+//!     states: u64,
+//!     is_breakpoint: F,
+//! ) -> u64
+//! ```
+//!
+//! The generated code falls back to the interpreter for the code that it cannot know statically:
+//! the targets of computed jumps (PCHL), the code outside the image, and the blocks that have been
+//! modified, which it checks before entering a block and after each instruction in a block that
+//! writes memory. The `i8080-recompile` binary writes the module for an image file.
+//!
+//! [`Program::new`]: struct.Program.html#method.new
+//! [`Program::to_rust`]: struct.Program.html#method.to_rust
+//! [`Cpu`]: ../cpu/struct.Cpu.html
+//! [`Memory`]: ../memory/struct.Memory.html
+//! [`Cpu::run_with_io`]: ../cpu/struct.Cpu.html#method.run_with_io
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    ops::Range,
+};
+
+use crate::{
+    cpu::{Instruction, MemoryAccess, INTEL8080_OPCODES},
+    Error, Result,
+};
+
+/// The code of a ROM image that is reachable from its entry points.
+#[derive(Debug)]
+pub struct Program {
+    image: Vec<u8>,
+    origin: u16,
+    // The instructions of each block by its start address.
+    blocks: BTreeMap<u16, Vec<(u16, Instruction)>>,
+}
+
+// How an instruction passes control on.
+enum Flow {
+    Next,
+    Jump(u16),
+    ConditionalJump(u16),
+    // CALL, Ccc, and RST, which return to the next instruction
+    Call(u16),
+    Return,
+    ConditionalReturn,
+    // PCHL
+    Computed,
+    // HLT, after which an interrupt returns to the next instruction
+    Halt,
+}
+
+impl Program {
+    /// Disassembles `image`, which is loaded into memory starting at `origin`, from
+    /// `entry_points`. The entry points outside the image are ignored.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an [`Error::TooLargeImage`] error if `image` is too large to be
+    /// loaded into memory starting at `origin`.
+    ///
+    /// [`Error::TooLargeImage`]: ../enum.Error.html#variant.TooLargeImage
+    pub fn new(image: &[u8], origin: u16, entry_points: &[u16]) -> Result<Self> {
+        if image.len() > 0x10000 - usize::from(origin) {
+            return Err(Error::TooLargeImage { size: image.len(), start_address: origin });
+        }
+        let mut program = Self { image: image.to_vec(), origin, blocks: BTreeMap::new() };
+
+        let mut instructions = BTreeMap::new();
+        let mut block_starts: BTreeSet<u16> = entry_points.iter().copied().collect();
+        let mut pending: Vec<u16> = entry_points.to_vec();
+        while let Some(address) = pending.pop() {
+            if instructions.contains_key(&address) {
+                continue;
+            }
+            let instruction = match program.instruction(address) {
+                Some(instruction) => instruction,
+                None => continue,
+            };
+            instructions.insert(address, instruction);
+            let next_address = address + u16::from(length(instruction));
+            let (target, is_followed) = match flow(instruction) {
+                Flow::Next => {
+                    pending.push(next_address);
+                    continue;
+                }
+                Flow::Jump(target) => (Some(target), false),
+                Flow::ConditionalJump(target) | Flow::Call(target) => (Some(target), true),
+                Flow::Return | Flow::Computed => (None, false),
+                Flow::ConditionalReturn | Flow::Halt => (None, true),
+            };
+            for address in target.into_iter().chain(Some(next_address).filter(|_| is_followed)) {
+                block_starts.insert(address);
+                pending.push(address);
+            }
+        }
+
+        for &start in &block_starts {
+            let mut block = Vec::new();
+            let mut address = start;
+            while let Some(&instruction) = instructions.get(&address) {
+                block.push((address, instruction));
+                address += u16::from(length(instruction));
+                if !matches!(flow(instruction), Flow::Next) || block_starts.contains(&address) {
+                    break;
+                }
+            }
+            if !block.is_empty() {
+                program.blocks.insert(start, block);
+            }
+        }
+        Ok(program)
+    }
+
+    /// Returns the addresses of the blocks of straight-line code in ascending order of their start
+    /// addresses.
+    pub fn blocks(&self) -> impl Iterator<Item = Range<u16>> + '_ {
+        self.blocks.iter().map(|(&start, block)| start..block_end(block))
+    }
+
+    /// Returns the source of a Rust module that runs the program, noting that it was recompiled
+    /// from `source`.
+    pub fn to_rust(&self, source: &str) -> String {
+        let mut rust = String::new();
+        let _ = writeln!(
+            rust,
+            "// Recompiled by i8080-recompile from {} loaded at {}. Do not edit.",
+            source,
+            hex(format!("{:04X}", self.origin)),
+        );
+        rust.push_str(PRELUDE);
+
+        let _ = writeln!(rust, "const ORIGIN: u16 = {:#06X};", self.origin);
+        let _ = writeln!(rust, "const IMAGE: [u8; {}] = [", self.image.len());
+        for chunk in self.image.chunks(16) {
+            let bytes: Vec<String> = chunk.iter().map(|byte| format!("{:#04X},", byte)).collect();
+            let _ = writeln!(rust, "    {}", bytes.join(" "));
+        }
+        let _ = writeln!(rust, "];");
+        rust.push_str(RUN_START);
+
+        for (&start, block) in &self.blocks {
+            let end = block_end(block);
+            let _ = writeln!(
+                rust,
+                "{:16}{:#06X} if is_unchanged(memory, {:#06X}, {:#06X}) => {{",
+                "", start, start, end
+            );
+            for (index, &(address, instruction)) in block.iter().enumerate() {
+                let bytes: Vec<String> =
+                    instruction.iter().map(|byte| format!("{:#04X}", byte)).collect();
+                let _ = writeln!(
+                    rust,
+                    "{:20}execute!({:#06X}, [{}]); // {}",
+                    "",
+                    address,
+                    bytes.join(", "),
+                    disassemble(instruction),
+                );
+                let next_address = address + u16::from(length(instruction));
+                let access = INTEL8080_OPCODES[usize::from(instruction[0])].memory_access;
+                if index + 1 < block.len() && access.intersects(WRITES) {
+                    let _ = writeln!(
+                        rust,
+                        "{:20}if !is_unchanged(memory, {:#06X}, {:#06X}) {{",
+                        "", next_address, end
+                    );
+                    let _ = writeln!(rust, "{:24}continue;", "");
+                    let _ = writeln!(rust, "{:20}}}", "");
+                }
+            }
+            let _ = writeln!(rust, "{:16}}}", "");
+        }
+        rust.push_str(RUN_END);
+        rust
+    }
+
+    // Returns the instruction at `address` unless it lies partly outside the image or ends at
+    // the last address, past which the generated code cannot compare memory with the image.
+    fn instruction(&self, address: u16) -> Option<Instruction> {
+        let offset = usize::from(address.checked_sub(self.origin)?);
+        let opcode = *self.image.get(offset)?;
+        let length = usize::from(INTEL8080_OPCODES[usize::from(opcode)].length);
+        if offset + length > self.image.len() || usize::from(address) + length > 0xFFFF {
+            return None;
+        }
+        let mut instruction = [0; 3];
+        instruction[..length].copy_from_slice(&self.image[offset..offset + length]);
+        Some(instruction)
+    }
+}
+
+const WRITES: MemoryAccess = MemoryAccess::from_bits_truncate(
+    MemoryAccess::WRITE_M.bits()
+        | MemoryAccess::WRITE_INDIRECT.bits()
+        | MemoryAccess::WRITE_DIRECT.bits()
+        | MemoryAccess::WRITE_STACK.bits(),
+);
+
+const PRELUDE: &str = "
+use i8080::{
+    cpu::{Cpu, Io, Variant},
+    memory::Memory,
+};
+
+";
+
+const RUN_START: &str = "
+/// Runs the program as `Cpu::run_with_io` does until the program counter reaches an address for
+/// which `is_breakpoint` returns `true`, the CPU halts, or at least `states` states have elapsed,
+/// returning the number of states taken.
+#[allow(clippy::cognitive_complexity)]
+pub fn run_with_io<I: Io + ?Sized, F: FnMut(u16) -> bool>(
+    cpu: &mut Cpu,
+    memory: &mut Memory,
+    io: &mut I,
+    states: u64,
+    mut is_breakpoint: F,
+) -> u64 {
+    if let Variant::Intel8085 | Variant::Z80 = cpu.variant() {
+        return cpu.run_with_io(memory, io, states, is_breakpoint);
+    }
+    cpu.run_recompiled(|cpu| {
+        let mut elapsed = 0;
+        macro_rules! execute {
+            ($address:expr, $instruction:expr) => {
+                let instruction_states =
+                    cpu.execute_recompiled_instruction($address, $instruction, memory, io);
+                elapsed += u64::from(instruction_states);
+                if elapsed >= states || is_breakpoint(cpu.pc) {
+                    return elapsed;
+                }
+            };
+        }
+        while !cpu.is_halted() {
+            match cpu.pc {
+";
+
+const RUN_END: &str = "                // Computed jumps, modified code, and code outside the image
+                address => {
+                    let instruction = [
+                        memory[address],
+                        memory[address.wrapping_add(1)],
+                        memory[address.wrapping_add(2)],
+                    ];
+                    execute!(address, instruction);
+                }
+            }
+        }
+        elapsed
+    })
+}
+
+// Returns `true` if the code from `start` up to `end` is as it was recompiled.
+fn is_unchanged(memory: &Memory, start: u16, end: u16) -> bool {
+    memory[start..end] == IMAGE[usize::from(start - ORIGIN)..usize::from(end - ORIGIN)]
+}
+";
+
+fn length(instruction: Instruction) -> u8 {
+    INTEL8080_OPCODES[usize::from(instruction[0])].length
+}
+
+fn block_end(block: &[(u16, Instruction)]) -> u16 {
+    let &(address, instruction) = block.last().unwrap();
+    address + u16::from(length(instruction))
+}
+
+fn flow(instruction: Instruction) -> Flow {
+    let target = u16::from_le_bytes([instruction[1], instruction[2]]);
+    match instruction[0] {
+        0x76 => Flow::Halt,
+        // JMP, RET, CALL, and PCHL, including the undocumented ones
+        0xC3 | 0xCB => Flow::Jump(target),
+        0xC9 | 0xD9 => Flow::Return,
+        0xCD | 0xDD | 0xED | 0xFD => Flow::Call(target),
+        0xE9 => Flow::Computed,
+        // Rcc, Jcc, Ccc, and RST
+        opcode @ 0xC0..=0xFF => match opcode & 0x07 {
+            0b000 => Flow::ConditionalReturn,
+            0b010 => Flow::ConditionalJump(target),
+            0b100 => Flow::Call(target),
+            0b111 => Flow::Call(u16::from(opcode & 0x38)),
+            _ => Flow::Next,
+        },
+        _ => Flow::Next,
+    }
+}
+
+// Returns the instruction in assembly language: "MVI A,0FFH" or "JMP 0100H".
+fn disassemble(instruction: Instruction) -> String {
+    let info = &INTEL8080_OPCODES[usize::from(instruction[0])];
+    let operand = match info.length {
+        2 => hex(format!("{:02X}", instruction[1])),
+        3 => hex(format!("{:02X}{:02X}", instruction[2], instruction[1])),
+        _ => return info.mnemonic.to_string(),
+    };
+    let separator = if info.mnemonic.contains(' ') { ',' } else { ' ' };
+    format!("{}{}{}", info.mnemonic, separator, operand)
+}
+
+// Appends the suffix H to hexadecimal `digits`, which need a leading 0 if they start with a letter.
+fn hex(digits: String) -> String {
+    if digits.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        format!("0{}H", digits)
+    } else {
+        format!("{}H", digits)
+    }
+}
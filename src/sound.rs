@@ -0,0 +1,237 @@
+//! Offline rendering of discrete sound circuits.
+//!
+//! Arcade boards such as Space Invaders have no sound chip. Each sound is a separate analog
+//! circuit that is triggered by a bit of an output port. A [`SoundLog`] records the writes to
+//! those ports with the number of states elapsed at the time, and a [`Mixer`] later plays
+//! recorded samples or synthesized approximations of the circuits into PCM audio.
+//!
+//! [`SoundLog`]: struct.SoundLog.html
+//! [`Mixer`]: struct.Mixer.html
+
+use crate::wav::Wave;
+
+/// A write to a sound port.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SoundEvent {
+    /// The number of states elapsed when the byte was written.
+    pub states: u64,
+    /// The output port.
+    pub port: u8,
+    /// The byte written.
+    pub byte: u8,
+}
+
+/// A record of the writes to sound ports. Writes that do not change the value of a port are not
+/// recorded.
+#[derive(Clone, Debug)]
+pub struct SoundLog {
+    events: Vec<SoundEvent>,
+    latches: [u8; 256],
+}
+
+impl SoundLog {
+    /// Creates an empty log with every port latched at 0.
+    pub fn new() -> Self {
+        Self { events: Vec::new(), latches: [0; 256] }
+    }
+
+    /// Records that `byte` was written to `port` when `states` states had elapsed.
+    pub fn record(&mut self, states: u64, port: u8, byte: u8) {
+        if self.latches[usize::from(port)] != byte {
+            self.latches[usize::from(port)] = byte;
+            self.events.push(SoundEvent { states, port, byte });
+        }
+    }
+
+    /// Returns the recorded writes in order.
+    pub fn events(&self) -> &[SoundEvent] {
+        &self.events
+    }
+
+    /// Returns the latest byte written to `port`.
+    pub fn latch(&self, port: u8) -> u8 {
+        self.latches[usize::from(port)]
+    }
+
+    /// Removes the recorded writes, keeping the latched values.
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+impl Default for SoundLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The shape of a synthesized tone.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Waveform {
+    /// A square wave.
+    Square,
+    /// A triangle wave.
+    Triangle,
+    /// White noise, sampled and held at the frequency of the tone.
+    Noise,
+}
+
+/// A sound that a circuit makes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Sound {
+    /// A recording of the circuit.
+    Sample(Wave),
+    /// A tone whose frequency sweeps linearly from `start_frequency` to `end_frequency` over
+    /// `duration` seconds.
+    Tone { waveform: Waveform, start_frequency: f64, end_frequency: f64, duration: f64 },
+}
+
+impl Sound {
+    /// Returns the length in seconds.
+    pub fn duration(&self) -> f64 {
+        match self {
+            Sound::Sample(wave) => wave.duration(),
+            Sound::Tone { duration, .. } => *duration,
+        }
+    }
+
+    // Returns the amplitude, between -1.0 and 1.0, at `time` seconds from the start.
+    fn amplitude(&self, time: f64) -> f64 {
+        match self {
+            Sound::Sample(wave) => {
+                let index = (time * f64::from(wave.sample_rate)) as usize;
+                wave.samples.get(index).map_or(0.0, |&sample| f64::from(sample) / 32768.0)
+            }
+            Sound::Tone { waveform, start_frequency, end_frequency, duration } => {
+                let sweep = if *duration > 0.0 {
+                    (end_frequency - start_frequency) / duration
+                } else {
+                    0.0
+                };
+                let cycles = start_frequency * time + sweep * time * time / 2.0;
+                let phase = cycles.fract();
+                match waveform {
+                    Waveform::Square => {
+                        if phase < 0.5 {
+                            1.0
+                        } else {
+                            -1.0
+                        }
+                    }
+                    Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+                    Waveform::Noise => {
+                        // A hash of the cycle number, so that renders are reproducible.
+                        let mut x = (cycles as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+                        x ^= x >> 29;
+                        x = x.wrapping_mul(0xBF58_476D_1CE4_E5B9);
+                        x ^= x >> 32;
+                        if x & 1 == 0 {
+                            1.0
+                        } else {
+                            -1.0
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A sound circuit and the port bits that trigger it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Voice {
+    /// The output port.
+    pub port: u8,
+    /// The bits of the port that trigger the sound when any of them turns on.
+    pub mask: u8,
+    /// The sound.
+    pub sound: Sound,
+    /// The sound repeats for as long as the bits stay on, rather than playing once to the end.
+    pub looping: bool,
+    /// The volume, between 0.0 and 1.0.
+    pub volume: f64,
+}
+
+/// A mixer that renders sound events into PCM audio.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mixer {
+    /// The clock rate of the CPU in hertz, which converts states into time.
+    pub clock_rate: u32,
+    /// The sample rate of the rendered audio.
+    pub sample_rate: u32,
+    /// The sound circuits.
+    pub voices: Vec<Voice>,
+}
+
+impl Mixer {
+    /// Creates a mixer for a CPU clocked at `clock_rate` hertz that renders `voices` at
+    /// `sample_rate`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `clock_rate` or `sample_rate` is 0.
+    pub fn new(clock_rate: u32, sample_rate: u32, voices: Vec<Voice>) -> Self {
+        assert!(clock_rate != 0, "the clock rate is 0");
+        assert!(sample_rate != 0, "the sample rate is 0");
+        Self { clock_rate, sample_rate, voices }
+    }
+
+    /// Renders the audio of `events` from state 0 until `end_states` states have elapsed. All
+    /// ports are assumed to be 0 at state 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `clock_rate` or `sample_rate` is 0.
+    pub fn render(&self, events: &[SoundEvent], end_states: u64) -> Wave {
+        assert!(self.clock_rate != 0, "the clock rate is 0");
+        assert!(self.sample_rate != 0, "the sample rate is 0");
+        let length = self.sample_index(end_states);
+        let mut mix = vec![0.0; length];
+        for voice in &self.voices {
+            for (start, end) in self.segments(voice, events, length) {
+                for (offset, sample) in mix[start..end].iter_mut().enumerate() {
+                    let mut time = offset as f64 / f64::from(self.sample_rate);
+                    let duration = voice.sound.duration();
+                    if voice.looping && duration > 0.0 {
+                        time %= duration;
+                    }
+                    *sample += voice.volume * voice.sound.amplitude(time);
+                }
+            }
+        }
+        let samples =
+            mix.iter().map(|sample| (sample * 32767.0).clamp(-32768.0, 32767.0) as i16).collect();
+        Wave::new(self.sample_rate, samples)
+    }
+
+    fn sample_index(&self, states: u64) -> usize {
+        (u128::from(states) * u128::from(self.sample_rate) / u128::from(self.clock_rate)) as usize
+    }
+
+    // Returns the ranges of samples during which `voice` plays. A sound that is triggered again
+    // restarts from the beginning.
+    fn segments(&self, voice: &Voice, events: &[SoundEvent], length: usize) -> Vec<(usize, usize)> {
+        let one_shot_length = (voice.sound.duration() * f64::from(self.sample_rate)) as usize;
+        let mut segments: Vec<(usize, usize)> = Vec::new();
+        let mut is_on = false;
+        for event in events.iter().filter(|event| event.port == voice.port) {
+            let index = self.sample_index(event.states).min(length);
+            let was_on = is_on;
+            is_on = event.byte & voice.mask != 0;
+            if is_on && !was_on {
+                if let Some((_, end)) = segments.last_mut() {
+                    *end = (*end).min(index);
+                }
+                // A sample with a sample rate of 0 lasts forever.
+                let end =
+                    if voice.looping { length } else { index.saturating_add(one_shot_length) };
+                segments.push((index, end.min(length)));
+            } else if !is_on && was_on && voice.looping {
+                if let Some((_, end)) = segments.last_mut() {
+                    *end = index;
+                }
+            }
+        }
+        segments
+    }
+}
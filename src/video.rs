@@ -0,0 +1,435 @@
+//! Headless rendering of frame buffers that live in memory.
+//!
+//! A [`Bitmap`] describes how a region of [`Memory`] is scanned out to a monitor, and renders it
+//! into a [`Frame`], which can be written as a PPM or PNG image without a window.
+//!
+//! [`Bitmap`]: struct.Bitmap.html
+//! [`Memory`]: ../memory/struct.Memory.html
+//! [`Frame`]: struct.Frame.html
+
+use std::{
+    convert::TryFrom,
+    io::{self, Write},
+};
+
+use crate::memory::Memory;
+
+/// A color as red, green, and blue intensities.
+pub type Rgb = [u8; 3];
+
+/// Black.
+pub const BLACK: Rgb = [0x00, 0x00, 0x00];
+
+/// White.
+pub const WHITE: Rgb = [0xFF, 0xFF, 0xFF];
+
+/// The orientation of the monitor relative to the order in which the frame buffer is scanned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rotation {
+    /// The monitor is upright.
+    None,
+    /// The monitor is rotated 90 degrees clockwise, so the first scan line is the rightmost
+    /// column, drawn from the top.
+    Clockwise90,
+    /// The monitor is upside down.
+    Rotate180,
+    /// The monitor is rotated 90 degrees counterclockwise, so the first scan line is the leftmost
+    /// column, drawn from the bottom.
+    Counterclockwise90,
+}
+
+/// A rectangle of colored film laid over the monitor, which tints the pixels under it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Overlay {
+    /// The column of the left edge in the rendered frame.
+    pub x: usize,
+    /// The row of the top edge in the rendered frame.
+    pub y: usize,
+    /// The width in pixels.
+    pub width: usize,
+    /// The height in pixels.
+    pub height: usize,
+    /// The color of the film.
+    pub color: Rgb,
+}
+
+/// The layout of a frame buffer in memory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bitmap {
+    /// The address of the first byte of the first scan line.
+    pub start: u16,
+    /// The number of pixels per scan line.
+    pub width: usize,
+    /// The number of scan lines.
+    pub height: usize,
+    /// The number of bits per pixel, which is 1, 2, 4, or 8.
+    pub bits_per_pixel: u8,
+    /// The number of bytes from the start of a scan line to the start of the next one.
+    pub stride: usize,
+    /// The leftmost pixel of a byte is in its least significant bits, rather than its most
+    /// significant bits.
+    pub lsb_first: bool,
+    /// The orientation of the monitor.
+    pub rotation: Rotation,
+    /// The colors of the pixel values. Values without a color are rendered as shades of gray.
+    pub palette: Vec<Rgb>,
+    /// The color overlays, which are applied in order.
+    pub overlays: Vec<Overlay>,
+}
+
+impl Bitmap {
+    /// Creates a monochrome bitmap of `width` × `height` pixels starting at `start`, with the
+    /// scan lines packed without gaps and the leftmost pixel in the most significant bit.
+    pub fn monochrome(start: u16, width: usize, height: usize) -> Self {
+        Self {
+            start,
+            width,
+            height,
+            bits_per_pixel: 1,
+            stride: (width + 7) / 8,
+            lsb_first: false,
+            rotation: Rotation::None,
+            palette: vec![BLACK, WHITE],
+            overlays: Vec::new(),
+        }
+    }
+
+    /// Returns the value of the pixel at `x` in scan line `y`, before rotation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits_per_pixel` is not 1, 2, 4, or 8.
+    pub fn value(&self, memory: &Memory, x: usize, y: usize) -> u8 {
+        self.check_bits_per_pixel();
+        let bits_per_pixel = usize::from(self.bits_per_pixel);
+        let bit = x * bits_per_pixel;
+        let offset = y * self.stride + bit / 8;
+        let byte = memory[self.start.wrapping_add(offset as u16)];
+        let shift = if self.lsb_first { bit % 8 } else { 8 - bits_per_pixel - bit % 8 };
+        (byte >> shift) & (0xFF >> (8 - bits_per_pixel))
+    }
+
+    fn check_bits_per_pixel(&self) {
+        assert!([1, 2, 4, 8].contains(&self.bits_per_pixel), "unsupported bits per pixel");
+    }
+
+    /// Returns the size of the rendered frame as (width, height).
+    pub fn frame_size(&self) -> (usize, usize) {
+        match self.rotation {
+            Rotation::None | Rotation::Rotate180 => (self.width, self.height),
+            Rotation::Clockwise90 | Rotation::Counterclockwise90 => (self.height, self.width),
+        }
+    }
+
+    /// Renders the frame buffer in `memory` as it appears on the monitor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits_per_pixel` is not 1, 2, 4, or 8.
+    pub fn render(&self, memory: &Memory) -> Frame {
+        self.check_bits_per_pixel();
+        let (frame_width, frame_height) = self.frame_size();
+        let mut frame = Frame::new(frame_width, frame_height);
+        let max_value = (0xFF >> (8 - self.bits_per_pixel)) as u8;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let value = self.value(memory, x, y);
+                let color = self.palette.get(usize::from(value)).copied().unwrap_or_else(|| {
+                    let intensity = (u16::from(value) * 0xFF / u16::from(max_value)) as u8;
+                    [intensity; 3]
+                });
+                let (frame_x, frame_y) = match self.rotation {
+                    Rotation::None => (x, y),
+                    Rotation::Clockwise90 => (self.height - 1 - y, x),
+                    Rotation::Rotate180 => (self.width - 1 - x, self.height - 1 - y),
+                    Rotation::Counterclockwise90 => (y, self.width - 1 - x),
+                };
+                frame.set_pixel(frame_x, frame_y, color);
+            }
+        }
+        let tint = |value: u8, tint: u8| (u16::from(value) * u16::from(tint) / 0xFF) as u8;
+        for overlay in &self.overlays {
+            let x_end = (overlay.x + overlay.width).min(frame_width);
+            let y_end = (overlay.y + overlay.height).min(frame_height);
+            for y in overlay.y..y_end {
+                for x in overlay.x..x_end {
+                    let [r, g, b] = frame.pixel(x, y);
+                    let [tint_r, tint_g, tint_b] = overlay.color;
+                    frame.set_pixel(x, y, [tint(r, tint_r), tint(g, tint_g), tint(b, tint_b)]);
+                }
+            }
+        }
+        frame
+    }
+}
+
+/// A rendered image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Frame {
+    /// Creates a black image of `width` × `height` pixels.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height, pixels: vec![BLACK; width * height] }
+    }
+
+    /// Returns the width in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the pixels in row-major order from the top left corner.
+    pub fn pixels(&self) -> &[Rgb] {
+        &self.pixels
+    }
+
+    /// Returns the color of the pixel at (`x`, `y`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pixel is outside the image.
+    pub fn pixel(&self, x: usize, y: usize) -> Rgb {
+        assert!(x < self.width && y < self.height, "pixel outside the image");
+        self.pixels[y * self.width + x]
+    }
+
+    /// Sets the color of the pixel at (`x`, `y`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pixel is outside the image.
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Rgb) {
+        assert!(x < self.width && y < self.height, "pixel outside the image");
+        self.pixels[y * self.width + x] = color;
+    }
+
+    /// Writes the image in the binary PPM (P6) format.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `writer` cannot be written successfully.
+    pub fn write_ppm<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        writer.write_all(&self.pixels.concat())
+    }
+
+    /// Writes the image in the PNG format as 8-bit RGB. The image data is compressed the same way
+    /// every time, so the same image is always encoded into the same bytes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `writer` cannot be written successfully or the image
+    /// is too large for the PNG format.
+    pub fn write_png<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "image too large for PNG");
+        let width = u32::try_from(self.width).map_err(|_| too_large())?;
+        let height = u32::try_from(self.height).map_err(|_| too_large())?;
+
+        writer.write_all(b"\x89PNG\r\n\x1A\n")?;
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&width.to_be_bytes());
+        header.extend_from_slice(&height.to_be_bytes());
+        // Bit depth 8, color type 2 (RGB), deflate compression, adaptive filtering, no interlace.
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+        write_png_chunk(&mut writer, b"IHDR", &header)?;
+
+        // Each scan line is preceded by filter type 0 (None).
+        let mut scan_lines = Vec::with_capacity(self.height * (1 + self.width * 3));
+        for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            scan_lines.push(0);
+            scan_lines.extend(row.iter().flatten());
+        }
+        write_png_chunk(&mut writer, b"IDAT", &zlib(&scan_lines))?;
+
+        write_png_chunk(&mut writer, b"IEND", &[])
+    }
+}
+
+fn write_png_chunk<W: Write>(writer: &mut W, chunk_type: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    let length = u32::try_from(data.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "PNG chunk too large"))?;
+    writer.write_all(&length.to_be_bytes())?;
+    writer.write_all(chunk_type)?;
+    writer.write_all(data)?;
+    let crc = !crc32(crc32(!0, chunk_type), data);
+    writer.write_all(&crc.to_be_bytes())
+}
+
+// The lengths and the distances that the deflate length and distance codes start at, with the
+// numbers of extra bits that follow the codes.
+const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA_BITS: [u8; 29] =
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA_BITS: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+const WINDOW_SIZE: usize = 0x8000;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+// The number of earlier positions with the same hash that are tried for a match.
+const MAX_CHAIN: usize = 64;
+
+// Compresses `data` into a zlib stream of a single deflate block with the fixed Huffman codes,
+// finding repeated strings greedily.
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut stream = BitWriter::default();
+    // Deflate with a 32K window and the fastest compression level.
+    stream.bytes.extend_from_slice(&[0x78, 0x01]);
+    // The final block, compressed with the fixed Huffman codes.
+    stream.write_bits(0b011, 3);
+
+    // The latest position of each hash, and the previous position with the same hash as each
+    // position.
+    let mut head = vec![usize::MAX; 0x10000];
+    let mut previous = vec![usize::MAX; data.len()];
+    let mut position = 0;
+    while position < data.len() {
+        let (mut length, mut distance) = (0, 0);
+        if position + MIN_MATCH <= data.len() {
+            let max_length = MAX_MATCH.min(data.len() - position);
+            let mut candidate = head[hash(data, position)];
+            for _ in 0..MAX_CHAIN {
+                if candidate == usize::MAX || position - candidate > WINDOW_SIZE {
+                    break;
+                }
+                let matched = (0..max_length)
+                    .take_while(|&offset| data[candidate + offset] == data[position + offset])
+                    .count();
+                if matched > length {
+                    length = matched;
+                    distance = position - candidate;
+                    if length == max_length {
+                        break;
+                    }
+                }
+                candidate = previous[candidate];
+            }
+        }
+        if length >= MIN_MATCH {
+            stream.write_length_distance(length, distance);
+            for offset in 0..length {
+                insert(data, &mut head, &mut previous, position + offset);
+            }
+            position += length;
+        } else {
+            stream.write_symbol(u16::from(data[position]));
+            insert(data, &mut head, &mut previous, position);
+            position += 1;
+        }
+    }
+    // End of block
+    stream.write_symbol(256);
+    stream.flush();
+    stream.bytes.extend_from_slice(&adler32(data).to_be_bytes());
+    stream.bytes
+}
+
+fn hash(data: &[u8], position: usize) -> usize {
+    (usize::from(data[position]) << 8
+        ^ usize::from(data[position + 1]) << 4
+        ^ usize::from(data[position + 2]))
+        & 0xFFFF
+}
+
+// Records `position` as the latest position with its hash.
+fn insert(data: &[u8], head: &mut [usize], previous: &mut [usize], position: usize) {
+    if position + MIN_MATCH <= data.len() {
+        let hash = hash(data, position);
+        previous[position] = head[hash];
+        head[hash] = position;
+    }
+}
+
+// Writes bits from the least significant bit of each byte, as deflate streams are packed.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn write_bits(&mut self, bits: u32, count: u32) {
+        self.buffer |= bits << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    // Writes a Huffman code, which is packed from its most significant bit.
+    fn write_code(&mut self, code: u32, length: u32) {
+        self.write_bits(code.reverse_bits() >> (32 - length), length);
+    }
+
+    // Writes a literal/length symbol with the fixed Huffman codes.
+    fn write_symbol(&mut self, symbol: u16) {
+        let symbol = u32::from(symbol);
+        match symbol {
+            0..=143 => self.write_code(0x30 + symbol, 8),
+            144..=255 => self.write_code(0x190 + symbol - 144, 9),
+            256..=279 => self.write_code(symbol - 256, 7),
+            _ => self.write_code(0xC0 + symbol - 280, 8),
+        }
+    }
+
+    fn write_length_distance(&mut self, length: usize, distance: usize) {
+        let (length, distance) = (length as u16, distance as u16);
+        let code = LENGTH_BASES.iter().rposition(|&base| base <= length).unwrap();
+        self.write_symbol(257 + code as u16);
+        let extra = u32::from(length - LENGTH_BASES[code]);
+        self.write_bits(extra, u32::from(LENGTH_EXTRA_BITS[code]));
+        let code = DISTANCE_BASES.iter().rposition(|&base| base <= distance).unwrap();
+        self.write_code(code as u32, 5);
+        let extra = u32::from(distance - DISTANCE_BASES[code]);
+        self.write_bits(extra, u32::from(DISTANCE_EXTRA_BITS[code]));
+    }
+
+    fn flush(&mut self) {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer = 0;
+            self.count = 0;
+        }
+    }
+}
+
+fn crc32(mut crc: u32, data: &[u8]) -> u32 {
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { crc >> 1 ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1_u32, 0_u32);
+    for byte in data {
+        a = (a + u32::from(*byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    b << 16 | a
+}
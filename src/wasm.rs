@@ -0,0 +1,236 @@
+//! The WebAssembly API, which the `wasm` feature enables on `wasm32` targets.
+//!
+//! The functions take and return numbers only, so that JavaScript can call them without a binding
+//! generator; `wasm/i8080.js` wraps them in an `Intel8080` class. An emulator is a pointer that
+//! [`intel8080_new`] returns, and its memory is a view of the 65536 bytes at
+//! [`intel8080_memory`], through which programs are loaded. The I/O ports call the `input` and
+//! `output` functions that the module imports from `i8080`, passing the emulator to them.
+//!
+//! Build the module with
+//!
+//! ```text
+//! cargo rustc --release --target wasm32-unknown-unknown --features wasm --lib --crate-type cdylib
+//! ```
+//!
+//! [`intel8080_new`]: fn.intel8080_new.html
+//! [`intel8080_memory`]: fn.intel8080_memory.html
+
+use crate::{
+    cpu::{ConditionFlags, Cpu, Io, Variant},
+    Intel8080,
+};
+
+/// The program counter for [`intel8080_register`] and [`intel8080_set_register`].
+///
+/// [`intel8080_register`]: fn.intel8080_register.html
+/// [`intel8080_set_register`]: fn.intel8080_set_register.html
+pub const REGISTER_PC: u32 = 0;
+/// The stack pointer.
+pub const REGISTER_SP: u32 = 1;
+/// The accumulator.
+pub const REGISTER_A: u32 = 2;
+/// Register B.
+pub const REGISTER_B: u32 = 3;
+/// Register C.
+pub const REGISTER_C: u32 = 4;
+/// Register D.
+pub const REGISTER_D: u32 = 5;
+/// Register E.
+pub const REGISTER_E: u32 = 6;
+/// Register H.
+pub const REGISTER_H: u32 = 7;
+/// Register L.
+pub const REGISTER_L: u32 = 8;
+/// The condition flags as PUSH PSW stores them.
+pub const REGISTER_FLAGS: u32 = 9;
+
+#[link(wasm_import_module = "i8080")]
+extern "C" {
+    #[link_name = "input"]
+    fn port_input(emulator: usize, port: u8) -> u8;
+    #[link_name = "output"]
+    fn port_output(emulator: usize, port: u8, byte: u8);
+}
+
+/// An Intel 8080 system with its breakpoints.
+pub struct Emulator {
+    i8080: Intel8080,
+    breakpoints: Vec<bool>,
+}
+
+// The I/O ports of an emulator, which call the functions imported from JavaScript with its
+// address.
+struct Ports(usize);
+
+impl Io for Ports {
+    fn input(&mut self, port: u8) -> u8 {
+        unsafe { port_input(self.0, port) }
+    }
+
+    fn output(&mut self, port: u8, byte: u8) {
+        unsafe { port_output(self.0, port, byte) }
+    }
+}
+
+/// Creates an emulator of `variant` (0: Intel 8080A, 1: NEC µPD8080AF, 2: AMD Am9080A, 3: Intel
+/// 8085A, 4: Z80) with the memory cleared. Returns a null pointer if `variant` is unknown.
+#[no_mangle]
+pub extern "C" fn intel8080_new(variant: u32) -> *mut Emulator {
+    let variant = match variant {
+        0 => Variant::Intel8080,
+        1 => Variant::NecUpd8080af,
+        2 => Variant::Amd9080,
+        3 => Variant::Intel8085,
+        4 => Variant::Z80,
+        _ => return core::ptr::null_mut(),
+    };
+    let i8080 = Intel8080 { cpu: Cpu::new(variant), ..Intel8080::default() };
+    Box::into_raw(Box::new(Emulator { i8080, breakpoints: vec![false; 0x10000] }))
+}
+
+/// Destroys `emulator`. A null pointer is ignored.
+///
+/// # Safety
+///
+/// `emulator` must be null or have been returned by [`intel8080_new`] and not destroyed yet.
+///
+/// [`intel8080_new`]: fn.intel8080_new.html
+#[no_mangle]
+pub unsafe extern "C" fn intel8080_free(emulator: *mut Emulator) {
+    if !emulator.is_null() {
+        drop(Box::from_raw(emulator));
+    }
+}
+
+/// Returns a pointer to the 65536 bytes of memory.
+///
+/// # Safety
+///
+/// `emulator` must be a live emulator.
+#[no_mangle]
+pub unsafe extern "C" fn intel8080_memory(emulator: *mut Emulator) -> *mut u8 {
+    (*emulator).i8080.memory.as_mut_ptr()
+}
+
+/// Fetches and executes an instruction, returning the number of states taken, or -1 if the CPU
+/// is in the halt state. The port functions must not call back into the emulator.
+///
+/// # Safety
+///
+/// `emulator` must be a live emulator.
+#[no_mangle]
+pub unsafe extern "C" fn intel8080_step(emulator: *mut Emulator) -> i32 {
+    let mut ports = Ports(emulator as usize);
+    match (*emulator).i8080.fetch_execute_instruction_with_io(&mut ports) {
+        Ok((_, states)) => states as i32,
+        Err(_) => -1,
+    }
+}
+
+/// Fetches and executes instructions until the program counter reaches a breakpoint, the CPU
+/// halts, or at least `states` states have elapsed, returning the number of states taken.
+///
+/// # Safety
+///
+/// `emulator` must be a live emulator.
+#[no_mangle]
+pub unsafe extern "C" fn intel8080_run(emulator: *mut Emulator, states: u32) -> u32 {
+    let mut ports = Ports(emulator as usize);
+    let Emulator { i8080, breakpoints } = &mut *emulator;
+    let elapsed =
+        i8080.run_with_io(&mut ports, u64::from(states), |pc| breakpoints[usize::from(pc)]);
+    elapsed.min(u64::from(u32::MAX)) as u32
+}
+
+/// Sets or clears the breakpoint at `address`.
+///
+/// # Safety
+///
+/// `emulator` must be a live emulator.
+#[no_mangle]
+pub unsafe extern "C" fn intel8080_set_breakpoint(
+    emulator: *mut Emulator,
+    address: u16,
+    is_set: bool,
+) {
+    let emulator = &mut *emulator;
+    emulator.breakpoints[usize::from(address)] = is_set;
+}
+
+/// Escapes from the halt state, if necessary, and executes the one-byte `instruction`, usually an
+/// RST, with further interrupts disabled, returning the number of states taken, or -1 if the
+/// interrupt system is disabled.
+///
+/// # Safety
+///
+/// `emulator` must be a live emulator.
+#[no_mangle]
+pub unsafe extern "C" fn intel8080_interrupt(emulator: *mut Emulator, instruction: u8) -> i32 {
+    let mut ports = Ports(emulator as usize);
+    match (*emulator).i8080.interrupt_with_io([instruction, 0, 0], &mut ports) {
+        Ok(states) => states as i32,
+        Err(_) => -1,
+    }
+}
+
+/// Returns the register `register`, one of the `REGISTER_*` constants, or 0 if it is unknown.
+///
+/// # Safety
+///
+/// `emulator` must be a live emulator.
+#[no_mangle]
+pub unsafe extern "C" fn intel8080_register(emulator: *const Emulator, register: u32) -> u32 {
+    let cpu = &(*emulator).i8080.cpu;
+    match register {
+        REGISTER_PC => u32::from(cpu.pc),
+        REGISTER_SP => u32::from(cpu.sp),
+        REGISTER_A => u32::from(cpu.a),
+        REGISTER_B => u32::from(cpu.b),
+        REGISTER_C => u32::from(cpu.c),
+        REGISTER_D => u32::from(cpu.d),
+        REGISTER_E => u32::from(cpu.e),
+        REGISTER_H => u32::from(cpu.h),
+        REGISTER_L => u32::from(cpu.l),
+        REGISTER_FLAGS => u32::from(cpu.condition_flags.bits()),
+        _ => 0,
+    }
+}
+
+/// Sets the register `register`, one of the `REGISTER_*` constants, to the low bits of `value`.
+/// An unknown register is ignored.
+///
+/// # Safety
+///
+/// `emulator` must be a live emulator.
+#[no_mangle]
+pub unsafe extern "C" fn intel8080_set_register(
+    emulator: *mut Emulator,
+    register: u32,
+    value: u32,
+) {
+    let cpu = &mut (*emulator).i8080.cpu;
+    let (word, byte) = (value as u16, value as u8);
+    match register {
+        REGISTER_PC => cpu.pc = word,
+        REGISTER_SP => cpu.sp = word,
+        REGISTER_A => cpu.a = byte,
+        REGISTER_B => cpu.b = byte,
+        REGISTER_C => cpu.c = byte,
+        REGISTER_D => cpu.d = byte,
+        REGISTER_E => cpu.e = byte,
+        REGISTER_H => cpu.h = byte,
+        REGISTER_L => cpu.l = byte,
+        REGISTER_FLAGS => cpu.condition_flags = ConditionFlags::from_bits_truncate(byte),
+        _ => {}
+    }
+}
+
+/// Returns `true` if the CPU is in the halt state.
+///
+/// # Safety
+///
+/// `emulator` must be a live emulator.
+#[no_mangle]
+pub unsafe extern "C" fn intel8080_is_halted(emulator: *const Emulator) -> bool {
+    (*emulator).i8080.cpu.is_halted()
+}
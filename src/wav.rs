@@ -0,0 +1,127 @@
+//! Reading and writing of PCM audio in the WAV format.
+
+use std::{
+    convert::TryFrom,
+    io::{self, Read, Write},
+};
+
+/// Monaural 16-bit PCM audio.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Wave {
+    /// The number of samples per second.
+    pub sample_rate: u32,
+    /// The samples.
+    pub samples: Vec<i16>,
+}
+
+impl Wave {
+    /// Creates audio of `samples` played at `sample_rate`.
+    pub fn new(sample_rate: u32, samples: Vec<i16>) -> Self {
+        Self { sample_rate, samples }
+    }
+
+    /// Returns the length in seconds.
+    pub fn duration(&self) -> f64 {
+        self.samples.len() as f64 / f64::from(self.sample_rate)
+    }
+
+    /// Reads a WAV file of 8-bit or 16-bit PCM audio. The channels of a stereo file are mixed
+    /// down into one.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `reader` cannot be read successfully or it is not a
+    /// WAV file in a supported format, including a file with a sample rate of 0.
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let invalid = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut header = [0; 12];
+        reader.read_exact(&mut header)?;
+        if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+            return Err(invalid("not a WAV file"));
+        }
+        let mut format = None;
+        loop {
+            let mut chunk_header = [0; 8];
+            reader.read_exact(&mut chunk_header)?;
+            let size = u32::from_le_bytes([
+                chunk_header[4],
+                chunk_header[5],
+                chunk_header[6],
+                chunk_header[7],
+            ]);
+            let mut chunk = Vec::new();
+            // Chunks are padded to an even size.
+            reader.by_ref().take(u64::from(size) + u64::from(size % 2)).read_to_end(&mut chunk)?;
+            if chunk.len() < size as usize {
+                return Err(invalid("truncated WAV file"));
+            }
+            chunk.truncate(size as usize);
+            match &chunk_header[0..4] {
+                b"fmt " => {
+                    if chunk.len() < 16 {
+                        return Err(invalid("truncated format chunk"));
+                    }
+                    let tag = u16::from_le_bytes([chunk[0], chunk[1]]);
+                    let channels = u16::from_le_bytes([chunk[2], chunk[3]]);
+                    let sample_rate = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+                    let bits_per_sample = u16::from_le_bytes([chunk[14], chunk[15]]);
+                    if tag != 1
+                        || channels == 0
+                        || sample_rate == 0
+                        || ![8, 16].contains(&bits_per_sample)
+                    {
+                        return Err(invalid("unsupported WAV format"));
+                    }
+                    format = Some((channels, sample_rate, bits_per_sample));
+                }
+                b"data" => {
+                    let (channels, sample_rate, bits_per_sample) =
+                        format.ok_or_else(|| invalid("data chunk before format chunk"))?;
+                    let samples: Vec<i32> = if bits_per_sample == 8 {
+                        chunk.iter().map(|&byte| (i32::from(byte) - 0x80) << 8).collect()
+                    } else {
+                        chunk
+                            .chunks_exact(2)
+                            .map(|bytes| i32::from(i16::from_le_bytes([bytes[0], bytes[1]])))
+                            .collect()
+                    };
+                    let samples = samples
+                        .chunks_exact(usize::from(channels))
+                        .map(|frame| (frame.iter().sum::<i32>() / i32::from(channels)) as i16)
+                        .collect();
+                    return Ok(Self { sample_rate, samples });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Writes the audio as a WAV file of monaural 16-bit PCM audio.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `writer` cannot be written successfully or the audio
+    /// is too long for the WAV format.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let data_size = u32::try_from(self.samples.len() * 2)
+            .ok()
+            .filter(|size| *size <= u32::MAX - 36)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "audio too long for WAV"))?;
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&(36 + data_size).to_le_bytes())?;
+        writer.write_all(b"WAVEfmt ")?;
+        writer.write_all(&16_u32.to_le_bytes())?;
+        // PCM, 1 channel.
+        writer.write_all(&1_u16.to_le_bytes())?;
+        writer.write_all(&1_u16.to_le_bytes())?;
+        writer.write_all(&self.sample_rate.to_le_bytes())?;
+        writer.write_all(&(self.sample_rate * 2).to_le_bytes())?;
+        // 2 bytes per frame, 16 bits per sample.
+        writer.write_all(&2_u16.to_le_bytes())?;
+        writer.write_all(&16_u16.to_le_bytes())?;
+        writer.write_all(b"data")?;
+        writer.write_all(&data_size.to_le_bytes())?;
+        let bytes: Vec<u8> = self.samples.iter().flat_map(|sample| sample.to_le_bytes()).collect();
+        writer.write_all(&bytes)
+    }
+}
@@ -0,0 +1,257 @@
+use std::io;
+
+use i8080::{
+    console::BufferConsole,
+    cpu::{Cpu, StatusWord, Variant},
+    machine::altair::{
+        dcdd::{Disk, SECTOR_SIZE},
+        Altair,
+    },
+    Error,
+};
+
+// Waits for a character from the 88-2SIO and echoes it back.
+#[rustfmt::skip]
+const ECHO: &[u8] = &[
+    0xDB, 0x10,       // 0x0000: IN 0x10
+    0xE6, 0x01,       //         ANI 0x01
+    0xCA, 0x00, 0x00, //         JZ 0x0000
+    0xDB, 0x11,       //         IN 0x11
+    0xD3, 0x11,       //         OUT 0x11
+    0xC3, 0x00, 0x00, //         JMP 0x0000
+];
+
+fn toggle_in(altair: &mut Altair<BufferConsole>, address: u16, program: &[u8]) {
+    altair.set_switches(address);
+    altair.examine();
+    for (index, byte) in program.iter().enumerate() {
+        altair.set_switches(u16::from(*byte));
+        if index == 0 {
+            altair.deposit();
+        } else {
+            altair.deposit_next();
+        }
+    }
+}
+
+#[test]
+fn front_panel() {
+    let mut altair = Altair::new(BufferConsole::default(), 0x10000);
+    #[rustfmt::skip]
+    toggle_in(&mut altair, 0x0000, &[
+        0x3E, b'H', // MVI A,'H'
+        0xD3, 0x11, // OUT 0x11
+        0x3E, b'I', // MVI A,'I'
+        0xD3, 0x11, // OUT 0x11
+        0xDB, 0xFF, // IN 0xFF
+        0x32, 0x00, 0x01, // STA 0x0100
+        0x76, // HLT
+    ]);
+    assert_eq!(altair.leds().address, 0x000D);
+    assert_eq!(altair.leds().data, 0x76);
+
+    altair.set_switches(0x0002);
+    altair.examine();
+    let leds = altair.leds();
+    assert_eq!((leds.address, leds.data), (0x0002, 0xD3));
+    assert!(leds.wait);
+
+    altair.reset();
+    altair.single_step().unwrap();
+    altair.single_step().unwrap();
+    assert_eq!(altair.i8080.cpu.pc, 0x0004);
+    assert_eq!(altair.console().output, b"H");
+
+    // The sense switches are the upper half of the address/data switches.
+    altair.set_switches(0xA500);
+    altair.run().unwrap();
+    assert_eq!(altair.console().output, b"HI");
+    assert_eq!(altair.i8080.memory[0x0100], 0xA5);
+    let leds = altair.leds();
+    assert!(!altair.is_running());
+    assert!(leds.status.contains(StatusWord::HLTA));
+    assert!(!leds.status.contains(StatusWord::M1));
+    assert!(!leds.inte);
+}
+
+#[test]
+fn serial_card() {
+    let mut altair = Altair::new(BufferConsole::new("ab"), 0x1000);
+    toggle_in(&mut altair, 0x0000, ECHO);
+    altair.reset();
+    altair.run_for(10_000).unwrap();
+    assert!(altair.is_running());
+    assert_eq!(altair.console().output, b"ab");
+    assert!(altair.states() >= 10_000);
+}
+
+#[test]
+fn memory_size() {
+    let mut altair = Altair::new(BufferConsole::default(), 0x1000);
+    #[rustfmt::skip]
+    toggle_in(&mut altair, 0x0000, &[
+        0x31, 0x00, 0x20, // LXI SP,0x2000
+        0x21, 0x34, 0x12, // LXI H,0x1234
+        0xE5,             // PUSH H
+        0x36, 0x00,       // MVI M,0x00
+        0x22, 0xFF, 0x0F, // SHLD 0x0FFF
+        0x76,             // HLT
+    ]);
+    altair.reset();
+    altair.run().unwrap();
+    assert_eq!(altair.i8080.memory[0x1FFE], 0xFF);
+    assert_eq!(altair.i8080.memory[0x1FFF], 0xFF);
+    assert_eq!(altair.i8080.memory[0x1234], 0xFF);
+    assert_eq!(altair.i8080.memory[0x0FFF], 0x34);
+    assert_eq!(altair.i8080.memory[0x1000], 0xFF);
+
+    altair.set_switches(0x3000);
+    altair.examine();
+    altair.deposit();
+    assert_eq!(altair.leds().data, 0xFF);
+}
+
+#[test]
+fn unpopulated_memory_is_protected() {
+    let mut altair = Altair::new(BufferConsole::default(), 0x1000);
+    altair.i8080.cpu = Cpu::new(Variant::Z80);
+    #[rustfmt::skip]
+    toggle_in(&mut altair, 0x0000, &[
+        0x31, 0x00, 0x20,       // LD SP,0x2000
+        0xDD, 0x21, 0x00, 0x30, // LD IX,0x3000
+        0xDD, 0x36, 0x05, 0x42, // LD (IX+5),0x42
+        0xED, 0x43, 0x00, 0x30, // LD (0x3000),BC
+        0xFB,                   // EI
+        0x76,                   // HALT
+    ]);
+    altair.reset();
+    altair.run().unwrap();
+    assert_eq!(altair.i8080.memory[0x3005], 0xFF);
+    assert_eq!(altair.i8080.memory[0x3000], 0xFF);
+
+    altair.interrupt([0xFF, 0x00, 0x00]).unwrap(); // RST 38H
+    assert_eq!(altair.i8080.cpu.pc, 0x0038);
+    assert_eq!(altair.i8080.memory[0x1FFE..0x2000], [0xFF, 0xFF]);
+}
+
+#[test]
+fn paper_tape() {
+    let mut tape = vec![0x00; 16];
+    // A loader that contains a sync byte, followed by a leader.
+    tape.extend_from_slice(&[0x3C, 0x02, 0x00, 0x10, 0xAA, 0xBB, 0xCC]);
+    tape.extend_from_slice(&[0x00; 8]);
+    for (address, chunk) in (0x1000_u16..).step_by(8).zip(ECHO.chunks(8)) {
+        let [low, high] = address.to_le_bytes();
+        tape.extend_from_slice(&[0x3C, chunk.len() as u8, low, high]);
+        tape.extend_from_slice(chunk);
+        let checksum =
+            chunk.iter().fold(low.wrapping_add(high), |sum, byte| sum.wrapping_add(*byte));
+        tape.push(checksum);
+    }
+    tape.extend_from_slice(&[0x78, 0x00, 0x10]);
+
+    let mut altair = Altair::new(BufferConsole::new("x"), 0x2000);
+    assert_eq!(altair.load_tape(&tape).unwrap(), 0x1000);
+    assert_eq!(altair.i8080.cpu.pc, 0x1000);
+    assert_eq!(&altair.i8080.memory[0x1000..0x100E], ECHO);
+    // The echo program jumps back to 0x0000, so send it on to 0x1000 from there.
+    altair.i8080.memory[0x0000] = 0xC3;
+    altair.i8080.memory[0x0001] = 0x00;
+    altair.i8080.memory[0x0002] = 0x10;
+    altair.run_for(1000).unwrap();
+    assert_eq!(altair.console().output, b"x");
+
+    assert!(matches!(altair.load_tape(&[0x00, 0x3C, 0x01]), Err(Error::InvalidPaperTape)));
+}
+
+#[test]
+fn end_of_input() {
+    // Reading the data port without checking the status reaches the end of the input.
+    let mut altair = Altair::new(BufferConsole::default(), 0x1000);
+    toggle_in(&mut altair, 0x0000, &ECHO[7..]);
+    altair.reset();
+    match altair.run() {
+        Err(Error::Io { source, .. }) => assert_eq!(source.kind(), io::ErrorKind::UnexpectedEof),
+        result => panic!("unexpected result: {:?}", result.map(|_| ())),
+    }
+    assert!(!altair.is_running());
+}
+
+// Selects drive 0, loads the head, steps in to track 1, and waits for sector 5.
+#[rustfmt::skip]
+const SEEK: &[u8] = &[
+    0x3E, 0x00,       // 0x0000: MVI A,0x00
+    0xD3, 0x08,       //         OUT 0x08
+    0x3E, 0x04,       //         MVI A,0x04
+    0xD3, 0x09,       //         OUT 0x09
+    0xDB, 0x08,       // 0x0008: IN 0x08
+    0xE6, 0x06,       //         ANI 0x06
+    0xC2, 0x08, 0x00, //         JNZ 0x0008
+    0x3E, 0x01,       //         MVI A,0x01
+    0xD3, 0x09,       //         OUT 0x09
+    0xDB, 0x08,       // 0x0013: IN 0x08
+    0xE6, 0x02,       //         ANI 0x02
+    0xC2, 0x13, 0x00, //         JNZ 0x0013
+    0xDB, 0x09,       // 0x001A: IN 0x09
+    0x1F,             //         RAR
+    0xDA, 0x1A, 0x00, //         JC 0x001A
+    0xE6, 0x1F,       //         ANI 0x1F
+    0xFE, 0x05,       //         CPI 0x05
+    0xC2, 0x1A, 0x00, //         JNZ 0x001A
+    0x0E, 0x89,       // 0x0027: MVI C,137
+];
+
+#[test]
+fn disk_read() {
+    let mut image = vec![0x00; (32 + 5) * SECTOR_SIZE];
+    image.extend((0..SECTOR_SIZE).map(|byte| byte as u8));
+    let mut altair = Altair::new(BufferConsole::default(), 0x10000);
+    altair.mount(0, Disk::from_bytes(image).unwrap());
+    toggle_in(&mut altair, 0x0000, SEEK);
+    #[rustfmt::skip]
+    toggle_in(&mut altair, 0x0029, &[
+        0x21, 0x00, 0x10, //         LXI H,0x1000
+        0xDB, 0x08,       // 0x002C: IN 0x08
+        0xB7,             //         ORA A
+        0xFA, 0x2C, 0x00, //         JM 0x002C
+        0xDB, 0x0A,       //         IN 0x0A
+        0x77,             //         MOV M,A
+        0x23,             //         INX H
+        0x0D,             //         DCR C
+        0xC2, 0x2C, 0x00, //         JNZ 0x002C
+        0x76,             //         HLT
+    ]);
+    altair.reset();
+    altair.run().unwrap();
+    let expected: Vec<u8> = (0..SECTOR_SIZE).map(|byte| byte as u8).collect();
+    assert_eq!(&altair.i8080.memory[0x1000..0x1089], &expected[..]);
+    // The head load and the step take time, and sector 5 comes around once per revolution.
+    assert!(altair.states() > 100_000);
+}
+
+#[test]
+fn disk_write() {
+    let mut altair = Altair::new(BufferConsole::default(), 0x10000);
+    altair.mount(0, Disk::new());
+    toggle_in(&mut altair, 0x0000, SEEK);
+    #[rustfmt::skip]
+    toggle_in(&mut altair, 0x0029, &[
+        0x3E, 0x80,       //         MVI A,0x80
+        0xD3, 0x09,       //         OUT 0x09
+        0xDB, 0x08,       // 0x002D: IN 0x08
+        0x1F,             //         RAR
+        0xDA, 0x2D, 0x00, //         JC 0x002D
+        0x79,             //         MOV A,C
+        0xD3, 0x0A,       //         OUT 0x0A
+        0x0D,             //         DCR C
+        0xC2, 0x2D, 0x00, //         JNZ 0x002D
+        0x76,             //         HLT
+    ]);
+    altair.reset();
+    altair.run().unwrap();
+    let disk = altair.unmount(0).unwrap();
+    let expected: Vec<u8> = (1..=SECTOR_SIZE as u8).rev().collect();
+    assert_eq!(disk.sector(1, 5).unwrap(), &expected[..]);
+    assert!(disk.sector(1, 4).unwrap().iter().all(|&byte| byte == 0xE5));
+    assert!(altair.disk(0).is_none());
+}
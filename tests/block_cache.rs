@@ -0,0 +1,163 @@
+#![warn(rust_2018_idioms)]
+
+use std::path::Path;
+
+use i8080::{
+    console::BufferConsole,
+    cpm::{Bdos, BDOS_BASE},
+    cpu::{ConditionFlags, Cpu, Engine},
+    Intel8080,
+};
+
+#[test]
+fn cpu_tests() {
+    for &program in &[
+        "tests/cpu_tests/8080PRE.COM",
+        "tests/cpu_tests/TST8080.COM",
+        "tests/cpu_tests/CPUTEST.COM",
+    ] {
+        let (output, states) = run_program(program, Engine::BlockCache);
+        assert_eq!((output, states), run_program(program, Engine::Interpreter), "{}", program);
+    }
+}
+
+#[test]
+fn lockstep() {
+    let mut bdos = Bdos::new(BufferConsole::default(), "tests/cpu_tests");
+    let mut interpreter = bdos.load_program("tests/cpu_tests/TST8080.COM", &[] as &[&str]).unwrap();
+    let mut block_cache = bdos.load_program("tests/cpu_tests/TST8080.COM", &[] as &[&str]).unwrap();
+    block_cache.cpu.set_engine(Engine::BlockCache);
+    // Small budgets stop the block cache in the middle of blocks.
+    for &budget in [1, 7, 13, 50].iter().cycle() {
+        bdos.trap(&mut interpreter).unwrap();
+        bdos.trap(&mut block_cache).unwrap();
+        if interpreter.cpu.pc == 0x0000 {
+            break;
+        }
+        let is_breakpoint = |pc| pc == 0x0000 || pc >= BDOS_BASE;
+        assert_eq!(block_cache.run(budget, is_breakpoint), interpreter.run(budget, is_breakpoint));
+        assert_eq!(registers(&block_cache.cpu), registers(&interpreter.cpu));
+        assert!(block_cache.memory[0x0000..] == interpreter.memory[0x0000..]);
+    }
+    assert_eq!(block_cache.cpu.pc, 0x0000);
+}
+
+#[test]
+fn self_modifying_code() {
+    // MVI A,0; LXI H,0108H; MVI M,3CH; NOP; NOP; HLT, which overwrites the second NOP with INR A
+    // before the CPU reaches it within the same block.
+    let program = [0x3E, 0x00, 0x21, 0x08, 0x01, 0x36, 0x3C, 0x00, 0x00, 0x76];
+    for &engine in &[Engine::Interpreter, Engine::BlockCache] {
+        let mut i8080 = load(&program, engine);
+        assert_eq!(i8080.run(1_000, |_| false), 7 + 10 + 10 + 4 + 5 + 7);
+        assert!(i8080.cpu.is_halted());
+        assert_eq!(i8080.cpu.a, 0x01, "{:?}", engine);
+    }
+}
+
+#[test]
+fn rewritten_in_a_loop() {
+    // MVI B,3; LXI H,010AH; INR M; NOP; NOP; NOP; ADI 0; DCR B; JNZ 0105H; HLT, which increments
+    // the operand of ADI on every pass through the loop.
+    let program = [
+        0x06, 0x03, 0x21, 0x0A, 0x01, 0x34, 0x00, 0x00, 0x00, 0xC6, 0x00, 0x05, 0xC2, 0x05, 0x01,
+        0x76,
+    ];
+    for &engine in &[Engine::Interpreter, Engine::BlockCache] {
+        let mut i8080 = load(&program, engine);
+        assert_eq!(i8080.run(1_000, |_| false), 7 + 10 + 3 * (10 + 3 * 4 + 7 + 5 + 10) + 7);
+        assert!(i8080.cpu.is_halted());
+        assert_eq!(i8080.cpu.a, 1 + 2 + 3, "{:?}", engine);
+        assert_eq!(i8080.memory[0x010A], 0x03);
+    }
+}
+
+#[test]
+fn written_between_runs() {
+    // INR A; NOP; NOP; JMP 0100H
+    let program = [0x3C, 0x00, 0x00, 0xC3, 0x00, 0x01];
+    for &engine in &[Engine::Interpreter, Engine::BlockCache] {
+        let mut i8080 = load(&program, engine);
+        i8080.run(1_000, |pc| pc == 0x0103);
+        assert_eq!(i8080.cpu.a, 0x01);
+
+        // INR A; INR A; NOP; JMP 0100H
+        i8080.memory[0x0101] = 0x3C;
+        i8080.cpu.pc = 0x0100;
+        i8080.run(1_000, |pc| pc == 0x0103);
+        assert_eq!(i8080.cpu.a, 0x03, "{:?}", engine);
+
+        // INR A; INR A; INR A; JMP 0100H
+        i8080.memory[0x0102..0x0103].copy_from_slice(&[0x3C]);
+        i8080.cpu.pc = 0x0100;
+        i8080.run(1_000, |pc| pc == 0x0103);
+        assert_eq!(i8080.cpu.a, 0x06, "{:?}", engine);
+
+        // NOP; NOP; NOP; JMP 0100H
+        i8080.memory.load_bytes(&[0x00; 3], 0x0100).unwrap();
+        i8080.cpu.pc = 0x0100;
+        i8080.run(1_000, |pc| pc == 0x0103);
+        assert_eq!(i8080.cpu.a, 0x06, "{:?}", engine);
+    }
+}
+
+#[test]
+fn enable_interrupts_and_halt() {
+    // EI; NOP; HLT
+    let program = [0xFB, 0x00, 0x76];
+    for &engine in &[Engine::Interpreter, Engine::BlockCache] {
+        let mut i8080 = load(&program, engine);
+        assert_eq!(i8080.run(4, |_| false), 4);
+        assert!(i8080.cpu.is_interrupt_enabled());
+        assert_eq!(i8080.run(1_000, |_| false), 4 + 7);
+        assert!(i8080.cpu.is_halted());
+        assert_eq!(i8080.run(1_000, |_| false), 0);
+        assert_eq!(i8080.cpu.pc, 0x0103);
+    }
+}
+
+#[test]
+fn engine() {
+    let mut cpu = Cpu::default();
+    assert_eq!(cpu.engine(), Engine::Interpreter);
+    cpu.set_engine(Engine::BlockCache);
+    assert_eq!(cpu.engine(), Engine::BlockCache);
+    cpu.set_engine(Engine::Interpreter);
+    assert_eq!(cpu.engine(), Engine::Interpreter);
+}
+
+fn load(program: &[u8], engine: Engine) -> Intel8080 {
+    let mut i8080 = Intel8080::default();
+    i8080.memory.load_bytes(program, 0x0100).unwrap();
+    i8080.cpu.pc = 0x0100;
+    i8080.cpu.set_engine(engine);
+    i8080
+}
+
+fn registers(cpu: &Cpu) -> (u16, u16, [u8; 7], ConditionFlags, bool, bool) {
+    (
+        cpu.pc,
+        cpu.sp,
+        [cpu.b, cpu.c, cpu.d, cpu.e, cpu.h, cpu.l, cpu.a],
+        cpu.condition_flags,
+        cpu.is_interrupt_enabled(),
+        cpu.is_halted(),
+    )
+}
+
+// Runs `program` on `engine` until it warm boots, returning the console output and the number of
+// states taken.
+fn run_program<P: AsRef<Path>>(program: P, engine: Engine) -> (Vec<u8>, u64) {
+    let mut bdos = Bdos::new(BufferConsole::default(), "tests/cpu_tests");
+    let mut i8080 = bdos.load_program(program, &[] as &[&str]).unwrap();
+    i8080.cpu.set_engine(engine);
+    let mut states = 0;
+    loop {
+        bdos.trap(&mut i8080).unwrap();
+        if i8080.cpu.pc == 0x0000 {
+            return (bdos.into_console().output, states);
+        }
+        assert!(!i8080.cpu.is_halted());
+        states += i8080.run(u64::MAX, |pc| pc == 0x0000 || pc >= BDOS_BASE);
+    }
+}
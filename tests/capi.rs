@@ -0,0 +1,222 @@
+#![warn(rust_2018_idioms)]
+
+use std::{ffi::c_void, fs, ptr};
+
+use i8080::capi::*;
+
+extern "C" fn input(_user_data: *mut c_void, port: u8) -> u8 {
+    port.wrapping_add(1)
+}
+
+extern "C" fn output(user_data: *mut c_void, port: u8, byte: u8) {
+    let outputs = unsafe { &mut *(user_data as *mut Vec<(u8, u8)>) };
+    outputs.push((port, byte));
+}
+
+extern "C" fn write(user_data: *mut c_void, address: u16, byte: u8) {
+    let writes = unsafe { &mut *(user_data as *mut Vec<(u16, u8)>) };
+    writes.push((address, byte));
+}
+
+#[test]
+fn header_declares_every_item() {
+    let header = fs::read_to_string("include/i8080.h").unwrap();
+    let source = fs::read_to_string("src/capi.rs").unwrap();
+    for line in source.lines() {
+        let name = match line.split_whitespace().skip_while(|&word| word != "fn").nth(1) {
+            Some(name) => name.split('(').next().unwrap(),
+            None => match line.strip_prefix("pub const ") {
+                Some(rest) => rest.split(':').next().unwrap(),
+                None => continue,
+            },
+        };
+        if name.starts_with("i8080_") || name.starts_with("I8080_") {
+            assert!(header.contains(name), "{} is not declared in i8080.h", name);
+        }
+    }
+}
+
+// Links a C program that includes i8080.h against the library built as a static library, so
+// that the header is checked by a compiler as well.
+#[cfg(unix)]
+#[test]
+fn c_program() {
+    use std::{path::Path, process::Command};
+
+    let directory = Path::new(env!("CARGO_TARGET_TMPDIR")).join("capi");
+    let build = Command::new(env!("CARGO"))
+        .args(["rustc", "--lib", "--features", "capi", "--crate-type", "staticlib"])
+        .arg("--target-dir")
+        .arg(&directory)
+        .args(["--", "--print", "native-static-libs"])
+        .output()
+        .unwrap();
+    let messages = String::from_utf8_lossy(&build.stderr);
+    assert!(build.status.success(), "{}", messages);
+    let libraries = messages
+        .lines()
+        .find_map(|line| line.split("native-static-libs: ").nth(1))
+        .expect("cargo does not tell the native libraries to link");
+
+    let program = directory.join("program");
+    let compile = Command::new("cc")
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror", "-pedantic", "-Iinclude"])
+        .arg("tests/capi/program.c")
+        .arg(directory.join("debug/libi8080.a"))
+        .args(libraries.split_whitespace())
+        .arg("-o")
+        .arg(&program)
+        .output()
+        .unwrap();
+    assert!(compile.status.success(), "{}", String::from_utf8_lossy(&compile.stderr));
+    let run = Command::new(&program).arg("tests/cpu_tests/NONEXISTENT.COM").output().unwrap();
+    assert!(run.status.success(), "{}", String::from_utf8_lossy(&run.stderr));
+    assert_eq!(run.stdout, b"ok\n");
+}
+
+#[test]
+fn new() {
+    for variant in I8080_VARIANT_INTEL8080..=I8080_VARIANT_Z80 {
+        let handle = i8080_new(variant);
+        assert!(!handle.is_null());
+        unsafe { i8080_free(handle) };
+    }
+    assert!(i8080_new(I8080_VARIANT_Z80 + 1).is_null());
+    unsafe { i8080_free(ptr::null_mut()) };
+}
+
+#[test]
+fn step_and_run() {
+    // IN 41H; OUT 10H; STA 2000H; PUSH B; HLT
+    let program = [0xDB, 0x41, 0xD3, 0x10, 0x32, 0x00, 0x20, 0xC5, 0x76];
+    unsafe {
+        let handle = i8080_new(I8080_VARIANT_INTEL8080);
+        assert_eq!(i8080_load(handle, program.as_ptr(), program.len(), 0x0100), I8080_OK);
+        let mut registers = Registers::default();
+        i8080_get_registers(handle, &mut registers);
+        registers.pc = 0x0100;
+        registers.sp = 0x0000;
+        registers.b = 0x12;
+        registers.c = 0x34;
+        i8080_set_registers(handle, &registers);
+
+        // Without callbacks, IN reads 0xFF.
+        let mut states = 0;
+        assert_eq!(i8080_step(handle, &mut states), I8080_OK);
+        assert_eq!(states, 10);
+        i8080_get_registers(handle, &mut registers);
+        assert_eq!((registers.pc, registers.a), (0x0102, 0xFF));
+
+        let mut outputs: Vec<(u8, u8)> = Vec::new();
+        let mut writes: Vec<(u16, u8)> = Vec::new();
+        registers.pc = 0x0100;
+        i8080_set_registers(handle, &registers);
+        i8080_set_io(handle, Some(input), Some(output), &mut outputs as *mut _ as *mut c_void);
+        i8080_set_write_callback(handle, Some(write), &mut writes as *mut _ as *mut c_void);
+        let breakpoints = [0x0107];
+        assert_eq!(i8080_run(handle, 1_000, breakpoints.as_ptr(), breakpoints.len()), 33);
+        i8080_get_registers(handle, &mut registers);
+        assert_eq!((registers.pc, registers.a), (0x0107, 0x42));
+        assert_eq!(outputs, [(0x10, 0x42)]);
+        assert_eq!(writes, [(0x2000, 0x42)]);
+        assert_eq!(i8080_read_memory(handle, 0x2000), 0x42);
+
+        assert_eq!(i8080_run(handle, 1_000, ptr::null(), 0), 11 + 7);
+        assert_eq!(writes[1..], [(0xFFFF, 0x12), (0xFFFE, 0x34)]);
+        assert!(i8080_is_halted(handle));
+        assert_eq!(i8080_step(handle, ptr::null_mut()), I8080_HALTED);
+
+        // Without a write callback, the CPU runs in a tight loop.
+        i8080_set_write_callback(handle, None, ptr::null_mut());
+        assert_eq!(i8080_run(handle, 1_000, ptr::null(), 0), 0);
+        i8080_free(handle);
+    }
+}
+
+#[test]
+fn z80_writes() {
+    // LD IX,2000H; LD (IX+5),42H; LD (3000H),BC; EX (SP),IX; LD HL,2005H; LD DE,4000H; LD BC,2;
+    // LDIR; HALT
+    let program = [
+        0xDD, 0x21, 0x00, 0x20, 0xDD, 0x36, 0x05, 0x42, 0xED, 0x43, 0x00, 0x30, 0xDD, 0xE3, 0x21,
+        0x05, 0x20, 0x11, 0x00, 0x40, 0x01, 0x02, 0x00, 0xED, 0xB0, 0x76,
+    ];
+    unsafe {
+        let handle = i8080_new(I8080_VARIANT_Z80);
+        assert_eq!(i8080_load(handle, program.as_ptr(), program.len(), 0x0100), I8080_OK);
+        let mut registers = Registers::default();
+        i8080_get_registers(handle, &mut registers);
+        registers.pc = 0x0100;
+        registers.sp = 0x1000;
+        registers.b = 0x12;
+        registers.c = 0x34;
+        i8080_set_registers(handle, &registers);
+        let mut writes: Vec<(u16, u8)> = Vec::new();
+        i8080_set_write_callback(handle, Some(write), &mut writes as *mut _ as *mut c_void);
+        i8080_run(handle, 1_000, ptr::null(), 0);
+        assert!(i8080_is_halted(handle));
+        // EX (SP),IX leaves 1000H unchanged, and LDIR copies 00H over 00H at 4001H.
+        assert_eq!(
+            writes,
+            [(0x2005, 0x42), (0x3000, 0x34), (0x3001, 0x12), (0x1001, 0x20), (0x4000, 0x42)]
+        );
+        i8080_free(handle);
+    }
+}
+
+#[test]
+fn interrupt() {
+    // EI; NOP; HLT
+    let program = [0xFB, 0x00, 0x76];
+    unsafe {
+        let handle = i8080_new(I8080_VARIANT_INTEL8080);
+        assert_eq!(i8080_load(handle, program.as_ptr(), program.len(), 0x0000), I8080_OK);
+        let rst_1 = [0xCF, 0x00, 0x00];
+        assert_eq!(
+            i8080_interrupt(handle, rst_1.as_ptr(), ptr::null_mut()),
+            I8080_INTERRUPT_NOT_ENABLED
+        );
+        assert_eq!(i8080_run(handle, 1_000, ptr::null(), 0), 4 + 4 + 7);
+        let mut writes: Vec<(u16, u8)> = Vec::new();
+        i8080_set_write_callback(handle, Some(write), &mut writes as *mut _ as *mut c_void);
+        i8080_write_memory(handle, 0xFFFF, 0xFF);
+        let mut states = 0;
+        assert_eq!(i8080_interrupt(handle, rst_1.as_ptr(), &mut states), I8080_OK);
+        assert_eq!(states, 11);
+        assert_eq!(writes, [(0xFFFF, 0x00), (0xFFFE, 0x03)]);
+        assert!(!i8080_is_halted(handle));
+        let mut registers = Registers::default();
+        i8080_get_registers(handle, &mut registers);
+        assert_eq!(registers.pc, 0x0008);
+        i8080_free(handle);
+    }
+}
+
+#[test]
+fn memory() {
+    unsafe {
+        let handle = i8080_new(I8080_VARIANT_INTEL8080);
+        i8080_write_memory(handle, 0x1234, 0x56);
+        let memory = i8080_memory(handle);
+        assert_eq!(*memory.add(0x1234), 0x56);
+        *memory.add(0xFFFF) = 0x78;
+        assert_eq!(i8080_read_memory(handle, 0xFFFF), 0x78);
+
+        assert_eq!(i8080_load(handle, [0x00; 2].as_ptr(), 2, 0xFFFF), I8080_TOO_LARGE);
+        assert_eq!(i8080_load(handle, ptr::null(), 0, 0x0000), I8080_OK);
+        let path = b"tests/cpu_tests/TST8080.COM\0";
+        assert_eq!(i8080_load_file(handle, path.as_ptr() as *const _, 0x0100), I8080_OK);
+        assert_eq!(i8080_read_memory(handle, 0x0100), 0xC3);
+        let path = b"tests/cpu_tests/NONEXISTENT.COM\0";
+        assert_eq!(
+            i8080_load_file(handle, path.as_ptr() as *const _, 0x0100),
+            I8080_FILE_NOT_FOUND
+        );
+        let path = b"\xFF\0";
+        assert_eq!(
+            i8080_load_file(handle, path.as_ptr() as *const _, 0x0100),
+            I8080_INVALID_ARGUMENT
+        );
+        i8080_free(handle);
+    }
+}
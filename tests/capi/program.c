@@ -0,0 +1,98 @@
+/*
+ * Calls every function declared in i8080.h, so that a declaration that does not match the library
+ * fails to compile, link, or behave. tests/capi.rs builds and runs it.
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "i8080.h"
+
+#define CHECK(condition)                                                           \
+    do {                                                                           \
+        if (!(condition)) {                                                        \
+            fprintf(stderr, "%s:%d: %s failed\n", __FILE__, __LINE__, #condition); \
+            exit(EXIT_FAILURE);                                                    \
+        }                                                                          \
+    } while (0)
+
+static uint8_t read_port(void *user_data, uint8_t port) {
+    (void)user_data;
+    return (uint8_t)(port + 1);
+}
+
+static void write_port(void *user_data, uint8_t port, uint8_t byte) {
+    (void)port;
+    *(uint8_t *)user_data = byte;
+}
+
+static void write_memory(void *user_data, uint16_t address, uint8_t byte) {
+    (void)byte;
+    *(uint16_t *)user_data = address;
+}
+
+int main(int argc, char **argv) {
+    /* IN 41H; OUT 10H; STA 2000H; EI; HLT */
+    static const uint8_t program[] = {0xDB, 0x41, 0xD3, 0x10, 0x32, 0x00, 0x20, 0xFB, 0x76};
+    static const uint8_t rst_1[] = {0xCF, 0x00, 0x00};
+    const uint16_t breakpoints[] = {0x0104};
+    uint32_t variants[] = {I8080_VARIANT_INTEL8080, I8080_VARIANT_NEC_UPD8080AF,
+                           I8080_VARIANT_AMD9080, I8080_VARIANT_INTEL8085, I8080_VARIANT_Z80};
+    I8080Registers registers;
+    uint32_t states;
+    uint8_t byte = 0;
+    uint16_t address = 0;
+    size_t i;
+    I8080 *i8080;
+
+    CHECK(argc == 2);
+    for (i = 0; i < sizeof variants / sizeof variants[0]; i++) {
+        i8080 = i8080_new(variants[i]);
+        CHECK(i8080 != NULL);
+        i8080_free(i8080);
+    }
+    CHECK(i8080_new(I8080_VARIANT_Z80 + 1) == NULL);
+    i8080_free(NULL);
+
+    i8080 = i8080_new(I8080_VARIANT_INTEL8080);
+    CHECK(i8080_load(i8080, program, sizeof program, 0x0100) == I8080_OK);
+    CHECK(i8080_load(i8080, program, 2, 0xFFFF) == I8080_TOO_LARGE);
+    CHECK(i8080_load_file(i8080, argv[1], 0x0000) == I8080_FILE_NOT_FOUND);
+    CHECK(i8080_load_file(i8080, "\xFF", 0x0000) == I8080_INVALID_ARGUMENT);
+
+    i8080_get_registers(i8080, &registers);
+    registers.pc = 0x0100;
+    registers.sp = 0x0000;
+    registers.b = 0x12;
+    registers.flags = 0x02;
+    i8080_set_registers(i8080, &registers);
+    i8080_get_registers(i8080, &registers);
+    CHECK(registers.pc == 0x0100 && registers.b == 0x12 && registers.flags == 0x02);
+
+    CHECK(i8080_step(i8080, &states) == I8080_OK);
+    CHECK(states == 10);
+    i8080_get_registers(i8080, &registers);
+    CHECK(registers.a == 0xFF);
+
+    i8080_set_io(i8080, read_port, write_port, &byte);
+    i8080_set_write_callback(i8080, write_memory, &address);
+    registers.pc = 0x0100;
+    i8080_set_registers(i8080, &registers);
+    CHECK(i8080_run(i8080, 1000, breakpoints, 1) == 20);
+    CHECK(byte == 0x42);
+    i8080_get_registers(i8080, &registers);
+    CHECK(registers.pc == 0x0104);
+    CHECK(i8080_interrupt(i8080, rst_1, NULL) == I8080_INTERRUPT_NOT_ENABLED);
+    CHECK(i8080_run(i8080, 1000, NULL, 0) == 13 + 4 + 7);
+    CHECK(address == 0x2000 && i8080_read_memory(i8080, 0x2000) == 0x42);
+    CHECK(i8080_is_halted(i8080));
+    CHECK(i8080_step(i8080, NULL) == I8080_HALTED);
+
+    CHECK(i8080_interrupt(i8080, rst_1, &states) == I8080_OK);
+    CHECK(states == 11 && address == 0xFFFE && !i8080_is_halted(i8080));
+    i8080_write_memory(i8080, 0x1234, 0x56);
+    CHECK(i8080_memory(i8080)[0x1234] == 0x56);
+    i8080_free(i8080);
+    puts("ok");
+    return EXIT_SUCCESS;
+}
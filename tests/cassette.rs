@@ -0,0 +1,137 @@
+use std::{env, fs, process};
+
+use i8080::{
+    cassette::{self, Cassette, Format},
+    console::{BufferConsole, Console},
+    machine::{altair::Altair, sol20::Sol20},
+    wav::Wave,
+};
+
+const PROGRAM: &[u8] = b"10 PRINT \"HELLO\"\r20 GOTO 10\r\x00\xFF\x55\xAA";
+
+#[test]
+fn round_trip() {
+    for &(format, sample_rate) in
+        &[(Format::KANSAS_CITY, 44100), (Format::KANSAS_CITY, 8000), (Format::CUTS, 22050)]
+    {
+        let wave = cassette::encode(PROGRAM, format, sample_rate, 0.5);
+        let bits = PROGRAM.len() as f64 * f64::from(9 + format.stop_bits) + f64::from(format.baud);
+        assert!((wave.duration() - bits / f64::from(format.baud)).abs() < 0.01);
+        assert_eq!(cassette::decode(&wave, format), PROGRAM);
+    }
+}
+
+#[test]
+fn poor_recording() {
+    // A quiet recording with a DC offset and some noise.
+    let wave = cassette::encode(PROGRAM, Format::KANSAS_CITY, 44100, 0.2);
+    let mut noise = 1_u32;
+    let samples = wave
+        .samples
+        .iter()
+        .map(|&sample| {
+            noise = noise.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            let noise = (noise >> 16) as i32 % 1000 - 500;
+            (i32::from(sample) / 8 + 2000 + noise) as i16
+        })
+        .collect();
+    let wave = Wave::new(44100, samples);
+    assert_eq!(cassette::decode(&wave, Format::KANSAS_CITY), PROGRAM);
+    // The wrong format finds no valid frames.
+    assert_ne!(cassette::decode(&wave, Format::CUTS), PROGRAM);
+}
+
+#[test]
+fn files() {
+    let mut tape = Cassette::new(Format::CUTS, 2_000_000);
+    for &byte in PROGRAM {
+        tape.write_byte(byte).unwrap();
+    }
+    let path = env::temp_dir().join(format!("i8080-cassette-{}.wav", process::id()));
+    tape.save(&path, 44100).unwrap();
+    let mut tape = Cassette::load(&path, Format::CUTS, 2_000_000).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(tape.playback().len(), PROGRAM.len());
+    let mut bytes = Vec::new();
+    while !tape.playback().is_empty() {
+        tape.elapse(u32::MAX);
+        bytes.push(tape.read_byte().unwrap().unwrap());
+    }
+    assert_eq!(bytes, PROGRAM);
+    assert_eq!(tape.read_byte().unwrap(), None);
+}
+
+#[test]
+fn timing() {
+    // Each frame of 10 bits takes 16,666 states at 1200 baud and 2 MHz.
+    let wave = cassette::encode(b"AB", Format::CUTS, 44100, 0.1);
+    let mut tape = Cassette::from_wave(&wave, Format::CUTS, 2_000_000);
+    assert!(!tape.is_input_ready());
+    tape.elapse(16_665);
+    assert!(!tape.is_input_ready());
+    tape.elapse(1);
+    assert!(tape.is_input_ready());
+    assert_eq!(tape.read_byte().unwrap(), Some(b'A'));
+    assert!(!tape.is_input_ready());
+    // Reading before the next byte arrives returns the last byte again.
+    assert_eq!(tape.read_byte().unwrap(), Some(b'A'));
+    tape.elapse(16_666);
+    assert_eq!(tape.read_byte().unwrap(), Some(b'B'));
+    tape.elapse(u32::MAX);
+    assert!(!tape.is_input_ready());
+    assert_eq!(tape.read_byte().unwrap(), None);
+}
+
+#[test]
+fn no_stop_bits() {
+    // Without stop bits, a start bit can only be told apart from the last data bit of the
+    // previous frame if that bit is 1.
+    let format = Format { stop_bits: 0, ..Format::CUTS };
+    let wave = cassette::encode(b"\x80\xC1\xFF", format, 44100, 0.5);
+    assert_eq!(cassette::decode(&wave, format), b"\x80\xC1\xFF");
+}
+
+#[test]
+fn altair_acr() {
+    let wave = cassette::encode(b"BASIC", Format::KANSAS_CITY, 22050, 0.1);
+    let mut altair = Altair::new(BufferConsole::default(), 0x10000);
+    #[rustfmt::skip]
+    altair.i8080.memory[0x0000..0x0011].copy_from_slice(&[
+        0x21, 0x00, 0x10, //         LXI H,0x1000
+        0xDB, 0x06,       // 0x0003: IN 0x06
+        0x1F,             //         RAR
+        0xDA, 0x03, 0x00, //         JC 0x0003
+        0xDB, 0x07,       //         IN 0x07
+        0x77,             //         MOV M,A
+        0x23,             //         INX H
+        0xC3, 0x03, 0x00, //         JMP 0x0003
+        0x00,
+    ]);
+    altair.attach_cassette(Box::new(Cassette::from_wave(&wave, Format::KANSAS_CITY, 2_000_000)));
+    // Each frame of 11 bits takes 73,333 states at 300 baud.
+    altair.run_for(4 * 73_333).unwrap();
+    assert_eq!(&altair.i8080.memory[0x1000..0x1005], b"BAS\x00\x00");
+    altair.run_for(2 * 73_333).unwrap();
+    assert_eq!(&altair.i8080.memory[0x1000..0x1006], b"BASIC\x00");
+    assert!(altair.detach_cassette().is_some());
+}
+
+#[test]
+fn sol20_tape() {
+    let wave = cassette::encode(b"X", Format::CUTS, 44100, 0.1);
+    #[rustfmt::skip]
+    let rom = [
+        0xDB, 0xFA,       // 0xC000: IN 0xFA
+        0xE6, 0x40,       //         ANI 0x40
+        0xCA, 0x00, 0xC0, //         JZ 0xC000
+        0xDB, 0xFB,       //         IN 0xFB
+        0xD3, 0xFB,       //         OUT 0xFB
+        0x76,             //         HLT
+    ];
+    let cassette = Cassette::from_wave(&wave, Format::CUTS, 2_000_000);
+    let mut sol = Sol20::new(BufferConsole::default(), cassette);
+    sol.load_rom(&rom);
+    sol.run().unwrap();
+    assert_eq!(sol.cassette().recording(), b"X");
+    assert_eq!(cassette::decode(&sol.cassette().to_wave(44100), Format::CUTS), b"X");
+}
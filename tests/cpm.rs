@@ -0,0 +1,439 @@
+#![warn(rust_2018_idioms)]
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use i8080::{
+    console::BufferConsole,
+    cpm::{
+        disk::{DiskFormat, DiskImage},
+        Bdos, System, DEFAULT_BIOS_BASE,
+    },
+    Error, Intel8080,
+};
+
+#[test]
+fn print_string() {
+    let directory = Directory::new("print_string");
+    #[rustfmt::skip]
+    let program = [
+        0x11, 0x09, 0x01, // LXI D,0109H
+        0x0E, 0x09,       // MVI C,9 (Print String)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0xC9,             // RET
+        b'H', b'i', b'!', b'$',
+    ];
+    let (bdos, _) = run(&directory, &program, &[], b"");
+    assert_eq!(bdos.console().output, b"Hi!");
+}
+
+#[test]
+fn print_string_without_terminator() {
+    let directory = Directory::new("print_string_without_terminator");
+    #[rustfmt::skip]
+    let program = [
+        0x11, 0x00, 0x02, // LXI D,0200H
+        0x0E, 0x09,       // MVI C,9 (Print String)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0xC3, 0x00, 0x00, // JMP 0000H
+    ];
+    let (bdos, _) = run(&directory, &program, &[], b"");
+    // No byte in memory is '$'.
+    assert_eq!(bdos.console().output.len(), 0x10000);
+}
+
+#[test]
+fn read_console_buffer() {
+    let directory = Directory::new("read_console_buffer");
+    #[rustfmt::skip]
+    let program = [
+        0x21, 0x00, 0x02, // LXI H,0200H
+        0x36, 0x10,       // MVI M,16
+        0xEB,             // XCHG
+        0x0E, 0x0A,       // MVI C,10 (Read Console Buffer)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0x21, 0x01, 0x02, // LXI H,0201H
+        0x5E,             // MOV E,M
+        0x16, 0x00,       // MVI D,0
+        0x23,             // INX H
+        0x19,             // DAD D
+        0x36, b'$',       // MVI M,'$'
+        0x11, 0x02, 0x02, // LXI D,0202H
+        0x0E, 0x09,       // MVI C,9 (Print String)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0xC3, 0x00, 0x00, // JMP 0000H
+    ];
+    let (bdos, _) = run(&directory, &program, &[], b"helo\x08lo\r");
+    assert_eq!(bdos.console().output, b"helo\x08 \x08lohello");
+}
+
+#[test]
+fn command_tail_and_default_fcbs() {
+    let directory = Directory::new("command_tail_and_default_fcbs");
+    let program = [0xC9]; // RET
+    let (_, i8080) = run(&directory, &program, &["b:file.txt", "*.c"], b"");
+    assert_eq!(&i8080.memory[0x0080..0x0091], b"\x0F B:FILE.TXT *.C\0");
+    assert_eq!(&i8080.memory[0x005C..0x0068], b"\x02FILE    TXT");
+    assert_eq!(&i8080.memory[0x006C..0x0078], b"\x00????????C  ");
+}
+
+#[test]
+fn write_sequential() {
+    let directory = Directory::new("write_sequential");
+    fs::write(directory.0.join("OLD.TXT"), b"old").unwrap();
+    #[rustfmt::skip]
+    let program = [
+        0x11, 0x5C, 0x00, // LXI D,005CH
+        0x0E, 0x13,       // MVI C,19 (Delete File)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0x11, 0x5C, 0x00, // LXI D,005CH
+        0x0E, 0x16,       // MVI C,22 (Make File)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0x11, 0x00, 0x02, // LXI D,0200H
+        0x0E, 0x1A,       // MVI C,26 (Set DMA Address)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0x11, 0x5C, 0x00, // LXI D,005CH
+        0x0E, 0x15,       // MVI C,21 (Write Sequential)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0x11, 0x5C, 0x00, // LXI D,005CH
+        0x0E, 0x10,       // MVI C,16 (Close File)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0x11, 0x6C, 0x00, // LXI D,006CH
+        0x0E, 0x13,       // MVI C,19 (Delete File)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0xC3, 0x00, 0x00, // JMP 0000H
+    ];
+    let mut data = [0x1A; 128];
+    data[..7].copy_from_slice(b"HELLO\r\n");
+    let (_, i8080) = run_with(&directory, &program, &["new.txt", "old.txt"], b"", |i8080| {
+        i8080.memory[0x0200..0x0280].copy_from_slice(&data);
+    });
+    assert_eq!(i8080.cpu.a, 0x00);
+    assert_eq!(fs::read(directory.0.join("NEW.TXT")).unwrap(), &data[..]);
+    assert!(!directory.0.join("OLD.TXT").exists());
+}
+
+#[test]
+fn make_file_at_end_of_memory() {
+    let directory = Directory::new("make_file_at_end_of_memory");
+    #[rustfmt::skip]
+    let program = [
+        0x11, 0xF1, 0xFF, // LXI D,0FFF1H
+        0x0E, 0x16,       // MVI C,22 (Make File)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0x11, 0xF1, 0xFF, // LXI D,0FFF1H
+        0x0E, 0x10,       // MVI C,16 (Close File)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0xC3, 0x00, 0x00, // JMP 0000H
+    ];
+    let (_, i8080) = run_with(&directory, &program, &[], b"", |i8080| {
+        i8080.memory[0xFFF1..0xFFFD].copy_from_slice(b"\x00END     TXT");
+        i8080.memory[0xFFFD..].iter_mut().for_each(|byte| *byte = 0);
+    });
+    assert_eq!(i8080.cpu.a, 0x00);
+    assert_eq!(i8080.memory[0x0000], 0x00); // RC wraps around to 0000H.
+    assert!(directory.0.join("END.TXT").exists());
+}
+
+#[test]
+fn read_sequential() {
+    let directory = Directory::new("read_sequential");
+    fs::write(directory.0.join("data.txt"), b"Hello, CP/M!$").unwrap();
+    #[rustfmt::skip]
+    let program = [
+        0x11, 0x5C, 0x00, // LXI D,005CH
+        0x0E, 0x0F,       // MVI C,15 (Open File)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0x11, 0x5C, 0x00, // LXI D,005CH
+        0x0E, 0x14,       // MVI C,20 (Read Sequential)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0x11, 0x80, 0x00, // LXI D,0080H
+        0x0E, 0x09,       // MVI C,9 (Print String)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0x11, 0x5C, 0x00, // LXI D,005CH
+        0x0E, 0x14,       // MVI C,20 (Read Sequential)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0xC9,             // RET
+    ];
+    let (bdos, i8080) = run(&directory, &program, &["DATA.TXT"], b"");
+    assert_eq!(bdos.console().output, b"Hello, CP/M!");
+    // The second read reports the end of the file.
+    assert_eq!(i8080.cpu.a, 0x01);
+    assert_eq!(i8080.memory[0x005C + 32], 1); // CR
+    assert_eq!(i8080.memory[0x005C + 15], 1); // RC
+}
+
+#[test]
+fn random_records() {
+    let directory = Directory::new("random_records");
+    let mut data = vec![0; 3 * 128];
+    data[2 * 128..2 * 128 + 4].copy_from_slice(b"two$");
+    fs::write(directory.0.join("RANDOM.DAT"), &data).unwrap();
+    #[rustfmt::skip]
+    let program = [
+        0x11, 0x5C, 0x00, // LXI D,005CH
+        0x0E, 0x23,       // MVI C,35 (Compute File Size)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0x2A, 0x7D, 0x00, // LHLD 007DH
+        0x22, 0x00, 0x02, // SHLD 0200H
+        0x21, 0x02, 0x00, // LXI H,0002H
+        0x22, 0x7D, 0x00, // SHLD 007DH
+        0x11, 0x5C, 0x00, // LXI D,005CH
+        0x0E, 0x21,       // MVI C,33 (Read Random)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0x11, 0x80, 0x00, // LXI D,0080H
+        0x0E, 0x09,       // MVI C,9 (Print String)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0x21, 0x05, 0x00, // LXI H,0005H
+        0x22, 0x7D, 0x00, // SHLD 007DH
+        0x11, 0x5C, 0x00, // LXI D,005CH
+        0x0E, 0x28,       // MVI C,40 (Write Random with Zero Fill)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0xC3, 0x00, 0x00, // JMP 0000H
+    ];
+    let (bdos, i8080) = run(&directory, &program, &["random.dat"], b"");
+    assert_eq!(&i8080.memory[0x0200..0x0202], &[3, 0]);
+    assert_eq!(bdos.console().output, b"two");
+    assert_eq!(fs::metadata(directory.0.join("RANDOM.DAT")).unwrap().len(), 6 * 128);
+}
+
+#[test]
+fn search_and_rename() {
+    let directory = Directory::new("search_and_rename");
+    fs::write(directory.0.join("B.TXT"), b"b").unwrap();
+    fs::write(directory.0.join("a.txt"), b"a").unwrap();
+    fs::write(directory.0.join("C.DOC"), b"c").unwrap();
+    #[rustfmt::skip]
+    let program = [
+        0x11, 0x5C, 0x00, // LXI D,005CH
+        0x0E, 0x11,       // MVI C,17 (Search for First)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0x11, 0x00, 0x02, // LXI D,0200H
+        0x0E, 0x1A,       // MVI C,26 (Set DMA Address)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0x0E, 0x12,       // MVI C,18 (Search for Next)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0x32, 0x00, 0x03, // STA 0300H
+        0x0E, 0x12,       // MVI C,18 (Search for Next)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0x32, 0x01, 0x03, // STA 0301H
+        0x11, 0x5C, 0x00, // LXI D,005CH
+        0x0E, 0x17,       // MVI C,23 (Rename File)
+        0xCD, 0x05, 0x00, // CALL 0005H
+        0xC3, 0x00, 0x00, // JMP 0000H
+    ];
+    let (_, i8080) = run_with(&directory, &program, &["*.TXT"], b"", |i8080| {
+        // The new name for the rename is placed in the second half of the FCB.
+        i8080.memory[0x006C..0x0078].copy_from_slice(b"\x00ALL     TXT");
+    });
+    assert_eq!(&i8080.memory[0x0081..0x008C], b"A       TXT");
+    assert_eq!(&i8080.memory[0x0201..0x020C], b"B       TXT");
+    assert_eq!(&i8080.memory[0x0300..0x0302], &[0x00, 0xFF]);
+    assert!(directory.0.join("ALL.TXT").exists());
+    assert!(directory.0.join("C.DOC").exists());
+}
+
+#[test]
+fn system_boot_and_read() {
+    #[rustfmt::skip]
+    let ccp = [
+        0x0E, 0x00,       // E400: MVI C,0
+        0xCD, 0x1B, 0xFA, // E402: CALL SELDSK
+        0x22, 0x00, 0xE5, // E405: SHLD E500H
+        0x01, 0x02, 0x00, // E408: LXI B,2
+        0xCD, 0x1E, 0xFA, // E40B: CALL SETTRK
+        0x5E,             // E40E: MOV E,M
+        0x23,             // E40F: INX H
+        0x56,             // E410: MOV D,M
+        0x01, 0x01, 0x00, // E411: LXI B,1
+        0xCD, 0x30, 0xFA, // E414: CALL SECTRAN
+        0x44,             // E417: MOV B,H
+        0x4D,             // E418: MOV C,L
+        0xCD, 0x21, 0xFA, // E419: CALL SETSEC
+        0x01, 0x00, 0x01, // E41C: LXI B,0100H
+        0xCD, 0x24, 0xFA, // E41F: CALL SETDMA
+        0xCD, 0x27, 0xFA, // E422: CALL READ
+        0x32, 0x02, 0xE5, // E425: STA E502H
+        0x21, 0x00, 0x01, // E428: LXI H,0100H
+        0x7E,             // E42B: MOV A,M
+        0xFE, b'$',       // E42C: CPI '$'
+        0xCA, 0x39, 0xE4, // E42E: JZ E439H
+        0x4F,             // E431: MOV C,A
+        0xCD, 0x0C, 0xFA, // E432: CALL CONOUT
+        0x23,             // E435: INX H
+        0xC3, 0x2B, 0xE4, // E436: JMP E42BH
+        0x76,             // E439: HLT
+    ];
+    let mut disk = system_disk(&ccp);
+    // Logical sector 1 of track 2 is physical sector 7 because of the skew.
+    let offset = (2 * 26 + 6) * 128;
+    disk[offset..offset + 19].copy_from_slice(b"Hello from track 2$");
+    let mut system = System::new(BufferConsole::default(), DEFAULT_BIOS_BASE);
+    system.mount(0, DiskImage::from_bytes(DiskFormat::ibm_3740(), disk).unwrap());
+    system.boot().unwrap();
+    assert!(matches!(system.run(), Err(Error::Halted)));
+    assert_eq!(system.console().output, b"Hello from track 2");
+    assert_eq!(system.i8080.memory[0xE502], 0x00);
+
+    let memory = &system.i8080.memory;
+    assert_eq!(&memory[0x0000..0x0003], &[0xC3, 0x03, 0xFA]); // JMP WBOOT
+    assert_eq!(&memory[0x0005..0x0008], &[0xC3, 0x06, 0xEC]); // JMP BDOS
+    let word = |address: u16| u16::from_le_bytes([memory[address], memory[address + 1]]);
+    let dph = word(0xE500);
+    assert!(dph > DEFAULT_BIOS_BASE);
+    let dpb = word(dph + 10);
+    assert_eq!(&memory[dpb..dpb + 15], &DiskFormat::ibm_3740().dpb.to_bytes());
+    assert_eq!(memory[word(dph) + 25], 22);
+}
+
+#[test]
+fn system_write() {
+    let directory = Directory::new("system_write");
+    #[rustfmt::skip]
+    let ccp = [
+        0x0E, 0x01,       // E400: MVI C,1
+        0xCD, 0x1B, 0xFA, // E402: CALL SELDSK
+        0x01, 0x03, 0x00, // E405: LXI B,3
+        0xCD, 0x1E, 0xFA, // E408: CALL SETTRK
+        0x01, 0x01, 0x00, // E40B: LXI B,1
+        0xCD, 0x21, 0xFA, // E40E: CALL SETSEC
+        0x01, 0x40, 0xE4, // E411: LXI B,E440H
+        0xCD, 0x24, 0xFA, // E414: CALL SETDMA
+        0x0E, 0x00,       // E417: MVI C,0
+        0xCD, 0x2A, 0xFA, // E419: CALL WRITE
+        0x76,             // E41C: HLT
+    ];
+    let mut system = System::new(BufferConsole::default(), DEFAULT_BIOS_BASE);
+    let mut boot_disk = system_disk(&ccp);
+    boot_disk[128 + 0x40..128 + 0x47].copy_from_slice(b"written");
+    system.mount(0, DiskImage::from_bytes(DiskFormat::ibm_3740(), boot_disk).unwrap());
+    let path = directory.0.join("B.IMG");
+    fs::write(&path, vec![0xE5; 77 * 26 * 128]).unwrap();
+    system.mount(1, DiskImage::open(&path, DiskFormat::ibm_3740()).unwrap());
+    system.boot().unwrap();
+    assert!(matches!(system.run(), Err(Error::Halted)));
+    assert_eq!(system.i8080.cpu.a, 0x00);
+
+    let expected = &system.i8080.memory[0xE440..0xE4C0];
+    assert_eq!(&expected[..7], b"written");
+    let offset = 3 * 26 * 128;
+    assert_eq!(&system.disk(1).unwrap().as_bytes()[offset..offset + 128], expected);
+    assert_eq!(&fs::read(&path).unwrap()[offset..offset + 128], expected);
+}
+
+#[test]
+fn system_warm_boot() {
+    #[rustfmt::skip]
+    let ccp = [
+        0x0E, b'A',       // E400: MVI C,'A'
+        0xCD, 0x0C, 0xFA, // E402: CALL CONOUT
+        0x21, 0x00, 0x02, // E405: LXI H,0200H
+        0x34,             // E408: INR M
+        0x7E,             // E409: MOV A,M
+        0xFE, 0x02,       // E40A: CPI 2
+        0xCA, 0x12, 0xE4, // E40C: JZ E412H
+        0xC3, 0x00, 0x00, // E40F: JMP 0000H
+        0x76,             // E412: HLT
+    ];
+    let mut system = System::new(BufferConsole::default(), DEFAULT_BIOS_BASE);
+    system.mount(0, DiskImage::from_bytes(DiskFormat::ibm_3740(), system_disk(&ccp)).unwrap());
+    system.boot().unwrap();
+    assert!(matches!(system.run(), Err(Error::Halted)));
+    assert_eq!(system.console().output, b"AA");
+}
+
+#[test]
+fn system_no_disk() {
+    let mut system = System::new(BufferConsole::default(), DEFAULT_BIOS_BASE);
+    assert!(matches!(system.boot(), Err(Error::NoDisk { drive: 0 })));
+}
+
+#[test]
+fn system_no_system_tracks() {
+    let mut system = System::new(BufferConsole::default(), DEFAULT_BIOS_BASE);
+    let format = DiskFormat { tracks: 1, ..DiskFormat::ibm_3740() };
+    system.mount(0, DiskImage::new(format));
+    assert!(matches!(system.boot(), Err(Error::InvalidDiskImage { path: None, size: 3328 })));
+}
+
+#[test]
+fn system_remount() {
+    let mut system = System::new(BufferConsole::default(), DEFAULT_BIOS_BASE);
+    // The disk parameters of both formats do not fit in memory twice, so they must be reused.
+    for _ in 0..10 {
+        system.mount(1, DiskImage::new(DiskFormat::ibm_3740()));
+        system.mount(1, DiskImage::new(DiskFormat::hard_disk_4m()));
+        system.mount(2, DiskImage::new(DiskFormat::ibm_3740()));
+        system.unmount(2);
+    }
+}
+
+#[test]
+#[should_panic(expected = "the BIOS is not aligned on a sector")]
+fn system_unaligned_bios() {
+    System::new(BufferConsole::default(), DEFAULT_BIOS_BASE + 3);
+}
+
+#[test]
+#[should_panic(expected = "no room for the CCP and the BDOS")]
+fn system_bios_too_low() {
+    System::new(BufferConsole::default(), 0x1000);
+}
+
+#[test]
+#[should_panic(expected = "no room for the BIOS jump table and the directory buffer")]
+fn system_bios_too_high() {
+    System::new(BufferConsole::default(), 0xFF80);
+}
+
+// Returns an IBM 3740 disk image whose system tracks hold `ccp` in place of the CCP.
+fn system_disk(ccp: &[u8]) -> Vec<u8> {
+    let mut disk = vec![0xE5; 77 * 26 * 128];
+    disk[128..128 + ccp.len()].copy_from_slice(ccp);
+    disk
+}
+
+struct Directory(PathBuf);
+
+impl Directory {
+    fn new(name: &str) -> Self {
+        let path = env::temp_dir().join(format!("i8080-cpm-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+impl Drop for Directory {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn run(
+    directory: &Directory,
+    program: &[u8],
+    args: &[&str],
+    input: &[u8],
+) -> (Bdos<BufferConsole>, Intel8080) {
+    run_with(directory, program, args, input, |_| ())
+}
+
+fn run_with<F: FnOnce(&mut Intel8080)>(
+    directory: &Directory,
+    program: &[u8],
+    args: &[&str],
+    input: &[u8],
+    set_up: F,
+) -> (Bdos<BufferConsole>, Intel8080) {
+    let path: &Path = &directory.0.join("TEST.COM");
+    fs::write(path, program).unwrap();
+    let mut bdos = Bdos::new(BufferConsole::new(input), &directory.0);
+    let mut i8080 = bdos.load_program(path, args).unwrap();
+    set_up(&mut i8080);
+    bdos.run(&mut i8080).unwrap();
+    (bdos, i8080)
+}
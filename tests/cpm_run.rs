@@ -0,0 +1,31 @@
+#![warn(rust_2018_idioms)]
+
+use std::process::Command;
+
+#[test]
+fn tst8080() {
+    let output = Command::new(env!("CARGO_BIN_EXE_cpm-run"))
+        .arg("TST8080.COM")
+        .current_dir("tests/cpu_tests")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.ends_with(b" CPU IS OPERATIONAL"));
+}
+
+#[test]
+fn missing_program() {
+    let output = Command::new(env!("CARGO_BIN_EXE_cpm-run"))
+        .arg("MISSING.COM")
+        .current_dir("tests/cpu_tests")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stderr.starts_with(b"Error: "));
+}
+
+#[test]
+fn usage() {
+    let output = Command::new(env!("CARGO_BIN_EXE_cpm-run")).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -2,7 +2,11 @@
 
 use std::path::Path;
 
-use i8080::Intel8080;
+use i8080::{
+    console::BufferConsole,
+    cpm::{Bdos, BDOS_BASE},
+    cpu::{Cpu, Variant},
+};
 
 #[test]
 fn cpu_tests_8080pre() {
@@ -21,12 +25,12 @@ fn cpu_tests_tst8080() {
 }
 
 #[test]
-#[ignore = "it takes dozens of seconds even with the --release flag"]
+#[ignore = "it executes billions of instructions; run it with --release"]
 fn cpu_tests_8080exm() {
-    cpu_tests("tests/cpu_tests/8080EXM.COM", |output| {
-        print!("{}", String::from_utf8_lossy(output));
-        assert!(output.windows(b"ERROR".len()).all(|window| window != b"ERROR"));
-    });
+    let output = run_on(Variant::Intel8080, "tests/cpu_tests/8080EXM.COM", u64::MAX);
+    print!("{}", String::from_utf8_lossy(&output));
+    assert!(contains(&output, b"Tests complete"));
+    assert!(!contains(&output, b"ERROR"));
 }
 
 #[test]
@@ -37,34 +41,67 @@ fn cpu_tests_cputest() {
     });
 }
 
+#[test]
+fn cpu_tests_amd9080() {
+    let output = run_on(Variant::Amd9080, "tests/cpu_tests/8080PRE.COM", 500_000_000);
+    assert_eq!(output, b"8080 Preliminary tests complete");
+    let output = run_on(Variant::Amd9080, "tests/cpu_tests/TST8080.COM", 500_000_000);
+    assert!(output.ends_with(b" CPU IS OPERATIONAL"));
+    // CPUTEST.COM expects ANA to set the auxiliary carry flag as the Intel 8080A does.
+    let output = run_on(Variant::Amd9080, "tests/cpu_tests/CPUTEST.COM", 500_000_000);
+    assert!(contains(&output, b"CPU FAILED"));
+    assert!(contains(&output, b"INSTRUCTION SEQUENCE WAS A00000H")); // ANA B
+}
+
+#[test]
+fn cpu_tests_nec_upd8080af() {
+    let output = run_on(Variant::NecUpd8080af, "tests/cpu_tests/8080PRE.COM", 500_000_000);
+    assert_eq!(output, b"8080 Preliminary tests complete");
+    // TST8080.COM expects even parity after an arithmetic instruction that overflows.
+    let output = run_on(Variant::NecUpd8080af, "tests/cpu_tests/TST8080.COM", 500_000_000);
+    assert!(output.ends_with(b" CPU HAS FAILED!    ERROR EXIT=01FB"));
+    // CPUTEST.COM tells a Z80 from an 8080 by the parity flag after an overflow.
+    let output = run_on(Variant::NecUpd8080af, "tests/cpu_tests/CPUTEST.COM", 500_000_000);
+    assert!(contains(&output, b"CPU IS Z80"));
+}
+
+fn contains(output: &[u8], text: &[u8]) -> bool {
+    output.windows(text.len()).any(|window| window == text)
+}
+
+// Runs `program` on a CPU of `variant` until it warm boots or takes `limit` states, returning the
+// console output.
+fn run_on<P: AsRef<Path>>(variant: Variant, program: P, limit: u64) -> Vec<u8> {
+    let mut bdos = Bdos::new(BufferConsole::default(), "tests/cpu_tests");
+    let mut i8080 = bdos.load_program(program, &[] as &[&str]).unwrap();
+    let Cpu { pc, sp, .. } = i8080.cpu;
+    i8080.cpu = Cpu::new(variant);
+    i8080.cpu.pc = pc;
+    i8080.cpu.sp = sp;
+    let mut states = 0;
+    while states < limit {
+        bdos.trap(&mut i8080).unwrap();
+        if i8080.cpu.pc == 0x0000 {
+            break;
+        }
+        assert!(!i8080.cpu.is_halted());
+        states += i8080.run(limit - states, |pc| pc == 0x0000 || pc >= BDOS_BASE);
+    }
+    bdos.into_console().output
+}
+
 fn cpu_tests<P: AsRef<Path>, F: FnOnce(&[u8])>(program: P, check: F) {
-    let mut i8080 = Intel8080::new(&[program], 0x100).unwrap();
-    // Location 0x0005 (CP/M BOOT + 0x0005) is the principal entry to the CP/M FDOS (BIOS + BDOS)
-    // functions.
-    i8080.memory[0x0005] = 0xC9; // RET (Return)
-    let mut output = Vec::new();
+    let mut bdos = Bdos::new(BufferConsole::default(), "tests/cpu_tests");
+    let mut i8080 = bdos.load_program(program, &[] as &[&str]).unwrap();
     loop {
-        match i8080.cpu.pc {
-            // The machine code found at location 0x0000 (CP/M BOOT) performs a system warm start,
-            // which returns control to the Console Command Processor (CCP).
-            0x0000 => {
-                check(&output);
-                break;
-            }
-            // Location 0x0005 (CP/M BOOT + 0x0005) is the principal entry to the CP/M FDOS (BIOS +
-            // BDOS) functions. The function number is passed in register C.
-            0x0005 => match i8080.cpu.c {
-                // FDOS function 2 - console output (E = ASCII character).
-                0x02 => output.push(i8080.cpu.e),
-                // FDOS function 9 - print string (DE = string address).
-                0x09 => {
-                    let address = u16::from_le_bytes([i8080.cpu.e, i8080.cpu.d]);
-                    let string = i8080.memory[address..].iter().take_while(|byte| **byte != b'$');
-                    output.extend(string);
-                }
-                _ => unimplemented!("Call 5 (C = {:#04X})", i8080.cpu.c),
-            },
-            _ => (),
+        // Location 0x0005 (CP/M BOOT + 0x0005) is the principal entry to the CP/M FDOS (BIOS +
+        // BDOS) functions, which jumps to the BDOS entry point that `Bdos` services.
+        bdos.trap(&mut i8080).unwrap();
+        // The machine code found at location 0x0000 (CP/M BOOT) performs a system warm start,
+        // which returns control to the Console Command Processor (CCP).
+        if i8080.cpu.pc == 0x0000 {
+            check(&bdos.console().output);
+            break;
         }
         let (instruction, states) = i8080.fetch_execute_instruction().unwrap();
         match (instruction, states) {
@@ -0,0 +1,142 @@
+use i8080::{
+    console::BufferConsole,
+    cpm::disk::{DiskFormat, DiskImage},
+    machine::imsai::Imsai,
+    Error,
+};
+
+#[test]
+fn imsai() {
+    let mut imsai = Imsai::new(BufferConsole::new("!"));
+    #[rustfmt::skip]
+    let program = [
+        0xDB, 0x03,       // 0x0000: IN 0x03
+        0xE6, 0x02,       //         ANI 0x02
+        0xCA, 0x00, 0x00, //         JZ 0x0000
+        0xDB, 0x02,       //         IN 0x02
+        0xD3, 0x02,       //         OUT 0x02
+        0x32, 0x02, 0xF0, //         STA 0xF002
+        0x21, 0x4A, 0x00, //         LXI H,0x004A
+        0x11, 0x50, 0xF0, //         LXI D,0xF050
+        0x7E,             // 0x0014: MOV A,M
+        0xB7,             //         ORA A
+        0xCA, 0x1F, 0x00, //         JZ 0x001F
+        0x12,             //         STAX D
+        0x23,             //         INX H
+        0x13,             //         INX D
+        0xC3, 0x14, 0x00, //         JMP 0x0014
+        0xDB, 0xFF,       // 0x001F: IN 0xFF
+        0xD3, 0xFF,       //         OUT 0xFF
+        0x76,             //         HLT
+    ];
+    imsai.i8080.memory[0x0000..program.len() as u16].copy_from_slice(&program);
+    imsai.i8080.memory[0x004A..0x0050].copy_from_slice(b"HELLO\0");
+    imsai.i8080.memory[0xF000] = b'>' | 0x80;
+    imsai.set_switches(0x5A00);
+    imsai.run().unwrap();
+
+    assert_eq!(imsai.console().output, b"!");
+    assert_eq!(imsai.vio_line(0), format!(">{:79}", " !"));
+    let mut expected = String::from(">\u{20}!\nHELLO\n");
+    expected.push_str(&"\n".repeat(22));
+    assert_eq!(imsai.vio_text(), expected);
+    let leds = imsai.leds();
+    assert_eq!(leds.programmed_output, 0x5A);
+    assert!(leds.wait);
+}
+
+#[test]
+fn front_panel() {
+    let mut imsai = Imsai::new(BufferConsole::default());
+    imsai.set_switches(0x0100);
+    imsai.examine();
+    // MVI A,0x42; OUT 0xFF
+    for (index, byte) in [0x3E_u8, 0x42, 0xD3, 0xFF].iter().enumerate() {
+        imsai.set_switches(u16::from(*byte));
+        if index == 0 {
+            imsai.deposit();
+        } else {
+            imsai.deposit_next();
+        }
+    }
+    imsai.set_switches(0x0100);
+    imsai.examine();
+    imsai.single_step().unwrap();
+    imsai.single_step().unwrap();
+    assert_eq!(imsai.leds().programmed_output, 0x42);
+    assert_eq!(imsai.leds().address, 0x0104);
+}
+
+#[test]
+fn boot_cpm() {
+    // A stand-in for the CCP, which prompts with the current drive and echoes a command line
+    // through the BIOS.
+    #[rustfmt::skip]
+    let ccp = [
+        0x79,             // D000: MOV A,C
+        0xE6, 0x0F,       // D001: ANI 0FH
+        0xC6, b'A',       // D003: ADI 'A'
+        0xF5,             // D005: PUSH PSW
+        0x0E, b'\r',      // D006: MVI C,0DH
+        0xCD, 0x0C, 0xE6, // D008: CALL CONOUT
+        0x0E, b'\n',      // D00B: MVI C,0AH
+        0xCD, 0x0C, 0xE6, // D00D: CALL CONOUT
+        0xF1,             // D010: POP PSW
+        0x4F,             // D011: MOV C,A
+        0xCD, 0x0C, 0xE6, // D012: CALL CONOUT
+        0x0E, b'>',       // D015: MVI C,'>'
+        0xCD, 0x0C, 0xE6, // D017: CALL CONOUT
+        0xCD, 0x09, 0xE6, // D01A: CALL CONIN
+        0xFE, b'\r',      // D01D: CPI 0DH
+        0xCA, 0x29, 0xD0, // D01F: JZ D029H
+        0x4F,             // D022: MOV C,A
+        0xCD, 0x0C, 0xE6, // D023: CALL CONOUT
+        0xC3, 0x1A, 0xD0, // D026: JMP D01AH
+        0x76,             // D029: HLT
+    ];
+    let mut disk = vec![0xE5; 77 * 26 * 128];
+    disk[128..128 + ccp.len()].copy_from_slice(&ccp);
+    let mut imsai = Imsai::new(BufferConsole::new("DIR\r"));
+    assert!(matches!(imsai.boot(), Err(Error::NoDisk { drive: 0 })));
+    imsai.mount(0, DiskImage::from_bytes(DiskFormat::ibm_3740(), disk).unwrap());
+    imsai.boot().unwrap();
+    assert_eq!(imsai.i8080.cpu.pc, 0xD000);
+    imsai.run().unwrap();
+
+    assert_eq!(imsai.console().output, b"\r\nA>DIR");
+    let mut expected = String::from("\nA>DIR\n");
+    expected.push_str(&"\n".repeat(22));
+    assert_eq!(imsai.vio_text(), expected);
+}
+
+#[test]
+fn vio_scrolls() {
+    // A stand-in for the CCP that writes 26 numbered lines from 0100H to the console, of which
+    // the first three scroll off the screen.
+    let lines: String = (0..26).map(|line| format!("{}\r\n", line)).collect();
+    #[rustfmt::skip]
+    let ccp = [
+        0x21, 0x00, 0x01, // D000: LXI H,0100H
+        0x06, 94,         // D003: MVI B,94
+        0x4E,             // D005: MOV C,M
+        0x23,             // D006: INX H
+        0xE5,             // D007: PUSH H
+        0xC5,             // D008: PUSH B
+        0xCD, 0x0C, 0xE6, // D009: CALL CONOUT
+        0xC1,             // D00C: POP B
+        0xE1,             // D00D: POP H
+        0x05,             // D00E: DCR B
+        0xC2, 0x05, 0xD0, // D00F: JNZ D005H
+        0x76,             // D012: HLT
+    ];
+    let mut disk = vec![0xE5; 77 * 26 * 128];
+    disk[128..128 + ccp.len()].copy_from_slice(&ccp);
+    let mut imsai = Imsai::new(BufferConsole::default());
+    imsai.mount(0, DiskImage::from_bytes(DiskFormat::ibm_3740(), disk).unwrap());
+    imsai.boot().unwrap();
+    imsai.i8080.memory[0x0100..0x0100 + 94].copy_from_slice(lines.as_bytes());
+    imsai.run().unwrap();
+    let text = imsai.vio_text();
+    assert!(text.starts_with("3\n4\n"), "{}", text);
+    assert!(text.ends_with("25\n\n"), "{}", text);
+}
@@ -0,0 +1,16 @@
+#![warn(rust_2018_idioms)]
+
+use i8080::{memory::Memory, Error};
+
+#[test]
+fn load_bytes() {
+    let mut memory = Memory::new();
+    assert_eq!(memory.load_bytes(&[0x3E, 0x42, 0x76], 0x0100).unwrap(), 0x0103);
+    assert_eq!(&memory[0x0100..0x0104], &[0x3E, 0x42, 0x76, 0x00]);
+    assert_eq!(memory.load_bytes(&[0xFF; 2], 0xFFFE).unwrap(), 0x0000);
+    assert!(matches!(
+        memory.load_bytes(&[0xFF; 2], 0xFFFF),
+        Err(Error::TooLargeImage { size: 2, start_address: 0xFFFF })
+    ));
+    assert_eq!(memory[0x0000], 0x00);
+}
@@ -0,0 +1,97 @@
+use std::{env, fs, process};
+
+use i8080::{
+    console::Console,
+    machine::altair::Altair,
+    paper_tape::{PaperTape, TELETYPE_SPEED},
+};
+
+// The bootstrap loader of 4K Altair BASIC for the 88-2SIO, which reads the checksum loader from
+// the tape into memory downwards from 0x0FAE and jumps to it.
+#[rustfmt::skip]
+const BOOTSTRAP: &[u8] = &[
+    0x21, 0xAE, 0x0F, // 0x0000: LXI H,0x0FAE
+    0x31, 0x12, 0x00, // 0x0003: LXI SP,0x0012
+    0xDB, 0x10,       //         IN 0x10
+    0x0F,             //         RRC
+    0xD0,             //         RNC
+    0xDB, 0x11,       //         IN 0x11
+    0xBD,             //         CMP L
+    0xC8,             //         RZ
+    0x2D,             //         DCR L
+    0x77,             //         MOV M,A
+    0xC0,             //         RNZ
+    0xE9,             //         PCHL
+    0x03, 0x00,       // 0x0012: DW 0x0003
+];
+
+#[test]
+fn bootstrap() {
+    // A leader of the length of the loader, followed by the loader, last byte first.
+    let mut loader = [0x00; 0xAE];
+    #[rustfmt::skip]
+    loader[..9].copy_from_slice(&[
+        0x3E, b'O', // 0x0F00: MVI A,'O'
+        0xD3, 0x11, //         OUT 0x11
+        0x3E, b'K', //         MVI A,'K'
+        0xD3, 0x11, //         OUT 0x11
+        0x76,       //         HLT
+    ]);
+    let mut tape = vec![0xAE; 8];
+    tape.extend(loader.iter().rev());
+
+    let mut reader = PaperTape::new(2_000_000, 1000);
+    reader.load(&tape);
+    let mut altair = Altair::new(reader, 0x1000);
+    altair.i8080.memory[0x0000..BOOTSTRAP.len() as u16].copy_from_slice(BOOTSTRAP);
+    altair.run().unwrap();
+
+    assert_eq!(altair.console().punched(), b"OK");
+    assert!(altair.console().reader().is_empty());
+    assert_eq!(&altair.i8080.memory[0x0F00..0x0FAE], &loader[..]);
+    // Each character takes 2000 states at 1000 characters per second.
+    assert!(altair.states() >= (tape.len() as u64 - 1) * 2000);
+}
+
+#[test]
+fn timing() {
+    let mut tape = PaperTape::new(2_000_000, TELETYPE_SPEED);
+    assert!(!tape.is_input_ready());
+    tape.load(b"AB");
+    assert!(!tape.is_input_ready());
+    tape.elapse(199_999);
+    assert!(!tape.is_input_ready());
+    tape.elapse(1);
+    assert!(tape.is_input_ready());
+    assert_eq!(tape.read_byte().unwrap(), Some(b'A'));
+    assert!(!tape.is_input_ready());
+    // Reading before the next character arrives returns the last character again.
+    assert_eq!(tape.read_byte().unwrap(), Some(b'A'));
+    tape.elapse(200_000);
+    assert_eq!(tape.read_byte().unwrap(), Some(b'B'));
+    tape.elapse(u32::MAX);
+    assert!(!tape.is_input_ready());
+    assert_eq!(tape.read_byte().unwrap(), None);
+}
+
+#[test]
+fn files() {
+    let directory = env::temp_dir();
+    let input = directory.join(format!("i8080-paper-tape-{}.bin", process::id()));
+    let output = directory.join(format!("i8080-paper-tape-{}.punch", process::id()));
+    fs::write(&input, b"XY").unwrap();
+    fs::write(&output, b"12").unwrap();
+
+    let mut tape = PaperTape::new(2_000_000, TELETYPE_SPEED);
+    tape.load_file(&input).unwrap();
+    tape.punch_to_file(&output).unwrap();
+    tape.elapse(u32::MAX);
+    while let Some(byte) = tape.read_byte().unwrap() {
+        tape.write_byte(byte).unwrap();
+        tape.elapse(u32::MAX);
+    }
+    assert_eq!(tape.punched(), b"XY");
+    assert_eq!(fs::read(&output).unwrap(), b"12XY");
+    fs::remove_file(&input).unwrap();
+    fs::remove_file(&output).unwrap();
+}
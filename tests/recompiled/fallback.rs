@@ -0,0 +1,75 @@
+// Recompiled by i8080-recompile from fallback.bin loaded at 0100H. Do not edit.
+
+use i8080::{
+    cpu::{Cpu, Io, Variant},
+    memory::Memory,
+};
+
+const ORIGIN: u16 = 0x0100;
+const IMAGE: [u8; 25] = [
+    0x21, 0x0A, 0x01, 0xE9, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3E, 0x01, 0xC3, 0x10, 0x01, 0x00,
+    0x21, 0x17, 0x01, 0x36, 0x3C, 0x00, 0x00, 0x00, 0x76,
+];
+
+/// Runs the program as `Cpu::run_with_io` does until the program counter reaches an address for
+/// which `is_breakpoint` returns `true`, the CPU halts, or at least `states` states have elapsed,
+/// returning the number of states taken.
+#[allow(clippy::cognitive_complexity)]
+pub fn run_with_io<I: Io + ?Sized, F: FnMut(u16) -> bool>(
+    cpu: &mut Cpu,
+    memory: &mut Memory,
+    io: &mut I,
+    states: u64,
+    mut is_breakpoint: F,
+) -> u64 {
+    if let Variant::Intel8085 | Variant::Z80 = cpu.variant() {
+        return cpu.run_with_io(memory, io, states, is_breakpoint);
+    }
+    cpu.run_recompiled(|cpu| {
+        let mut elapsed = 0;
+        macro_rules! execute {
+            ($address:expr, $instruction:expr) => {
+                let instruction_states =
+                    cpu.execute_recompiled_instruction($address, $instruction, memory, io);
+                elapsed += u64::from(instruction_states);
+                if elapsed >= states || is_breakpoint(cpu.pc) {
+                    return elapsed;
+                }
+            };
+        }
+        while !cpu.is_halted() {
+            match cpu.pc {
+                0x0100 if is_unchanged(memory, 0x0100, 0x0104) => {
+                    execute!(0x0100, [0x21, 0x0A, 0x01]); // LXI H,010AH
+                    execute!(0x0103, [0xE9, 0x00, 0x00]); // PCHL
+                }
+                0x0110 if is_unchanged(memory, 0x0110, 0x0119) => {
+                    execute!(0x0110, [0x21, 0x17, 0x01]); // LXI H,0117H
+                    execute!(0x0113, [0x36, 0x3C, 0x00]); // MVI M,3CH
+                    if !is_unchanged(memory, 0x0115, 0x0119) {
+                        continue;
+                    }
+                    execute!(0x0115, [0x00, 0x00, 0x00]); // NOP
+                    execute!(0x0116, [0x00, 0x00, 0x00]); // NOP
+                    execute!(0x0117, [0x00, 0x00, 0x00]); // NOP
+                    execute!(0x0118, [0x76, 0x00, 0x00]); // HLT
+                }
+                // Computed jumps, modified code, and code outside the image
+                address => {
+                    let instruction = [
+                        memory[address],
+                        memory[address.wrapping_add(1)],
+                        memory[address.wrapping_add(2)],
+                    ];
+                    execute!(address, instruction);
+                }
+            }
+        }
+        elapsed
+    })
+}
+
+// Returns `true` if the code from `start` up to `end` is as it was recompiled.
+fn is_unchanged(memory: &Memory, start: u16, end: u16) -> bool {
+    memory[start..end] == IMAGE[usize::from(start - ORIGIN)..usize::from(end - ORIGIN)]
+}
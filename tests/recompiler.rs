@@ -0,0 +1,128 @@
+#![warn(rust_2018_idioms)]
+
+use std::fs;
+
+use i8080::{
+    console::BufferConsole,
+    cpm::{Bdos, BDOS_BASE},
+    cpu::{ConditionFlags, Cpu, Io},
+    recompiler::Program,
+    Error, Intel8080,
+};
+
+#[path = "recompiled/fallback.rs"]
+mod fallback;
+#[path = "../examples/tst8080/recompiled.rs"]
+mod tst8080;
+
+// LXI H,010AH; PCHL; ...; MVI A,01H; JMP 0110H; ...; LXI H,0117H; MVI M,3CH; NOP; NOP; NOP; HLT,
+// which jumps to code that is not recompiled and overwrites the third NOP with INR A.
+const FALLBACK: [u8; 25] = [
+    0x21, 0x0A, 0x01, 0xE9, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3E, 0x01, 0xC3, 0x10, 0x01, 0x00,
+    0x21, 0x17, 0x01, 0x36, 0x3C, 0x00, 0x00, 0x00, 0x76,
+];
+
+struct NoDevice;
+
+impl Io for NoDevice {
+    fn input(&mut self, _port: u8) -> u8 {
+        0xFF
+    }
+
+    fn output(&mut self, _port: u8, _byte: u8) {}
+}
+
+#[test]
+fn recompiled_sources_are_up_to_date() {
+    let tst8080 = fs::read("tests/cpu_tests/TST8080.COM").unwrap();
+    assert_eq!(
+        Program::new(&tst8080, 0x0100, &[0x0100]).unwrap().to_rust("TST8080.COM"),
+        fs::read_to_string("examples/tst8080/recompiled.rs").unwrap()
+    );
+    assert_eq!(
+        Program::new(&FALLBACK, 0x0100, &[0x0100, 0x0110]).unwrap().to_rust("fallback.bin"),
+        fs::read_to_string("tests/recompiled/fallback.rs").unwrap()
+    );
+}
+
+#[test]
+fn blocks() {
+    let program = Program::new(&FALLBACK, 0x0100, &[0x0100, 0x0110, 0x2000]).unwrap();
+    assert_eq!(program.blocks().collect::<Vec<_>>(), [0x0100..0x0104, 0x0110..0x0119]);
+
+    // CALL 0106H; JZ 0100H; RST 1; RET
+    let program =
+        Program::new(&[0xCD, 0x06, 0x01, 0xCA, 0x00, 0x01, 0xCF, 0xC9], 0x0100, &[0x0100]).unwrap();
+    assert_eq!(
+        program.blocks().collect::<Vec<_>>(),
+        [0x0100..0x0103, 0x0103..0x0106, 0x0106..0x0107, 0x0107..0x0108]
+    );
+
+    assert!(matches!(
+        Program::new(&[0x00; 2], 0xFFFF, &[0xFFFF]),
+        Err(Error::TooLargeImage { size: 2, start_address: 0xFFFF })
+    ));
+}
+
+#[test]
+fn tst8080() {
+    let mut bdos = Bdos::new(BufferConsole::default(), "tests/cpu_tests");
+    let mut interpreted = bdos.load_program("tests/cpu_tests/TST8080.COM", &[] as &[&str]).unwrap();
+    let mut recompiled = bdos.load_program("tests/cpu_tests/TST8080.COM", &[] as &[&str]).unwrap();
+    // Small budgets stop the recompiled code in the middle of blocks.
+    for &budget in [1, 7, 13, 50, u64::MAX].iter().cycle() {
+        bdos.trap(&mut interpreted).unwrap();
+        bdos.trap(&mut recompiled).unwrap();
+        if interpreted.cpu.pc == 0x0000 {
+            break;
+        }
+        let is_breakpoint = |pc| pc == 0x0000 || pc >= BDOS_BASE;
+        let Intel8080 { cpu, memory } = &mut recompiled;
+        assert_eq!(
+            tst8080::run_with_io(cpu, memory, &mut NoDevice, budget, is_breakpoint),
+            interpreted.run_with_io(&mut NoDevice, budget, is_breakpoint)
+        );
+        assert_eq!(registers(&recompiled.cpu), registers(&interpreted.cpu));
+        assert!(recompiled.memory[0x0000..] == interpreted.memory[0x0000..]);
+    }
+    assert_eq!(recompiled.cpu.pc, 0x0000);
+    assert!(bdos.into_console().output.ends_with(b" CPU IS OPERATIONAL"));
+}
+
+#[test]
+fn computed_jumps_and_modified_code() {
+    // The second run overwrites the first NOP with INR A before the recompiled block is entered.
+    for &(address, byte, a) in &[(0x0100, 0x21, 0x02), (0x0115, 0x3C, 0x03)] {
+        let mut interpreted = load(&FALLBACK);
+        interpreted.memory[address] = byte;
+        let mut recompiled = load(&FALLBACK);
+        recompiled.memory[address] = byte;
+        let Intel8080 { cpu, memory } = &mut recompiled;
+        assert_eq!(
+            fallback::run_with_io(cpu, memory, &mut NoDevice, 1_000, |_| false),
+            interpreted.run_with_io(&mut NoDevice, 1_000, |_| false)
+        );
+        assert_eq!(registers(&recompiled.cpu), registers(&interpreted.cpu));
+        assert!(recompiled.memory[0x0000..] == interpreted.memory[0x0000..]);
+        assert!(recompiled.cpu.is_halted());
+        assert_eq!(recompiled.cpu.a, a);
+    }
+}
+
+fn load(program: &[u8]) -> Intel8080 {
+    let mut i8080 = Intel8080::default();
+    i8080.memory.load_bytes(program, 0x0100).unwrap();
+    i8080.cpu.pc = 0x0100;
+    i8080
+}
+
+fn registers(cpu: &Cpu) -> (u16, u16, [u8; 7], ConditionFlags, bool, bool) {
+    (
+        cpu.pc,
+        cpu.sp,
+        [cpu.b, cpu.c, cpu.d, cpu.e, cpu.h, cpu.l, cpu.a],
+        cpu.condition_flags,
+        cpu.is_interrupt_enabled(),
+        cpu.is_halted(),
+    )
+}
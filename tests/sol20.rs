@@ -0,0 +1,75 @@
+use i8080::{
+    console::BufferConsole,
+    machine::sol20::{Sol20, ROM_START, VDM_COLUMNS},
+    Error,
+};
+
+#[rustfmt::skip]
+const ROM: &[u8] = &[
+    0xDB, 0xFA,       // 0xC000: IN 0xFA
+    0xE6, 0x01,       //         ANI 0x01
+    0xC2, 0x00, 0xC0, //         JNZ 0xC000
+    0xDB, 0xFC,       //         IN 0xFC
+    0x32, 0x40, 0xCC, //         STA 0xCC40
+    0x3E, 0x01,       //         MVI A,0x01
+    0xD3, 0xFE,       //         OUT 0xFE
+    0x3E, 0x80,       //         MVI A,0x80
+    0xD3, 0xFA,       //         OUT 0xFA
+    0xDB, 0xFA,       // 0xC014: IN 0xFA
+    0xE6, 0x40,       //         ANI 0x40
+    0xCA, 0x14, 0xC0, //         JZ 0xC014
+    0xDB, 0xFB,       //         IN 0xFB
+    0x32, 0x41, 0xCC, //         STA 0xCC41
+    0xD3, 0xFB,       //         OUT 0xFB
+    0x32, 0x00, 0xC0, //         STA 0xC000
+    0x76,             //         HLT
+];
+
+#[test]
+fn sol20() {
+    let mut sol = Sol20::new(BufferConsole::new("A"), BufferConsole::new("T"));
+    sol.load_rom(ROM);
+    sol.run().unwrap();
+
+    // The ROM cannot be written.
+    assert_eq!(sol.i8080.memory[0xC000], 0xDB);
+    assert_eq!(sol.cassette().output, b"T");
+    assert_eq!(sol.tape_motors(), (true, false));
+    // The display is scrolled by a line.
+    assert_eq!(sol.screen_line(0), format!("{:width$}", "AT", width = VDM_COLUMNS));
+    let mut expected = String::from("AT\n");
+    expected.push_str(&"\n".repeat(15));
+    assert_eq!(sol.screen_text(), expected);
+
+    sol.i8080.memory[0xCC00] = b'>' | 0x80;
+    assert!(sol.screen_text().ends_with("\n>\n"));
+}
+
+#[test]
+fn waits_for_keyboard() {
+    let mut sol = Sol20::new(BufferConsole::default(), BufferConsole::default());
+    sol.load_rom(ROM);
+    sol.run_for(1000).unwrap();
+    assert!(sol.i8080.cpu.pc < 0xC007);
+    sol.keyboard_mut().input.push_back(b'B');
+    sol.run_for(1000).unwrap();
+    assert_eq!(sol.i8080.memory[0xCC40], b'B');
+    sol.reset();
+    assert_eq!(sol.i8080.cpu.pc, 0xC000);
+}
+
+#[test]
+fn interrupt() {
+    let mut sol = Sol20::new(BufferConsole::default(), BufferConsole::default());
+    sol.load_rom(&[0xFB, 0x76]); // EI; HLT
+    sol.i8080.cpu.sp = ROM_START + 0x0100;
+    sol.run().unwrap();
+    let rst_7 = [0xFF, 0x00, 0x00];
+    sol.interrupt(rst_7).unwrap();
+    assert_eq!(sol.i8080.cpu.pc, 0x0038);
+    assert_eq!(sol.i8080.cpu.sp, ROM_START + 0x00FE);
+    // The return address is not pushed onto the ROM.
+    assert_eq!(sol.i8080.memory[ROM_START + 0x00FE..ROM_START + 0x0100], [0x00, 0x00]);
+    assert_eq!(sol.states(), 4 + 7 + 11);
+    assert!(matches!(sol.interrupt(rst_7), Err(Error::InterruptNotEnabled)));
+}
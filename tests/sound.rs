@@ -0,0 +1,129 @@
+use std::io;
+
+use i8080::{
+    machine::space_invaders::{self, SpaceInvaders, CLOCK_RATE},
+    sound::{Mixer, Sound, SoundEvent, SoundLog, Voice, Waveform},
+    wav::Wave,
+};
+
+fn square(looping: bool) -> Voice {
+    Voice {
+        port: 3,
+        mask: 0x01,
+        sound: Sound::Tone {
+            waveform: Waveform::Square,
+            start_frequency: 100.0,
+            end_frequency: 100.0,
+            duration: 0.1,
+        },
+        looping,
+        volume: 0.5,
+    }
+}
+
+fn event(states: u64, byte: u8) -> SoundEvent {
+    SoundEvent { states, port: 3, byte }
+}
+
+#[test]
+fn log_records_changes_only() {
+    let mut log = SoundLog::new();
+    log.record(10, 3, 0x01);
+    log.record(20, 3, 0x01);
+    log.record(30, 5, 0x01);
+    log.record(40, 3, 0x00);
+    assert_eq!(
+        log.events(),
+        &[event(10, 0x01), SoundEvent { states: 30, port: 5, byte: 1 }, event(40, 0)]
+    );
+    log.clear();
+    assert!(log.events().is_empty());
+    assert_eq!(log.latch(5), 0x01);
+}
+
+#[test]
+fn one_shot_plays_to_the_end() {
+    // 1 state per sample: the sound starts at sample 100 and lasts 100 samples.
+    let mixer = Mixer::new(1000, 1000, vec![square(false)]);
+    let wave = mixer.render(&[event(100, 0x01), event(110, 0x00)], 300);
+    assert_eq!(wave.samples.len(), 300);
+    assert!(wave.samples[..100].iter().all(|&sample| sample == 0));
+    assert_eq!(wave.samples[100], 16383);
+    assert_eq!(wave.samples[105], -16383);
+    assert!(wave.samples[100..200].iter().all(|&sample| sample != 0));
+    assert!(wave.samples[200..].iter().all(|&sample| sample == 0));
+}
+
+#[test]
+fn looping_plays_while_on() {
+    let mixer = Mixer::new(1000, 1000, vec![square(true)]);
+    let wave = mixer.render(&[event(100, 0x01), event(250, 0x00)], 300);
+    assert!(wave.samples[100..250].iter().all(|&sample| sample != 0));
+    assert!(wave.samples[250..].iter().all(|&sample| sample == 0));
+}
+
+#[test]
+fn wav_round_trip() {
+    let wave = Wave::new(22050, vec![0, 1, -1, i16::MAX, i16::MIN]);
+    let mut bytes = Vec::new();
+    wave.write(&mut bytes).unwrap();
+    assert_eq!(bytes.len(), 44 + 10);
+    assert_eq!(&bytes[0..4], b"RIFF");
+    assert_eq!(Wave::read(&bytes[..]).unwrap(), wave);
+}
+
+#[test]
+fn wav_zero_sample_rate() {
+    let mut bytes = Vec::new();
+    Wave::new(0, vec![0; 4]).write(&mut bytes).unwrap();
+    assert_eq!(Wave::read(&bytes[..]).unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn wav_odd_chunk_of_maximum_size() {
+    let mut bytes = b"RIFF\0\0\0\0WAVEjunk\xFF\xFF\xFF\xFF".to_vec();
+    bytes.extend_from_slice(&[0; 16]);
+    assert_eq!(Wave::read(&bytes[..]).unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn sample_with_zero_sample_rate() {
+    let mut voice = square(false);
+    voice.sound = Sound::Sample(Wave::new(0, vec![i16::MAX]));
+    let mixer = Mixer::new(1000, 1000, vec![voice]);
+    let wave = mixer.render(&[event(100, 0x01)], 300);
+    assert_eq!(wave.samples.len(), 300);
+}
+
+#[test]
+#[should_panic(expected = "the clock rate is 0")]
+fn mixer_zero_clock_rate() {
+    Mixer::new(0, 1000, Vec::new());
+}
+
+#[test]
+fn space_invaders_sound_events() {
+    #[rustfmt::skip]
+    let rom = [
+        // MVI A,0x02; OUT 3; MVI A,0x01; OUT 5; HLT
+        0x3E, 0x02, 0xD3, 0x03, 0x3E, 0x01, 0xD3, 0x05, 0x76,
+    ];
+    let mut machine = SpaceInvaders::from_rom(&rom);
+    machine.run_frame();
+    let events = machine.sound_log().events().to_vec();
+    assert_eq!(
+        events,
+        &[
+            SoundEvent { states: 7, port: 3, byte: 0x02 },
+            SoundEvent { states: 24, port: 5, byte: 0x01 }
+        ]
+    );
+    assert_eq!(machine.sound_ports(), (0x02, 0x01));
+
+    let mixer = Mixer::new(CLOCK_RATE, 44100, space_invaders::synthesized_voices());
+    let wave = mixer.render(&events, u64::from(CLOCK_RATE));
+    assert_eq!(wave.samples.len(), 44100);
+    assert!(wave.samples[..4410].iter().any(|&sample| sample != 0));
+    // The shot and the fleet movement are over in a second.
+    assert!(wave.samples[44100 / 2..].iter().all(|&sample| sample == 0));
+}
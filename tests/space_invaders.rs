@@ -0,0 +1,80 @@
+use i8080::machine::space_invaders::{
+    Controls, SpaceInvaders, SCREEN_HEIGHT, SCREEN_WIDTH, STATES_PER_FRAME,
+};
+
+// A program that counts the interrupts, draws the pixels at two corners of the frame buffer, and
+// exercises the shift register and the input ports.
+#[rustfmt::skip]
+const ROM: &[u8] = &[
+    // 0x0000: JMP 0x0020
+    0xC3, 0x20, 0x00, 0, 0, 0, 0, 0,
+    // 0x0008 (RST 1): LXI H,0x2000; INR M; EI; RET
+    0x21, 0x00, 0x20, 0x34, 0xFB, 0xC9, 0, 0,
+    // 0x0010 (RST 2): LXI H,0x2001; INR M; EI; RET
+    0x21, 0x01, 0x20, 0x34, 0xFB, 0xC9, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0,
+    // 0x0020: LXI SP,0x2400
+    0x31, 0x00, 0x24,
+    // MVI A,0x01; STA 0x2400; MVI A,0x80; STA 0x3FFF
+    0x3E, 0x01, 0x32, 0x00, 0x24, 0x3E, 0x80, 0x32, 0xFF, 0x3F,
+    // MVI A,0x5A; OUT 4; MVI A,0xC3; OUT 4; MVI A,4; OUT 2; IN 3; STA 0x2002
+    0x3E, 0x5A, 0xD3, 0x04, 0x3E, 0xC3, 0xD3, 0x04, 0x3E, 0x04, 0xD3, 0x02, 0xDB, 0x03,
+    0x32, 0x02, 0x20,
+    // EI
+    0xFB,
+    // 0x003F: IN 1; STA 0x2003; IN 2; STA 0x2004; JMP 0x003F
+    0xDB, 0x01, 0x32, 0x03, 0x20, 0xDB, 0x02, 0x32, 0x04, 0x20, 0xC3, 0x3F, 0x00,
+];
+
+#[test]
+fn interrupts() {
+    let mut machine = SpaceInvaders::from_rom(ROM);
+    for _ in 0..3 {
+        machine.run_frame();
+    }
+    assert_eq!(machine.i8080.memory[0x2000], 3);
+    // The handler of the last vertical blank interrupt runs in the next frame.
+    assert_eq!(machine.i8080.memory[0x2001], 2);
+    assert_eq!(machine.i8080.cpu.pc, 0x0010);
+    assert!(machine.states() >= 3 * STATES_PER_FRAME);
+}
+
+#[test]
+fn shift_register() {
+    let mut machine = SpaceInvaders::from_rom(ROM);
+    machine.run_frame();
+    // (0xC35A << 4) >> 8 & 0xFF
+    assert_eq!(machine.i8080.memory[0x2002], 0x35);
+}
+
+#[test]
+fn inputs() {
+    let mut machine = SpaceInvaders::from_rom(ROM);
+    machine.run_frame();
+    assert_eq!(machine.i8080.memory[0x2003], 0x08);
+    assert_eq!(machine.i8080.memory[0x2004], 0x00);
+
+    machine.inputs.coin = true;
+    machine.inputs.player1_start = true;
+    machine.inputs.player1 = Controls { shot: true, left: false, right: true };
+    machine.inputs.player2 = Controls { shot: false, left: true, right: false };
+    machine.dip_switches.ships = 6;
+    machine.dip_switches.extra_ship_at_1000 = true;
+    machine.dip_switches.coin_info = false;
+    machine.run_frame();
+    assert_eq!(machine.i8080.memory[0x2003], 0x5D);
+    assert_eq!(machine.i8080.memory[0x2004], 0xAB);
+}
+
+#[test]
+fn pixels() {
+    let mut machine = SpaceInvaders::from_rom(ROM);
+    machine.run_frame();
+    let pixels = machine.pixels();
+    assert_eq!(pixels.len(), SCREEN_WIDTH * SCREEN_HEIGHT);
+    // The first byte of the frame buffer is drawn at the bottom left corner and the last byte at
+    // the top right corner.
+    assert!(pixels[(SCREEN_HEIGHT - 1) * SCREEN_WIDTH]);
+    assert!(pixels[SCREEN_WIDTH - 1]);
+    assert_eq!(pixels.iter().filter(|&&pixel| pixel).count(), 2);
+}
@@ -0,0 +1,126 @@
+use std::{env, fs, path::Path};
+
+use i8080::{
+    machine::space_invaders::SpaceInvaders,
+    memory::Memory,
+    video::{Bitmap, Frame, Overlay, Rotation, BLACK, WHITE},
+};
+
+// Compares `bytes` with the golden file `name` in tests/video, or overwrites the golden file if
+// the environment variable `UPDATE_GOLDEN` is set.
+fn assert_golden(name: &str, bytes: &[u8]) {
+    let path = Path::new("tests/video").join(name);
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, bytes).unwrap();
+    }
+    let golden = fs::read(&path).unwrap();
+    assert!(golden == bytes, "{} does not match the rendered frame", path.display());
+}
+
+fn ppm(frame: &Frame) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    frame.write_ppm(&mut bytes).unwrap();
+    bytes
+}
+
+fn png(frame: &Frame) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    frame.write_png(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn two_bits_per_pixel_with_stride() {
+    let mut memory = Memory::new();
+    // Two scan lines of 4 pixels with a byte of padding in between.
+    memory[0x1000] = 0b00_01_10_11;
+    memory[0x1002] = 0b11_10_01_00;
+    let bitmap = Bitmap {
+        start: 0x1000,
+        width: 4,
+        height: 2,
+        bits_per_pixel: 2,
+        stride: 2,
+        lsb_first: false,
+        rotation: Rotation::None,
+        palette: vec![BLACK, [0xFF, 0x00, 0x00]],
+        overlays: Vec::new(),
+    };
+    let frame = bitmap.render(&memory);
+    let red = [0xFF, 0x00, 0x00];
+    let gray = [0xAA; 3];
+    assert_eq!(frame.pixels(), &[BLACK, red, gray, WHITE, WHITE, gray, red, BLACK]);
+}
+
+#[test]
+fn rotations() {
+    let mut memory = Memory::new();
+    // A 3×2 bitmap with only the first pixel lit.
+    memory[0x0000] = 0x80;
+    let mut bitmap = Bitmap::monochrome(0x0000, 3, 2);
+    let expected = [
+        (Rotation::None, (3, 2), (0, 0)),
+        (Rotation::Clockwise90, (2, 3), (1, 0)),
+        (Rotation::Rotate180, (3, 2), (2, 1)),
+        (Rotation::Counterclockwise90, (2, 3), (0, 2)),
+    ];
+    for &(rotation, (width, height), (x, y)) in &expected {
+        bitmap.rotation = rotation;
+        let frame = bitmap.render(&memory);
+        assert_eq!((frame.width(), frame.height()), (width, height), "{:?}", rotation);
+        assert_eq!(frame.pixel(x, y), WHITE, "{:?}", rotation);
+        assert_eq!(frame.pixels().iter().filter(|&&pixel| pixel == WHITE).count(), 1);
+    }
+}
+
+#[test]
+fn overlay() {
+    let mut memory = Memory::new();
+    memory[0x0000] = 0xFF;
+    let mut bitmap = Bitmap::monochrome(0x0000, 8, 1);
+    bitmap.overlays.push(Overlay { x: 2, y: 0, width: 10, height: 5, color: [0x00, 0x80, 0xFF] });
+    let frame = bitmap.render(&memory);
+    assert_eq!(frame.pixel(1, 0), WHITE);
+    assert_eq!(frame.pixel(2, 0), [0x00, 0x80, 0xFF]);
+    assert_eq!(frame.pixel(7, 0), [0x00, 0x80, 0xFF]);
+}
+
+#[test]
+#[should_panic(expected = "unsupported bits per pixel")]
+fn unsupported_bits_per_pixel() {
+    let mut bitmap = Bitmap::monochrome(0x0000, 8, 1);
+    bitmap.bits_per_pixel = 3;
+    bitmap.render(&Memory::new());
+}
+
+#[test]
+fn ppm_header() {
+    let mut frame = Frame::new(2, 1);
+    frame.set_pixel(1, 0, [0x01, 0x02, 0x03]);
+    assert_eq!(ppm(&frame), b"P6\n2 1\n255\n\x00\x00\x00\x01\x02\x03");
+}
+
+#[test]
+fn space_invaders_golden() {
+    let mut machine = SpaceInvaders::from_rom(&[]);
+    let video_ram = 0x2400..0x4000;
+    for address in video_ram {
+        let offset = address - 0x2400;
+        let (scan_line, column) = (offset / 32, offset % 32);
+        // A checkerboard of 8×8 blocks with a solid border.
+        machine.i8080.memory[address] = if scan_line == 0 || scan_line == 223 {
+            0xFF
+        } else if column == 0 {
+            0x01
+        } else if column == 31 {
+            0x80
+        } else if (scan_line / 8 + column) % 2 == 0 {
+            0xFF
+        } else {
+            0x00
+        };
+    }
+    let frame = machine.render();
+    assert_eq!((frame.width(), frame.height()), (224, 256));
+    assert_golden("space_invaders.png", &png(&frame));
+}
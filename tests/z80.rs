@@ -0,0 +1,328 @@
+#![warn(rust_2018_idioms)]
+
+use std::{env, fs, process::Command};
+
+use i8080::{
+    console::BufferConsole,
+    cpm::Bdos,
+    cpu::{ConditionFlags, Cpu, InterruptMode, Variant},
+    memory::Memory,
+    Error, Intel8080,
+};
+
+// Creates a Z80 system with `program` loaded at 0x0000 and the stack at 0x8000.
+fn z80(program: &[u8]) -> Intel8080 {
+    let mut cpu = Cpu::new(Variant::Z80);
+    cpu.sp = 0x8000;
+    let mut memory = Memory::new();
+    memory[0x0000..program.len() as u16].copy_from_slice(program);
+    Intel8080 { cpu, memory }
+}
+
+// Executes `count` instructions, returning the total number of states taken.
+fn step(i8080: &mut Intel8080, count: usize) -> u32 {
+    (0..count).map(|_| i8080.fetch_execute_instruction().unwrap().1).sum()
+}
+
+#[test]
+fn relative_jumps() {
+    #[rustfmt::skip]
+    let mut i8080 = z80(&[
+        0x06, 0x03, // LD B,3
+        0x3C,       // INC A
+        0x10, 0xFD, // DJNZ $-1
+        0x18, 0x02, // JR $+4
+        0x76,       // HALT
+        0x76,       // HALT
+        0x28, 0xFC, // JR Z,$-2
+        0x20, 0xFA, // JR NZ,$-4
+    ]);
+    assert_eq!(step(&mut i8080, 2), 7 + 4);
+    assert_eq!(step(&mut i8080, 1), 13);
+    step(&mut i8080, 3);
+    assert_eq!(i8080.cpu.a, 3);
+    assert_eq!(step(&mut i8080, 1), 8);
+    assert_eq!(step(&mut i8080, 1), 12);
+    assert_eq!(i8080.cpu.pc, 0x0009);
+    assert_eq!(step(&mut i8080, 1), 7);
+    assert_eq!(step(&mut i8080, 1), 12);
+    assert_eq!(i8080.cpu.pc, 0x0007);
+    assert!(matches!(i8080.fetch_execute_instruction(), Ok(([0x76, 0x00, 0x00], 4))));
+    assert!(matches!(i8080.fetch_execute_instruction(), Err(Error::Halted)));
+}
+
+#[test]
+fn index_registers() {
+    #[rustfmt::skip]
+    let mut i8080 = z80(&[
+        0xDD, 0x21, 0x10, 0x01,       // LD IX,0110H
+        0xDD, 0x36, 0xFE, 0x41,       // LD (IX-2),41H
+        0xDD, 0x7E, 0xFE,             // LD A,(IX-2)
+        0xFD, 0x21, 0x00, 0x02,       // LD IY,0200H
+        0xFD, 0x26, 0x12,             // LD IYH,12H
+        0xFD, 0x7C,                   // LD A,IYH
+        0xDD, 0xCB, 0xFE, 0xC6,       // SET 0,(IX-2)
+        0xDD, 0xCB, 0xFE, 0x3F,       // SRL (IX-2),A
+        0xFD, 0xE5,                   // PUSH IY
+        0xDD, 0xE1,                   // POP IX
+        0xDD, 0x66, 0x00,             // LD H,(IX+0)
+    ]);
+    assert_eq!(step(&mut i8080, 1), 14);
+    assert_eq!(step(&mut i8080, 1), 19);
+    assert_eq!(i8080.memory[0x010E], 0x41);
+    assert_eq!(step(&mut i8080, 1), 19);
+    assert_eq!(i8080.cpu.a, 0x41);
+    assert_eq!(step(&mut i8080, 3), 14 + 11 + 8);
+    assert_eq!(i8080.cpu.z80.iy, 0x1200);
+    assert_eq!(i8080.cpu.a, 0x12);
+    assert_eq!(step(&mut i8080, 1), 23);
+    assert_eq!(i8080.memory[0x010E], 0x41);
+    assert_eq!(step(&mut i8080, 1), 23);
+    assert_eq!(i8080.memory[0x010E], 0x20);
+    assert_eq!(i8080.cpu.a, 0x20);
+    assert!(i8080.cpu.condition_flags.contains(ConditionFlags::CARRY));
+    assert_eq!(step(&mut i8080, 2), 15 + 14);
+    assert_eq!(i8080.cpu.z80.ix, 0x1200);
+    i8080.memory[0x1200] = 0x99;
+    step(&mut i8080, 1);
+    assert_eq!(i8080.cpu.h, 0x99);
+}
+
+#[test]
+fn alternate_registers() {
+    #[rustfmt::skip]
+    let mut i8080 = z80(&[
+        0x01, 0x34, 0x12, // LD BC,1234H
+        0x3E, 0x56,       // LD A,56H
+        0x37,             // SCF
+        0x08,             // EX AF,AF'
+        0xD9,             // EXX
+        0x01, 0x78, 0x56, // LD BC,5678H
+        0xD9,             // EXX
+    ]);
+    step(&mut i8080, 5);
+    assert_eq!(i8080.cpu.a, 0);
+    assert!(!i8080.cpu.condition_flags.contains(ConditionFlags::CARRY));
+    assert_eq!(i8080.cpu.z80.af_alternate, 0x5601);
+    assert_eq!((i8080.cpu.b, i8080.cpu.c), (0x00, 0x00));
+    assert_eq!(i8080.cpu.z80.bc_alternate, 0x1234);
+    step(&mut i8080, 2);
+    assert_eq!((i8080.cpu.b, i8080.cpu.c), (0x12, 0x34));
+    assert_eq!(i8080.cpu.z80.bc_alternate, 0x5678);
+}
+
+#[test]
+fn flags() {
+    #[rustfmt::skip]
+    let mut i8080 = z80(&[
+        0x3E, 0x7F, // LD A,7FH
+        0xC6, 0x01, // ADD A,1
+        0xD6, 0x01, // SUB 1
+        0xED, 0x44, // NEG
+        0x3E, 0x15, // LD A,15H
+        0xD6, 0x06, // SUB 6
+        0x27,       // DAA
+        0x21, 0x00, 0x80, // LD HL,8000H
+        0x11, 0x01, 0x00, // LD DE,1
+        0xB7,             // OR A
+        0xED, 0x52,       // SBC HL,DE
+    ]);
+    let flags = |i8080: &Intel8080| i8080.cpu.condition_flags;
+    step(&mut i8080, 2);
+    assert_eq!(i8080.cpu.a, 0x80);
+    assert_eq!(
+        flags(&i8080),
+        ConditionFlags::SIGN | ConditionFlags::AUX_CARRY | ConditionFlags::PARITY
+    );
+    step(&mut i8080, 1);
+    assert_eq!(
+        flags(&i8080),
+        ConditionFlags::AUX_CARRY | ConditionFlags::PARITY | ConditionFlags::SUBTRACT
+    );
+    assert_eq!(step(&mut i8080, 1), 8);
+    assert_eq!(i8080.cpu.a, 0x81);
+    assert_eq!(
+        flags(&i8080),
+        ConditionFlags::SIGN
+            | ConditionFlags::AUX_CARRY
+            | ConditionFlags::SUBTRACT
+            | ConditionFlags::CARRY
+    );
+    step(&mut i8080, 3);
+    assert_eq!(i8080.cpu.a, 0x09);
+    step(&mut i8080, 3);
+    assert_eq!(step(&mut i8080, 1), 15);
+    assert_eq!((i8080.cpu.h, i8080.cpu.l), (0x7F, 0xFF));
+    assert_eq!(
+        flags(&i8080),
+        ConditionFlags::AUX_CARRY | ConditionFlags::PARITY | ConditionFlags::SUBTRACT
+    );
+}
+
+#[test]
+fn bit_instructions() {
+    #[rustfmt::skip]
+    let mut i8080 = z80(&[
+        0x3E, 0x81,       // LD A,81H
+        0xCB, 0x07,       // RLC A
+        0xCB, 0x7F,       // BIT 7,A
+        0x21, 0x00, 0x10, // LD HL,1000H
+        0xCB, 0xFE,       // SET 7,(HL)
+        0xCB, 0x7E,       // BIT 7,(HL)
+        0xCB, 0xBE,       // RES 7,(HL)
+    ]);
+    assert_eq!(step(&mut i8080, 2), 7 + 8);
+    assert_eq!(i8080.cpu.a, 0x03);
+    assert!(i8080.cpu.condition_flags.contains(ConditionFlags::CARRY));
+    step(&mut i8080, 1);
+    assert!(i8080.cpu.condition_flags.contains(ConditionFlags::ZERO));
+    assert_eq!(step(&mut i8080, 2), 10 + 15);
+    assert_eq!(i8080.memory[0x1000], 0x80);
+    assert_eq!(step(&mut i8080, 1), 12);
+    assert!(!i8080.cpu.condition_flags.contains(ConditionFlags::ZERO));
+    step(&mut i8080, 1);
+    assert_eq!(i8080.memory[0x1000], 0x00);
+}
+
+#[test]
+fn block_instructions() {
+    #[rustfmt::skip]
+    let mut i8080 = z80(&[
+        0x21, 0x00, 0x10, // LD HL,1000H
+        0x11, 0x00, 0x20, // LD DE,2000H
+        0x01, 0x04, 0x00, // LD BC,4
+        0xED, 0xB0,       // LDIR
+        0x21, 0x00, 0x20, // LD HL,2000H
+        0x01, 0x04, 0x00, // LD BC,4
+        0x3E, 0x03,       // LD A,3
+        0xED, 0xB1,       // CPIR
+    ]);
+    i8080.memory[0x1000..0x1004].copy_from_slice(&[1, 2, 3, 4]);
+    step(&mut i8080, 3);
+    assert_eq!(step(&mut i8080, 4), 21 + 21 + 21 + 16);
+    assert_eq!(&i8080.memory[0x2000..0x2004], &[1, 2, 3, 4]);
+    assert_eq!((i8080.cpu.b, i8080.cpu.c), (0, 0));
+    assert!(!i8080.cpu.condition_flags.contains(ConditionFlags::PARITY));
+    step(&mut i8080, 3);
+    assert_eq!(step(&mut i8080, 3), 21 + 21 + 16);
+    assert_eq!((i8080.cpu.h, i8080.cpu.l), (0x20, 0x03));
+    assert_eq!((i8080.cpu.b, i8080.cpu.c), (0, 1));
+    assert!(i8080.cpu.condition_flags.contains(ConditionFlags::ZERO));
+    assert!(i8080.cpu.condition_flags.contains(ConditionFlags::PARITY));
+    assert_eq!(i8080.cpu.pc, 0x0015);
+}
+
+#[test]
+fn interrupt_modes() {
+    #[rustfmt::skip]
+    let mut i8080 = z80(&[
+        0xFB,       // EI
+        0x76,       // HALT
+        0xED, 0x56, // IM 1
+        0xFB,       // EI
+        0x76,       // HALT
+        0x3E, 0x30, // LD A,30H
+        0xED, 0x47, // LD I,A
+        0xED, 0x5E, // IM 2
+        0xFB,       // EI
+        0x76,       // HALT
+    ]);
+    i8080.memory[0x0038] = 0xFB; // EI
+    i8080.memory[0x0039] = 0xED; // RETI
+    i8080.memory[0x003A] = 0x4D;
+    i8080.memory[0x3040..0x3042].copy_from_slice(&[0x00, 0x40]);
+
+    step(&mut i8080, 2);
+    assert_eq!(i8080.cpu.z80.interrupt_mode, InterruptMode::Mode0);
+    assert_eq!(i8080.interrupt([0xFF, 0x00, 0x00]).unwrap(), 13);
+    assert_eq!(i8080.cpu.pc, 0x0038);
+
+    i8080.cpu.pc = 0x0002;
+    step(&mut i8080, 3);
+    assert_eq!(i8080.interrupt([0xFF, 0x00, 0x00]).unwrap(), 13);
+    assert_eq!(i8080.cpu.pc, 0x0038);
+    assert!(i8080.interrupt([0xFF, 0x00, 0x00]).is_err());
+    step(&mut i8080, 2);
+    assert_eq!(i8080.cpu.pc, 0x0006);
+
+    step(&mut i8080, 5);
+    assert_eq!(i8080.interrupt([0x40, 0x00, 0x00]).unwrap(), 19);
+    assert_eq!(i8080.cpu.pc, 0x4000);
+    assert_eq!(&i8080.memory[0x7FFC..0x7FFE], &[0x0E, 0x00]);
+}
+
+#[test]
+fn non_maskable_interrupt() {
+    #[rustfmt::skip]
+    let mut i8080 = z80(&[
+        0xFB,       // EI
+        0x00,       // NOP
+        0x76,       // HALT
+    ]);
+    i8080.memory[0x0066] = 0xED; // LD A,I
+    i8080.memory[0x0067] = 0x57;
+    i8080.memory[0x0068] = 0xED; // RETN
+    i8080.memory[0x0069] = 0x45;
+    step(&mut i8080, 3);
+    assert!(i8080.cpu.is_halted());
+    assert_eq!(i8080.cpu.non_maskable_interrupt(&mut i8080.memory), 11);
+    assert_eq!(i8080.cpu.pc, 0x0066);
+    assert!(!i8080.cpu.is_interrupt_enabled());
+    step(&mut i8080, 1);
+    // P/V holds IFF2, the state of the interrupt system before the interrupt.
+    assert!(i8080.cpu.condition_flags.contains(ConditionFlags::PARITY));
+    step(&mut i8080, 1);
+    assert_eq!(i8080.cpu.pc, 0x0003);
+    assert!(i8080.cpu.is_interrupt_enabled());
+}
+
+#[test]
+fn refresh_register() {
+    #[rustfmt::skip]
+    let mut i8080 = z80(&[
+        0x00,             // NOP
+        0xDD, 0x23,       // INC IX
+        0xDD, 0xCB, 0x00, 0x06, // RLC (IX+0)
+        0xED, 0x5F,       // LD A,R
+    ]);
+    i8080.cpu.z80.r = 0xFE;
+    step(&mut i8080, 4);
+    assert_eq!(i8080.cpu.a, 0x85);
+}
+
+#[test]
+fn cpm_program() {
+    #[rustfmt::skip]
+    let program = [
+        0x21, 0x1B, 0x01,       // LD HL,MESSAGE
+        0x11, 0x00, 0x02,       // LD DE,0200H
+        0x01, 0x07, 0x00,       // LD BC,7
+        0xED, 0xB0,             // LDIR
+        0xDD, 0x21, 0x00, 0x02, // LD IX,0200H
+        0xDD, 0x34, 0x00,       // INC (IX+0)
+        0x11, 0x00, 0x02,       // LD DE,0200H
+        0x0E, 0x09,             // LD C,9 (Print String)
+        0xCD, 0x05, 0x00,       // CALL 0005H
+        0xC9,                   // RET
+        b'Y', b'8', b'0', b' ', b'O', b'K', b'$', // MESSAGE
+    ];
+    let mut i8080 = Intel8080 { cpu: Cpu::new(Variant::Z80), memory: Memory::new() };
+    i8080.memory[0x0100..0x0100 + program.len() as u16].copy_from_slice(&program);
+    i8080.cpu.pc = 0x0100;
+    let mut bdos = Bdos::new(BufferConsole::new(""), env::temp_dir());
+    bdos.install(&mut i8080, &[] as &[&str]);
+    bdos.run(&mut i8080).unwrap();
+    assert_eq!(bdos.console().output, b"Z80 OK");
+
+    let directory = env::temp_dir().join(format!("i8080-z80-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(directory.join("Z80.COM"), &program[..]).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_cpm-run"))
+        .args(["--z80", "Z80.COM"])
+        .current_dir(&directory)
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"Z80 OK");
+}
@@ -0,0 +1,100 @@
+// A JavaScript facade over the WebAssembly module that `cargo rustc --release --target
+// wasm32-unknown-unknown --features wasm --lib --crate-type cdylib` builds.
+//
+//     const { Intel8080 } = await load(await fetch("i8080.wasm").then((r) => r.arrayBuffer()));
+//     const i8080 = new Intel8080();
+//     i8080.load([0x3e, 0x42, 0x76], 0x0000); // MVI A,42H; HLT
+//     i8080.run(1000);
+//     console.log(i8080.a, i8080.isHalted);
+
+const REGISTERS = ["pc", "sp", "a", "b", "c", "d", "e", "h", "l", "flags"];
+
+const VARIANTS = ["intel8080", "necUpd8080af", "amd9080", "intel8085", "z80"];
+
+// Instantiates the module from `bytes`, returning the `Intel8080` class bound to it.
+export async function load(bytes) {
+  const emulators = new Map();
+  const imports = {
+    i8080: {
+      input: (emulator, port) => emulators.get(emulator).onInput(port) & 0xff,
+      output: (emulator, port, byte) => emulators.get(emulator).onOutput(port, byte),
+    },
+  };
+  const { instance } = await WebAssembly.instantiate(bytes, imports);
+  const wasm = instance.exports;
+
+  class Intel8080 {
+    // Creates an emulator of `variant`, one of "intel8080", "necUpd8080af", "amd9080",
+    // "intel8085", and "z80", with the memory cleared.
+    constructor(variant = "intel8080") {
+      const index = VARIANTS.indexOf(variant);
+      if (index < 0) {
+        throw new RangeError(`unknown variant: ${variant}`);
+      }
+      this.pointer = wasm.intel8080_new(index);
+      // Reading a port without a device returns 0xFF; writing to one does nothing.
+      this.onInput = () => 0xff;
+      this.onOutput = () => {};
+      emulators.set(this.pointer, this);
+    }
+
+    // Destroys the emulator, which must not be used afterwards.
+    free() {
+      emulators.delete(this.pointer);
+      wasm.intel8080_free(this.pointer);
+    }
+
+    // The 65536 bytes of memory. The view is created on each access because growing the memory
+    // of the module detaches the views of the old memory.
+    get memory() {
+      return new Uint8Array(wasm.memory.buffer, wasm.intel8080_memory(this.pointer), 0x10000);
+    }
+
+    // Copies `bytes` into memory starting at `address`.
+    load(bytes, address) {
+      if (address + bytes.length > 0x10000) {
+        throw new RangeError("too many bytes to load at the address");
+      }
+      this.memory.set(bytes, address);
+    }
+
+    // Fetches and executes an instruction, returning the number of states taken, or -1 if the CPU
+    // is in the halt state.
+    step() {
+      return wasm.intel8080_step(this.pointer);
+    }
+
+    // Fetches and executes instructions until the program counter reaches a breakpoint, the CPU
+    // halts, or at least `states` states have elapsed, returning the number of states taken.
+    run(states) {
+      return wasm.intel8080_run(this.pointer, states) >>> 0;
+    }
+
+    setBreakpoint(address, isSet = true) {
+      wasm.intel8080_set_breakpoint(this.pointer, address, isSet);
+    }
+
+    // Executes `instruction`, usually an RST, as an interrupt, returning the number of states
+    // taken, or -1 if the interrupt system is disabled.
+    interrupt(instruction) {
+      return wasm.intel8080_interrupt(this.pointer, instruction);
+    }
+
+    get isHalted() {
+      return wasm.intel8080_is_halted(this.pointer) !== 0;
+    }
+  }
+
+  REGISTERS.forEach((name, index) => {
+    Object.defineProperty(Intel8080.prototype, name, {
+      get() {
+        return wasm.intel8080_register(this.pointer, index);
+      },
+      set(value) {
+        wasm.intel8080_set_register(this.pointer, index, value);
+      },
+    });
+  });
+
+  return { Intel8080 };
+}
@@ -0,0 +1,85 @@
+// Tests the WebAssembly build in Node.js:
+//
+//     cargo rustc --release --target wasm32-unknown-unknown --features wasm --lib --crate-type cdylib
+//     node wasm/test.mjs target/wasm32-unknown-unknown/release/i8080.wasm
+
+import assert from "node:assert/strict";
+import { readFileSync } from "node:fs";
+
+import { load } from "./i8080.js";
+
+const bytes = readFileSync(process.argv[2]);
+const { Intel8080 } = await load(bytes);
+
+// Registers, memory, and ports
+{
+  const i8080 = new Intel8080();
+  const outputs = [];
+  i8080.onInput = (port) => port + 1;
+  i8080.onOutput = (port, byte) => outputs.push([port, byte]);
+  // IN 41H; OUT 10H; STA 2000H; HLT
+  i8080.load([0xdb, 0x41, 0xd3, 0x10, 0x32, 0x00, 0x20, 0x76], 0x0100);
+  i8080.pc = 0x0100;
+  assert.equal(i8080.step(), 10);
+  assert.equal(i8080.a, 0x42);
+  assert.equal(i8080.run(1000), 10 + 13 + 7);
+  assert.deepEqual(outputs, [[0x10, 0x42]]);
+  assert.equal(i8080.memory[0x2000], 0x42);
+  assert.ok(i8080.isHalted);
+  assert.equal(i8080.step(), -1);
+  i8080.free();
+}
+
+// Breakpoints and interrupts
+{
+  const i8080 = new Intel8080();
+  // EI; NOP; HLT
+  i8080.load([0xfb, 0x00, 0x76], 0x0000);
+  i8080.sp = 0x1000;
+  i8080.setBreakpoint(0x0001);
+  assert.equal(i8080.run(1000), 4);
+  assert.equal(i8080.pc, 0x0001);
+  i8080.setBreakpoint(0x0001, false);
+  assert.equal(i8080.run(1000), 4 + 7);
+  assert.equal(i8080.interrupt(0xcf), 11); // RST 1
+  assert.equal(i8080.pc, 0x0008);
+  assert.equal(i8080.interrupt(0xcf), -1);
+  i8080.free();
+}
+
+// TST8080.COM with the console output of the BDOS, which it calls at 0005H
+{
+  const i8080 = new Intel8080();
+  i8080.load(readFileSync(new URL("../tests/cpu_tests/TST8080.COM", import.meta.url)), 0x0100);
+  i8080.load([0xc9], 0x0005); // RET
+  i8080.pc = 0x0100;
+  i8080.setBreakpoint(0x0000);
+  i8080.setBreakpoint(0x0005);
+  let output = "";
+  while (i8080.pc !== 0x0000) {
+    i8080.run(1000000);
+    if (i8080.pc === 0x0005) {
+      if (i8080.c === 2) {
+        output += String.fromCharCode(i8080.e);
+      } else if (i8080.c === 9) {
+        const memory = i8080.memory;
+        for (let address = (i8080.d << 8) | i8080.e; memory[address] !== 0x24; address++) {
+          output += String.fromCharCode(memory[address]);
+        }
+      }
+      i8080.step();
+    }
+  }
+  assert.match(output, / CPU IS OPERATIONAL$/);
+  i8080.free();
+}
+
+assert.throws(() => new Intel8080("6502"), RangeError);
+
+// Destroying a null emulator does nothing.
+{
+  const imports = { i8080: { input: () => 0xff, output: () => {} } };
+  const { instance } = await WebAssembly.instantiate(bytes, imports);
+  instance.exports.intel8080_free(0);
+}
+console.log("ok");